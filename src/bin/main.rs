@@ -1,5 +1,34 @@
 extern crate audio_server;
 
+use std::{env, process};
+
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    // Render the soundscape to a WAV faster than realtime rather than running the GUI.
+    if args.iter().any(|arg| arg == "--offline") {
+        let result = audio_server::offline::Options::from_args(args)
+            .and_then(audio_server::offline::run);
+        match result {
+            Ok(path) => println!("Rendered \"{}\"", path.display()),
+            Err(err) => {
+                eprintln!("offline render failed: {}", err);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    // Run the server without the GUI, controlled via OSC.
+    if args.iter().any(|arg| arg == "--headless") {
+        let result = audio_server::headless::Options::from_args(args)
+            .and_then(audio_server::headless::run);
+        if let Err(err) = result {
+            eprintln!("headless server failed: {}", err);
+            process::exit(1);
+        }
+        return;
+    }
+
     audio_server::run();
 }
@@ -0,0 +1,526 @@
+//! Higher-order Ambisonics encoding and AllRAD decoding.
+//!
+//! Signals are encoded using real spherical harmonics with ACN channel ordering and SN3D
+//! normalisation (i.e. the AmbiX convention) up to third order.
+//!
+//! Decoders are calculated using All-Round Ambisonic Decoding (Zotter & Frank, 2012). The
+//! ambisonic bus is first decoded to a dense, uniform layout of virtual speakers before each
+//! virtual speaker is panned onto the real speaker layout via VBAP. The resulting matrix is
+//! normalised so that the average energy of a plane wave is preserved regardless of the layout.
+//!
+//! The virtual speakers are panned between triplets of the convex hull of the speaker directions,
+//! so the elevation of each speaker is respected. Imaginary speakers are placed at the zenith and
+//! nadir when no real speaker is near them, with their signal shared between their neighbours.
+//! Layouts that do not span three dimensions (e.g. a line array) fall back to panning between
+//! pairs of speakers on the horizontal plane.
+
+use crate::audio::vbap;
+use nannou::glam::DMat3 as Matrix3;
+use nannou::glam::DVec3 as Point3;
+
+/// The highest ambisonic order supported by the encoder and decoder.
+pub const MAX_ORDER: usize = 3;
+
+/// The number of ambisonic channels required for the `MAX_ORDER`.
+pub const MAX_CHANNELS: usize = (MAX_ORDER + 1) * (MAX_ORDER + 1);
+
+/// The number of virtual speakers used to sample the sphere when calculating AllRAD decoders.
+const NUM_VIRTUAL_SPEAKERS: usize = 240;
+
+/// An imaginary speaker is added at a pole if no speaker direction has a dot product with the
+/// pole of at least this value (roughly 20 degrees).
+const MIN_POLE_COVERAGE: f64 = 0.94;
+
+/// The tolerance used when triangulating and panning between speaker directions.
+const EPSILON: f64 = 1e-9;
+
+/// Rotates an ambisonic sound field about the vertical axis.
+#[derive(Copy, Clone, Debug)]
+pub struct YawRotation {
+    /// The cosine and sine of `m * radians` for each degree `m` from `1` up to `MAX_ORDER`.
+    cos_sin: [(f32, f32); MAX_ORDER],
+}
+
+/// A matrix for decoding an ambisonic bus to a speaker layout.
+#[derive(Clone, Debug)]
+pub struct Decoder {
+    /// The ambisonic order of the bus that is to be decoded.
+    order: usize,
+    /// The location that is considered the centre of the speaker layout.
+    ///
+    /// Sounds are encoded relative to this point.
+    centre: Point3,
+    /// The number of ambisonic channels for the decoder's order.
+    channels: usize,
+    /// The decoding gain for each ambisonic channel, laid out one row per speaker.
+    matrix: Vec<f64>,
+}
+
+/// A triplet of speaker directions forming one face of the triangulated layout.
+#[derive(Clone, Debug)]
+struct Triplet {
+    /// Indices into the triangulation's directions.
+    vertices: [usize; 3],
+    /// The inverse of the matrix whose columns are the directions of the vertices.
+    inverse: Matrix3,
+}
+
+/// The speaker directions of a layout triangulated for panning in three dimensions.
+#[derive(Clone, Debug)]
+struct Triangulation {
+    /// The index of the real speaker for each direction, or `None` for imaginary speakers.
+    speakers: Vec<Option<usize>>,
+    /// The real speakers sharing a triplet with each imaginary speaker. Empty for real speakers.
+    neighbours: Vec<Vec<usize>>,
+    /// The faces of the convex hull of the directions.
+    triplets: Vec<Triplet>,
+}
+
+/// The number of channels required for an ambisonic bus of the given order.
+pub fn channel_count(order: usize) -> usize {
+    (order + 1) * (order + 1)
+}
+
+/// The ambisonic order of the given ACN channel index.
+fn acn_order(acn: usize) -> usize {
+    (acn as f64).sqrt().floor() as usize
+}
+
+/// Encode a plane wave arriving from the given direction.
+///
+/// `direction` should be a unit vector where `x` is forward, `y` is to the left and `z` is up.
+/// A zero length `direction` results in an omni-directional source, i.e. only `W` is non-zero.
+///
+/// Writes `channel_count(order)` coefficients to the front of `coefficients`.
+pub fn encode(direction: Point3, order: usize, coefficients: &mut [f64]) {
+    assert!(order <= MAX_ORDER);
+    let n = channel_count(order);
+    for c in coefficients[..n].iter_mut() {
+        *c = 0.0;
+    }
+    coefficients[0] = 1.0;
+    if direction.length_squared() <= ::std::f64::EPSILON {
+        return;
+    }
+    let Point3 { x, y, z } = direction.normalize();
+    if order >= 1 {
+        coefficients[1] = y;
+        coefficients[2] = z;
+        coefficients[3] = x;
+    }
+    if order >= 2 {
+        let sqrt_3 = 3f64.sqrt();
+        coefficients[4] = sqrt_3 * x * y;
+        coefficients[5] = sqrt_3 * y * z;
+        coefficients[6] = 0.5 * (3.0 * z * z - 1.0);
+        coefficients[7] = sqrt_3 * x * z;
+        coefficients[8] = 0.5 * sqrt_3 * (x * x - y * y);
+    }
+    if order >= 3 {
+        let sqrt_5_8 = (5.0f64 / 8.0).sqrt();
+        let sqrt_3_8 = (3.0f64 / 8.0).sqrt();
+        let sqrt_15 = 15f64.sqrt();
+        coefficients[9] = sqrt_5_8 * y * (3.0 * x * x - y * y);
+        coefficients[10] = sqrt_15 * x * y * z;
+        coefficients[11] = sqrt_3_8 * y * (5.0 * z * z - 1.0);
+        coefficients[12] = 0.5 * z * (5.0 * z * z - 3.0);
+        coefficients[13] = sqrt_3_8 * x * (5.0 * z * z - 1.0);
+        coefficients[14] = 0.5 * sqrt_15 * z * (x * x - y * y);
+        coefficients[15] = sqrt_5_8 * x * (x * x - 3.0 * y * y);
+    }
+}
+
+/// The max-rE weight for each order, reducing side lobes of the decoded plane wave.
+fn max_re_weight(order: usize, max_order: usize) -> f64 {
+    let radians = (137.9f64 / (max_order as f64 + 1.51)).to_radians();
+    legendre(order, radians.cos())
+}
+
+/// The Legendre polynomial of the given order evaluated at `x`.
+fn legendre(order: usize, x: f64) -> f64 {
+    match order {
+        0 => 1.0,
+        1 => x,
+        2 => 0.5 * (3.0 * x * x - 1.0),
+        _ => 0.5 * (5.0 * x * x * x - 3.0 * x),
+    }
+}
+
+/// Directions approximately uniformly distributed over the sphere via a Fibonacci lattice.
+fn virtual_speaker_directions() -> impl Iterator<Item = Point3> {
+    let golden_angle = ::std::f64::consts::PI * (3.0 - 5f64.sqrt());
+    (0..NUM_VIRTUAL_SPEAKERS).map(move |i| {
+        let z = 1.0 - (i as f64 + 0.5) * 2.0 / NUM_VIRTUAL_SPEAKERS as f64;
+        let r = (1.0 - z * z).sqrt();
+        let radians = golden_angle * i as f64;
+        Point3::new(radians.cos() * r, radians.sin() * r, z)
+    })
+}
+
+/// The faces of the convex hull of the given points, wound anti-clockwise when viewed from outside.
+///
+/// Returns `None` if the points do not span three dimensions.
+fn convex_hull(points: &[Point3]) -> Option<Vec<[usize; 3]>> {
+    if points.len() < 4 {
+        return None;
+    }
+
+    // The index of the point with the greatest non-negligible value of `f`.
+    let max_by = |f: &dyn Fn(Point3) -> f64| {
+        (0..points.len())
+            .map(|i| (i, f(points[i])))
+            .filter(|&(_, v)| v > EPSILON)
+            .fold(None, |max: Option<(usize, f64)>, (i, v)| match max {
+                Some((_, mv)) if mv >= v => max,
+                _ => Some((i, v)),
+            })
+            .map(|(i, _)| i)
+    };
+
+    // Begin with the largest tetrahedron that is easily found.
+    let a = 0;
+    let pa = points[a];
+    let b = max_by(&|p| p.distance_squared(pa))?;
+    let ab = (points[b] - pa).normalize();
+    let c = max_by(&|p| {
+        let ap = p - pa;
+        (ap - ab * ap.dot(ab)).length_squared()
+    })?;
+    let normal = ab.cross(points[c] - pa).normalize();
+    let d = max_by(&|p| (p - pa).dot(normal).abs())?;
+    let interior = (pa + points[b] + points[c] + points[d]) / 4.0;
+
+    let face_normal = |f: &[usize; 3]| {
+        let [x, y, z] = *f;
+        (points[y] - points[x]).cross(points[z] - points[x]).normalize_or_zero()
+    };
+    let mut faces = vec![];
+    for &face in &[[a, b, c], [a, b, d], [a, c, d], [b, c, d]] {
+        let [x, y, z] = face;
+        if face_normal(&face).dot(interior - points[x]) > 0.0 {
+            faces.push([x, z, y]);
+        } else {
+            faces.push(face);
+        }
+    }
+
+    // Add each remaining point, replacing the faces that it can see.
+    let mut edges = vec![];
+    for i in 0..points.len() {
+        if i == a || i == b || i == c || i == d {
+            continue;
+        }
+        let p = points[i];
+        let is_visible = |f: &[usize; 3]| face_normal(f).dot(p - points[f[0]]) > EPSILON;
+        edges.clear();
+        for f in faces.iter().filter(|f| is_visible(f)) {
+            edges.extend(&[(f[0], f[1]), (f[1], f[2]), (f[2], f[0])]);
+        }
+        if edges.is_empty() {
+            continue;
+        }
+        faces.retain(|f| !is_visible(f));
+        for &(u, v) in &edges {
+            if !edges.contains(&(v, u)) {
+                faces.push([u, v, i]);
+            }
+        }
+    }
+
+    Some(faces)
+}
+
+impl Triangulation {
+    /// Triangulate the given speaker directions.
+    ///
+    /// Speakers with a zero length direction are ignored.
+    ///
+    /// Returns `None` if the directions do not span three dimensions.
+    fn new(directions: &[Point3]) -> Option<Self> {
+        let mut points = vec![];
+        let mut speakers = vec![];
+        for (i, &direction) in directions.iter().enumerate() {
+            if direction.length_squared() > EPSILON {
+                points.push(direction.normalize());
+                speakers.push(Some(i));
+            }
+        }
+        for &pole in &[Point3::Z, -Point3::Z] {
+            if !points.iter().any(|p| p.dot(pole) >= MIN_POLE_COVERAGE) {
+                points.push(pole);
+                speakers.push(None);
+            }
+        }
+
+        let faces = convex_hull(&points)?;
+        let mut neighbours = vec![vec![]; points.len()];
+        let mut triplets = Vec::with_capacity(faces.len());
+        for vertices in faces {
+            let [a, b, c] = vertices;
+            let matrix = Matrix3::from_cols(points[a], points[b], points[c]);
+            if matrix.determinant().abs() <= EPSILON {
+                continue;
+            }
+            for &v in &vertices {
+                if speakers[v].is_some() {
+                    continue;
+                }
+                for &other in &vertices {
+                    if let Some(s) = speakers[other] {
+                        if !neighbours[v].contains(&s) {
+                            neighbours[v].push(s);
+                        }
+                    }
+                }
+            }
+            let inverse = matrix.inverse();
+            triplets.push(Triplet { vertices, inverse });
+        }
+
+        Some(Triangulation {
+            speakers,
+            neighbours,
+            triplets,
+        })
+    }
+
+    /// The VBAP gain of each of the `n` speakers for the given unit direction.
+    ///
+    /// The gain of an imaginary speaker is shared between its neighbours with constant power.
+    fn speaker_gains(&self, direction: Point3, n: usize, gains: &mut Vec<f64>) {
+        gains.clear();
+        gains.resize(n, 0.0);
+        for triplet in &self.triplets {
+            let g = triplet.inverse * direction;
+            if g.min_element() < -EPSILON {
+                continue;
+            }
+            let g = g.max(Point3::ZERO);
+            let norm = g.length();
+            if norm <= 0.0 {
+                continue;
+            }
+            let g = g / norm;
+            for (&v, &gain) in triplet.vertices.iter().zip(&[g.x, g.y, g.z]) {
+                match self.speakers[v] {
+                    Some(s) => gains[s] += gain,
+                    None => {
+                        let neighbours = &self.neighbours[v];
+                        let share = gain / (neighbours.len().max(1) as f64).sqrt();
+                        for &s in neighbours {
+                            gains[s] += share;
+                        }
+                    }
+                }
+            }
+            return;
+        }
+    }
+}
+
+impl YawRotation {
+    /// A rotation of the given angle, anti-clockwise when viewed from above.
+    pub fn new(radians: f64) -> Self {
+        let mut cos_sin = [(1.0, 0.0); MAX_ORDER];
+        for (i, cs) in cos_sin.iter_mut().enumerate() {
+            let m_radians = (i + 1) as f64 * radians;
+            *cs = (m_radians.cos() as f32, m_radians.sin() as f32);
+        }
+        YawRotation { cos_sin }
+    }
+
+    /// Rotate the ambisonic frame of the given order in place.
+    pub fn apply(&self, order: usize, frame: &mut [f32]) {
+        for n in 1..order + 1 {
+            for m in 1..n + 1 {
+                let (cos, sin) = self.cos_sin[m - 1];
+                let c_i = n * n + n + m;
+                let s_i = n * n + n - m;
+                let c = frame[c_i];
+                let s = frame[s_i];
+                frame[c_i] = c * cos - s * sin;
+                frame[s_i] = c * sin + s * cos;
+            }
+        }
+    }
+}
+
+impl Decoder {
+    /// Calculate an AllRAD decoder for the given speaker locations.
+    ///
+    /// Returns `None` if `speakers` is empty.
+    pub fn allrad(order: usize, speakers: &[Point3]) -> Option<Self> {
+        assert!(order <= MAX_ORDER);
+        if speakers.is_empty() {
+            return None;
+        }
+        let centre = speakers.iter().fold(Point3::ZERO, |acc, &p| acc + p) / speakers.len() as f64;
+        let channels = channel_count(order);
+
+        // The speaker directions triangulated for 3D VBAP.
+        let directions: Vec<_> = speakers.iter().map(|&p| p - centre).collect();
+        let triangulation = Triangulation::new(&directions);
+
+        // The speakers in the form expected by 2D VBAP, used if the layout is not triangulated.
+        let vbap_speakers: Vec<_> = speakers
+            .iter()
+            .map(|p| vbap::Speaker {
+                point: p.truncate(),
+                weight: 1.0,
+            })
+            .collect();
+
+        // Sum the contribution of each virtual speaker to each real speaker.
+        let mut matrix = vec![0.0; speakers.len() * channels];
+        let mut vbap_gains = Vec::with_capacity(speakers.len());
+        let mut coefficients = [0.0; MAX_CHANNELS];
+        for direction in virtual_speaker_directions() {
+            match triangulation {
+                Some(ref t) => t.speaker_gains(direction, speakers.len(), &mut vbap_gains),
+                None => {
+                    let point = centre.truncate() + direction.truncate();
+                    let listener = centre.truncate();
+                    vbap::speaker_gains(point, listener, &vbap_speakers, &mut vbap_gains);
+                }
+            }
+            encode(direction, order, &mut coefficients);
+            for (s, &gain) in vbap_gains.iter().enumerate() {
+                if gain == 0.0 {
+                    continue;
+                }
+                let row = &mut matrix[s * channels..(s + 1) * channels];
+                for (acn, m) in row.iter_mut().enumerate() {
+                    let n = acn_order(acn);
+                    let weight = (2 * n + 1) as f64 * max_re_weight(n, order);
+                    *m += gain * weight * coefficients[acn] / NUM_VIRTUAL_SPEAKERS as f64;
+                }
+            }
+        }
+
+        let mut decoder = Decoder {
+            order,
+            centre,
+            channels,
+            matrix,
+        };
+
+        // Normalise so that the average decoded energy of a plane wave is 1.
+        let mut energy = 0.0;
+        let mut gains = Vec::with_capacity(speakers.len());
+        for direction in virtual_speaker_directions() {
+            encode(direction, order, &mut coefficients);
+            decoder.speaker_gains(&coefficients, &mut gains);
+            energy += gains.iter().map(|g| g * g).sum::<f64>();
+        }
+        energy /= NUM_VIRTUAL_SPEAKERS as f64;
+        if energy > 0.0 {
+            let scale = 1.0 / energy.sqrt();
+            for m in decoder.matrix.iter_mut() {
+                *m *= scale;
+            }
+        }
+
+        Some(decoder)
+    }
+
+    /// The ambisonic order of the bus decoded by this decoder.
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// The number of speakers for which the decoder was calculated.
+    pub fn speaker_count(&self) -> usize {
+        self.matrix.len() / self.channels
+    }
+
+    /// The gain applied to the given ambisonic channel when decoding to the given speaker.
+    pub fn gain(&self, speaker: usize, acn: usize) -> f64 {
+        self.matrix[speaker * self.channels + acn]
+    }
+
+    /// The direction of the given point from the centre of the decoder's speaker layout.
+    pub fn direction(&self, point: Point3) -> Point3 {
+        let direction = point - self.centre;
+        if direction.length_squared() <= ::std::f64::EPSILON {
+            Point3::ZERO
+        } else {
+            direction.normalize()
+        }
+    }
+
+    /// Decode the given ambisonic coefficients to a gain for each speaker.
+    ///
+    /// `gains` is cleared first and will not allocate as long as it has a capacity of at least
+    /// `speaker_count()`.
+    pub fn speaker_gains(&self, coefficients: &[f64], gains: &mut Vec<f64>) {
+        gains.clear();
+        let rows = self.matrix.chunks(self.channels);
+        gains.extend(rows.map(|row| row.iter().zip(coefficients).map(|(m, c)| m * c).sum::<f64>()));
+    }
+}
+
+#[test]
+fn encode_channel_count() {
+    let mut coefficients = [0.0; MAX_CHANNELS];
+    encode(Point3::new(1.0, 0.0, 0.0), MAX_ORDER, &mut coefficients);
+    assert_eq!(coefficients[0], 1.0);
+    assert_eq!(coefficients[3], 1.0);
+    assert_eq!(channel_count(1), 4);
+    assert_eq!(channel_count(MAX_ORDER), MAX_CHANNELS);
+}
+
+#[test]
+fn allrad_ring_localises() {
+    let speakers: Vec<_> = (0..8)
+        .map(|i| {
+            let radians = i as f64 / 8.0 * 2.0 * ::std::f64::consts::PI;
+            Point3::new(radians.cos() * 4.0, radians.sin() * 4.0, 0.0)
+        })
+        .collect();
+    let decoder = Decoder::allrad(3, &speakers).unwrap();
+    let mut coefficients = [0.0; MAX_CHANNELS];
+    let mut gains = Vec::new();
+    encode(decoder.direction(speakers[2]), 3, &mut coefficients);
+    decoder.speaker_gains(&coefficients, &mut gains);
+    let loudest = (0..gains.len())
+        .max_by(|&a, &b| gains[a].partial_cmp(&gains[b]).unwrap())
+        .unwrap();
+    assert_eq!(loudest, 2);
+}
+
+#[test]
+fn allrad_respects_elevation() {
+    // A ring at ear height with a smaller ring overhead.
+    let ring = |n: usize, radius: f64, z: f64| {
+        (0..n).map(move |i| {
+            let radians = i as f64 / n as f64 * 2.0 * ::std::f64::consts::PI;
+            Point3::new(radians.cos() * radius, radians.sin() * radius, z)
+        })
+    };
+    let speakers: Vec<_> = ring(8, 4.0, 0.0).chain(ring(4, 2.0, 3.0)).collect();
+    let decoder = Decoder::allrad(3, &speakers).unwrap();
+    let mut coefficients = [0.0; MAX_CHANNELS];
+    let mut gains = Vec::new();
+    for &target in &[2, 9] {
+        encode(decoder.direction(speakers[target]), 3, &mut coefficients);
+        decoder.speaker_gains(&coefficients, &mut gains);
+        let loudest = (0..gains.len())
+            .max_by(|&a, &b| gains[a].partial_cmp(&gains[b]).unwrap())
+            .unwrap();
+        assert_eq!(loudest, target);
+    }
+}
+
+#[test]
+fn yaw_rotation_matches_encoding() {
+    let radians = 0.7;
+    let mut encoded = [0.0; MAX_CHANNELS];
+    encode(Point3::new(1.0, 0.0, 0.3), MAX_ORDER, &mut encoded);
+    let mut rotated: Vec<f32> = encoded.iter().map(|&c| c as f32).collect();
+    YawRotation::new(radians).apply(MAX_ORDER, &mut rotated);
+    let direction = Point3::new(radians.cos(), radians.sin(), 0.3);
+    encode(direction, MAX_ORDER, &mut encoded);
+    for (&a, &b) in rotated.iter().zip(encoded.iter()) {
+        assert!((a as f64 - b).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,92 @@
+//! A buffer of interleaved audio samples.
+//!
+//! This allows the render and capture functions to be driven either by an audio device or by the
+//! server itself, e.g. when rendering offline or when running with a virtual device.
+
+use std::ops::{Deref, DerefMut};
+use std::slice;
+
+/// A buffer of interleaved samples with a fixed number of channels.
+///
+/// `S` is typically a borrowed slice: `&[f32]` for input and `&mut [f32]` for output.
+pub struct Buffer<S> {
+    samples: S,
+    channels: usize,
+}
+
+impl<S> Buffer<S>
+where
+    S: Deref<Target = [f32]>,
+{
+    /// Treat the given interleaved samples as a buffer with the given number of channels.
+    pub fn new(samples: S, channels: usize) -> Self {
+        assert!(channels > 0);
+        assert_eq!(samples.len() % channels, 0);
+        Buffer { samples, channels }
+    }
+
+    /// The number of channels in the buffer.
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// The number of frames in the buffer.
+    pub fn len_frames(&self) -> usize {
+        self.samples.len() / self.channels
+    }
+
+    /// An iterator yielding each frame of the buffer.
+    pub fn frames(&self) -> slice::Chunks<f32> {
+        self.samples.chunks(self.channels)
+    }
+}
+
+impl<S> Buffer<S>
+where
+    S: DerefMut<Target = [f32]>,
+{
+    /// An iterator yielding each mutable frame of the buffer.
+    pub fn frames_mut(&mut self) -> slice::ChunksMut<f32> {
+        let channels = self.channels;
+        self.samples.chunks_mut(channels)
+    }
+}
+
+impl<S> Deref for Buffer<S>
+where
+    S: Deref<Target = [f32]>,
+{
+    type Target = [f32];
+    fn deref(&self) -> &Self::Target {
+        &self.samples
+    }
+}
+
+impl<S> DerefMut for Buffer<S>
+where
+    S: DerefMut<Target = [f32]>,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.samples
+    }
+}
+
+#[test]
+fn frames() {
+    let mut samples = [0.0, 1.0, 2.0, 3.0, 4.0, 5.0];
+    let mut buffer = Buffer::new(&mut samples[..], 3);
+    assert_eq!(buffer.channels(), 3);
+    assert_eq!(buffer.len_frames(), 2);
+    assert_eq!(buffer.frames().nth(1), Some(&[3.0, 4.0, 5.0][..]));
+    for frame in buffer.frames_mut() {
+        frame[0] = -1.0;
+    }
+    assert_eq!(&buffer[..], &[-1.0, 1.0, 2.0, -1.0, 4.0, 5.0][..]);
+}
+
+#[test]
+#[should_panic]
+fn partial_frames_are_rejected() {
+    let samples = [0.0; 5];
+    Buffer::new(&samples[..], 2);
+}
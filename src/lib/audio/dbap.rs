@@ -1,6 +1,6 @@
 //! An implementation of Distance-Based Amplitude Panning as published by Trond Lossius, 2009.
 
-use nannou::geom::Point2;
+use nannou::glam::DVec3 as Point3;
 
 #[derive(Copy, Clone, Debug)]
 pub struct Speaker {
@@ -25,10 +25,14 @@ pub struct SpeakerGains<'a> {
 /// speaker only."
 ///
 /// A non-zero blur will ensure that the distance is greater than `0.0` and that we never divide by 0.0.
-pub fn blurred_distance_2(source: Point2, speaker: Point2, blur: f64) -> f64 {
+///
+/// The `z` component of each point is the elevation in metres. The blur is applied on top of any
+/// real difference in elevation between the source and speaker.
+pub fn blurred_distance_2(source: Point3, speaker: Point3, blur: f64) -> f64 {
     let x = speaker.x - source.x;
     let y = speaker.y - source.y;
-    (x * x + y * y + blur * blur).max(::std::f64::EPSILON)
+    let z = speaker.z - source.z;
+    (x * x + y * y + z * z + blur * blur).max(::std::f64::EPSILON)
 }
 
 impl<'a> SpeakerGains<'a> {
@@ -0,0 +1,270 @@
+//! The thread on which the ambisonic decoders are calculated.
+//!
+//! Calculating an AllRAD decoder involves a convex hull over hundreds of virtual speakers along
+//! with a number of allocations, so it must not occur on the audio output thread. Instead, the
+//! audio thread sends a snapshot of the speaker layout to this thread whenever the layout changes.
+//! The finished buses are returned via a lock-free queue and swapped in at the beginning of the
+//! next buffer, while the replaced buses are sent back here to be dropped.
+
+use super::{ambisonics, FRAMES_PER_BUFFER, MAX_CHANNELS};
+use crossbeam::queue::SegQueue;
+use fxhash::FxHashMap;
+use crate::installation;
+use std::mem;
+use std::sync::{Arc, Mutex};
+use std::{thread, time};
+
+type Point3 = nannou::glam::DVec3;
+
+/// The number of layout buffers to prepare for cycling between the audio and decoding threads.
+const LAYOUTS_TO_PREPARE: usize = 2;
+
+/// The type used for queueing messages for processing by the decoding thread.
+type MessageQueue = Arc<SegQueue<Message>>;
+
+/// The type used for queueing layout buffers for re-use.
+type LayoutQueue = Arc<SegQueue<Vec<BusSpeaker>>>;
+
+/// The type used for returning the calculated buses to the audio thread.
+type DecodedQueue = Arc<SegQueue<Decoded>>;
+
+/// The ambisonic buses and their decoders.
+pub type Buses = FxHashMap<BusId, Bus>;
+
+/// Identifies an ambisonic bus.
+///
+/// Sounds that may play on all speakers are encoded onto the `All` bus, while sounds that are
+/// assigned to installations are encoded onto the bus of each of their installations. Each bus is
+/// decoded only to the speakers assigned to it.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum BusId {
+    All,
+    Installation(installation::Id),
+}
+
+/// An ambisonic bus along with the decoder used to render it to its speakers.
+pub struct Bus {
+    /// Decodes the bus to the speakers below.
+    pub decoder: ambisonics::Decoder,
+    /// The output channel of each of the speakers for each row of the decoder.
+    pub output_channels: Vec<usize>,
+    /// The encoded samples for the current buffer, interleaved by ambisonic channel.
+    pub samples: Vec<f32>,
+    /// Whether or not any sound has been encoded onto the bus during the current buffer.
+    pub has_signal: bool,
+}
+
+/// A speaker within the layout snapshot, listed once for each bus to which it is decoded.
+#[derive(Copy, Clone, Debug)]
+pub struct BusSpeaker {
+    pub bus: BusId,
+    pub point: Point3,
+    pub channel: usize,
+}
+
+/// The buses calculated for a request.
+pub struct Decoded {
+    /// The ID of the request for which the buses were calculated.
+    pub request: u64,
+    /// The order of the buses.
+    pub order: usize,
+    pub buses: Buses,
+}
+
+/// A request to calculate the buses for a layout.
+struct Request {
+    id: u64,
+    order: usize,
+    layout: Vec<BusSpeaker>,
+}
+
+/// Messages received by the decoding thread.
+enum Message {
+    Decode(Request),
+    Retire(Buses),
+    Exit,
+}
+
+/// A handle for communicating with the decoding thread.
+pub struct Handle {
+    tx: MessageQueue,
+    thread: Arc<Mutex<Option<thread::JoinHandle<()>>>>,
+    layout_rx: LayoutQueue,
+    decoded_rx: DecodedQueue,
+}
+
+impl Handle {
+    /// Request the buses of the given order for the given layout.
+    ///
+    /// If several requests are queued, only the most recent is calculated.
+    pub fn decode(&self, id: u64, order: usize, layout: Vec<BusSpeaker>) {
+        self.tx.push(Message::Decode(Request { id, order, layout }));
+    }
+
+    /// Send buses that are no longer in use to be dropped on the decoding thread.
+    pub fn retire(&self, buses: Buses) {
+        self.tx.push(Message::Retire(buses));
+    }
+
+    /// Pop the next available layout buffer for use off the queue.
+    pub fn pop_layout(&self) -> Vec<BusSpeaker> {
+        let mut layout = self.layout_rx.pop().unwrap_or_else(Vec::new);
+        layout.clear();
+        layout
+    }
+
+    /// Pop the next buses calculated by the decoding thread, if any.
+    pub fn pop_decoded(&self) -> Option<Decoded> {
+        self.decoded_rx.pop()
+    }
+
+    /// Stops the decoding thread and returns the raw handle to its thread.
+    ///
+    /// This is called automatically when the handle is dropped.
+    fn exit(&self) -> Option<thread::JoinHandle<()>> {
+        self.tx.push(Message::Exit);
+        self.thread.lock().unwrap().take()
+    }
+}
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        if let Some(thread) = self.exit() {
+            thread.join().ok();
+        }
+    }
+}
+
+/// Spawn the decoding thread, returning a handle that may be used for communication.
+pub fn spawn() -> Handle {
+    let queue = Arc::new(SegQueue::new());
+    let tx = queue.clone();
+    let rx = queue;
+
+    let layout_queue = Arc::new(SegQueue::new());
+    let layout_tx = layout_queue.clone();
+    let layout_rx = layout_queue;
+
+    let decoded_queue = Arc::new(SegQueue::new());
+    let decoded_tx = decoded_queue.clone();
+    let decoded_rx = decoded_queue;
+
+    // Pre-prepare some layout buffers.
+    for _ in 0..LAYOUTS_TO_PREPARE {
+        layout_tx.push(Vec::with_capacity(MAX_CHANNELS * 4));
+    }
+
+    let thread = thread::Builder::new()
+        .name("audio_decoding".into())
+        .spawn(move || run(rx, layout_tx, decoded_tx))
+        .unwrap();
+    let thread = Arc::new(Mutex::new(Some(thread)));
+
+    Handle {
+        tx,
+        thread,
+        layout_rx,
+        decoded_rx,
+    }
+}
+
+/// The main loop for the decoding thread.
+fn run(rx: MessageQueue, layout_tx: LayoutQueue, decoded_tx: DecodedQueue) {
+    loop {
+        let msg = match rx.pop() {
+            // If there are no messages waiting, sleep for a tiny bit to avoid rinsing cpu.
+            None => {
+                thread::sleep(time::Duration::from_millis(1));
+                continue;
+            }
+            Some(msg) => msg,
+        };
+
+        match msg {
+            Message::Decode(mut request) => {
+                // Skip any requests that have since been superseded.
+                let mut exit = false;
+                while let Some(msg) = rx.pop() {
+                    match msg {
+                        Message::Decode(next) => {
+                            layout_tx.push(mem::replace(&mut request, next).layout);
+                        }
+                        Message::Retire(buses) => drop(buses),
+                        Message::Exit => {
+                            exit = true;
+                            break;
+                        }
+                    }
+                }
+                if exit {
+                    break;
+                }
+                let buses = decode(request.order, &request.layout);
+                layout_tx.push(request.layout);
+                decoded_tx.push(Decoded {
+                    request: request.id,
+                    order: request.order,
+                    buses,
+                });
+            }
+
+            // The buses are no longer used by the audio thread and are simply dropped.
+            Message::Retire(buses) => drop(buses),
+
+            Message::Exit => break,
+        }
+    }
+}
+
+/// Calculate the bus and decoder of the given order for each bus within the layout.
+///
+/// Buses without any speakers are omitted.
+pub fn decode(order: usize, layout: &[BusSpeaker]) -> Buses {
+    let mut bus_ids = vec![];
+    for speaker in layout {
+        if !bus_ids.contains(&speaker.bus) {
+            bus_ids.push(speaker.bus);
+        }
+    }
+
+    let mut buses = FxHashMap::default();
+    for bus_id in bus_ids {
+        let (points, output_channels): (Vec<_>, Vec<_>) = layout
+            .iter()
+            .filter(|s| s.bus == bus_id)
+            .map(|s| (s.point, s.channel))
+            .unzip();
+        let decoder = match ambisonics::Decoder::allrad(order, &points) {
+            None => continue,
+            Some(decoder) => decoder,
+        };
+        let samples = Vec::with_capacity(FRAMES_PER_BUFFER * ambisonics::MAX_CHANNELS);
+        let bus = Bus {
+            decoder,
+            output_channels,
+            samples,
+            has_signal: false,
+        };
+        buses.insert(bus_id, bus);
+    }
+    buses
+}
+
+#[test]
+fn decode_per_bus() {
+    let inst = BusId::Installation(installation::Id(0));
+    let mut layout = vec![];
+    for i in 0..8 {
+        let radians = i as f64 * std::f64::consts::PI / 4.0;
+        let point = Point3::new(radians.cos(), radians.sin(), 0.0);
+        layout.push(BusSpeaker { bus: BusId::All, point, channel: i });
+        if i % 2 == 0 {
+            layout.push(BusSpeaker { bus: inst, point, channel: i });
+        }
+    }
+    let buses = decode(1, &layout);
+    assert_eq!(buses.len(), 2);
+    assert_eq!(buses[&BusId::All].output_channels, (0..8).collect::<Vec<_>>());
+    assert_eq!(buses[&inst].output_channels, vec![0, 2, 4, 6]);
+    assert_eq!(buses[&inst].decoder.order(), 1);
+}
@@ -0,0 +1,89 @@
+//! A simple single-channel delay line with a fixed maximum length.
+
+/// A single-channel ring buffer from which previously written samples may be read.
+///
+/// The buffer is allocated once upon construction so that the delay line may be used on the audio
+/// thread.
+#[derive(Clone, Debug)]
+pub struct DelayLine {
+    buffer: Vec<f32>,
+    /// The index at which the next sample will be written.
+    write_index: usize,
+}
+
+impl DelayLine {
+    /// Create a new, silent delay line capable of delaying by up to `max_frames - 1` frames.
+    pub fn new(max_frames: usize) -> Self {
+        let buffer = vec![0.0; max_frames.max(1)];
+        let write_index = 0;
+        DelayLine {
+            buffer,
+            write_index,
+        }
+    }
+
+    /// The maximum number of frames by which the delay line may delay a signal.
+    pub fn max_delay_frames(&self) -> usize {
+        self.buffer.len() - 1
+    }
+
+    /// Write the given sample to the delay line, advancing the write position.
+    pub fn write(&mut self, sample: f32) {
+        self.buffer[self.write_index] = sample;
+        self.write_index = (self.write_index + 1) % self.buffer.len();
+    }
+
+    /// Read the sample that was written `delay_frames` frames before the most recent sample.
+    ///
+    /// `delay_frames` is clamped to `max_delay_frames`.
+    pub fn read(&self, delay_frames: usize) -> f32 {
+        let len = self.buffer.len();
+        let delay_frames = delay_frames.min(len - 1);
+        let index = (self.write_index + len - 1 - delay_frames) % len;
+        self.buffer[index]
+    }
+
+    /// Read the signal at a fractional delay, linearly interpolating between adjacent samples.
+    ///
+    /// `delay_frames` is clamped to `max_delay_frames`.
+    pub fn read_interpolated(&self, delay_frames: f64) -> f32 {
+        let delay_frames = delay_frames.max(0.0).min(self.max_delay_frames() as f64);
+        let index = delay_frames.floor() as usize;
+        let fract = (delay_frames - index as f64) as f32;
+        let a = self.read(index);
+        let b = self.read(index + 1);
+        a + (b - a) * fract
+    }
+
+    /// Write the given sample and return the sample delayed by `delay_frames`.
+    pub fn process(&mut self, sample: f32, delay_frames: usize) -> f32 {
+        self.write(sample);
+        self.read(delay_frames)
+    }
+
+    /// Silence the delay line.
+    pub fn clear(&mut self) {
+        for s in self.buffer.iter_mut() {
+            *s = 0.0;
+        }
+    }
+}
+
+#[test]
+fn delay_line() {
+    let mut delay = DelayLine::new(4);
+    assert_eq!(delay.process(1.0, 0), 1.0);
+    assert_eq!(delay.process(2.0, 1), 1.0);
+    assert_eq!(delay.process(3.0, 2), 1.0);
+    assert_eq!(delay.process(4.0, 3), 1.0);
+    assert_eq!(delay.process(5.0, 3), 2.0);
+}
+
+#[test]
+fn delay_line_interpolated() {
+    let mut delay = DelayLine::new(4);
+    delay.write(0.0);
+    delay.write(1.0);
+    assert_eq!(delay.read_interpolated(0.5), 0.5);
+    assert_eq!(delay.read_interpolated(0.0), 1.0);
+}
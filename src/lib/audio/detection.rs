@@ -5,6 +5,9 @@
 //! - RMS and Peak per Sound.
 //! - RMS and Peak per Speaker channel.
 //! - FFT and avg RMS and Peak per installation.
+//!
+//! The avg RMS per installation is also shared with the soundscape via `InstallationRms`, which
+//! the soundscape samples once per tick for reactive movement.
 
 use super::detector::{EnvDetector, Fft, FftDetector, FFT_WINDOW_LEN};
 use super::{fft, sound, speaker};
@@ -42,6 +45,16 @@ type Speakers = FxHashMap<speaker::Id, Speaker>;
 /// A map from `installation::Id`s to their relevant detection state.
 type Installations = FxHashMap<installation::Id, Installation>;
 
+/// The average RMS across the speakers of each installation, as most recently measured on the
+/// output.
+///
+/// Written by the detection thread and sampled by the soundscape once per tick, so that the
+/// soundscape never has to process a message per installation per buffer.
+#[derive(Clone, Default)]
+pub struct InstallationRms {
+    map: Arc<Mutex<FxHashMap<installation::Id, f32>>>,
+}
+
 /// Information relevant to analysis of the output buffer.
 #[derive(Default)]
 pub struct OutputInfo {
@@ -133,6 +146,7 @@ pub struct Model {
 
     gui_audio_monitor_msg_tx: gui::monitor::Sender,
     osc_output_msg_tx: osc::output::Tx,
+    installation_rms: InstallationRms,
     sound_buffer_tx: BufferQueue,
     output_buffer_tx: OutputBufferQueue,
 
@@ -251,6 +265,7 @@ impl Model {
     pub fn new(
         gui_audio_monitor_msg_tx: gui::monitor::Sender,
         osc_output_msg_tx: osc::output::Tx,
+        installation_rms: InstallationRms,
         sound_buffer_tx: BufferQueue,
         output_buffer_tx: OutputBufferQueue,
     ) -> Self {
@@ -280,6 +295,7 @@ impl Model {
             cpu_saving_enabled,
             gui_audio_monitor_msg_tx,
             osc_output_msg_tx,
+            installation_rms,
             sound_buffer_tx,
             output_buffer_tx,
             num_active_sound_buffers,
@@ -291,6 +307,28 @@ impl Model {
     }
 }
 
+impl InstallationRms {
+    /// Update the RMS most recently measured for the installation.
+    pub fn set(&self, id: installation::Id, rms: f32) {
+        self.map.lock().unwrap().insert(id, rms);
+    }
+
+    /// Remove the RMS of the installation.
+    pub fn remove(&self, id: &installation::Id) {
+        self.map.lock().unwrap().remove(id);
+    }
+
+    /// Remove the RMS of all installations.
+    pub fn clear(&self) {
+        self.map.lock().unwrap().clear();
+    }
+
+    /// The RMS most recently measured for the installation, if any.
+    pub fn get(&self, id: &installation::Id) -> Option<f32> {
+        self.map.lock().unwrap().get(id).cloned()
+    }
+}
+
 /// Create a slice of channel detectors from the given number of channels.
 fn new_channel_detectors(channels: usize) -> Box<[EnvDetector]> {
     (0..channels)
@@ -309,6 +347,7 @@ fn new_sound(channels: usize) -> Sound {
 pub fn spawn(
     gui_audio_monitor_msg_tx: gui::monitor::Sender,
     osc_output_msg_tx: osc::output::Tx,
+    installation_rms: InstallationRms,
 ) -> Handle {
     let queue = Arc::new(SegQueue::new());
     let tx = queue.clone();
@@ -328,6 +367,7 @@ pub fn spawn(
             run(
                 gui_audio_monitor_msg_tx,
                 osc_output_msg_tx,
+                installation_rms,
                 rx,
                 sound_buffer_tx,
                 output_buffer_tx,
@@ -348,6 +388,7 @@ pub fn spawn(
 fn run(
     gui_audio_monitor_msg_tx: gui::monitor::Sender,
     osc_output_msg_tx: osc::output::Tx,
+    installation_rms: InstallationRms,
     rx: MessageQueue,
     sound_buffer_tx: BufferQueue,
     output_buffer_tx: OutputBufferQueue,
@@ -355,6 +396,7 @@ fn run(
     let mut model = Model::new(
         gui_audio_monitor_msg_tx,
         osc_output_msg_tx,
+        installation_rms,
         sound_buffer_tx,
         output_buffer_tx,
     );
@@ -477,6 +519,7 @@ fn run(
             // Remove the given detection state for the given installation.
             Message::RemoveInstallation(installation_id) => {
                 model.installations.remove(&installation_id);
+                model.installation_rms.remove(&installation_id);
             }
 
             // Perform analysis for the output buffer.
@@ -495,6 +538,7 @@ fn run(
                     ref mut fft_frequency_amplitudes_2,
                     ref gui_audio_monitor_msg_tx,
                     ref osc_output_msg_tx,
+                    ref installation_rms,
                     ref output_buffer_tx,
                     cpu_saving_enabled,
                     ..
@@ -532,22 +576,21 @@ fn run(
                         env_detector: EnvDetector::new(),
                     });
 
-                    // Only update the envelope detector if CPU saving is not enabled.
-                    let mut rms = 0.0;
-                    let mut peak = 0.0;
-                    if !cpu_saving_enabled {
-                        // Update the envelope detector.
-                        for frame in samples.chunks(channels) {
-                            let sample = frame[speaker.channel];
-                            state.env_detector.next(sample);
-                        }
+                    // Update the envelope detector.
+                    //
+                    // This runs even in CPU saving mode, as the RMS and peak are also sent to the
+                    // installation computers and used by the soundscape to measure activity.
+                    for frame in samples.chunks(channels) {
+                        let sample = frame[speaker.channel];
+                        state.env_detector.next(sample);
+                    }
 
-                        // The current env detector states.
-                        let (current_rms, current_peak) = state.env_detector.current();
-                        rms = current_rms;
-                        peak = current_peak;
+                    // The current env detector states.
+                    let (rms, peak) = state.env_detector.current();
 
-                        // Send the detector state for this speaker to the GUI.
+                    // Only send the detector state for this speaker to the GUI if CPU saving is
+                    // not enabled.
+                    if !cpu_saving_enabled {
                         let speaker_msg = gui::SpeakerMessage::Update { rms, peak };
                         let msg = gui::AudioMonitorMessage::Speaker(id, speaker_msg);
                         gui_audio_monitor_msg_tx.push(msg);
@@ -561,16 +604,16 @@ fn run(
                             Some(installation) => installation,
                         };
 
-                        // If the installation has no computers, skip it.
-                        if installation.computers == 0 {
-                            continue;
-                        }
-
                         // Insert the speaker analysis for this speaker into the installation.
                         let index = speaker.channel;
                         let analysis = SpeakerAnalysis { peak, rms, index };
                         installation.speaker_analyses.push(analysis);
 
+                        // If the installation has no computers, there's no need for its samples.
+                        if installation.computers == 0 {
+                            continue;
+                        }
+
                         // Sum the audio data for the speaker onto its associated installation buffers.
                         installation
                             .summed_samples_of_all_channels
@@ -589,6 +632,22 @@ fn run(
                         continue;
                     }
 
+                    // Find the average peak and RMS across all speakers in the installation.
+                    let (avg_peak, avg_rms) = {
+                        let n_speakers_f = installation.speaker_analyses.len() as f32;
+                        let mut iter = installation.speaker_analyses.iter();
+                        iter.next()
+                            .map(|s| {
+                                let init = (s.peak, s.rms);
+                                iter.fold(init, |(acc_p, acc_r), s| (acc_p + s.peak, acc_r + s.rms))
+                            })
+                            .map(|(sum_p, sum_r)| (sum_p / n_speakers_f, sum_r / n_speakers_f))
+                            .unwrap_or((0.0, 0.0))
+                    };
+
+                    // Let the soundscape know how busy the installation is.
+                    installation_rms.set(id, avg_rms);
+
                     // If the installation has no computers, there's no point analysing audio.
                     if installation.computers == 0 {
                         continue;
@@ -641,19 +700,6 @@ fn run(
                         fft_data
                     };
 
-                    // Find the average peak and RMS across all speakers in the installation.
-                    let (avg_peak, avg_rms) = {
-                        let n_speakers_f = installation.speaker_analyses.len() as f32;
-                        let mut iter = installation.speaker_analyses.iter();
-                        iter.next()
-                            .map(|s| {
-                                let init = (s.peak, s.rms);
-                                iter.fold(init, |(acc_p, acc_r), s| (acc_p + s.peak, acc_r + s.rms))
-                            })
-                            .map(|(sum_p, sum_r)| (sum_p / n_speakers_f, sum_r / n_speakers_f))
-                            .unwrap_or((0.0, 0.0))
-                    };
-
                     // Sort the speakers by channel index as the OSC output thread assumes that
                     // speakers are in order of index.
                     installation
@@ -695,6 +741,7 @@ fn run(
                 model.sounds.clear();
                 model.speakers.clear();
                 model.installations.clear();
+                model.installation_rms.clear();
             }
 
             Message::CpuSavingEnabled(enabled) => {
@@ -0,0 +1,132 @@
+//! An optional per-source model of how a sound changes as it moves away from a reference point.
+//!
+//! Beyond the `min_distance`, sounds are attenuated by a fixed number of decibels per doubling of
+//! distance and low-pass filtered to approximate the absorption of high frequencies by the air.
+
+use crate::metres::Metres;
+use serde::{Deserialize, Serialize};
+
+/// The low-pass cutoff applied to sounds within the `min_distance`, above the audible range.
+pub const MAX_CUTOFF_HZ: f64 = 20_000.0;
+
+/// The lowest low-pass cutoff that may be applied regardless of distance.
+pub const MIN_CUTOFF_HZ: f64 = 500.0;
+
+/// The point from which the distance of each sound channel is measured.
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub enum Reference {
+    /// The project's listening point.
+    Listener,
+    /// The centroid of the speakers of the nearest installation in which the sound may play.
+    InstallationCentroid,
+}
+
+/// Describes the attenuation and air absorption applied to a source's sounds.
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Model {
+    /// The point from which distance is measured.
+    #[serde(default)]
+    pub reference: Reference,
+    /// The distance within which sounds are unaffected.
+    #[serde(default = "default_min_distance")]
+    pub min_distance: Metres,
+    /// The attenuation in decibels applied per doubling of distance beyond the `min_distance`.
+    ///
+    /// `6.0` matches the inverse distance law of a point source.
+    #[serde(default = "default_rolloff_db")]
+    pub rolloff_db: f64,
+    /// The distance beyond the `min_distance` over which the low-pass cutoff is halved.
+    #[serde(default = "default_absorption_distance")]
+    pub absorption_distance: Metres,
+}
+
+impl Reference {
+    pub const VARIANT_COUNT: usize = 2;
+
+    /// Produce the index of the variant.
+    pub fn to_index(&self) -> usize {
+        match *self {
+            Reference::Listener => 0,
+            Reference::InstallationCentroid => 1,
+        }
+    }
+
+    /// The label for the variant at the given index.
+    pub fn label_from_index(index: usize) -> &'static str {
+        match index {
+            0 => "Listener",
+            1 => "Installation Centroid",
+            _ => "",
+        }
+    }
+
+    /// The variant at the given index.
+    pub fn from_index(index: usize) -> Option<Self> {
+        match index {
+            0 => Some(Reference::Listener),
+            1 => Some(Reference::InstallationCentroid),
+            _ => None,
+        }
+    }
+}
+
+impl Default for Reference {
+    fn default() -> Self {
+        Reference::Listener
+    }
+}
+
+impl Default for Model {
+    fn default() -> Self {
+        Model {
+            reference: Default::default(),
+            min_distance: default_min_distance(),
+            rolloff_db: default_rolloff_db(),
+            absorption_distance: default_absorption_distance(),
+        }
+    }
+}
+
+impl Model {
+    /// The distance beyond the `min_distance`.
+    fn excess_distance(&self, distance: Metres) -> f64 {
+        (distance.0 - self.min_distance.0).max(0.0)
+    }
+
+    /// The linear gain applied to a sound channel at the given distance from the reference.
+    pub fn gain(&self, distance: Metres) -> f64 {
+        let min = self.min_distance.0.max(::std::f64::EPSILON);
+        if distance.0 <= min {
+            return 1.0;
+        }
+        let doublings = (distance.0 / min).log2();
+        crate::utils::db_to_linear(-self.rolloff_db * doublings)
+    }
+
+    /// The low-pass cutoff applied to a sound channel at the given distance from the reference.
+    pub fn cutoff_hz(&self, distance: Metres) -> f64 {
+        let halvings = self.excess_distance(distance) / self.absorption_distance.0.max(::std::f64::EPSILON);
+        (MAX_CUTOFF_HZ * 0.5f64.powf(halvings)).max(MIN_CUTOFF_HZ)
+    }
+}
+
+fn default_min_distance() -> Metres {
+    Metres(1.0)
+}
+
+fn default_rolloff_db() -> f64 {
+    6.0
+}
+
+fn default_absorption_distance() -> Metres {
+    Metres(15.0)
+}
+
+#[test]
+fn gain_and_cutoff_fall_with_distance() {
+    let model = Model::default();
+    assert_eq!(model.gain(Metres(0.5)), 1.0);
+    assert!((model.gain(Metres(2.0)) - crate::utils::db_to_linear(-6.0)).abs() < 1e-9);
+    assert_eq!(model.cutoff_hz(Metres(1.0)), MAX_CUTOFF_HZ);
+    assert!((model.cutoff_hz(Metres(16.0)) - MAX_CUTOFF_HZ * 0.5).abs() < 1e-6);
+}
@@ -0,0 +1,80 @@
+//! An optional per-source Doppler effect for moving sounds.
+//!
+//! Each channel of a sound is passed through a delay line whose length follows the propagation
+//! time from the sound to a reference point. Reading the delay line at a continuously changing,
+//! fractional position resamples the signal, raising the pitch of approaching sounds and lowering
+//! the pitch of receding ones.
+//!
+//! Positions are only updated at the rate of the soundscape tick, so the delay is advanced each
+//! frame by the sound's radial velocity and only slowly corrected towards the true propagation
+//! time. This avoids the stepped pitch that would result from following the positions alone.
+
+use crate::audio::{distance, SAMPLE_RATE, SPEED_OF_SOUND_M_PER_SEC};
+use serde::{Deserialize, Serialize};
+use time_calc::Ms;
+
+/// The longest propagation delay that may be simulated, limiting the size of each delay line.
+pub const MAX_DELAY: Ms = Ms(300.0);
+
+/// The number of frames over which the delay is corrected towards the true propagation time.
+const CORRECTION_FRAMES: f64 = SAMPLE_RATE;
+
+/// The range over which the delay may change per frame, limiting the pitch shift to an octave in
+/// either direction.
+///
+/// The playback rate of the delayed signal is `1.0 - slope`, so a slope of `-1.0` doubles the
+/// pitch of an approaching sound and a slope of `0.5` halves the pitch of a receding one.
+const MIN_DELAY_SLOPE: f64 = -1.0;
+const MAX_DELAY_SLOPE: f64 = 0.5;
+
+/// Describes the Doppler effect applied to a source's sounds.
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Model {
+    /// The point relative to which the velocity of each sound is measured.
+    #[serde(default)]
+    pub reference: distance::Reference,
+    /// A multiplier for the strength of the effect, where `1.0` is physically accurate.
+    #[serde(default = "default_scale")]
+    pub scale: f64,
+}
+
+impl Default for Model {
+    fn default() -> Self {
+        Model {
+            reference: Default::default(),
+            scale: default_scale(),
+        }
+    }
+}
+
+impl Model {
+    /// The propagation delay in frames for a sound at the given distance from the reference.
+    pub fn delay_frames(&self, distance: f64) -> f64 {
+        distance * self.scale / SPEED_OF_SOUND_M_PER_SEC * SAMPLE_RATE
+    }
+
+    /// The change in delay per frame for a sound moving away from the reference at the given
+    /// radial velocity in metres per second, corrected towards `target_delay_frames`.
+    pub fn delay_slope(&self, radial_velocity: f64, delay_frames: f64, target_delay_frames: f64) -> f64 {
+        let slope = radial_velocity * self.scale / SPEED_OF_SOUND_M_PER_SEC;
+        let correction = (target_delay_frames - delay_frames) / CORRECTION_FRAMES;
+        (slope + correction).max(MIN_DELAY_SLOPE).min(MAX_DELAY_SLOPE)
+    }
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+#[test]
+fn receding_sound_increases_delay() {
+    let model = Model::default();
+    let delay = model.delay_frames(10.0);
+    assert!(model.delay_slope(10.0, delay, delay) > 0.0);
+    assert!(model.delay_slope(-10.0, delay, delay) < 0.0);
+    assert_eq!(model.delay_slope(0.0, delay, delay), 0.0);
+
+    // Supersonic sounds are limited to an octave either way.
+    assert_eq!(model.delay_slope(1_000.0, delay, delay), MAX_DELAY_SLOPE);
+    assert_eq!(model.delay_slope(-1_000.0, delay, delay), MIN_DELAY_SLOPE);
+}
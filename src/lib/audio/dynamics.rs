@@ -0,0 +1,271 @@
+//! Dynamics processing applied to the output of the audio thread.
+//!
+//! A lookahead brickwall limiter protects the master output from clipping the audio interface,
+//! while each installation may optionally compress the combined output of its speakers.
+
+use crate::audio::delay::DelayLine;
+use crate::audio::MAX_CHANNELS;
+use crate::utils;
+use time_calc::Ms;
+
+/// The duration by which the limiter delays the output in order to anticipate peaks.
+pub const LIMITER_LOOKAHEAD: Ms = Ms(5.0);
+
+/// The time taken for a compressor to respond to a rise in level.
+pub const COMPRESSOR_ATTACK: Ms = Ms(10.0);
+
+/// Parameters for the lookahead limiter applied to the master output.
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct LimiterParams {
+    /// The level in decibels that the output will never exceed.
+    #[serde(default = "default_limiter_threshold_db")]
+    pub threshold_db: f64,
+    /// The time taken for the gain to recover once the signal falls below the threshold.
+    #[serde(default = "default_limiter_release")]
+    pub release: Ms,
+}
+
+/// Parameters for a compressor applied to the speakers of a single installation.
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct CompressorParams {
+    /// Whether or not the compressor is applied.
+    #[serde(default)]
+    pub enabled: bool,
+    /// The level in decibels above which the signal is compressed.
+    #[serde(default = "default_compressor_threshold_db")]
+    pub threshold_db: f64,
+    /// The ratio of input level to output level above the threshold.
+    #[serde(default = "default_compressor_ratio")]
+    pub ratio: f64,
+    /// The time taken for the gain to recover once the signal falls below the threshold.
+    #[serde(default = "default_compressor_release")]
+    pub release: Ms,
+}
+
+/// A lookahead brickwall limiter shared by all output channels.
+///
+/// All channels are delayed by `LIMITER_LOOKAHEAD` so that the gain may be reduced smoothly ahead
+/// of a peak. The same gain is applied to all channels in order to preserve the spatial image.
+#[derive(Clone, Debug)]
+pub struct Limiter {
+    params: LimiterParams,
+    threshold: f32,
+    attack_coeff: f32,
+    release_coeff: f32,
+    lookahead_frames: usize,
+    /// A delay line for each output channel.
+    delay_lines: Vec<DelayLine>,
+    /// The gain required by each frame within the lookahead window.
+    required_gains: Vec<f32>,
+    required_gains_index: usize,
+    /// The currently applied gain.
+    gain: f32,
+}
+
+/// A feed-forward peak compressor applied to a group of output channels.
+#[derive(Clone, Debug)]
+pub struct Compressor {
+    params: CompressorParams,
+    attack_coeff: f32,
+    release_coeff: f32,
+    /// The smoothed peak level of the group of channels.
+    envelope: f32,
+}
+
+impl Default for LimiterParams {
+    fn default() -> Self {
+        LimiterParams {
+            threshold_db: default_limiter_threshold_db(),
+            release: default_limiter_release(),
+        }
+    }
+}
+
+impl Default for CompressorParams {
+    fn default() -> Self {
+        CompressorParams {
+            enabled: false,
+            threshold_db: default_compressor_threshold_db(),
+            ratio: default_compressor_ratio(),
+            release: default_compressor_release(),
+        }
+    }
+}
+
+/// The coefficient of a one-pole smoothing filter with the given time constant in frames.
+fn smoothing_coeff(frames: f64) -> f32 {
+    if frames <= 0.0 {
+        0.0
+    } else {
+        (-1.0 / frames).exp() as f32
+    }
+}
+
+impl Limiter {
+    /// Create a new limiter for the given sample rate.
+    pub fn new(params: LimiterParams, sample_hz: f64) -> Self {
+        let lookahead_frames = LIMITER_LOOKAHEAD.samples(sample_hz) as usize;
+        let delay_lines = (0..MAX_CHANNELS)
+            .map(|_| DelayLine::new(lookahead_frames + 1))
+            .collect();
+        let required_gains = vec![1.0; lookahead_frames + 1];
+        // Reach the required gain over roughly the duration of the lookahead.
+        let attack_coeff = smoothing_coeff(lookahead_frames as f64 / 5.0);
+        let mut limiter = Limiter {
+            params,
+            threshold: 1.0,
+            attack_coeff,
+            release_coeff: 0.0,
+            lookahead_frames,
+            delay_lines,
+            required_gains,
+            required_gains_index: 0,
+            gain: 1.0,
+        };
+        limiter.set_params(params, sample_hz);
+        limiter
+    }
+
+    /// The current parameters of the limiter.
+    pub fn params(&self) -> LimiterParams {
+        self.params
+    }
+
+    /// Update the parameters without resetting the limiter state.
+    pub fn set_params(&mut self, params: LimiterParams, sample_hz: f64) {
+        self.params = params;
+        self.threshold = utils::db_to_linear(params.threshold_db) as f32;
+        self.release_coeff = smoothing_coeff(params.release.samples(sample_hz) as f64);
+    }
+
+    /// Limit the given interleaved buffer in place.
+    ///
+    /// Returns the greatest gain reduction applied throughout the buffer in decibels.
+    pub fn process(&mut self, buffer: &mut [f32], channels: usize) -> f32 {
+        let mut min_gain = 1.0f32;
+        let len = self.required_gains.len();
+        for frame in buffer.chunks_mut(channels) {
+            // The gain required to keep this frame beneath the threshold.
+            let peak = frame.iter().fold(0.0f32, |max, s| s.abs().max(max));
+            let required = if peak > self.threshold {
+                self.threshold / peak
+            } else {
+                1.0
+            };
+            self.required_gains[self.required_gains_index] = required;
+            self.required_gains_index = (self.required_gains_index + 1) % len;
+
+            // Move towards the lowest gain required within the lookahead window.
+            let target = self.required_gains.iter().fold(1.0f32, |min, &g| g.min(min));
+            let coeff = if target < self.gain {
+                self.attack_coeff
+            } else {
+                self.release_coeff
+            };
+            self.gain = target + (self.gain - target) * coeff;
+            min_gain = min_gain.min(self.gain);
+
+            // Apply the gain to the delayed signal, clamping any residual overshoot.
+            let threshold = self.threshold;
+            for (sample, delay_line) in frame.iter_mut().zip(self.delay_lines.iter_mut()) {
+                let delayed = delay_line.process(*sample, self.lookahead_frames);
+                *sample = (delayed * self.gain).max(-threshold).min(threshold);
+            }
+        }
+        -utils::linear_to_db(min_gain as f64) as f32
+    }
+}
+
+impl Compressor {
+    /// Create a new compressor for the given sample rate.
+    pub fn new(params: CompressorParams, sample_hz: f64) -> Self {
+        let attack_coeff = smoothing_coeff(COMPRESSOR_ATTACK.samples(sample_hz) as f64);
+        let mut compressor = Compressor {
+            params,
+            attack_coeff,
+            release_coeff: 0.0,
+            envelope: 0.0,
+        };
+        compressor.set_params(params, sample_hz);
+        compressor
+    }
+
+    /// The current parameters of the compressor.
+    pub fn params(&self) -> CompressorParams {
+        self.params
+    }
+
+    /// Update the parameters without resetting the compressor state.
+    pub fn set_params(&mut self, params: CompressorParams, sample_hz: f64) {
+        self.params = params;
+        self.release_coeff = smoothing_coeff(params.release.samples(sample_hz) as f64);
+    }
+
+    /// Compress the given output `channels` of the interleaved buffer in place.
+    ///
+    /// The level is detected from the loudest of the given channels and the same gain is applied
+    /// to all of them.
+    ///
+    /// Returns the greatest gain reduction applied throughout the buffer in decibels.
+    pub fn process(&mut self, buffer: &mut [f32], n_channels: usize, channels: &[usize]) -> f32 {
+        let threshold_db = self.params.threshold_db;
+        let slope = 1.0 - 1.0 / self.params.ratio.max(1.0);
+        let mut max_reduction_db = 0.0;
+        for frame in buffer.chunks_mut(n_channels) {
+            let peak = channels
+                .iter()
+                .filter(|&&ch| ch < frame.len())
+                .fold(0.0f32, |max, &ch| frame[ch].abs().max(max));
+            let coeff = if peak > self.envelope {
+                self.attack_coeff
+            } else {
+                self.release_coeff
+            };
+            self.envelope = peak + (self.envelope - peak) * coeff;
+
+            let over_db = utils::linear_to_db(self.envelope as f64) - threshold_db;
+            if over_db <= 0.0 {
+                continue;
+            }
+            let reduction_db = over_db * slope;
+            let gain = utils::db_to_linear(-reduction_db) as f32;
+            for &ch in channels.iter().filter(|&&ch| ch < frame.len()) {
+                frame[ch] *= gain;
+            }
+            if reduction_db > max_reduction_db {
+                max_reduction_db = reduction_db;
+            }
+        }
+        max_reduction_db as f32
+    }
+}
+
+fn default_limiter_threshold_db() -> f64 {
+    -1.0
+}
+
+fn default_limiter_release() -> Ms {
+    Ms(100.0)
+}
+
+fn default_compressor_threshold_db() -> f64 {
+    -12.0
+}
+
+fn default_compressor_ratio() -> f64 {
+    4.0
+}
+
+fn default_compressor_release() -> Ms {
+    Ms(250.0)
+}
+
+#[test]
+fn limiter_never_exceeds_threshold() {
+    let params = LimiterParams::default();
+    let mut limiter = Limiter::new(params, 48_000.0);
+    let threshold = utils::db_to_linear(params.threshold_db) as f32;
+    let mut buffer: Vec<f32> = (0..4096).map(|i| if i % 64 < 32 { 2.0 } else { -2.0 }).collect();
+    limiter.process(&mut buffer, 2);
+    assert!(buffer.iter().all(|s| s.abs() <= threshold));
+}
@@ -0,0 +1,140 @@
+//! Simple IIR filters used for processing speaker feeds on the audio output thread.
+//!
+//! Coefficients are calculated via the formulae from Robert Bristow-Johnson's "Audio EQ Cookbook".
+
+use std::f64::consts::PI;
+
+/// The Q of a second-order Butterworth filter.
+pub const BUTTERWORTH_Q: f64 = ::std::f64::consts::FRAC_1_SQRT_2;
+
+/// The normalised coefficients of a second-order filter.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Coefficients {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+}
+
+/// A second-order IIR filter implemented in transposed direct form II.
+#[derive(Copy, Clone, Debug)]
+pub struct Biquad {
+    coefficients: Coefficients,
+    z1: f32,
+    z2: f32,
+}
+
+/// A fourth-order Linkwitz-Riley crossover, producing a low and high band that sum flat.
+#[derive(Copy, Clone, Debug)]
+pub struct Crossover {
+    low: [Biquad; 2],
+    high: [Biquad; 2],
+}
+
+impl Coefficients {
+    /// A pass-through filter.
+    pub const IDENTITY: Self = Coefficients {
+        b0: 1.0,
+        b1: 0.0,
+        b2: 0.0,
+        a1: 0.0,
+        a2: 0.0,
+    };
+
+    fn from_unnormalised(b0: f64, b1: f64, b2: f64, a0: f64, a1: f64, a2: f64) -> Self {
+        Coefficients {
+            b0: (b0 / a0) as f32,
+            b1: (b1 / a0) as f32,
+            b2: (b2 / a0) as f32,
+            a1: (a1 / a0) as f32,
+            a2: (a2 / a0) as f32,
+        }
+    }
+
+    /// A second-order low-pass filter with the given cutoff and Q.
+    pub fn low_pass(cutoff_hz: f64, q: f64, sample_hz: f64) -> Self {
+        let w0 = 2.0 * PI * (cutoff_hz / sample_hz).min(0.49);
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / (2.0 * q);
+        let b1 = 1.0 - cos;
+        let b0 = b1 / 2.0;
+        Self::from_unnormalised(b0, b1, b0, 1.0 + alpha, -2.0 * cos, 1.0 - alpha)
+    }
+
+    /// A second-order high-pass filter with the given cutoff and Q.
+    pub fn high_pass(cutoff_hz: f64, q: f64, sample_hz: f64) -> Self {
+        let w0 = 2.0 * PI * (cutoff_hz / sample_hz).min(0.49);
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / (2.0 * q);
+        let b0 = (1.0 + cos) / 2.0;
+        let b1 = -(1.0 + cos);
+        Self::from_unnormalised(b0, b1, b0, 1.0 + alpha, -2.0 * cos, 1.0 - alpha)
+    }
+}
+
+impl Biquad {
+    /// A silent filter with the given coefficients.
+    pub fn new(coefficients: Coefficients) -> Self {
+        Biquad {
+            coefficients,
+            z1: 0.0,
+            z2: 0.0,
+        }
+    }
+
+    /// Update the coefficients without resetting the filter state.
+    pub fn set_coefficients(&mut self, coefficients: Coefficients) {
+        self.coefficients = coefficients;
+    }
+
+    /// Filter a single sample.
+    pub fn process(&mut self, x: f32) -> f32 {
+        let Coefficients { b0, b1, b2, a1, a2 } = self.coefficients;
+        let y = b0 * x + self.z1;
+        self.z1 = b1 * x - a1 * y + self.z2;
+        self.z2 = b2 * x - a2 * y;
+        y
+    }
+}
+
+impl Crossover {
+    /// A crossover splitting the signal at the given frequency.
+    pub fn new(crossover_hz: f64, sample_hz: f64) -> Self {
+        let low = Biquad::new(Coefficients::low_pass(crossover_hz, BUTTERWORTH_Q, sample_hz));
+        let high = Biquad::new(Coefficients::high_pass(crossover_hz, BUTTERWORTH_Q, sample_hz));
+        Crossover {
+            low: [low; 2],
+            high: [high; 2],
+        }
+    }
+
+    /// Update the crossover frequency without resetting the filter state.
+    pub fn set_crossover_hz(&mut self, crossover_hz: f64, sample_hz: f64) {
+        let low = Coefficients::low_pass(crossover_hz, BUTTERWORTH_Q, sample_hz);
+        let high = Coefficients::high_pass(crossover_hz, BUTTERWORTH_Q, sample_hz);
+        for f in self.low.iter_mut() {
+            f.set_coefficients(low);
+        }
+        for f in self.high.iter_mut() {
+            f.set_coefficients(high);
+        }
+    }
+
+    /// Split the given sample into its `(low, high)` bands.
+    pub fn process(&mut self, x: f32) -> (f32, f32) {
+        let low = self.low[1].process(self.low[0].process(x));
+        let high = self.high[1].process(self.high[0].process(x));
+        (low, high)
+    }
+}
+
+#[test]
+fn low_pass_dc() {
+    let mut lp = Biquad::new(Coefficients::low_pass(100.0, BUTTERWORTH_Q, 48_000.0));
+    let mut y = 0.0;
+    for _ in 0..48_000 {
+        y = lp.process(1.0);
+    }
+    assert!((y - 1.0).abs() < 1e-3);
+}
@@ -2,15 +2,16 @@
 //!
 //! The input stream has a number of `Source`s that read from one or more of the stream's channels.
 
-use audio::source;
+use audio::buffer::Buffer;
+use audio::{self, source};
 use fxhash::FxHashMap;
-use nannou_audio::Buffer;
+use nannou_audio;
 use std::cmp;
 use std::sync::Arc;
 use std::sync::atomic::{self, AtomicBool};
 
-/// Simplified type alias for the nannou audio input stream used by the audio server.
-pub type Stream = nannou_audio::Stream<Model>;
+/// Simplified type alias for the audio input stream used by the audio server.
+pub type Stream = audio::stream::Stream<Model>;
 
 /// The state stored on each device's input audio stream.
 pub struct Model {
@@ -70,7 +71,16 @@ impl Model {
 }
 
 /// The function given to nannou to use for capturing audio for a device.
-pub fn capture(model: &mut Model, buffer: &Buffer) {
+pub fn capture(model: &mut Model, buffer: &nannou_audio::Buffer) {
+    let channels = buffer.channels();
+    capture_buffer(model, &Buffer::new(&buffer[..], channels));
+}
+
+/// Send the given interleaved input buffer to all active realtime sounds.
+///
+/// This is called by `capture` when driven by an audio device, or directly when the server drives
+/// the input itself.
+pub fn capture_buffer(model: &mut Model, buffer: &Buffer<&[f32]>) {
     let Model {
         ref sources,
         ref mut active_sounds,
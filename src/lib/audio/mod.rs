@@ -7,15 +7,28 @@ pub use self::sound::Sound;
 pub use self::source::Source;
 pub use self::speaker::Speaker;
 
+pub mod ambisonics;
+pub mod buffer;
 pub mod dbap;
+pub mod decoding;
+pub mod delay;
 pub mod detection;
 pub mod detector;
+pub mod distance;
+pub mod doppler;
+pub mod dynamics;
 pub mod fft;
+pub mod filter;
 pub mod input;
 pub mod output;
+pub mod recorder;
+pub mod reverb;
 pub mod sound;
 pub mod source;
 pub mod speaker;
+pub mod stream;
+pub mod vbap;
+pub mod virtual_device;
 
 /// The maximum number of audio channels.
 #[cfg(not(feature = "test_with_stereo"))]
@@ -45,6 +58,21 @@ pub const DEFAULT_REALTIME_SOURCE_LATENCY: Ms = Ms(512.0);
 /// The default rolloff decibel amount, used to attenuate speaker gains over distances.
 pub const DEFAULT_DBAP_ROLLOFF_DB: f64 = 4.0;
 
+/// The default order of the ambisonic bus used by the `Ambisonics` panning law.
+pub const DEFAULT_AMBISONIC_ORDER: usize = 3;
+
+/// The maximum delay that may be applied to a speaker's output for alignment.
+pub const MAX_SPEAKER_DELAY: Ms = Ms(500.0);
+
+/// The default frequency at which full-range speaker feeds are crossed over to subwoofers.
+pub const DEFAULT_CROSSOVER_HZ: f64 = 80.0;
+
+/// The speed of sound in metres per second, used for calculating automatic speaker delays.
+pub const SPEED_OF_SOUND_M_PER_SEC: f64 = 343.0;
+
+/// The default elevation of the reference listening point, roughly ear height.
+pub const DEFAULT_LISTENING_ELEVATION: f64 = 1.2;
+
 /// The "blurring" amount applied to the distance function used for calculating DBAP.
 pub const DISTANCE_BLUR: f64 = 0.01;
 
@@ -53,6 +81,56 @@ pub const DEFAULT_PROXIMITY_LIMIT: Metres = Metres(7.0);
 /// Proximity limit squared for efficientcy efficiency.
 pub const DEFAULT_PROXIMITY_LIMIT_2: Metres = Metres(DEFAULT_PROXIMITY_LIMIT.0 * DEFAULT_PROXIMITY_LIMIT.0);
 
+/// The law used to distribute each sound channel across the speakers of the exhibition.
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub enum PanningLaw {
+    /// Distance-Based Amplitude Panning. See the `dbap` module.
+    Dbap,
+    /// Vector Base Amplitude Panning. See the `vbap` module.
+    Vbap,
+    /// Higher-order Ambisonics encoding with an AllRAD decoder. See the `ambisonics` module.
+    Ambisonics,
+}
+
+impl PanningLaw {
+    pub const VARIANT_COUNT: usize = 3;
+
+    /// Produce the index of the panning law variant.
+    pub fn to_index(&self) -> usize {
+        match *self {
+            PanningLaw::Dbap => 0,
+            PanningLaw::Vbap => 1,
+            PanningLaw::Ambisonics => 2,
+        }
+    }
+
+    /// Produce a label for the variant at the given index.
+    pub fn label_from_index(i: usize) -> &'static str {
+        match i {
+            0 => "DBAP",
+            1 => "VBAP",
+            2 => "HOA",
+            _ => "",
+        }
+    }
+
+    /// Produce the panning law for the given variant index.
+    pub fn from_index(i: usize) -> Option<Self> {
+        match i {
+            0 => Some(PanningLaw::Dbap),
+            1 => Some(PanningLaw::Vbap),
+            2 => Some(PanningLaw::Ambisonics),
+            _ => None,
+        }
+    }
+}
+
+impl Default for PanningLaw {
+    fn default() -> Self {
+        PanningLaw::Dbap
+    }
+}
+
 /// Retrieve the desired audio host for the system.
 ///
 /// In general, this uses the default host, but uses the ASIO host if the "asio" feature is enabled
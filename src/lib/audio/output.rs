@@ -3,27 +3,33 @@
 //! The render function is passed to `nannou::App`'s build output stream method and describes how
 //! audio should be rendered to the output.
 
-use crate::audio::{dbap, detection, sound, source, speaker};
-use crate::audio::{Sound, Speaker};
-use crate::audio::{DISTANCE_BLUR, FRAMES_PER_BUFFER, MAX_CHANNELS, MAX_SOUNDS};
+use crate::audio::decoding::{self, Bus as AmbisonicBus, BusId as AmbisonicBusId};
+use crate::audio::{ambisonics, dbap, delay, detection, distance, doppler, dynamics, filter};
+use crate::audio::{recorder, reverb, sound, source};
+use crate::audio::{speaker, vbap};
+use crate::audio::{PanningLaw, Sound, Speaker};
+use crate::audio::{DISTANCE_BLUR, FRAMES_PER_BUFFER, MAX_CHANNELS, MAX_SOUNDS, SAMPLE_RATE};
 use crate::gui;
 use crate::installation;
 use crate::metres::Metres;
 use crate::osc;
+use crate::scene;
 use crate::soundscape;
 use crate::utils;
 use fxhash::{FxHashMap, FxHashSet};
-use nannou_audio::Buffer;
+use crate::audio::buffer::Buffer;
 use std;
 use std::ops::{self, Deref, DerefMut};
 use std::sync::atomic::AtomicUsize;
 use std::sync::{atomic, mpsc, Arc};
+use std::{thread, time};
 use time_calc::Samples;
 
 type Point2 = nannou::glam::DVec2;
+type Point3 = nannou::glam::DVec3;
 
-/// Simplified type alias for the nannou audio output stream used by the audio server.
-pub type Stream = nannou_audio::Stream<Model>;
+/// Simplified type alias for the audio output stream used by the audio server.
+pub type Stream = crate::audio::stream::Stream<Model>;
 
 type Channel = usize;
 
@@ -33,16 +39,80 @@ type Channel = usize;
 // removal etc this way.
 type DbapSpeakerGains = FxHashMap<sound::Id, FxHashMap<Channel, FxHashMap<speaker::Id, f32>>>;
 
+// The most recently recorded ambisonic encoding gains for each channel of each sound per bus.
+type AmbisonicEncodeGains =
+    FxHashMap<(sound::Id, Channel, AmbisonicBusId), [f32; ambisonics::MAX_CHANNELS]>;
+
 /// A sound that is currently active on the audio thread.
 #[derive(Debug)]
 pub struct ActiveSound {
     sound: Sound,
     total_duration_frames: Option<Samples>,
+    /// State for applying the sound's distance model to each channel.
+    distance_channels: Vec<DistanceChannel>,
+    /// State for applying the sound's Doppler effect.
+    doppler: DopplerState,
+}
+
+/// The delay lines used to resample a sound's channels for the Doppler effect.
+struct DopplerState {
+    /// A delay line for each channel of the sound.
+    delay_lines: Vec<delay::DelayLine>,
+    /// The fractional delay at the end of the previous buffer.
+    delay_frames: Option<f64>,
+}
+
+/// The air absorption filter and attenuation applied to a single channel of a sound.
+struct DistanceChannel {
+    filter: filter::Biquad,
+    /// The gain applied at the end of the previous buffer, used to interpolate gain changes.
+    gain: Option<f32>,
 }
 
 /// A speaker that is currently active on the audio thread.
 pub struct ActiveSpeaker {
     speaker: Speaker,
+    /// Delays the speaker's output for alignment with the rest of the array.
+    delay_line: delay::DelayLine,
+    /// The current delay in frames, derived from the speaker's `delay`.
+    delay_frames: usize,
+    /// The linear gain derived from the speaker's `gain_db` trim.
+    gain: f32,
+    /// Splits the feed of full-range speakers for bass management.
+    crossover: filter::Crossover,
+    /// Whether or not the low end of the speaker is routed to at least one subwoofer.
+    ///
+    /// Only these speakers are split at the crossover, so that full-range speakers that share no
+    /// installation with a subwoofer keep their low end.
+    bass_managed: bool,
+}
+
+/// The low-frequency feed for a single subwoofer.
+struct BassRoute {
+    /// The output channel of the subwoofer.
+    output_channel: usize,
+    /// The output channel of each full-range speaker whose low end is routed to the subwoofer,
+    /// along with the gain applied to it.
+    sources: Vec<(usize, f32)>,
+}
+
+/// The compressor applied to the speakers of a single installation.
+struct InstallationCompressor {
+    compressor: dynamics::Compressor,
+    /// The output channels of all speakers assigned to the installation.
+    output_channels: Vec<usize>,
+}
+
+/// A reverb along with its send and the speakers onto which it is mixed.
+///
+/// Sounds that may play on all speakers are sent to the reverb of all panned speakers, while
+/// sounds that are assigned to installations are sent to the reverb of each of their installations.
+struct ReverbBus {
+    reverb: reverb::Reverb,
+    /// The summed reverb send of all assigned sounds, one sample per frame.
+    input: Vec<f32>,
+    /// The output channels of the panned speakers that receive the reverb.
+    output_channels: Vec<usize>,
 }
 
 /// Information relevant to a single `Sound` for the duration of a `render` pass.
@@ -85,9 +155,25 @@ impl ActiveSound {
     /// Create a new `ActiveSound`.
     pub fn new(sound: Sound) -> Self {
         let total_duration_frames = sound.signal.remaining_frames();
+        let distance_channels = (0..sound.channels)
+            .map(|_| DistanceChannel {
+                filter: filter::Biquad::new(filter::Coefficients::IDENTITY),
+                gain: None,
+            })
+            .collect();
+        let max_frames = doppler::MAX_DELAY.samples(SAMPLE_RATE) as usize + 1;
+        let delay_lines = (0..sound.channels)
+            .map(|_| delay::DelayLine::new(max_frames))
+            .collect();
+        let doppler = DopplerState {
+            delay_lines,
+            delay_frames: None,
+        };
         ActiveSound {
             sound,
             total_duration_frames,
+            distance_channels,
+            doppler,
         }
     }
 
@@ -156,8 +242,18 @@ pub struct Model {
     cpu_saving_enabled: bool,
     /// the master volume, controlled via the gui applied at the very end of processing.
     pub master_volume: f32,
+    /// The override of the master volume applied by the soundscape's schedule.
+    pub scheduled_volume: soundscape::schedule::Override,
+    /// The crossfade of the master volume towards a recalled scene's volume, if in progress.
+    ///
+    /// Stores the volumes at the start and end of the crossfade.
+    master_volume_fade: Option<(f32, f32, scene::Crossfade)>,
     /// the dbap rolloff decibel amount, used to attenuate speaker gains over distances.
     pub dbap_rolloff_db: f64,
+    /// The law used to calculate the gain of each speaker for each sound channel.
+    pub panning_law: PanningLaw,
+    /// The order of the ambisonic buses used by the `Ambisonics` panning law.
+    ambisonic_order: usize,
     /// the set of sources that are currently soloed. if not empty, only these sounds should play.
     pub soloed: FxHashSet<source::Id>,
     /// a map from audio sound ids to the audio sounds themselves.
@@ -192,6 +288,53 @@ pub struct Model {
     ///
     /// Only those speakers that are within the `audio::PROXIMITY_LIMIT` will be collected.
     dbap_speakers: Vec<dbap::Speaker>,
+    /// A buffer to re-use for collecting speakers ready for performing the VBAP calc.
+    ///
+    /// Only those speakers that are within the `audio::PROXIMITY_LIMIT` will be collected.
+    vbap_speakers: Vec<vbap::Speaker>,
+    /// A buffer to re-use for collecting the resulting VBAP gains.
+    vbap_gains: Vec<f64>,
+    /// The reference listening position used to calculate automatic speaker delays.
+    listening_point: Point3,
+    /// The centroid of all speakers, used as the distance reference for sounds that may play in
+    /// any installation.
+    speaker_centroid: Option<Point3>,
+    /// The centroid of the speakers assigned to each installation.
+    installation_centroids: FxHashMap<installation::Id, Point3>,
+    /// Delay lines allocated ahead of time for newly inserted speakers, as speakers are inserted on
+    /// the audio thread. The delay lines of removed speakers are returned here for re-use.
+    spare_delay_lines: Vec<delay::DelayLine>,
+    /// The frequency at which full-range speaker feeds are crossed over to the subwoofers.
+    crossover_hz: f64,
+    /// The low-frequency feed for each subwoofer, rebuilt whenever the speaker layout changes.
+    bass_routes: Vec<BassRoute>,
+    /// A buffer for collecting the low band of each output channel, laid out channel by channel.
+    lf_buffer: Vec<f32>,
+    /// The brickwall limiter applied to the master output.
+    limiter: dynamics::Limiter,
+    /// The optional compressor for each installation's group of speakers.
+    compressors: FxHashMap<installation::Id, InstallationCompressor>,
+    /// The reverb of sounds that may play on all speakers.
+    reverb: ReverbBus,
+    /// The reverb of each installation's group of speakers.
+    installation_reverbs: FxHashMap<installation::Id, ReverbBus>,
+    /// Whether or not the master output is currently being recorded.
+    is_recording: bool,
+    /// The ambisonic buses and their decoders, recalculated on the decoding thread when required
+    /// after the layout changes.
+    ambisonic_buses: decoding::Buses,
+    /// The order of the current ambisonic buses.
+    ///
+    /// This may differ from `ambisonic_order` until the recalculated buses are received.
+    ambisonic_buses_order: usize,
+    /// Whether or not the speaker layout has changed since the ambisonic buses were requested.
+    ambisonic_buses_outdated: bool,
+    /// The ID of the request for which the current ambisonic buses were calculated.
+    ambisonic_buses_request: u64,
+    /// The ID of the most recent request sent to the decoding thread.
+    ambisonic_request: u64,
+    /// The last calculated encoding gains used to interpolate between buffers.
+    ambisonic_encode_gains: AmbisonicEncodeGains,
     /// The current value of proximity limit. The limit in meters
     /// for a speaker to be considered in the dbap calculations
     pub proximity_limit_2: Metres,
@@ -200,6 +343,10 @@ pub struct Model {
 struct Channels {
     /// Channel for communicating with the audio detection thread.
     detection: detection::Handle,
+    /// Channel for sending the output buffer to the recorder thread.
+    recorder: recorder::Handle,
+    /// Channel for requesting ambisonic decoders from the decoding thread.
+    decoding: decoding::Handle,
     /// Channel for communicating active sound info to the GUI.
     gui_audio_monitor_msg_tx: gui::monitor::Sender,
     /// A handle to the soundscape thread - for notifying when a sound is complete.
@@ -227,10 +374,22 @@ impl Model {
         gui_audio_monitor_msg_tx: gui::monitor::Sender,
         osc_output_msg_tx: osc::output::Tx,
         soundscape_tx: mpsc::Sender<soundscape::Message>,
+        installation_rms: detection::InstallationRms,
         wav_reader: source::wav::reader::Handle,
     ) -> Self {
         // Spawn the audio detection thread.
-        let detection = detection::spawn(gui_audio_monitor_msg_tx.clone(), osc_output_msg_tx);
+        let detection = detection::spawn(
+            gui_audio_monitor_msg_tx.clone(),
+            osc_output_msg_tx,
+            installation_rms,
+        );
+
+        // Spawn the recorder thread.
+        let recorder = recorder::spawn();
+        let is_recording = false;
+
+        // Spawn the thread on which the ambisonic decoders are calculated.
+        let decoding = decoding::spawn();
 
         // The currently soloed sources (none by default).
         let soloed = Default::default();
@@ -270,12 +429,55 @@ impl Model {
         // For tracking DBAP speaker gains.
         let dbap_speaker_gains = FxHashMap::default();
         let dbap_speakers = Vec::with_capacity(MAX_CHANNELS);
+
+        // For calculating VBAP speaker gains.
+        let vbap_speakers = Vec::with_capacity(MAX_CHANNELS);
+        let vbap_gains = Vec::with_capacity(MAX_CHANNELS);
+
+        // Ambisonic buses are requested as speakers are inserted.
+        let ambisonic_order = super::DEFAULT_AMBISONIC_ORDER;
+        let ambisonic_buses = FxHashMap::default();
+        let ambisonic_buses_order = ambisonic_order;
+        let ambisonic_buses_outdated = false;
+        let ambisonic_buses_request = 0;
+        let ambisonic_request = 0;
+        let ambisonic_encode_gains = FxHashMap::default();
+
+        // The listening point from which automatic speaker delays are calculated.
+        let listening_point = Point3::new(0.0, 0.0, super::DEFAULT_LISTENING_ELEVATION);
+        let speaker_centroid = None;
+        let installation_centroids = Default::default();
+
+        // Speaker delays.
+        let mut spare_delay_lines = Vec::with_capacity(MAX_CHANNELS);
+        spare_delay_lines.extend((0..MAX_CHANNELS).map(|_| speaker_delay_line()));
+
+        // Bass management.
+        let crossover_hz = super::DEFAULT_CROSSOVER_HZ;
+        let bass_routes = Vec::with_capacity(MAX_CHANNELS);
+        let lf_buffer = Vec::with_capacity(MAX_CHANNELS * FRAMES_PER_BUFFER);
+
+        // Dynamics.
+        let limiter = dynamics::Limiter::new(Default::default(), SAMPLE_RATE);
+        let compressors = Default::default();
+
+        // Reverb.
+        let reverb = ReverbBus::new(Default::default());
+        let installation_reverbs = Default::default();
+
         // Initialise the master volume to the default value.
         let master_volume = super::DEFAULT_MASTER_VOLUME;
 
+        // The schedule leaves the master volume unchanged until told otherwise.
+        let scheduled_volume = soundscape::schedule::Override::NONE;
+        let master_volume_fade = None;
+
         // Initialise the rolloff to the default value.
         let dbap_rolloff_db = super::DEFAULT_DBAP_ROLLOFF_DB;
 
+        // DBAP is the default panning law.
+        let panning_law = PanningLaw::default();
+
         // By default, cpu saving mode is not enabled.
         let cpu_saving_enabled = false;
 
@@ -284,6 +486,8 @@ impl Model {
 
         let channels = Channels {
             detection,
+            recorder,
+            decoding,
             gui_audio_monitor_msg_tx,
             soundscape_tx,
             wav_reader,
@@ -293,7 +497,11 @@ impl Model {
             frame_count,
             cpu_saving_enabled,
             master_volume,
+            scheduled_volume,
+            master_volume_fade,
             dbap_rolloff_db,
+            panning_law,
+            ambisonic_order,
             soloed,
             sounds,
             sounds_ordered,
@@ -304,6 +512,26 @@ impl Model {
             channels,
             dbap_speaker_gains,
             dbap_speakers,
+            vbap_speakers,
+            vbap_gains,
+            listening_point,
+            spare_delay_lines,
+            speaker_centroid,
+            installation_centroids,
+            crossover_hz,
+            bass_routes,
+            lf_buffer,
+            limiter,
+            compressors,
+            reverb,
+            installation_reverbs,
+            is_recording,
+            ambisonic_buses,
+            ambisonic_buses_order,
+            ambisonic_buses_outdated,
+            ambisonic_buses_request,
+            ambisonic_request,
+            ambisonic_encode_gains,
             proximity_limit_2,
         }
     }
@@ -319,6 +547,11 @@ impl Model {
     /// Returns `true` if the installation did not yet exist or false otherwise.
     pub fn insert_installation(&mut self, id: installation::Id, computers: usize) {
         self.channels.detection.add_installation(id, computers);
+        if !self.installation_reverbs.contains_key(&id) {
+            let params = self.reverb.reverb.params();
+            self.installation_reverbs.insert(id, ReverbBus::new(params));
+            self.update_reverb_channels();
+        }
     }
 
     /// Remove the installation at the given `Id`.
@@ -330,6 +563,8 @@ impl Model {
     /// Returns `false` if there was no installation for the given `Id`.
     pub fn remove_installation(&mut self, id: &installation::Id) {
         self.channels.detection.remove_installation(*id);
+        self.compressors.remove(id);
+        self.installation_reverbs.remove(id);
 
         // Remove the installation from any speakers.
         for speaker in self.speakers.values_mut() {
@@ -342,42 +577,91 @@ impl Model {
                 set.remove(id);
             }
         }
+
+        self.ambisonic_buses_outdated = true;
+        self.update_bass_routes();
+        self.update_compressor_channels();
+        self.update_reverb_channels();
+        self.update_installation_centroids();
     }
 
     /// Inserts the speaker and sends an `Add` message to the GUI.
     pub fn insert_speaker(&mut self, id: speaker::Id, speaker: Speaker) -> Option<Speaker> {
-        let old_speaker = self
-            .speakers
-            .remove(&id)
-            .map(|ActiveSpeaker { speaker }| speaker);
-        let speaker = ActiveSpeaker { speaker };
+        let old_active = self.speakers.remove(&id);
+
+        // Re-use the delay line and filter state of the old speaker (if there was one) to avoid
+        // clicking. Otherwise take one of the spare delay lines to avoid allocating.
+        let (old_speaker, delay_line, crossover) = match old_active {
+            Some(active) => (Some(active.speaker), active.delay_line, active.crossover),
+            None => {
+                let delay_line = match self.spare_delay_lines.pop() {
+                    Some(mut delay_line) => {
+                        delay_line.clear();
+                        delay_line
+                    }
+                    None => speaker_delay_line(),
+                };
+                let crossover = filter::Crossover::new(self.crossover_hz, SAMPLE_RATE);
+                (None, delay_line, crossover)
+            }
+        };
+        let gain = utils::db_to_linear(speaker.gain_db) as f32;
+        let speaker = ActiveSpeaker {
+            speaker,
+            delay_line,
+            delay_frames: 0,
+            gain,
+            crossover,
+            bass_managed: false,
+        };
         let speaker_msg = gui::SpeakerMessage::Add;
         let msg = gui::AudioMonitorMessage::Speaker(id, speaker_msg);
         self.channels.gui_audio_monitor_msg_tx.push(msg);
         self.speakers.insert(id, speaker);
+        self.ambisonic_buses_outdated = true;
+        self.update_speaker_delays();
+        self.update_bass_routes();
+        self.update_compressor_channels();
+        self.update_reverb_channels();
+        self.update_installation_centroids();
         old_speaker
     }
 
     /// Removes the speaker and sends a `Removed` message to the GUI.
     pub fn remove_speaker(&mut self, id: speaker::Id) -> Option<Speaker> {
-        let removed = self
-            .speakers
-            .remove(&id)
-            .map(|ActiveSpeaker { speaker }| speaker);
+        let removed = self.speakers.remove(&id).map(|active| {
+            recycle_delay_line(&mut self.spare_delay_lines, active.delay_line);
+            active.speaker
+        });
         if removed.is_some() {
             let speaker_msg = gui::SpeakerMessage::Remove;
             let msg = gui::AudioMonitorMessage::Speaker(id, speaker_msg);
             self.channels.gui_audio_monitor_msg_tx.push(msg);
+            self.ambisonic_buses_outdated = true;
+            self.update_speaker_delays();
+            self.update_bass_routes();
+            self.update_compressor_channels();
+            self.update_reverb_channels();
+            self.update_installation_centroids();
         }
         removed
     }
 
     /// Inserts the installation into the speaker with the given `speaker::Id`.
     pub fn insert_speaker_installation(&mut self, id: speaker::Id, inst: installation::Id) -> bool {
-        self.speakers
+        let inserted = self
+            .speakers
             .get_mut(&id)
             .map(|active| active.speaker.installations.insert(inst))
-            .unwrap_or(false)
+            .unwrap_or(false);
+        if inserted {
+            self.ambisonic_buses_outdated = true;
+            self.update_bass_routes();
+            self.update_compressor_channels();
+            self.update_reverb_channels();
+            self.update_installation_centroids();
+        }
+        inserted
     }
 
     /// Removes the installation from the speaker with the given `speaker::Id`.
@@ -386,10 +670,363 @@ impl Model {
         id: speaker::Id,
         inst: &installation::Id,
     ) -> bool {
-        self.speakers
+        let removed = self
+            .speakers
             .get_mut(&id)
             .map(|active| active.speaker.installations.remove(inst))
-            .unwrap_or(false)
+            .unwrap_or(false);
+        if removed {
+            self.ambisonic_buses_outdated = true;
+            self.update_bass_routes();
+            self.update_compressor_channels();
+            self.update_reverb_channels();
+            self.update_installation_centroids();
+        }
+        removed
+    }
+
+    /// Set the elevation of the speaker with the given `speaker::Id`.
+    ///
+    /// Unlike `insert_speaker`, only the state that depends on the speaker's elevation is updated.
+    pub fn set_speaker_elevation(&mut self, id: speaker::Id, elevation: f64) {
+        match self.speakers.get_mut(&id) {
+            None => return,
+            Some(active) => active.speaker.elevation = elevation,
+        }
+        self.ambisonic_buses_outdated = true;
+        self.update_speaker_delays();
+        self.update_installation_centroids();
+    }
+
+    /// Set the level trim of the speaker with the given `speaker::Id`.
+    pub fn set_speaker_gain_db(&mut self, id: speaker::Id, gain_db: f64) {
+        if let Some(active) = self.speakers.get_mut(&id) {
+            active.speaker.gain_db = gain_db;
+            active.gain = utils::db_to_linear(gain_db) as f32;
+        }
+    }
+
+    /// Set the delay compensation of the speaker with the given `speaker::Id`.
+    ///
+    /// The delays of all other speakers are also updated, as the speaker may have been the
+    /// furthest automatically delayed speaker.
+    pub fn set_speaker_delay(&mut self, id: speaker::Id, delay: speaker::Delay) {
+        match self.speakers.get_mut(&id) {
+            None => return,
+            Some(active) => active.speaker.delay = delay,
+        }
+        self.update_speaker_delays();
+    }
+
+    /// Set the reference listening point used to calculate automatic speaker delays.
+    pub fn set_listening_point(&mut self, point: Point3) {
+        self.listening_point = point;
+        self.update_speaker_delays();
+    }
+
+    /// Update the delay in frames for each speaker.
+    ///
+    /// `Auto` delays align all automatically delayed speakers to the one that is furthest from the
+    /// listening point.
+    fn update_speaker_delays(&mut self) {
+        let listening_point = self.listening_point;
+        let distance = |s: &Speaker| s.point.extend(s.elevation).distance(listening_point);
+        let max_distance = self
+            .speakers
+            .values()
+            .filter(|s| s.delay == speaker::Delay::Auto)
+            .map(|s| distance(s))
+            .fold(0.0, f64::max);
+        for active in self.speakers.values_mut() {
+            let delay = match active.speaker.delay {
+                speaker::Delay::Manual(ms) => ms,
+                speaker::Delay::Auto => speaker::auto_delay(distance(&active.speaker), max_distance),
+            };
+            let frames = delay.samples(SAMPLE_RATE).max(0) as usize;
+            active.delay_frames = frames.min(active.delay_line.max_delay_frames());
+        }
+    }
+
+    /// Crossfade the master volume from its current value to the given volume.
+    pub fn fade_master_volume(&mut self, volume: f32, duration: time::Duration) {
+        let crossfade = scene::Crossfade::new(duration);
+        self.master_volume_fade = Some((self.master_volume, volume, crossfade));
+    }
+
+    /// Set the frequency at which full-range speaker feeds are crossed over to the subwoofers.
+    pub fn set_crossover_hz(&mut self, hz: f64) {
+        self.crossover_hz = hz;
+        for active in self.speakers.values_mut() {
+            active.crossover.set_crossover_hz(hz, SAMPLE_RATE);
+        }
+    }
+
+    /// The parameters of the limiter applied to the master output.
+    pub fn limiter(&self) -> dynamics::LimiterParams {
+        self.limiter.params()
+    }
+
+    /// Update the parameters of the limiter applied to the master output.
+    pub fn set_limiter(&mut self, params: dynamics::LimiterParams) {
+        self.limiter.set_params(params, SAMPLE_RATE);
+    }
+
+    /// The parameters shared by the reverb of all speakers and of each installation.
+    pub fn reverb(&self) -> reverb::Params {
+        self.reverb.reverb.params()
+    }
+
+    /// Update the parameters of the reverb of all speakers and of each installation.
+    pub fn set_reverb(&mut self, params: reverb::Params) {
+        self.reverb.reverb.set_params(params, SAMPLE_RATE);
+        for bus in self.installation_reverbs.values_mut() {
+            bus.reverb.set_params(params, SAMPLE_RATE);
+        }
+    }
+
+    /// Whether or not the master output is currently being recorded.
+    pub fn is_recording(&self) -> bool {
+        self.is_recording
+    }
+
+    /// Begin recording the master output, finalising the current recording if there is one.
+    pub fn start_recording(&mut self, recording: recorder::Recording) {
+        self.channels.recorder.start(recording);
+        self.is_recording = true;
+    }
+
+    /// Finalise the current recording of the master output.
+    pub fn stop_recording(&mut self) {
+        self.channels.recorder.stop();
+        self.is_recording = false;
+    }
+
+    /// Update the parameters of the compressor applied to the speakers of the given installation.
+    pub fn set_installation_compressor(
+        &mut self,
+        id: installation::Id,
+        params: dynamics::CompressorParams,
+    ) {
+        match self.compressors.get_mut(&id) {
+            Some(inst) => inst.compressor.set_params(params, SAMPLE_RATE),
+            None => {
+                let compressor = dynamics::Compressor::new(params, SAMPLE_RATE);
+                let output_channels = Vec::with_capacity(MAX_CHANNELS);
+                let inst = InstallationCompressor {
+                    compressor,
+                    output_channels,
+                };
+                self.compressors.insert(id, inst);
+            }
+        }
+        self.update_compressor_channels();
+    }
+
+    /// Update the centroid of all speakers and of each installation's speakers.
+    fn update_installation_centroids(&mut self) {
+        let Model {
+            ref speakers,
+            ref mut speaker_centroid,
+            ref mut installation_centroids,
+            ..
+        } = *self;
+        let centroid = |points: &mut dyn Iterator<Item = Point3>| {
+            let (sum, count) = points.fold((Point3::ZERO, 0), |(sum, n), p| (sum + p, n + 1));
+            if count == 0 {
+                None
+            } else {
+                Some(sum / count as f64)
+            }
+        };
+        let point = |s: &ActiveSpeaker| s.point.extend(s.elevation);
+        *speaker_centroid = centroid(&mut speakers.values().map(point));
+        installation_centroids.clear();
+        let installations: FxHashSet<_> = speakers
+            .values()
+            .flat_map(|s| s.installations.iter().cloned())
+            .collect();
+        for inst in installations {
+            let mut points = speakers
+                .values()
+                .filter(|s| s.installations.contains(&inst))
+                .map(point);
+            if let Some(c) = centroid(&mut points) {
+                installation_centroids.insert(inst, c);
+            }
+        }
+    }
+
+    /// Update the output channels of the speakers compressed by each installation's compressor.
+    fn update_compressor_channels(&mut self) {
+        let Model {
+            ref speakers,
+            ref mut compressors,
+            ..
+        } = *self;
+        for (id, inst) in compressors.iter_mut() {
+            inst.output_channels.clear();
+            let channels = speakers
+                .values()
+                .filter(|s| s.installations.contains(id))
+                .map(|s| s.channel);
+            inst.output_channels.extend(channels);
+        }
+    }
+
+    /// Update the output channels of the panned speakers that receive each reverb.
+    fn update_reverb_channels(&mut self) {
+        let Model {
+            ref speakers,
+            ref mut reverb,
+            ref mut installation_reverbs,
+            ..
+        } = *self;
+        let panned = || speakers.values().filter(|s| s.is_panned());
+        reverb.output_channels.clear();
+        reverb.output_channels.extend(panned().map(|s| s.channel));
+        for (id, bus) in installation_reverbs.iter_mut() {
+            bus.output_channels.clear();
+            let channels = panned()
+                .filter(|s| s.installations.contains(id))
+                .map(|s| s.channel);
+            bus.output_channels.extend(channels);
+        }
+    }
+
+    /// Rebuild the low-frequency feed for each subwoofer.
+    ///
+    /// The low end of each full-range speaker is split evenly between all subwoofers that it is
+    /// routed to.
+    fn update_bass_routes(&mut self) {
+        let Model {
+            ref mut speakers,
+            ref mut bass_routes,
+            ..
+        } = *self;
+        bass_routes.clear();
+        let full_range = || speakers.values().filter(|s| s.is_panned());
+        for sub in speakers.values().filter(|s| !s.is_panned()) {
+            let sources = full_range()
+                .filter(|s| {
+                    sub.installations.is_empty()
+                        || s.installations.intersection(&sub.installations).next().is_some()
+                })
+                .map(|s| (s.channel, 1.0))
+                .collect();
+            let output_channel = sub.channel;
+            bass_routes.push(BassRoute {
+                output_channel,
+                sources,
+            });
+        }
+
+        // Split the low end of each full-range speaker between its subwoofers.
+        for active in speakers.values_mut() {
+            let channel = active.channel;
+            let count = bass_routes
+                .iter()
+                .filter(|r| r.sources.iter().any(|&(ch, _)| ch == channel))
+                .count();
+            active.bass_managed = active.is_panned() && count > 0;
+            if !active.bass_managed {
+                continue;
+            }
+            for route in bass_routes.iter_mut() {
+                for source in route.sources.iter_mut().filter(|source| source.0 == channel) {
+                    source.1 = 1.0 / count as f32;
+                }
+            }
+        }
+    }
+
+    /// The order of the ambisonic buses used by the `Ambisonics` panning law.
+    pub fn ambisonic_order(&self) -> usize {
+        self.ambisonic_order
+    }
+
+    /// Set the order of the ambisonic buses, recalculating their decoders if necessary.
+    ///
+    /// The order is clamped to `ambisonics::MAX_ORDER`.
+    pub fn set_ambisonic_order(&mut self, order: usize) {
+        let order = order.min(ambisonics::MAX_ORDER);
+        if self.ambisonic_order != order {
+            self.ambisonic_order = order;
+            self.ambisonic_buses_outdated = true;
+        }
+    }
+
+    /// Block until the ambisonic buses reflect the current speaker layout and order.
+    ///
+    /// Offline renders call this before each buffer so that their output does not depend on how
+    /// long the decoding thread takes to calculate the decoders.
+    pub fn wait_for_ambisonic_buses(&mut self) {
+        self.request_ambisonic_buses_if_required();
+        self.receive_ambisonic_buses();
+        while self.ambisonic_buses_request != self.ambisonic_request {
+            thread::sleep(time::Duration::from_millis(1));
+            self.receive_ambisonic_buses();
+        }
+    }
+
+    /// Swap in any ambisonic buses calculated by the decoding thread.
+    ///
+    /// The replaced buses are sent back to the decoding thread to be dropped, along with any buses
+    /// calculated for a layout that has since been cleared.
+    fn receive_ambisonic_buses(&mut self) {
+        while let Some(decoded) = self.channels.decoding.pop_decoded() {
+            if decoded.request <= self.ambisonic_buses_request {
+                self.channels.decoding.retire(decoded.buses);
+                continue;
+            }
+            if decoded.order != self.ambisonic_buses_order {
+                self.ambisonic_encode_gains.clear();
+            }
+            self.ambisonic_buses_order = decoded.order;
+            self.ambisonic_buses_request = decoded.request;
+            let buses = std::mem::replace(&mut self.ambisonic_buses, decoded.buses);
+            self.channels.decoding.retire(buses);
+        }
+    }
+
+    /// Request new ambisonic buses if the speaker layout has changed and they are in use.
+    ///
+    /// The buses are only used by the `Ambisonics` panning law and by B-format sounds, so editing
+    /// the layout while neither is in use (e.g. dragging a speaker under DBAP) costs nothing. Any
+    /// number of edits received between two buffers result in a single request.
+    fn request_ambisonic_buses_if_required(&mut self) {
+        if !self.ambisonic_buses_outdated {
+            return;
+        }
+        let required = match self.panning_law {
+            PanningLaw::Ambisonics => true,
+            _ => self.sounds.values().any(|sound| match sound.channel_layout {
+                source::wav::ChannelLayout::AmbiX { .. } => true,
+                _ => false,
+            }),
+        };
+        if required {
+            self.request_ambisonic_buses();
+            self.ambisonic_buses_outdated = false;
+        }
+    }
+
+    /// Send a snapshot of the speaker layout to the decoding thread, which calculates the bus and
+    /// decoder for all speakers and for each installation.
+    fn request_ambisonic_buses(&mut self) {
+        let mut layout = self.channels.decoding.pop_layout();
+        for speaker in self.speakers.values().filter(|s| s.is_panned()) {
+            let point = speaker.point.extend(speaker.elevation);
+            let channel = speaker.channel;
+            let bus = AmbisonicBusId::All;
+            layout.push(decoding::BusSpeaker { bus, point, channel });
+            for &inst in &speaker.installations {
+                let bus = AmbisonicBusId::Installation(inst);
+                layout.push(decoding::BusSpeaker { bus, point, channel });
+            }
+        }
+        self.ambisonic_request += 1;
+        let order = self.ambisonic_order;
+        self.channels.decoding.decode(self.ambisonic_request, order, layout);
     }
 
     /// Inserts the sound and sends a `Start` active sound message to the GUI.
@@ -471,6 +1108,7 @@ impl Model {
         if let Some(sound) = removed {
             // Remove the sound from DBAP gain tracking.
             self.dbap_speaker_gains.remove(&id);
+            self.ambisonic_encode_gains.retain(|&(sound_id, _, _), _| sound_id != id);
             // Notify threads.
             self.channels.notify_sound_end(id, sound);
             true
@@ -492,7 +1130,24 @@ impl Model {
         self.channels.detection.clear_project_specific_data();
         self.frame_count.store(0, atomic::Ordering::Relaxed);
         self.soloed.clear();
-        self.speakers.clear();
+        for (_, active) in self.speakers.drain() {
+            recycle_delay_line(&mut self.spare_delay_lines, active.delay_line);
+        }
+        // Buses still being calculated for the previous project are discarded upon receipt.
+        let buses = std::mem::replace(&mut self.ambisonic_buses, FxHashMap::default());
+        self.channels.decoding.retire(buses);
+        self.ambisonic_buses_outdated = false;
+        self.ambisonic_buses_request = self.ambisonic_request;
+        self.ambisonic_encode_gains.clear();
+        self.bass_routes.clear();
+        self.compressors.clear();
+        self.reverb.reverb.clear();
+        self.reverb.output_channels.clear();
+        self.installation_reverbs.clear();
+        self.speaker_centroid = None;
+        self.installation_centroids.clear();
+        self.scheduled_volume = soundscape::schedule::Override::NONE;
+        self.master_volume_fade = None;
 
         let Model {
             ref mut sounds,
@@ -529,6 +1184,39 @@ impl Channels {
     }
 }
 
+impl ReverbBus {
+    /// A silent reverb bus with the given parameters that is not yet mixed onto any speakers.
+    fn new(params: reverb::Params) -> Self {
+        ReverbBus {
+            reverb: reverb::Reverb::new(params, SAMPLE_RATE),
+            input: Vec::with_capacity(FRAMES_PER_BUFFER),
+            output_channels: Vec::with_capacity(MAX_CHANNELS),
+        }
+    }
+
+    /// Sum the channels of the given interleaved samples onto the reverb send.
+    fn send(&mut self, samples: &[f32], channels: usize, gain: f32) {
+        for (input, frame) in self.input.iter_mut().zip(samples.chunks(channels)) {
+            *input += frame.iter().fold(0.0, |acc, &s| acc + s) * gain;
+        }
+    }
+}
+
+// A delay line long enough for a speaker's maximum delay.
+fn speaker_delay_line() -> delay::DelayLine {
+    let max_frames = super::MAX_SPEAKER_DELAY.samples(SAMPLE_RATE) as usize + 1;
+    delay::DelayLine::new(max_frames)
+}
+
+// Return the delay line of a removed speaker to the spare delay lines for re-use.
+//
+// The delay line is dropped rather than growing the spare delay lines beyond their capacity.
+fn recycle_delay_line(spare: &mut Vec<delay::DelayLine>, delay_line: delay::DelayLine) {
+    if spare.len() < spare.capacity() {
+        spare.push(delay_line);
+    }
+}
+
 /// A simple linear interpolation function.
 ///
 /// This is used to interpolate between previous and current DBAP speaker gains over the duration
@@ -537,12 +1225,44 @@ fn lerp(a: f32, b: f32, lerp: f32) -> f32 {
     a + (b - a) * lerp
 }
 
+/// Step the crossfade of the master volume forward by the given number of frames, if any.
+fn step_master_volume_fade(model: &mut Model, frames: usize) {
+    let is_complete = match model.master_volume_fade {
+        None => return,
+        Some((from, to, ref mut crossfade)) => {
+            let delta = time::Duration::from_secs_f64(frames as f64 / SAMPLE_RATE);
+            let t = crossfade.step(delta) as f32;
+            model.master_volume = lerp(from, to, t);
+            crossfade.is_complete()
+        }
+    };
+    if is_complete {
+        model.master_volume_fade = None;
+    }
+}
+
 /// The function given to nannou to use for rendering.
-pub fn render(model: &mut Model, buffer: &mut Buffer) {
+pub fn render(model: &mut Model, buffer: &mut nannou_audio::Buffer) {
+    let channels = buffer.channels();
+    render_buffer(model, &mut Buffer::new(&mut buffer[..], channels));
+}
+
+/// Render all active sounds to the given interleaved output buffer.
+///
+/// This is called by `render` when driven by an audio device, or directly when the server drives
+/// the output itself.
+pub fn render_buffer(model: &mut Model, buffer: &mut Buffer<&mut [f32]>) {
+    step_master_volume_fade(model, buffer.len_frames());
+    model.request_ambisonic_buses_if_required();
+    model.receive_ambisonic_buses();
+
     let Model {
         master_volume,
+        scheduled_volume,
         cpu_saving_enabled,
         dbap_rolloff_db,
+        panning_law,
+        ambisonic_buses_order,
         ref soloed,
         ref mut frame_count,
         ref mut sounds,
@@ -553,8 +1273,29 @@ pub fn render(model: &mut Model, buffer: &mut Buffer) {
         ref mut speakers,
         ref mut dbap_speaker_gains,
         ref mut dbap_speakers,
+        ref mut vbap_speakers,
+        ref mut vbap_gains,
+        ref mut ambisonic_buses,
+        ref mut ambisonic_encode_gains,
+        ref bass_routes,
+        ref mut lf_buffer,
+        ref mut limiter,
+        ref mut compressors,
+        ref mut reverb,
+        ref mut installation_reverbs,
+        is_recording,
         ref channels,
         proximity_limit_2,
+        listening_point,
+        speaker_centroid,
+        ref installation_centroids,
+        crossover_hz: _,
+        master_volume_fade: _,
+        ambisonic_order: _,
+        ambisonic_buses_outdated: _,
+        ambisonic_buses_request: _,
+        ambisonic_request: _,
+        spare_delay_lines: _,
     } = *model;
 
     // Always silence the buffer to begin.
@@ -566,10 +1307,12 @@ pub fn render(model: &mut Model, buffer: &mut Buffer) {
     //
     // TODO: Should probably move this into model for re-use, but its not showing up in
     // profiling.
+    //
+    // Subwoofers are excluded as they only receive the low end of other speakers.
     let channels_to_speakers: FxHashMap<_, _> = speakers
         .iter()
         .filter_map(|(&id, s)| {
-            if s.channel < buffer.channels() {
+            if s.channel < buffer.channels() && s.is_panned() {
                 Some((s.channel, id))
             } else {
                 None
@@ -596,6 +1339,66 @@ pub fn render(model: &mut Model, buffer: &mut Buffer) {
     sound_channels.clear();
     dbap_speaker_infos.clear();
 
+    // Silence the ambisonic buses.
+    //
+    // These are used by the `Ambisonics` panning law as well as by B-format sounds.
+    let ambisonic_channels = ambisonics::channel_count(ambisonic_buses_order);
+    for bus in ambisonic_buses.values_mut() {
+        bus.samples.clear();
+        bus.samples.resize(buffer.len_frames() * ambisonic_channels, 0.0);
+        bus.has_signal = false;
+    }
+
+    // Silence the reverb sends.
+    for bus in std::iter::once(&mut *reverb).chain(installation_reverbs.values_mut()) {
+        bus.input.clear();
+        bus.input.resize(buffer.len_frames(), 0.0);
+    }
+
+    // The point from which a sound's distance and velocity are measured for the given reference.
+    let reference_point = |reference: distance::Reference, sound: &Sound| -> Option<Point3> {
+        match reference {
+            distance::Reference::Listener => Some(listening_point),
+            distance::Reference::InstallationCentroid => match sound.installations {
+                sound::Installations::All => speaker_centroid,
+                sound::Installations::Set(ref set) => {
+                    let sound_point = sound.position.point.extend(sound.position.elevation);
+                    set.iter()
+                        .filter_map(|id| installation_centroids.get(id))
+                        .min_by(|a, b| {
+                            let da = a.distance_squared(sound_point);
+                            let db = b.distance_squared(sound_point);
+                            da.partial_cmp(&db).unwrap_or(std::cmp::Ordering::Equal)
+                        })
+                        .cloned()
+                }
+            },
+        }
+    };
+
+    // The point from which VBAP measures the direction of a sound, i.e. the centroid of the
+    // speakers of the sound's installations.
+    //
+    // This only changes with the speaker layout so that the direction of a sound does not depend
+    // on which speakers happen to be within proximity of it.
+    let vbap_listening_point = |sound: &Sound| -> Option<Point2> {
+        let centroid = match sound.installations {
+            sound::Installations::All => speaker_centroid,
+            sound::Installations::Set(ref set) => {
+                let (sum, count) = set
+                    .iter()
+                    .filter_map(|id| installation_centroids.get(id))
+                    .fold((Point3::ZERO, 0), |(sum, n), &c| (sum + c, n + 1));
+                if count == 0 {
+                    None
+                } else {
+                    Some(sum / count as f64)
+                }
+            }
+        };
+        centroid.map(|c| c.truncate())
+    };
+
     // For each sound, request `buffer.len()` number of frames and push them to the sound's
     // `unmixed_sounds` buffer.
     for (sound_i, ordered_sound) in sounds_ordered.iter_mut().enumerate() {
@@ -621,7 +1424,12 @@ pub fn render(model: &mut Model, buffer: &mut Buffer) {
         let msg = gui::AudioMonitorMessage::ActiveSound(sound_id, update);
         channels.gui_audio_monitor_msg_tx.push(msg);
 
-        let ActiveSound { ref mut sound, .. } = *sound;
+        let ActiveSound {
+            ref mut sound,
+            ref mut distance_channels,
+            doppler: ref mut doppler_state,
+            ..
+        } = *sound;
 
         // The number of samples to request from the sound for this buffer.
         let num_samples = buffer.len_frames() * sound.channels;
@@ -674,20 +1482,196 @@ pub fn render(model: &mut Model, buffer: &mut Buffer) {
             }
         }
 
+        // Resample the sound via a fractional delay for the source's Doppler effect.
+        match sound.doppler {
+            None => doppler_state.delay_frames = None,
+            Some(ref model) => {
+                if let Some(reference) = reference_point(model.reference, sound) {
+                    let sound_point = sound.position.point.extend(sound.position.elevation);
+                    let offset = sound_point - reference;
+                    let distance = offset.length();
+                    let radial_velocity = if distance > std::f64::EPSILON {
+                        sound.velocity.dot(offset / distance)
+                    } else {
+                        0.0
+                    };
+                    let target_delay = model.delay_frames(distance);
+                    let mut delay = doppler_state.delay_frames.unwrap_or(target_delay);
+                    let slope = model.delay_slope(radial_velocity, delay, target_delay);
+                    for frame_i in 0..buffer.len_frames() {
+                        delay += slope;
+                        let start = frame_i * n_channels;
+                        let frame = &mut ordered_sound.unmixed_samples[start..start + n_channels];
+                        for (sample, line) in frame.iter_mut().zip(&mut doppler_state.delay_lines) {
+                            line.write(*sample);
+                            *sample = line.read_interpolated(delay);
+                        }
+                    }
+                    let max_delay = doppler_state
+                        .delay_lines
+                        .first()
+                        .map(|line| line.max_delay_frames() as f64)
+                        .unwrap_or(0.0);
+                    doppler_state.delay_frames = Some(delay.max(0.0).min(max_delay));
+                }
+            }
+        }
+
+        // Apply the source's distance model to each channel of the sound.
+        if let Some(ref model) = sound.distance {
+            let sound_point = sound.position.point.extend(sound.position.elevation);
+            if let Some(reference) = reference_point(model.reference, sound) {
+                // B-format channels are not spatially located, so share the sound's position.
+                let is_ambix = match sound.channel_layout {
+                    source::wav::ChannelLayout::AmbiX { .. } => true,
+                    source::wav::ChannelLayout::Discrete => false,
+                };
+                let frames_len = buffer.len_frames();
+                for (channel, state) in distance_channels.iter_mut().enumerate().take(n_channels) {
+                    let point = match is_ambix {
+                        true => sound_point,
+                        false => sound.channel_point(channel).unwrap_or(sound_point),
+                    };
+                    let distance = Metres(point.distance(reference));
+                    let cutoff_hz = model.cutoff_hz(distance);
+                    let coefficients = filter::Coefficients::low_pass(
+                        cutoff_hz,
+                        filter::BUTTERWORTH_Q,
+                        SAMPLE_RATE,
+                    );
+                    state.filter.set_coefficients(coefficients);
+                    let gain = model.gain(distance) as f32;
+                    let previous_gain = state.gain.unwrap_or(gain);
+                    state.gain = Some(gain);
+                    for frame_i in 0..frames_len {
+                        let lerp = frame_i as f32 / frames_len as f32;
+                        let frame_gain = previous_gain + (gain - previous_gain) * lerp;
+                        let sample = &mut ordered_sound.unmixed_samples[frame_i * n_channels + channel];
+                        *sample = state.filter.process(*sample) * frame_gain;
+                    }
+                }
+            }
+        }
+
+        // Sum the sound's channels onto the reverb send of each of its installations, split
+        // evenly. The dry signal of the sound is attenuated by the reverb mix in proportion to the
+        // send, so that sounds without any send are unaffected by the mix.
+        if sound.reverb_send > 0.0 {
+            let samples = &ordered_sound.unmixed_samples;
+            match sound.installations {
+                sound::Installations::All => reverb.send(samples, n_channels, sound.reverb_send),
+                sound::Installations::Set(ref set) => {
+                    let send = sound.reverb_send / (set.len().max(1) as f32).sqrt();
+                    for id in set {
+                        if let Some(bus) = installation_reverbs.get_mut(id) {
+                            bus.send(samples, n_channels, send);
+                        }
+                    }
+                }
+            }
+            let dry_gain = reverb.reverb.params().dry_gain();
+            let dry_gain = 1.0 - sound.reverb_send * (1.0 - dry_gain);
+            if dry_gain < 1.0 {
+                for sample in ordered_sound.unmixed_samples.iter_mut() {
+                    *sample *= dry_gain;
+                }
+            }
+        }
+
         // Mix the audio from the signal onto each of the output channels.
         if speakers.is_empty() {
             continue;
         }
 
+        // Sounds are encoded onto the ambisonic bus of each of their installations, split evenly.
+        let is_assigned = |bus_id: &AmbisonicBusId| match (*bus_id, &sound.installations) {
+            (AmbisonicBusId::All, &sound::Installations::All) => true,
+            (AmbisonicBusId::Installation(ref id), &sound::Installations::Set(ref set)) => {
+                set.contains(id)
+            }
+            _ => false,
+        };
+        let assigned_buses = ambisonic_buses.keys().filter(|id| is_assigned(id)).count();
+        let bus_gain = 1.0 / (assigned_buses.max(1) as f64).sqrt();
+        let frames_len = buffer.len_frames();
+
+        // B-format sounds are rotated by the orientation of the sound and added directly to the
+        // ambisonic buses, regardless of the current panning law.
+        if let source::wav::ChannelLayout::AmbiX { order } = sound.channel_layout {
+            let order = order.min(ambisonic_buses_order);
+            let sound_ambisonic_channels = ambisonics::channel_count(order).min(n_channels);
+            let radians = (sound.position.radians + sound.channel_radians) as f64;
+            let rotation = ambisonics::YawRotation::new(radians);
+            let mut frame = [0.0; ambisonics::MAX_CHANNELS];
+            for (&bus_id, bus) in ambisonic_buses.iter_mut() {
+                if !is_assigned(&bus_id) {
+                    continue;
+                }
+                for frame_i in 0..frames_len {
+                    let start = frame_i * n_channels;
+                    let samples = &ordered_sound.unmixed_samples[start..start + n_channels];
+                    for (f, &s) in frame.iter_mut().zip(samples).take(sound_ambisonic_channels) {
+                        *f = s;
+                    }
+                    rotation.apply(order, &mut frame);
+                    let start = frame_i * ambisonic_channels;
+                    let bus_frame = &mut bus.samples[start..start + ambisonic_channels];
+                    for (s, &f) in bus_frame.iter_mut().zip(&frame[..sound_ambisonic_channels]) {
+                        *s += f * bus_gain as f32;
+                    }
+                }
+                bus.has_signal = true;
+            }
+            continue;
+        }
+
+        // When rendering via ambisonics, encode each channel of the sound onto the bus of each of
+        // its installations. The buses are decoded to the speakers once all sounds are encoded.
+        if let PanningLaw::Ambisonics = panning_law {
+            let mut coefficients = [0.0; ambisonics::MAX_CHANNELS];
+            for (sound_channel, channel_point) in sound.channel_points().enumerate() {
+                for (&bus_id, bus) in ambisonic_buses.iter_mut() {
+                    if !is_assigned(&bus_id) {
+                        continue;
+                    }
+                    let direction = bus.decoder.direction(channel_point);
+                    ambisonics::encode(direction, ambisonic_buses_order, &mut coefficients);
+                    let mut current = [0.0; ambisonics::MAX_CHANNELS];
+                    for (gain, &coefficient) in current.iter_mut().zip(coefficients.iter()) {
+                        *gain = (coefficient * bus_gain) as f32;
+                    }
+                    let key = (sound_id, sound_channel, bus_id);
+                    let previous = ambisonic_encode_gains.get(&key).cloned().unwrap_or(current);
+                    for frame_i in 0..frames_len {
+                        let lerp_amt = frame_i as f32 / frames_len as f32;
+                        let sample_i = frame_i * n_channels + sound_channel;
+                        let sample = ordered_sound.unmixed_samples[sample_i];
+                        let start = frame_i * ambisonic_channels;
+                        let bus_frame = &mut bus.samples[start..start + ambisonic_channels];
+                        for (acn, s) in bus_frame.iter_mut().enumerate() {
+                            *s += sample * lerp(previous[acn], current[acn], lerp_amt);
+                        }
+                    }
+                    ambisonic_encode_gains.insert(key, current);
+                    bus.has_signal = true;
+                }
+            }
+            continue;
+        }
+
         // Get the currently stored DBAP speaker gains for this sound.
         let dbap_speaker_gains = dbap_speaker_gains
             .entry(sound_id)
             .or_insert_with(FxHashMap::default);
 
+        // The listening point used if VBAP is the current panning law.
+        let vbap_listener = vbap_listening_point(sound);
+
         // Collect a `SoundChannel` for every channel in every sound.
         for (sound_channel, channel_point) in sound.channel_points().enumerate() {
             // Update the dbap_speakers buffer with their distances to this sound channel.
             dbap_speakers.clear();
+            vbap_speakers.clear();
 
             // Get the DBAP gains for this channel of the sound.
             let dbap_speaker_gains = dbap_speaker_gains
@@ -705,10 +1689,12 @@ pub fn render(model: &mut Model, buffer: &mut Buffer) {
                 };
                 let active = &speakers[speaker_id];
                 let speaker_point = &active.speaker.point;
+                let speaker_elevation = active.speaker.elevation;
 
                 // Get the current gain by performing DBAP calc.
-                let channel_point_f = Point2::new(channel_point.x, channel_point.y);
-                let speaker_point_f = Point2::new(speaker_point.x, speaker_point.y);
+                let channel_point_f = Point3::new(channel_point.x, channel_point.y, channel_point.z);
+                let speaker_point_f =
+                    Point3::new(speaker_point.x, speaker_point.y, speaker_elevation);
 
                 // Get the squared distance between the channel and speaker.
                 let distance_2 =
@@ -756,6 +1742,13 @@ pub fn render(model: &mut Model, buffer: &mut Buffer) {
                 };
                 dbap_speakers.push(speaker);
                 dbap_speaker_infos.push(dbap_speaker_info);
+
+                // Also collect the `vbap::Speaker` in case VBAP is the current panning law.
+                let speaker = vbap::Speaker {
+                    point: speaker_point_f.truncate(),
+                    weight,
+                };
+                vbap_speakers.push(speaker);
             }
 
             // Create the speaker infos range.
@@ -767,10 +1760,25 @@ pub fn render(model: &mut Model, buffer: &mut Buffer) {
                 continue;
             }
 
-            // Update the speaker gains.
-            let current_gains = dbap::SpeakerGains::new(&dbap_speakers, dbap_rolloff_db);
-            for (info_i, current_gain) in speaker_infos_range.clone().zip(current_gains) {
-                dbap_speaker_infos[info_i].current_gain = current_gain as _;
+            // Update the speaker gains using the current panning law.
+            //
+            // Sounds rendered via ambisonics are encoded above and never reach this point.
+            match panning_law {
+                PanningLaw::Dbap | PanningLaw::Ambisonics => {
+                    let current_gains = dbap::SpeakerGains::new(&dbap_speakers, dbap_rolloff_db);
+                    for (info_i, current_gain) in speaker_infos_range.clone().zip(current_gains) {
+                        dbap_speaker_infos[info_i].current_gain = current_gain as _;
+                    }
+                }
+                PanningLaw::Vbap => {
+                    let channel_point_f = Point2::new(channel_point.x, channel_point.y);
+                    let listener = vbap_listener.unwrap_or(channel_point_f);
+                    vbap::speaker_gains(channel_point_f, listener, &vbap_speakers, vbap_gains);
+                    let current_gains = vbap_gains.iter();
+                    for (info_i, &current_gain) in speaker_infos_range.clone().zip(current_gains) {
+                        dbap_speaker_infos[info_i].current_gain = current_gain as _;
+                    }
+                }
             }
 
             // Create the `SoundChannel` ready for mixing.
@@ -830,6 +1838,53 @@ pub fn render(model: &mut Model, buffer: &mut Buffer) {
         }
     }
 
+    // Decode each of the ambisonic buses onto their speakers.
+    for bus in ambisonic_buses.values().filter(|bus| bus.has_signal) {
+        for (frame_i, frame) in buffer.frames_mut().enumerate() {
+            let start = frame_i * ambisonic_channels;
+            let bus_frame = &bus.samples[start..start + ambisonic_channels];
+            for (row, &output_channel) in bus.output_channels.iter().enumerate() {
+                if frame.len() <= output_channel {
+                    continue;
+                }
+                let sample = bus_frame
+                    .iter()
+                    .enumerate()
+                    .fold(0.0, |acc, (acn, &s)| acc + s * bus.decoder.gain(row, acn) as f32);
+                frame[output_channel] += sample;
+            }
+        }
+    }
+
+    // Mix each reverb onto the panned speakers that receive it.
+    //
+    // Each speaker receives a different combination of the delay lines so that the reverb is
+    // decorrelated across the array.
+    let wet_gain = reverb.reverb.params().wet_gain();
+    if wet_gain > 0.0 {
+        let n_channels = buffer.channels();
+        for bus in std::iter::once(&mut *reverb).chain(installation_reverbs.values_mut()) {
+            let ReverbBus {
+                ref mut reverb,
+                ref input,
+                ref output_channels,
+            } = *bus;
+            let reverb_channels = output_channels.iter().filter(|&&ch| ch < n_channels).count();
+            let norm = wet_gain / (reverb::LINES as f32 * reverb_channels.max(1) as f32).sqrt();
+            for (frame, &input) in buffer.frames_mut().zip(input.iter()) {
+                let outputs = reverb.process(input);
+                let panned = output_channels.iter().filter(|&&ch| ch < n_channels);
+                for (i, &channel) in panned.enumerate() {
+                    let wet = outputs
+                        .iter()
+                        .enumerate()
+                        .fold(0.0, |acc, (line, &y)| acc + y * reverb::output_sign(i, line));
+                    frame[channel] += wet * norm;
+                }
+            }
+        }
+    }
+
     // Send output buffer to detection thread for analysis.
     let (mut detection_buffer, mut output_info) = channels.detection.pop_output_buffer();
     detection_buffer.extend(buffer.iter().cloned());
@@ -850,34 +1905,118 @@ pub fn render(model: &mut Model, buffer: &mut Buffer) {
     for sound_id in exhausted_sounds.drain(..) {
         // Remove the sound from DBAP gain tracking.
         dbap_speaker_gains.remove(&sound_id);
+        ambisonic_encode_gains.retain(|&(id, _, _), _| id != sound_id);
         // Send this with the `End` message to avoid de-allocating on audio thread.
         let sound = sounds.remove(&sound_id).unwrap();
         // Notify the other threads.
         channels.notify_sound_end(sound_id, sound);
     }
 
-    // Apply the master volume.
+    // Apply the master volume, as overridden by the schedule.
+    let master_volume = scheduled_volume.apply(master_volume as f64).max(0.0) as f32;
     for sample in buffer.iter_mut() {
         *sample *= master_volume;
     }
 
+    // Bass management: split each full-range feed at the crossover and route the low band to the
+    // subwoofers.
+    if !bass_routes.is_empty() {
+        let frames_len = buffer.len_frames();
+        let n_channels = buffer.channels();
+        lf_buffer.clear();
+        lf_buffer.resize(n_channels * frames_len, 0.0);
+        for active in speakers.values_mut() {
+            let channel = active.speaker.channel;
+            if n_channels <= channel || !active.bass_managed {
+                continue;
+            }
+            let lf = &mut lf_buffer[channel * frames_len..(channel + 1) * frames_len];
+            for (frame, lf) in buffer.frames_mut().zip(lf.iter_mut()) {
+                let (low, high) = active.crossover.process(frame[channel]);
+                frame[channel] = high;
+                *lf = low;
+            }
+        }
+        for route in bass_routes.iter() {
+            if n_channels <= route.output_channel {
+                continue;
+            }
+            for (frame_i, frame) in buffer.frames_mut().enumerate() {
+                let sum = route
+                    .sources
+                    .iter()
+                    .filter(|&&(ch, _)| ch < n_channels)
+                    .fold(0.0, |acc, &(ch, gain)| acc + lf_buffer[ch * frames_len + frame_i] * gain);
+                frame[route.output_channel] = sum;
+            }
+        }
+    }
+
+    // Apply each speaker's level trim and alignment delay to its output channel.
+    for active in speakers.values_mut() {
+        let channel = active.speaker.channel;
+        if buffer.channels() <= channel {
+            continue;
+        }
+        let ActiveSpeaker {
+            ref mut delay_line,
+            delay_frames,
+            gain,
+            ..
+        } = *active;
+        for frame in buffer.frames_mut() {
+            frame[channel] = delay_line.process(frame[channel], delay_frames) * gain;
+        }
+    }
+
+    // Compress the speakers of each installation, reporting gain reduction to the GUI.
+    let n_channels = buffer.channels();
+    for (&installation, inst) in compressors.iter_mut() {
+        if !inst.compressor.params().enabled {
+            continue;
+        }
+        let gain_reduction_db = inst.compressor.process(buffer, n_channels, &inst.output_channels);
+        let msg = gui::AudioMonitorMessage::Compressor { installation, gain_reduction_db };
+        channels.gui_audio_monitor_msg_tx.push(msg);
+    }
+
+    // Limit the master output so that stacked sounds never clip the interface.
+    let gain_reduction_db = limiter.process(buffer, n_channels);
+
+    // Send the final output to the recorder thread.
+    if is_recording {
+        let mut recorder_buffer = channels.recorder.pop_buffer();
+        recorder_buffer.extend(buffer.iter().cloned());
+        channels.recorder.write(recorder_buffer, n_channels);
+    }
+
+    // Forward recording failures so that the GUI may stop showing the recording as active.
+    while let Some(failure) = channels.recorder.pop_failure() {
+        let msg = gui::AudioMonitorMessage::RecordingFailed(failure);
+        channels.gui_audio_monitor_msg_tx.push(msg);
+    }
+
     // Find the peak amplitude and send it via the monitor channel.
     let peak = buffer.iter().fold(0.0, |peak, &s| s.max(peak));
     channels
         .gui_audio_monitor_msg_tx
-        .push(gui::AudioMonitorMessage::Master { peak });
+        .push(gui::AudioMonitorMessage::Master { peak, gain_reduction_db });
 
     // Step the frame count.
     frame_count.fetch_add(buffer.len_frames(), atomic::Ordering::Relaxed);
 }
 
+/// The location of the channel at the given index for a sound at the given point.
+///
+/// Channels are spread evenly around a horizontal circle at the elevation of the sound, i.e. the
+/// `z` component of `sound_point` is shared by all channels.
 pub fn channel_point(
-    sound_point: Point2,
+    sound_point: Point3,
     channel_index: usize,
     total_channels: usize,
     spread: Metres,
     radians: f32,
-) -> Point2 {
+) -> Point3 {
     assert!(channel_index < total_channels);
     if total_channels == 1 {
         sound_point
@@ -888,19 +2027,19 @@ pub fn channel_point(
         let (rel_x, rel_y) = utils::rad_mag_to_x_y(radians, spread);
         let x: Metres = sound_point.x + (rel_x);
         let y: Metres = sound_point.y + (rel_y);
-        Point2::new(x, y)
+        Point3::new(x, y, sound_point.z)
     }
 }
 
 /// Tests whether or not the given speaker position is within the `PROXIMITY_LIMIT` distance of the
 /// given `point` (normally a `Sound`'s channel position).
 pub fn speaker_is_in_proximity(
-    point: &Point2,
-    speaker: &Point2,
+    point: &Point3,
+    speaker: &Point3,
     proximity_limit_2: Metres,
 ) -> bool {
-    let point_f = Point2::new(point.x, point.y);
-    let speaker_f = Point2::new(speaker.x, speaker.y);
+    let point_f = Point3::new(point.x, point.y, point.z);
+    let speaker_f = Point3::new(speaker.x, speaker.y, speaker.z);
     let distance_2 = point_f.distance(speaker_f);
     distance_2 < proximity_limit_2
 }
\ No newline at end of file
@@ -0,0 +1,489 @@
+//! The thread on which the master output is recorded to disk.
+//!
+//! While recording, the audio output thread copies the final output buffer into a re-usable buffer
+//! and sends it to the recorder thread via a lock-free queue. The recorder thread writes the
+//! buffer to either a single multichannel WAV or a mono WAV per speaker and returns the buffer to
+//! the queue for re-use.
+//!
+//! As the size of a WAV is limited to 4 GiB, long recordings are continued within numbered files,
+//! e.g. `master.wav`, `master-2.wav`, `master-3.wav`. Failures are reported back via the handle so
+//! that the GUI may stop showing the recording as active.
+
+use super::{FRAMES_PER_BUFFER, MAX_CHANNELS};
+use crossbeam::queue::SegQueue;
+use hound;
+use slug::slugify;
+use std::fs;
+use std::io;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::{cmp, thread, time};
+
+/// The number of buffers to prepare for cycling between the audio output and recorder threads.
+const BUFFERS_TO_PREPARE: usize = 8;
+
+/// The file stem used for multichannel recordings.
+const MASTER_FILE_STEM: &'static str = "master";
+
+/// The maximum size of each WAV file before the recording continues within a new file.
+///
+/// This leaves some headroom beneath the 4 GiB limit of the RIFF chunk size for the header.
+pub const MAX_FILE_BYTES: u64 = 4_000_000_000;
+
+/// The type used for queueing messages for processing by the recorder thread.
+type MessageQueue = Arc<SegQueue<Message>>;
+
+/// The type used for queueing buffers for re-use.
+type BufferQueue = Arc<SegQueue<Vec<f32>>>;
+
+/// The type used for reporting failures from the recorder thread.
+type FailureQueue = Arc<SegQueue<Failure>>;
+
+/// The type of WAV writer used for all recordings.
+type WavWriter = hound::WavWriter<io::BufWriter<fs::File>>;
+
+/// Describes a recording of the master output.
+#[derive(Clone, Debug)]
+pub struct Recording {
+    /// The directory in which the recording's WAV files will be written.
+    pub directory: PathBuf,
+    /// The layout of the recorded files.
+    pub mode: Mode,
+}
+
+/// The way in which the output channels are written to disk.
+#[derive(Clone, Debug)]
+pub enum Mode {
+    /// All output channels are written to a single interleaved WAV.
+    Multichannel,
+    /// Each speaker's channel is written to its own mono WAV, named after the speaker.
+    PerSpeaker(Vec<SpeakerFile>),
+}
+
+/// The output channel and name of a speaker recorded to its own file.
+#[derive(Clone, Debug)]
+pub struct SpeakerFile {
+    pub channel: usize,
+    pub name: String,
+}
+
+/// A recording that was abandoned due to an error.
+#[derive(Debug)]
+pub struct Failure {
+    /// The directory of the failed recording.
+    pub directory: PathBuf,
+    /// The error that caused the failure.
+    pub error: hound::Error,
+}
+
+/// Writes 32-bit float WAV files, continuing within a new numbered file before the current file
+/// reaches the size limit of the format.
+pub struct Writer {
+    /// The path of the first file. Subsequent files have the part number appended to the stem.
+    path: PathBuf,
+    spec: hound::WavSpec,
+    /// The number of frames written to each file before continuing within the next.
+    frames_per_file: usize,
+    /// The part number of the current file, starting from `1`.
+    part: usize,
+    /// The number of frames written to the current file.
+    frames: usize,
+    wav: WavWriter,
+}
+
+/// Messages received by the recorder thread.
+enum Message {
+    Start(Recording),
+    Write(Vec<f32>, usize),
+    Stop,
+    Exit,
+}
+
+/// A handle for communicating with the recorder thread.
+pub struct Handle {
+    tx: MessageQueue,
+    thread: Arc<Mutex<Option<thread::JoinHandle<()>>>>,
+    buffer_rx: BufferQueue,
+    failure_rx: FailureQueue,
+}
+
+/// The recording currently being written by the recorder thread.
+struct Active {
+    recording: Recording,
+    /// The writers are created upon receiving the first buffer, once the number of channels is
+    /// known.
+    writers: Vec<(Option<usize>, Writer)>,
+    /// The maximum size of each file.
+    max_file_bytes: u64,
+}
+
+impl Handle {
+    /// Begin a new recording, finalising the current recording if there is one.
+    pub fn start(&self, recording: Recording) {
+        self.tx.push(Message::Start(recording));
+    }
+
+    /// Send an interleaved buffer of the output to be written to the current recording.
+    pub fn write(&self, samples: Vec<f32>, channels: usize) {
+        self.tx.push(Message::Write(samples, channels));
+    }
+
+    /// Finalise the current recording.
+    pub fn stop(&self) {
+        self.tx.push(Message::Stop);
+    }
+
+    /// Pop the next available buffer for use off the queue.
+    pub fn pop_buffer(&self) -> Vec<f32> {
+        let mut buffer = self.buffer_rx.pop().unwrap_or_else(Vec::new);
+        buffer.clear();
+        buffer
+    }
+
+    /// Pop the next failure reported by the recorder thread, if any.
+    pub fn pop_failure(&self) -> Option<Failure> {
+        self.failure_rx.pop()
+    }
+
+    /// Stops the recorder thread and returns the raw handle to its thread.
+    ///
+    /// This is called automatically when the handle is dropped.
+    fn exit(&self) -> Option<thread::JoinHandle<()>> {
+        self.tx.push(Message::Exit);
+        self.thread.lock().unwrap().take()
+    }
+}
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        if let Some(thread) = self.exit() {
+            thread.join().ok();
+        }
+    }
+}
+
+impl Writer {
+    /// Create a writer for the given number of interleaved channels, starting at the given path.
+    pub fn create(path: PathBuf, channels: usize) -> Result<Self, hound::Error> {
+        Self::with_max_file_bytes(path, channels, MAX_FILE_BYTES)
+    }
+
+    /// Create a writer that continues within a new file before reaching the given size.
+    pub fn with_max_file_bytes(
+        path: PathBuf,
+        channels: usize,
+        max_file_bytes: u64,
+    ) -> Result<Self, hound::Error> {
+        let spec = hound::WavSpec {
+            channels: channels as u16,
+            sample_rate: super::SAMPLE_RATE as u32,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        };
+        let frame_bytes = (channels * mem::size_of::<f32>()) as u64;
+        let frames_per_file = cmp::max(1, max_file_bytes / frame_bytes) as usize;
+        let wav = hound::WavWriter::create(&path, spec)?;
+        Ok(Writer {
+            path,
+            spec,
+            frames_per_file,
+            part: 1,
+            frames: 0,
+            wav,
+        })
+    }
+
+    /// The path of the file currently being written.
+    pub fn current_path(&self) -> PathBuf {
+        part_path(&self.path, self.part)
+    }
+
+    /// Write a single frame, continuing within the next file if the current file is full.
+    pub fn write_frame(&mut self, frame: &[f32]) -> Result<(), hound::Error> {
+        if self.frames == self.frames_per_file {
+            let path = part_path(&self.path, self.part + 1);
+            let wav = hound::WavWriter::create(path, self.spec)?;
+            mem::replace(&mut self.wav, wav).finalize()?;
+            self.part += 1;
+            self.frames = 0;
+        }
+        for &sample in frame {
+            self.wav.write_sample(sample)?;
+        }
+        self.frames += 1;
+        Ok(())
+    }
+
+    /// Finalise the file currently being written.
+    pub fn finalize(self) -> Result<(), hound::Error> {
+        self.wav.finalize()
+    }
+}
+
+impl Active {
+    /// Begin the given recording, writing files of up to the given size.
+    fn new(recording: Recording, max_file_bytes: u64) -> Self {
+        let writers = vec![];
+        Active {
+            recording,
+            writers,
+            max_file_bytes,
+        }
+    }
+
+    /// Create the WAV writers for the recording.
+    fn create_writers(&mut self, channels: usize) -> Result<(), hound::Error> {
+        let Active {
+            ref recording,
+            ref mut writers,
+            max_file_bytes,
+        } = *self;
+        fs::create_dir_all(&recording.directory)?;
+        let path = |stem: &str| recording.directory.join(stem).with_extension("wav");
+        match recording.mode {
+            Mode::Multichannel => {
+                let path = path(MASTER_FILE_STEM);
+                let writer = Writer::with_max_file_bytes(path, channels, max_file_bytes)?;
+                writers.push((None, writer));
+            }
+            Mode::PerSpeaker(ref speakers) => {
+                for speaker in speakers.iter().filter(|s| s.channel < channels) {
+                    let stem = format!("{}-ch{}", slugify(&speaker.name), speaker.channel + 1);
+                    let writer = Writer::with_max_file_bytes(path(&stem), 1, max_file_bytes)?;
+                    writers.push((Some(speaker.channel), writer));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the given interleaved buffer to the recording's files.
+    fn write(&mut self, samples: &[f32], channels: usize) -> Result<(), hound::Error> {
+        if self.writers.is_empty() {
+            self.create_writers(channels)?;
+        }
+        for (channel, writer) in self.writers.iter_mut() {
+            for frame in samples.chunks(channels) {
+                match *channel {
+                    None => writer.write_frame(frame)?,
+                    Some(channel) => writer.write_frame(&frame[channel..channel + 1])?,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Finalise all files of the recording, reporting the first error if any.
+    fn finalize(self, failure_tx: &FailureQueue) {
+        let Active { recording, writers, .. } = self;
+        let mut result = Ok(());
+        for (_, writer) in writers {
+            result = result.and(writer.finalize());
+        }
+        if let Err(error) = result {
+            let directory = recording.directory;
+            failure_tx.push(Failure { directory, error });
+        }
+    }
+}
+
+/// Spawn the recorder thread, returning a handle that may be used for communication.
+pub fn spawn() -> Handle {
+    let queue = Arc::new(SegQueue::new());
+    let tx = queue.clone();
+    let rx = queue;
+
+    let buffer_queue = Arc::new(SegQueue::new());
+    let buffer_tx = buffer_queue.clone();
+    let buffer_rx = buffer_queue;
+
+    let failure_queue = Arc::new(SegQueue::new());
+    let failure_tx = failure_queue.clone();
+    let failure_rx = failure_queue;
+
+    let thread = thread::Builder::new()
+        .name("audio_recorder".into())
+        .spawn(move || run(rx, buffer_tx, failure_tx, MAX_FILE_BYTES))
+        .unwrap();
+    let thread = Arc::new(Mutex::new(Some(thread)));
+
+    Handle {
+        tx,
+        thread,
+        buffer_rx,
+        failure_rx,
+    }
+}
+
+/// The main loop for the recorder thread.
+fn run(rx: MessageQueue, buffer_tx: BufferQueue, failure_tx: FailureQueue, max_file_bytes: u64) {
+    // Pre-prepare some buffers.
+    for _ in 0..BUFFERS_TO_PREPARE {
+        let buffer = Vec::with_capacity(FRAMES_PER_BUFFER * MAX_CHANNELS);
+        buffer_tx.push(buffer);
+    }
+
+    let mut active: Option<Active> = None;
+    loop {
+        let msg = match rx.pop() {
+            // If there are no messages waiting, sleep for a tiny bit to avoid rinsing cpu.
+            None => {
+                thread::sleep(time::Duration::from_millis(1));
+                continue;
+            }
+            Some(msg) => msg,
+        };
+
+        match msg {
+            Message::Start(recording) => {
+                if let Some(active) = active.take() {
+                    active.finalize(&failure_tx);
+                }
+                active = Some(Active::new(recording, max_file_bytes));
+            }
+
+            Message::Write(samples, channels) => {
+                let result = match active {
+                    Some(ref mut recording) => recording.write(&samples, channels),
+                    None => Ok(()),
+                };
+                // Abandon the recording on failure rather than reporting an error per buffer.
+                if let Err(error) = result {
+                    if let Some(Active { recording, .. }) = active.take() {
+                        let directory = recording.directory;
+                        failure_tx.push(Failure { directory, error });
+                    }
+                }
+                buffer_tx.push(samples);
+            }
+
+            Message::Stop => {
+                if let Some(active) = active.take() {
+                    active.finalize(&failure_tx);
+                }
+            }
+
+            Message::Exit => {
+                if let Some(active) = active.take() {
+                    active.finalize(&failure_tx);
+                }
+                break;
+            }
+        }
+    }
+}
+
+// The path of the given part of a recording, where the first part uses the path unchanged.
+fn part_path(path: &Path, part: usize) -> PathBuf {
+    if part <= 1 {
+        return path.to_path_buf();
+    }
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let mut name = format!("{}-{}", stem, part);
+    if let Some(extension) = path.extension() {
+        name = format!("{}.{}", name, extension.to_string_lossy());
+    }
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+fn test_directory(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir()
+        .join("audio_server_recorder_tests")
+        .join(format!("{}-{}", name, std::process::id()));
+    fs::remove_dir_all(&directory).ok();
+    fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+#[cfg(test)]
+fn read_samples(path: &Path) -> Vec<f32> {
+    let mut reader = hound::WavReader::open(path).unwrap();
+    reader.samples::<f32>().map(Result::unwrap).collect()
+}
+
+#[test]
+fn part_paths() {
+    let path = Path::new("recordings/master.wav");
+    assert_eq!(part_path(path, 1), Path::new("recordings/master.wav"));
+    assert_eq!(part_path(path, 2), Path::new("recordings/master-2.wav"));
+    assert_eq!(part_path(path, 12), Path::new("recordings/master-12.wav"));
+}
+
+#[test]
+fn writer_continues_within_numbered_files() {
+    let directory = test_directory("rollover");
+    let path = directory.join("master.wav");
+    // Room for 10 stereo frames per file.
+    let mut writer = Writer::with_max_file_bytes(path.clone(), 2, 80).unwrap();
+    for i in 0..25 {
+        let frame = [i as f32, -(i as f32)];
+        writer.write_frame(&frame).unwrap();
+    }
+    assert_eq!(writer.current_path(), directory.join("master-3.wav"));
+    writer.finalize().unwrap();
+
+    let parts = ["master.wav", "master-2.wav", "master-3.wav"];
+    let samples: Vec<f32> = parts
+        .iter()
+        .flat_map(|part| read_samples(&directory.join(part)))
+        .collect();
+    assert_eq!(read_samples(&directory.join("master-3.wav")).len(), 10);
+    assert!(!directory.join("master-4.wav").exists());
+    let expected: Vec<f32> = (0..25).flat_map(|i| vec![i as f32, -(i as f32)]).collect();
+    assert_eq!(samples, expected);
+    fs::remove_dir_all(&directory).ok();
+}
+
+#[test]
+fn records_each_speaker_to_its_own_file() {
+    let directory = test_directory("per_speaker");
+    let recorder = spawn();
+    let speakers = vec![
+        SpeakerFile { channel: 0, name: "Left".into() },
+        SpeakerFile { channel: 2, name: "Right".into() },
+    ];
+    let recording = Recording {
+        directory: directory.clone(),
+        mode: Mode::PerSpeaker(speakers),
+    };
+    recorder.start(recording);
+    let mut buffer = recorder.pop_buffer();
+    buffer.extend(&[0.1, 0.2, 0.3, 0.4, 0.5, 0.6]);
+    recorder.write(buffer, 3);
+    recorder.stop();
+    drop(recorder);
+
+    assert_eq!(read_samples(&directory.join("left-ch1.wav")), vec![0.1, 0.4]);
+    assert_eq!(read_samples(&directory.join("right-ch3.wav")), vec![0.3, 0.6]);
+    fs::remove_dir_all(&directory).ok();
+}
+
+#[test]
+fn reports_failure_to_create_recording() {
+    // A recording directory beneath a regular file can never be created.
+    let directory = test_directory("failure");
+    let file = directory.join("file");
+    fs::write(&file, b"").unwrap();
+    let recorder = spawn();
+    let recording = Recording {
+        directory: file.join("recording"),
+        mode: Mode::Multichannel,
+    };
+    recorder.start(recording);
+    let mut buffer = recorder.pop_buffer();
+    buffer.extend(&[0.0, 0.0]);
+    recorder.write(buffer, 2);
+
+    let start = time::Instant::now();
+    let failure = loop {
+        if let Some(failure) = recorder.pop_failure() {
+            break failure;
+        }
+        assert!(start.elapsed() < time::Duration::from_secs(5), "no failure was reported");
+        thread::sleep(time::Duration::from_millis(1));
+    };
+    assert_eq!(failure.directory, file.join("recording"));
+    fs::remove_dir_all(&directory).ok();
+}
@@ -0,0 +1,253 @@
+//! An algorithmic reverb fed by a per-source send.
+//!
+//! The reverb is a feedback delay network (FDN) of `LINES` delay lines mixed via a Hadamard
+//! matrix. Each output speaker receives a different combination of the delay lines, so that the
+//! reverberant field is decorrelated across the speaker array.
+//!
+//! The audio output runs one reverb for all speakers and one for each installation, all sharing
+//! the same `Params`, so that sounds only reverberate through the speakers they may play on.
+
+use crate::audio::delay::DelayLine;
+use crate::utils;
+use time_calc::Ms;
+
+/// The number of delay lines within the feedback delay network.
+pub const LINES: usize = 8;
+
+/// The number of speakers that receive distinct combinations of the delay lines via
+/// `output_sign`, beyond which the combinations repeat.
+///
+/// Each group of `LINES` speakers is assigned one of 16 sign patterns.
+pub const DISTINCT_OUTPUTS: usize = LINES * 16;
+
+/// The longest pre-delay that may be applied to the reverb input.
+pub const MAX_PRE_DELAY: Ms = Ms(250.0);
+
+/// The length of each delay line, chosen to be mutually prime when converted to frames.
+const LINE_DURATIONS: [Ms; LINES] = [
+    Ms(29.7),
+    Ms(37.1),
+    Ms(41.1),
+    Ms(43.7),
+    Ms(53.0),
+    Ms(59.9),
+    Ms(67.3),
+    Ms(73.1),
+];
+
+/// The amount of high-frequency damping applied within the feedback path.
+const DAMPING: f32 = 0.25;
+
+/// Parameters shared by the reverb of all speakers and of each installation.
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Params {
+    /// The balance between the dry signal and the reverb, where `0.0` is fully dry and `1.0` is
+    /// fully wet. Both are at full level at `0.5`.
+    ///
+    /// The dry signal of each sound is attenuated in proportion to its reverb send, so sounds
+    /// without a send always play at full level.
+    #[serde(default = "default_mix")]
+    pub mix: f64,
+    /// The time taken for the reverb to decay by 60dB.
+    #[serde(default = "default_decay")]
+    pub decay: Ms,
+    /// The delay applied to the reverb input, separating the reverb from the dry signal.
+    #[serde(default = "default_pre_delay")]
+    pub pre_delay: Ms,
+}
+
+/// The state of the reverb on the audio thread.
+#[derive(Clone, Debug)]
+pub struct Reverb {
+    params: Params,
+    pre_delay_line: DelayLine,
+    pre_delay_frames: usize,
+    lines: Vec<DelayLine>,
+    line_frames: [usize; LINES],
+    /// The gain applied to each line per pass through the network, derived from the `decay`.
+    feedback_gains: [f32; LINES],
+    /// The state of the damping filter for each line.
+    damping: [f32; LINES],
+    /// The output of each line for the most recently processed frame.
+    outputs: [f32; LINES],
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            mix: default_mix(),
+            decay: default_decay(),
+            pre_delay: default_pre_delay(),
+        }
+    }
+}
+
+impl Params {
+    /// The gain applied to the dry output.
+    pub fn dry_gain(&self) -> f32 {
+        ((1.0 - self.mix) * 2.0).max(0.0).min(1.0) as f32
+    }
+
+    /// The gain applied to the reverb output.
+    pub fn wet_gain(&self) -> f32 {
+        (self.mix * 2.0).max(0.0).min(1.0) as f32
+    }
+}
+
+/// The sign of the entry at the given row and column of a Hadamard matrix.
+pub fn hadamard_sign(row: usize, col: usize) -> f32 {
+    match (row & col).count_ones() % 2 {
+        0 => 1.0,
+        _ => -1.0,
+    }
+}
+
+/// The sign applied to the given delay line when mixing the output for the speaker at the given
+/// index.
+///
+/// The first `LINES` speakers receive the orthogonal rows of the Hadamard matrix. Each following
+/// group of `LINES` speakers receives the same rows multiplied by a different quadratic sign
+/// pattern over the lines, so that no two of the first `DISTINCT_OUTPUTS` speakers receive the same
+/// combination (or its inverse).
+pub fn output_sign(speaker: usize, line: usize) -> f32 {
+    let speaker = speaker % DISTINCT_OUTPUTS;
+    let row = speaker % LINES;
+    let pattern = speaker / LINES;
+    let bit = |i: usize| (line >> i) & 1;
+    let terms = [bit(0) & bit(1), bit(0) & bit(2), bit(1) & bit(2), bit(0) & bit(1) & bit(2)];
+    let parity = terms
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| (pattern >> i) & 1 == 1)
+        .fold(0, |acc, (_, &term)| acc ^ term);
+    match parity {
+        0 => hadamard_sign(row, line),
+        _ => -hadamard_sign(row, line),
+    }
+}
+
+impl Reverb {
+    /// Create a new, silent reverb for the given sample rate.
+    pub fn new(params: Params, sample_hz: f64) -> Self {
+        let max_pre_delay_frames = MAX_PRE_DELAY.samples(sample_hz) as usize;
+        let pre_delay_line = DelayLine::new(max_pre_delay_frames + 1);
+        let mut line_frames = [0; LINES];
+        for (frames, duration) in line_frames.iter_mut().zip(LINE_DURATIONS.iter()) {
+            *frames = duration.samples(sample_hz).max(1) as usize;
+        }
+        let lines = line_frames.iter().map(|&frames| DelayLine::new(frames)).collect();
+        let mut reverb = Reverb {
+            params,
+            pre_delay_line,
+            pre_delay_frames: 0,
+            lines,
+            line_frames,
+            feedback_gains: [0.0; LINES],
+            damping: [0.0; LINES],
+            outputs: [0.0; LINES],
+        };
+        reverb.set_params(params, sample_hz);
+        reverb
+    }
+
+    /// The current parameters of the reverb.
+    pub fn params(&self) -> Params {
+        self.params
+    }
+
+    /// Update the parameters without resetting the reverb state.
+    pub fn set_params(&mut self, params: Params, sample_hz: f64) {
+        self.params = params;
+        let pre_delay_frames = params.pre_delay.samples(sample_hz).max(0) as usize;
+        self.pre_delay_frames = pre_delay_frames.min(self.pre_delay_line.max_delay_frames());
+        let decay_frames = params.decay.samples(sample_hz).max(1) as f64;
+        for (gain, &frames) in self.feedback_gains.iter_mut().zip(self.line_frames.iter()) {
+            *gain = utils::db_to_linear(-60.0 * frames as f64 / decay_frames) as f32;
+        }
+    }
+
+    /// Feed a single frame of the summed reverb send through the network.
+    ///
+    /// Returns the output of each delay line, which may be combined via `output_sign` to produce
+    /// decorrelated outputs.
+    pub fn process(&mut self, input: f32) -> &[f32; LINES] {
+        let input = self.pre_delay_line.process(input, self.pre_delay_frames);
+
+        // Read the output of each line.
+        for (output, (line, &frames)) in self
+            .outputs
+            .iter_mut()
+            .zip(self.lines.iter().zip(self.line_frames.iter()))
+        {
+            *output = line.read(frames - 1);
+        }
+
+        // Mix the outputs via the normalised Hadamard matrix and feed them back with the input.
+        let norm = 1.0 / (LINES as f32).sqrt();
+        for (i, line) in self.lines.iter_mut().enumerate() {
+            let mixed = self
+                .outputs
+                .iter()
+                .enumerate()
+                .fold(0.0, |acc, (j, &y)| acc + y * hadamard_sign(i, j));
+            let feedback = mixed * norm * self.feedback_gains[i];
+            let damped = feedback + (self.damping[i] - feedback) * DAMPING;
+            self.damping[i] = damped;
+            line.write(input + damped);
+        }
+
+        &self.outputs
+    }
+
+    /// Silence the reverb.
+    pub fn clear(&mut self) {
+        self.pre_delay_line.clear();
+        for line in self.lines.iter_mut() {
+            line.clear();
+        }
+        self.damping = [0.0; LINES];
+        self.outputs = [0.0; LINES];
+    }
+}
+
+fn default_mix() -> f64 {
+    0.5
+}
+
+fn default_decay() -> Ms {
+    Ms(2_500.0)
+}
+
+fn default_pre_delay() -> Ms {
+    Ms(20.0)
+}
+
+#[test]
+fn reverb_decays() {
+    let mut reverb = Reverb::new(Params::default(), 48_000.0);
+    let energy = |reverb: &mut Reverb, input: f32, frames: usize| {
+        (0..frames).fold(0.0, |acc, i| {
+            let input = if i == 0 { input } else { 0.0 };
+            acc + reverb.process(input).iter().map(|y| y * y).sum::<f32>()
+        })
+    };
+    let early = energy(&mut reverb, 1.0, 48_000);
+    let late = energy(&mut reverb, 0.0, 48_000);
+    assert!(early > 0.0);
+    assert!(late < early);
+}
+
+#[test]
+fn output_signs_are_distinct() {
+    let signs = |speaker: usize| -> Vec<f32> {
+        (0..LINES).map(|line| output_sign(speaker, line)).collect()
+    };
+    for a in 0..DISTINCT_OUTPUTS {
+        let a_signs = signs(a);
+        let inverse: Vec<f32> = a_signs.iter().map(|s| -s).collect();
+        for b in a + 1..DISTINCT_OUTPUTS {
+            let b_signs = signs(b);
+            assert!(a_signs != b_signs && inverse != b_signs, "{} and {} are correlated", a, b);
+        }
+    }
+}
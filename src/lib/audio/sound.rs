@@ -1,4 +1,4 @@
-use crate::audio::{input, output, source, Source, SAMPLE_RATE};
+use crate::audio::{distance, doppler, input, output, source, Source, SAMPLE_RATE};
 use crate::installation;
 use crate::metres::Metres;
 use crossbeam::queue::SegQueue;
@@ -10,6 +10,8 @@ use std::sync::{Arc, Mutex};
 use time_calc::{Ms, Samples};
 
 type Point2 = nannou::glam::DVec2;
+type Point3 = nannou::glam::DVec3;
+type Vector3 = nannou::glam::DVec3;
 
 /// `Sound`s can be thought of as a stack of three primary components:
 ///
@@ -24,6 +26,8 @@ pub struct Sound {
     pub channels: usize,
     // An amplitude multiplier specified by the user for mixing the sound.
     pub volume: f32,
+    // The amount of the sound sent to the shared reverb.
+    pub reverb_send: f32,
     // Whether or not the sound's source has been muted.
     pub muted: bool,
     // Includes the source and pre-spatial effects.
@@ -39,6 +43,8 @@ pub struct Sound {
     pub signal: source::Signal,
     // The location and orientation of the sound within the space.
     pub position: Position,
+    // The velocity of the sound in metres per second, used for the Doppler effect.
+    pub velocity: Vector3,
     // A constant radians offset for the channels, provided by the sound's `Source`.
     //
     // When calculating the position of each channel around a `Sound`'s position, this is summed
@@ -48,6 +54,12 @@ pub struct Sound {
     pub spread: Metres,
     // Installations in which this sound can be played.
     pub installations: Installations,
+    // Describes how the channels of the sound should be interpreted.
+    pub channel_layout: source::wav::ChannelLayout,
+    // The optional distance attenuation and air absorption, provided by the sound's `Source`.
+    pub distance: Option<distance::Model>,
+    // The optional Doppler effect, provided by the sound's `Source`.
+    pub doppler: Option<doppler::Model>,
 }
 
 /// The location and orientation or a **Sound** within an exhibition.
@@ -58,6 +70,9 @@ pub struct Position {
     /// The orientation of the sound.
     #[serde(default)]
     pub radians: f32,
+    /// The elevation of the sound above the floor in metres.
+    #[serde(default)]
+    pub elevation: f64,
 }
 
 /// A handle to a currently playing sound.
@@ -168,10 +183,13 @@ pub fn spawn_from_source(
             wav,
             source.spread,
             source.volume,
+            source.reverb_send,
             source.muted,
             position,
             source.channel_radians,
             installations,
+            source.distance,
+            source.doppler,
             attack_duration_frames,
             release_duration_frames,
             continuous_preview,
@@ -187,10 +205,13 @@ pub fn spawn_from_source(
             realtime,
             source.spread,
             source.volume,
+            source.reverb_send,
             source.muted,
             position,
             source.channel_radians,
             installations,
+            source.distance,
+            source.doppler,
             attack_duration_frames,
             release_duration_frames,
             continuous_preview,
@@ -209,10 +230,13 @@ pub fn spawn_from_wav(
     wav: &source::Wav,
     spread: Metres,
     volume: f32,
+    reverb_send: f32,
     muted: bool,
     initial_position: Position,
     channel_radians: f32,
     installations: Installations,
+    distance: Option<distance::Model>,
+    doppler: Option<doppler::Model>,
     attack_duration_frames: Samples,
     release_duration_frames: Samples,
     continuous_preview: bool,
@@ -262,12 +286,17 @@ pub fn spawn_from_wav(
         shared: shared.clone(),
         channels: wav.channels,
         volume,
+        reverb_send,
         muted,
         signal,
         position: initial_position,
+        velocity: Vector3::ZERO,
         channel_radians,
         spread,
         installations,
+        channel_layout: wav.channel_layout,
+        distance,
+        doppler,
     };
 
     // Create the handle to the sound.
@@ -295,10 +324,13 @@ pub fn spawn_from_realtime(
     realtime: &source::Realtime,
     spread: Metres,
     volume: f32,
+    reverb_send: f32,
     muted: bool,
     initial_position: Position,
     channel_radians: f32,
     installations: Installations,
+    distance: Option<distance::Model>,
+    doppler: Option<doppler::Model>,
     attack_duration_frames: Samples,
     release_duration_frames: Samples,
     continuous_preview: bool,
@@ -391,12 +423,17 @@ pub fn spawn_from_realtime(
         shared: shared.clone(),
         channels: n_channels,
         volume,
+        reverb_send,
         muted,
         signal,
         position: initial_position,
+        velocity: Vector3::ZERO,
         channel_radians,
         spread,
         installations,
+        channel_layout: source::wav::ChannelLayout::Discrete,
+        distance,
+        doppler,
     };
 
     // Create the handle to the sound.
@@ -430,11 +467,11 @@ impl Sound {
     /// The location of the channel at the given index.
     ///
     /// Returns `None` if there is no channel for the given index.
-    pub fn channel_point(&self, index: usize) -> Option<Point2> {
+    pub fn channel_point(&self, index: usize) -> Option<Point3> {
         if self.channels <= index {
             return None;
         }
-        let point = self.position.point;
+        let point = self.position.point.extend(self.position.elevation);
         let radians = self.position.radians + self.channel_radians;
         Some(super::output::channel_point(
             point,
@@ -491,7 +528,7 @@ impl ops::DerefMut for Position {
 }
 
 impl<'a> Iterator for ChannelPoints<'a> {
-    type Item = Point2;
+    type Item = Point3;
     fn next(&mut self) -> Option<Self::Item> {
         self.sound.channel_point(self.index).map(|point| {
             self.index += 1;
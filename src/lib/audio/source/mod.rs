@@ -1,3 +1,4 @@
+use crate::audio::{distance, doppler};
 use crate::installation;
 use crate::metres::Metres;
 use crate::soundscape;
@@ -9,7 +10,7 @@ use serde::{Deserialize, Serialize};
 use std::ops;
 use time_calc::{Ms, Samples};
 
-pub use self::movement::Movement;
+pub use self::movement::{Movement, Spawn};
 pub use self::realtime::Realtime;
 pub use self::wav::Wav;
 
@@ -51,9 +52,18 @@ pub struct Source {
     /// An amplitude modulator specified by the user via the GUI.
     #[serde(default = "default::volume")]
     pub volume: f32,
+    /// The amount of the source's signal sent to the shared reverb.
+    #[serde(default)]
+    pub reverb_send: f32,
     /// Whether or not the source has been muted.
     #[serde(default)]
     pub muted: bool,
+    /// The optional distance attenuation and air absorption applied to the source's sounds.
+    #[serde(default)]
+    pub distance: Option<distance::Model>,
+    /// The optional Doppler effect applied to the source's moving sounds.
+    #[serde(default)]
+    pub doppler: Option<doppler::Model>,
 }
 
 /// A **Signal** yielding interleaved samples.
@@ -153,6 +163,13 @@ pub struct Soundscape {
     pub release_duration: Range<Ms>,
     #[serde(default = "default::movement")]
     pub movement: Movement,
+    #[serde(default = "default::spawn")]
+    pub spawn: Spawn,
+    /// The range of elevations in metres from which the elevation of each sound is chosen.
+    ///
+    /// If `None`, sounds take on the average elevation of their installation's speakers.
+    #[serde(default)]
+    pub elevation: Option<Range<f64>>,
 }
 
 /// Items related to the movement of a source's associated sounds within a soundscape.
@@ -194,6 +211,44 @@ pub mod movement {
     /// The skew applied to the perception of Ngon "nth" vertices.
     pub const NTH_SKEW: f32 = 0.75;
 
+    /// The maximum minimum distance in metres that may be kept between Poisson-disc spawned sounds.
+    pub const MAX_SPAWN_DISTANCE: f64 = 20.0;
+
+    /// The maximum distance in metres within which flocking sounds may consider each other.
+    pub const MAX_FLOCK_RADIUS: f64 = 20.0;
+
+    /// The maximum weight that may be applied to each of the flocking forces.
+    pub const MAX_FLOCK_WEIGHT: f64 = 5.0;
+
+    /// The maximum distance in metres that may be travelled with each step of a random walk.
+    pub const MAX_STEP: f64 = 5.0;
+
+    /// The maximum rate in metres per second at which a random walk may drift.
+    pub const MAX_DRIFT: f64 = 2.0;
+
+    /// The maximum radius in metres of an orbit's major axis.
+    pub const MAX_ORBIT_RADIUS: f64 = 20.0;
+
+    /// The maximum rate of rotation around an orbit's centre in radians per second.
+    pub const MAX_ANGULAR_SPEED: f64 = 2.0 * PI_F64;
+
+    /// The maximum eccentricity of an orbit.
+    ///
+    /// An eccentricity of `1.0` would collapse the orbit into a line.
+    pub const MAX_ECCENTRICITY: f64 = 0.95;
+
+    /// The amount of skew applied to the perception of the angular speed of an orbit.
+    pub const ANGULAR_SPEED_SKEW: f32 = 0.5;
+
+    /// The number of points sampled along each segment of a path traced as a spline.
+    pub const SPLINE_SUBDIVISIONS: usize = 16;
+
+    /// The amount of skew applied to the perception of the spawn distance.
+    pub const SPAWN_DISTANCE_SKEW: f32 = 0.5;
+
+    /// The maximum elevation in metres that may be assigned to a source's sounds.
+    pub const MAX_ELEVATION: f64 = 10.0;
+
     /// Whether a source may assigned to normalised fixed position or generative movement.
     #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
     pub enum Movement {
@@ -202,11 +257,32 @@ pub mod movement {
         Generative(Generative),
     }
 
+    /// Strategies for choosing the initial position of a sound within its installation.
+    #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+    pub enum Spawn {
+        /// A random point around the centroid of the installation.
+        Random,
+        /// Near the installation's speaker with the fewest active sounds closest to it.
+        LeastLoadedSpeaker,
+        /// A random point at least `min_distance` metres from all other active sounds.
+        ///
+        /// If no such point can be found, the candidate furthest from all other sounds is used.
+        PoissonDisc { min_distance: f64 },
+        /// A random point on the perimeter of the installation.
+        Perimeter,
+        /// A fixed anchor normalised to the constraints of the installation.
+        Anchor(Point2),
+    }
+
     /// Movement kinds that are guided by some generative algorithm.
     #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
     pub enum Generative {
         Agent(Agent),
         Ngon(Ngon),
+        Path(Path),
+        Flock(Flock),
+        RandomWalk(RandomWalk),
+        Orbit(Orbit),
     }
 
     /// A generative movement kind modelling an automonomous agent.
@@ -225,6 +301,16 @@ pub mod movement {
         /// Whether or not the agent's orientation should be applied to channel rotation.
         #[serde(default = "super::default::directional")]
         pub directional: bool,
+        /// The probability with which the agent chooses each new target in proportion to how busy
+        /// each installation currently is, rather than by the number of sounds each needs.
+        ///
+        /// `0.0` ignores activity entirely, while `1.0` always follows activity when there is any.
+        ///
+        /// Unless presence is reported via OSC, activity is measured from the output RMS of each
+        /// installation, which includes the soundscape's own sounds. Agents following it are
+        /// therefore drawn towards installations that they and others are already playing on.
+        #[serde(default = "super::default::activity_weight")]
+        pub activity_weight: f64,
     }
 
     /// A generative movement kind that traces the vertices of an n-sided polygon.
@@ -248,6 +334,103 @@ pub mod movement {
         pub speed: Range<f64>,
     }
 
+    /// A generative movement kind modelling a flock of autonomous agents.
+    ///
+    /// Each agent steers towards its own target while applying separation, alignment and cohesion
+    /// forces relative to nearby sounds spawned via the same source.
+    #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+    pub struct Flock {
+        /// The constraints of the agent steered by the flocking forces.
+        #[serde(default = "super::default::agent")]
+        pub agent: Agent,
+        /// The distance in metres within which other sounds are considered flockmates.
+        #[serde(default = "super::default::flock_radius")]
+        pub radius: f64,
+        /// The weight of the force steering away from flockmates that are too close.
+        #[serde(default = "super::default::separation")]
+        pub separation: f64,
+        /// The weight of the force steering towards the average heading of flockmates.
+        #[serde(default = "super::default::alignment")]
+        pub alignment: f64,
+        /// The weight of the force steering towards the average location of flockmates.
+        #[serde(default = "super::default::cohesion")]
+        pub cohesion: f64,
+        /// Whether sounds also flock with sounds spawned via other sources that share a group.
+        #[serde(default)]
+        pub groups: bool,
+    }
+
+    /// A generative movement kind that wanders the installation via a bounded random walk.
+    #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+    pub struct RandomWalk {
+        /// The distance in metres travelled in a random direction with each step of the walk.
+        #[serde(default = "super::default::step")]
+        pub step: Range<f64>,
+        /// The rate in metres per second at which the walk drifts in a constant, random direction.
+        #[serde(default = "super::default::drift")]
+        pub drift: Range<f64>,
+    }
+
+    /// A generative movement kind that circles some centre point along an ellipse.
+    #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+    pub struct Orbit {
+        /// The point around which sounds orbit.
+        #[serde(default = "super::default::orbit_centre")]
+        pub centre: OrbitCentre,
+        /// The radius of the orbit's major axis in metres.
+        #[serde(default = "super::default::orbit_radius")]
+        pub radius: Range<f64>,
+        /// The rate of rotation around the centre in radians per second.
+        ///
+        /// The direction of rotation is chosen randomly for each sound.
+        #[serde(default = "super::default::angular_speed")]
+        pub angular_speed: Range<f64>,
+        /// The eccentricity of the orbit's ellipse, where `0.0` is a circle.
+        #[serde(default = "super::default::eccentricity")]
+        pub eccentricity: Range<f64>,
+    }
+
+    /// The point around which an orbit circles.
+    #[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+    pub enum OrbitCentre {
+        /// The centroid of the installation for which the sound was spawned.
+        Installation,
+        /// A fixed point normalised to the constraints of the installation.
+        Fixed(Point2),
+    }
+
+    /// A generative movement kind that travels along a user-drawn path of waypoints.
+    #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+    pub struct Path {
+        /// The waypoints of the path in metres within the exhibition space.
+        #[serde(default)]
+        pub points: Vec<Point2>,
+        /// Whether the path should trace a Catmull-Rom spline through the points rather than
+        /// straight lines.
+        #[serde(default)]
+        pub spline: bool,
+        /// The rate at which the path is travelled in metres per second.
+        #[serde(default = "super::default::speed")]
+        pub speed: Range<f64>,
+        /// What happens when a sound reaches the end of the path.
+        #[serde(default = "super::default::path_mode")]
+        pub mode: PathMode,
+        /// Whether sounds should start from a random point along the path rather than the first.
+        #[serde(default)]
+        pub random_start: bool,
+    }
+
+    /// Describes what happens when a sound reaches the end of its path.
+    #[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+    pub enum PathMode {
+        /// Continue from the last point back to the first, treating the path as closed.
+        Loop,
+        /// Travel back and forth along the path.
+        PingPong,
+        /// Stop at the last point.
+        Once,
+    }
+
     impl Movement {
         pub const VARIANT_COUNT: usize = 2;
 
@@ -276,16 +459,69 @@ pub mod movement {
                 _ => None,
             }
         }
+
+        /// The agent steered by the movement, if any.
+        pub fn agent_mut(&mut self) -> Option<&mut Agent> {
+            match *self {
+                Movement::Generative(ref mut generative) => generative.agent_mut(),
+                Movement::Fixed(_) => None,
+            }
+        }
+    }
+
+    impl Spawn {
+        pub const VARIANT_COUNT: usize = 5;
+
+        /// Produce the index of the Spawn value variant.
+        pub fn to_index(&self) -> usize {
+            match *self {
+                Spawn::Random => 0,
+                Spawn::LeastLoadedSpeaker => 1,
+                Spawn::PoissonDisc { .. } => 2,
+                Spawn::Perimeter => 3,
+                Spawn::Anchor(_) => 4,
+            }
+        }
+
+        /// Produce a label for the variant at the given index.
+        pub fn label_from_index(i: usize) -> &'static str {
+            match i {
+                0 => "RANDOM",
+                1 => "LEAST LOADED SPEAKER",
+                2 => "POISSON DISC",
+                3 => "PERIMETER",
+                4 => "ANCHOR",
+                _ => "",
+            }
+        }
+
+        /// Produce a default Spawn variant for the given variant index.
+        pub fn from_index(i: usize) -> Option<Self> {
+            match i {
+                0 => Some(Spawn::Random),
+                1 => Some(Spawn::LeastLoadedSpeaker),
+                2 => Some(Spawn::PoissonDisc {
+                    min_distance: super::default::SPAWN_DISTANCE,
+                }),
+                3 => Some(Spawn::Perimeter),
+                4 => Some(Spawn::Anchor(super::default::FIXED)),
+                _ => None,
+            }
+        }
     }
 
     impl Generative {
-        pub const VARIANT_COUNT: usize = 2;
+        pub const VARIANT_COUNT: usize = 6;
 
         /// Produce the index of the Movement value variant.
         pub fn to_index(&self) -> usize {
             match *self {
                 Generative::Agent(_) => 0,
                 Generative::Ngon(_) => 1,
+                Generative::Path(_) => 2,
+                Generative::Flock(_) => 3,
+                Generative::RandomWalk(_) => 4,
+                Generative::Orbit(_) => 5,
             }
         }
 
@@ -294,6 +530,10 @@ pub mod movement {
             match i {
                 0 => "AGENT",
                 1 => "NGON",
+                2 => "PATH",
+                3 => "FLOCK",
+                4 => "WALK",
+                5 => "ORBIT",
                 _ => "",
             }
         }
@@ -303,6 +543,61 @@ pub mod movement {
             match i {
                 0 => Some(Generative::Agent(super::default::AGENT)),
                 1 => Some(Generative::Ngon(super::default::NGON)),
+                2 => Some(Generative::Path(super::default::PATH)),
+                3 => Some(Generative::Flock(super::default::FLOCK)),
+                4 => Some(Generative::RandomWalk(super::default::RANDOM_WALK)),
+                5 => Some(Generative::Orbit(super::default::ORBIT)),
+                _ => None,
+            }
+        }
+
+        /// The agent steered by the movement, if any.
+        pub fn agent(&self) -> Option<&Agent> {
+            match *self {
+                Generative::Agent(ref agent) => Some(agent),
+                Generative::Flock(ref flock) => Some(&flock.agent),
+                _ => None,
+            }
+        }
+
+        /// The agent steered by the movement, if any.
+        pub fn agent_mut(&mut self) -> Option<&mut Agent> {
+            match *self {
+                Generative::Agent(ref mut agent) => Some(agent),
+                Generative::Flock(ref mut flock) => Some(&mut flock.agent),
+                _ => None,
+            }
+        }
+    }
+
+    impl PathMode {
+        pub const VARIANT_COUNT: usize = 3;
+
+        /// Produce the index of the PathMode value variant.
+        pub fn to_index(&self) -> usize {
+            match *self {
+                PathMode::Loop => 0,
+                PathMode::PingPong => 1,
+                PathMode::Once => 2,
+            }
+        }
+
+        /// Produce a label for the variant at the given index.
+        pub fn label_from_index(i: usize) -> &'static str {
+            match i {
+                0 => "LOOP",
+                1 => "PING PONG",
+                2 => "ONCE",
+                _ => "",
+            }
+        }
+
+        /// Produce the PathMode for the given variant index.
+        pub fn from_index(i: usize) -> Option<Self> {
+            match i {
+                0 => Some(PathMode::Loop),
+                1 => Some(PathMode::PingPong),
+                2 => Some(PathMode::Once),
                 _ => None,
             }
         }
@@ -633,7 +928,7 @@ pub mod skew {
 }
 
 pub mod default {
-    use super::{movement, Movement};
+    use super::{movement, Movement, Spawn};
     use crate::metres::Metres;
     use crate::utils::{Range, HR_MS};
     use nannou::glam::DVec2 as Vector2;
@@ -673,11 +968,13 @@ pub mod default {
         max: super::movement::MAX_ROTATION,
     };
     pub const DIRECTIONAL: bool = true;
+    pub const ACTIVITY_WEIGHT: f64 = 0.0;
     pub const AGENT: movement::Agent = movement::Agent {
         max_speed: MAX_SPEED,
         max_force: MAX_FORCE,
         max_rotation: MAX_ROTATION,
         directional: DIRECTIONAL,
+        activity_weight: ACTIVITY_WEIGHT,
     };
     pub const VERTICES: Range<usize> = Range { min: 3, max: 8 };
     pub const NTH: Range<usize> = Range { min: 1, max: 3 };
@@ -696,8 +993,47 @@ pub mod default {
         radians_offset: RADIANS_OFFSET,
         speed: SPEED,
     };
+    pub const FLOCK_RADIUS: f64 = 4.0;
+    pub const SEPARATION: f64 = 1.5;
+    pub const ALIGNMENT: f64 = 1.0;
+    pub const COHESION: f64 = 1.0;
+    pub const FLOCK: movement::Flock = movement::Flock {
+        agent: AGENT,
+        radius: FLOCK_RADIUS,
+        separation: SEPARATION,
+        alignment: ALIGNMENT,
+        cohesion: COHESION,
+        groups: false,
+    };
+    pub const PATH_MODE: movement::PathMode = movement::PathMode::Loop;
+    pub const PATH: movement::Path = movement::Path {
+        points: Vec::new(),
+        spline: false,
+        speed: SPEED,
+        mode: PATH_MODE,
+        random_start: false,
+    };
+    pub const STEP: Range<f64> = Range { min: 0.25, max: 1.0 };
+    pub const DRIFT: Range<f64> = Range { min: 0.0, max: 0.25 };
+    pub const RANDOM_WALK: movement::RandomWalk = movement::RandomWalk {
+        step: STEP,
+        drift: DRIFT,
+    };
+    pub const ORBIT_CENTRE: movement::OrbitCentre = movement::OrbitCentre::Installation;
+    pub const ORBIT_RADIUS: Range<f64> = Range { min: 1.0, max: 3.0 };
+    pub const ANGULAR_SPEED: Range<f64> = Range { min: 0.1, max: 0.5 };
+    pub const ECCENTRICITY: Range<f64> = Range { min: 0.0, max: 0.5 };
+    pub const ORBIT: movement::Orbit = movement::Orbit {
+        centre: ORBIT_CENTRE,
+        radius: ORBIT_RADIUS,
+        angular_speed: ANGULAR_SPEED,
+        eccentricity: ECCENTRICITY,
+    };
     pub const GENERATIVE: movement::Generative = movement::Generative::Agent(AGENT);
     pub const MOVEMENT: Movement = Movement::Fixed(FIXED);
+    pub const SPAWN_DISTANCE: f64 = 2.0;
+    pub const SPAWN: Spawn = Spawn::Random;
+    pub const ELEVATION: Range<f64> = Range { min: 1.0, max: 2.0 };
 
     pub fn spread() -> Metres {
         SPREAD
@@ -735,6 +1071,62 @@ pub mod default {
         MOVEMENT
     }
 
+    pub fn spawn() -> Spawn {
+        SPAWN
+    }
+
+    pub fn agent() -> movement::Agent {
+        AGENT
+    }
+
+    pub fn flock_radius() -> f64 {
+        FLOCK_RADIUS
+    }
+
+    pub fn separation() -> f64 {
+        SEPARATION
+    }
+
+    pub fn alignment() -> f64 {
+        ALIGNMENT
+    }
+
+    pub fn cohesion() -> f64 {
+        COHESION
+    }
+
+    pub fn speed() -> Range<f64> {
+        SPEED
+    }
+
+    pub fn path_mode() -> movement::PathMode {
+        PATH_MODE
+    }
+
+    pub fn step() -> Range<f64> {
+        STEP
+    }
+
+    pub fn drift() -> Range<f64> {
+        DRIFT
+    }
+
+    pub fn orbit_centre() -> movement::OrbitCentre {
+        ORBIT_CENTRE
+    }
+
+    pub fn orbit_radius() -> Range<f64> {
+        ORBIT_RADIUS
+    }
+
+    pub fn angular_speed() -> Range<f64> {
+        ANGULAR_SPEED
+    }
+
+    pub fn eccentricity() -> Range<f64> {
+        ECCENTRICITY
+    }
+
     pub fn radians_offset() -> Range<f64> {
         RADIANS_OFFSET
     }
@@ -754,6 +1146,10 @@ pub mod default {
     pub fn directional() -> bool {
         DIRECTIONAL
     }
+
+    pub fn activity_weight() -> f64 {
+        ACTIVITY_WEIGHT
+    }
 }
 
 impl Default for Soundscape {
@@ -766,6 +1162,8 @@ impl Default for Soundscape {
         let attack_duration = default::ATTACK_DURATION;
         let release_duration = default::RELEASE_DURATION;
         let movement = default::MOVEMENT;
+        let spawn = default::SPAWN;
+        let elevation = None;
         Soundscape {
             installations,
             groups,
@@ -775,6 +1173,8 @@ impl Default for Soundscape {
             attack_duration,
             release_duration,
             movement,
+            spawn,
+            elevation,
         }
     }
 }
\ No newline at end of file
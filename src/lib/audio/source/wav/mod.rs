@@ -17,6 +17,21 @@ pub struct Wav {
     pub should_loop: bool,
     #[serde(default = "default_playback")]
     pub playback: Playback,
+    #[serde(default)]
+    pub channel_layout: ChannelLayout,
+}
+
+/// Describes how the channels of the WAV file should be interpreted.
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub enum ChannelLayout {
+    /// Each channel is a discrete signal, positioned around the sound via `spread` and
+    /// `channel_radians`.
+    Discrete,
+    /// The channels contain an ambisonic sound field of the given order in AmbiX format (ACN
+    /// channel ordering with SN3D normalisation).
+    ///
+    /// The sound field is rotated by the sound's orientation and decoded to the speakers.
+    AmbiX { order: usize },
 }
 
 /// The playback mode of the WAV file.
@@ -40,6 +55,30 @@ fn default_playback() -> Playback {
     Playback::Retrigger
 }
 
+impl Default for ChannelLayout {
+    fn default() -> Self {
+        ChannelLayout::Discrete
+    }
+}
+
+impl ChannelLayout {
+    /// Produce a label describing the layout.
+    pub fn label(&self) -> String {
+        match *self {
+            ChannelLayout::Discrete => "Discrete".into(),
+            ChannelLayout::AmbiX { order } => format!("AmbiX Order {}", order),
+        }
+    }
+
+    /// All layouts that are valid for a WAV with the given number of channels.
+    pub fn valid_for_channels(channels: usize) -> Vec<Self> {
+        let ambix = (1..audio::ambisonics::MAX_ORDER + 1)
+            .filter(|&order| audio::ambisonics::channel_count(order) <= channels)
+            .map(|order| ChannelLayout::AmbiX { order });
+        Some(ChannelLayout::Discrete).into_iter().chain(ambix).collect()
+    }
+}
+
 /// The default WAV `should_loop` state.
 fn default_should_loop() -> bool {
     false
@@ -57,6 +96,7 @@ impl Wav {
         let duration = Samples(reader.duration() as _);
         let playback = default_playback();
         let should_loop = default_should_loop();
+        let channel_layout = ChannelLayout::default();
         Ok(Wav {
             path,
             channels,
@@ -64,6 +104,7 @@ impl Wav {
             sample_hz,
             playback,
             should_loop,
+            channel_layout,
         })
     }
 
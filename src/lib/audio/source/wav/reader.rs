@@ -49,6 +49,8 @@ struct ChildId(usize);
 pub struct Handle {
     tx: Tx,
     thread: Arc<Mutex<Option<thread::JoinHandle<()>>>>,
+    // Whether or not `SamplesStream`s read their WAV on the calling thread.
+    synchronous: bool,
 }
 
 /// All state stored on the `wav::reader` thread.
@@ -167,6 +169,8 @@ pub struct SamplesStream {
     wav_len_samples: usize,
     // Whether or not the WAV is looped.
     wav_looped: bool,
+    // The WAV reader if samples are read on the calling thread rather than by the reader thread.
+    reader: Option<RefCell<WavReader>>,
 }
 
 impl Handle {
@@ -174,6 +178,9 @@ impl Handle {
     ///
     /// When called, the reader thread will add an entry for this sound into the map and prepare
     /// the first `NUM_BUFFERS` buffers by reading samples from the given `WavReader`.
+    ///
+    /// If the handle was spawned via `spawn_synchronous`, the returned stream reads the WAV itself
+    /// and the reader thread is not involved.
     pub fn play(
         &self,
         sound_id: sound::Id,
@@ -182,14 +189,21 @@ impl Handle {
         looped: bool,
     ) -> Result<SamplesStream, hound::Error>
     {
-        let reader = WavReader::open(wav_path)?;
+        let mut reader = WavReader::open(wav_path)?;
         let wav_len_samples = reader.len() as _;
         let buffer_queue = Arc::new(SegQueue::new());
         let buffer_tx = buffer_queue.clone();
         let buffer_rx = buffer_queue;
         let spec = reader.spec();
+        if self.synchronous {
+            seek_to_start_frame(&mut reader, start_frame)?;
+            let reader = Some(RefCell::new(reader));
+            let samples_stream =
+                SamplesStream::new(buffer_rx, spec, wav_len_samples, looped, reader);
+            return Ok(samples_stream);
+        }
         let play = Play { reader, buffer_tx, start_frame, looped };
-        let samples_stream = SamplesStream::new(buffer_rx, spec, wav_len_samples, looped);
+        let samples_stream = SamplesStream::new(buffer_rx, spec, wav_len_samples, looped, None);
         let msg = Message::Play(sound_id, play);
         self.tx.push(msg);
         Ok(samples_stream)
@@ -232,6 +246,7 @@ impl SamplesStream {
         wav_spec: hound::WavSpec,
         wav_len_samples: usize,
         wav_looped: bool,
+        reader: Option<RefCell<WavReader>>,
     ) -> Self {
         SamplesStream {
             buffer_rx,
@@ -240,6 +255,7 @@ impl SamplesStream {
             wav_spec,
             wav_len_samples,
             wav_looped,
+            reader,
         }
     }
 
@@ -253,6 +269,11 @@ impl SamplesStream {
         if self.wav_looped {
             return None;
         }
+        if let Some(ref reader) = self.reader {
+            let remaining_samples = super::samples::remaining(&mut *reader.borrow_mut());
+            let remaining_frames = (remaining_samples / self.wav_spec.channels as usize) as _;
+            return Some(Samples(remaining_frames));
+        }
         loop {
             if let Some(ref buffer) = *self.buffer.borrow() {
                 let remaining_samples =
@@ -275,9 +296,25 @@ impl SamplesStream {
             ref buffer,
             ref buffer_rx,
             ref mut buffer_index,
+            ref wav_spec,
+            wav_looped,
+            ref reader,
             ..
         } = *self;
 
+        // Read directly from the WAV if the stream is synchronous.
+        if let Some(ref reader) = *reader {
+            let mut reader = reader.borrow_mut();
+            let result = match wav_looped {
+                true => read_next_sample_cycled(&mut reader, wav_spec).map(Some),
+                false => read_next_sample(&mut reader, wav_spec),
+            };
+            return result.unwrap_or_else(|err| {
+                eprintln!("failed to read sample from wav source: {}", err);
+                None
+            });
+        }
+
         loop {
             // If there is a sample in the current buffer, return it.
             if let Some(ref buffer) = *buffer.borrow() {
@@ -337,9 +374,7 @@ fn play_sound(play: Play) -> Sound {
     //
     // If `frame` is larger than the number of samples in the file the remaining duration will be
     // wrapped around to the beginning.
-    let duration_frames = reader.duration() as u64;
-    let frames = start_frame % duration_frames;
-    reader.seek(frames as u32)
+    seek_to_start_frame(&mut reader, start_frame)
         .expect("failed to seek to start frame in wav source");
 
     // Prepare the buffers for the sound.
@@ -364,6 +399,17 @@ fn play_sound(play: Play) -> Sound {
     }
 }
 
+/// Seek the reader to the given `start_frame`, wrapped around to the duration of the WAV.
+fn seek_to_start_frame(reader: &mut WavReader, start_frame: u64) -> Result<(), hound::Error> {
+    let duration_frames = reader.duration() as u64;
+    if duration_frames == 0 {
+        return Ok(());
+    }
+    let frames = start_frame % duration_frames;
+    reader.seek(frames as u32)?;
+    Ok(())
+}
+
 /// Sends the next queued buffer to the `ThreadedSamplesStream` associated with the given
 /// `sound_id`.
 ///
@@ -487,6 +533,19 @@ fn read_next_sample(
 /// Runs the wav reader thread and returns a handle to it that may be used to play or seek sounds
 /// via their unique `Id`.
 pub fn spawn() -> Handle {
+    spawn_inner(false)
+}
+
+/// The same as `spawn` but the `SamplesStream`s of played sounds read their WAV synchronously on
+/// the thread that consumes them rather than via the reader thread.
+///
+/// This is useful when rendering faster than realtime, where the audio thread would otherwise
+/// outrun the reader thread.
+pub fn spawn_synchronous() -> Handle {
+    spawn_inner(true)
+}
+
+fn spawn_inner(synchronous: bool) -> Handle {
     let queue = Arc::new(SegQueue::new());
     let tx = queue.clone();
     let rx = queue;
@@ -496,7 +555,7 @@ pub fn spawn() -> Handle {
         .spawn(move || run(tx2, rx))
         .unwrap();
     let thread = Arc::new(Mutex::new(Some(thread)));
-    Handle { tx, thread }
+    Handle { tx, thread, synchronous }
 }
 
 /// Run the parent wav reader loop.
@@ -612,3 +671,42 @@ fn run_child(child_msg_queue: Arc<ChildMessageQueue>, parent_tx: Tx) {
         }
     }
 }
+
+#[test]
+fn synchronous_streams_read_every_sample() {
+    // Write a short stereo WAV.
+    let directory = std::env::temp_dir().join("audio_server_wav_reader_tests");
+    std::fs::create_dir_all(&directory).unwrap();
+    let path = directory.join(format!("synchronous-{}.wav", std::process::id()));
+    let spec = hound::WavSpec {
+        channels: 2,
+        sample_rate: 44_100,
+        bits_per_sample: 32,
+        sample_format: SampleFormat::Float,
+    };
+    let frames = audio::FRAMES_PER_BUFFER * 3 + 7;
+    let samples: Vec<f32> = (0..frames * 2).map(|i| i as f32).collect();
+    let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+    for &sample in &samples {
+        writer.write_sample(sample).unwrap();
+    }
+    writer.finalize().unwrap();
+
+    // Every sample is available immediately, without waiting on the reader thread.
+    let handle = spawn_synchronous();
+    let id = sound::IdGenerator::new().generate_next();
+    let stream = handle.play(id, &path, 0, false).unwrap();
+    assert_eq!(stream.remaining_frames(), Some(Samples(frames as _)));
+    assert_eq!(stream.collect::<Vec<_>>(), samples);
+
+    // Looped streams begin at the wrapped start frame and cycle back to the beginning.
+    let stream = handle.play(id, &path, (frames + 2) as u64, true).unwrap();
+    let looped: Vec<f32> = stream.take(samples.len()).collect();
+    let expected: Vec<f32> = samples[4..].iter().chain(&samples[..4]).cloned().collect();
+    assert_eq!(looped, expected);
+
+    if let Some(thread) = handle.exit() {
+        thread.join().ok();
+    }
+    std::fs::remove_file(&path).ok();
+}
@@ -3,6 +3,7 @@ use crate::installation;
 use fxhash::FxHashSet;
 use nannou::glam::DVec2 as Point2;
 use serde::Deserialize;
+use time_calc::Ms;
 use serde::Serialize;
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Deserialize, Serialize)]
@@ -15,11 +16,75 @@ pub struct Id(pub u64);
 pub struct Speaker {
     // The location of the speaker within the space.
     pub point: Point2,
+    // The elevation of the speaker above the floor in metres.
+    #[serde(default)]
+    pub elevation: f64,
     // The channel on which the output is rendered.
     pub channel: usize,
     // Installations assigned to this speaker.
     #[serde(default)]
     pub installations: FxHashSet<installation::Id>,
+    // A level trim applied to the speaker's output in decibels.
+    #[serde(default)]
+    pub gain_db: f64,
+    // The delay applied to the speaker's output.
+    #[serde(default)]
+    pub delay: Delay,
+    // Whether the speaker is full-range or a subwoofer.
+    #[serde(default)]
+    pub role: Role,
+}
+
+/// The frequency range reproduced by a speaker.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Deserialize, Serialize)]
+pub enum Role {
+    /// The speaker has sounds panned to it directly.
+    ///
+    /// If there are any subwoofers, the low end of the speaker's feed is redirected to them.
+    FullRange,
+    /// The speaker is never panned to, and instead receives the summed low end of all full-range
+    /// speakers that share an installation with it.
+    ///
+    /// A subwoofer that is not assigned to any installation receives the low end of all
+    /// full-range speakers.
+    Subwoofer,
+}
+
+impl Default for Role {
+    fn default() -> Self {
+        Role::FullRange
+    }
+}
+
+impl Speaker {
+    /// Whether or not sounds may be panned directly to the speaker.
+    pub fn is_panned(&self) -> bool {
+        self.role == Role::FullRange
+    }
+}
+
+/// The delay applied to a speaker's output so that mixed-distance arrays line up in time.
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub enum Delay {
+    /// A fixed delay set by the user.
+    Manual(Ms),
+    /// A delay calculated from the speaker's distance to the project's listening point.
+    ///
+    /// All automatically delayed speakers are aligned to the most distant of them.
+    Auto,
+}
+
+impl Default for Delay {
+    fn default() -> Self {
+        Delay::Manual(Ms(0.0))
+    }
+}
+
+/// The delay required to align a speaker at `distance` metres from the listening point with a
+/// speaker at `max_distance` metres.
+pub fn auto_delay(distance: f64, max_distance: f64) -> Ms {
+    let secs = (max_distance - distance).max(0.0) / audio::SPEED_OF_SOUND_M_PER_SEC;
+    Ms(secs * 1_000.0)
 }
 
 /// Calculate a speaker's DBAP weight taking into consideration its assigned installations.
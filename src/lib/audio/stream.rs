@@ -0,0 +1,88 @@
+//! A handle to the model of an audio stream.
+//!
+//! Streams are normally driven by an audio device, however the server may also drive the model
+//! itself, e.g. when rendering offline faster than realtime.
+
+use nannou_audio;
+use std::sync::{mpsc, Arc, Mutex};
+
+/// A handle for updating the model of an audio stream.
+pub enum Stream<M> {
+    /// A stream whose model is driven by an audio device.
+    Device(nannou_audio::Stream<M>),
+    /// A stream whose model is driven by the server itself.
+    Virtual(Arc<Mutex<M>>),
+}
+
+impl<M> Stream<M>
+where
+    M: 'static + Send,
+{
+    /// Create a stream whose model is driven by the server itself.
+    pub fn new_virtual(model: M) -> Self {
+        Stream::Virtual(Arc::new(Mutex::new(model)))
+    }
+
+    /// Send a `FnOnce(&mut M)` function to update the model of the stream.
+    ///
+    /// The update is applied immediately for `Virtual` streams.
+    pub fn send<F>(&self, update: F) -> Result<(), mpsc::SendError<()>>
+    where
+        F: FnOnce(&mut M) + Send + 'static,
+    {
+        match *self {
+            Stream::Device(ref stream) => stream.send(update).map_err(|_| mpsc::SendError(())),
+            Stream::Virtual(ref model) => {
+                let mut model = model.lock().map_err(|_| mpsc::SendError(()))?;
+                update(&mut *model);
+                Ok(())
+            }
+        }
+    }
+
+    /// Start processing the stream.
+    ///
+    /// `Virtual` streams are driven by the server and are always considered to be playing.
+    pub fn play(&self) -> Result<(), String> {
+        match *self {
+            Stream::Device(ref stream) => stream.play().map_err(|err| err.to_string()),
+            Stream::Virtual(_) => Ok(()),
+        }
+    }
+
+    /// Lock the model of a `Virtual` stream in order to drive it.
+    ///
+    /// Returns `None` for `Device` streams.
+    pub fn lock_virtual(&self) -> Option<::std::sync::MutexGuard<M>> {
+        match *self {
+            Stream::Device(_) => None,
+            Stream::Virtual(ref model) => model.lock().ok(),
+        }
+    }
+}
+
+impl<M> Clone for Stream<M> {
+    fn clone(&self) -> Self {
+        match *self {
+            Stream::Device(ref stream) => Stream::Device(stream.clone()),
+            Stream::Virtual(ref model) => Stream::Virtual(model.clone()),
+        }
+    }
+}
+
+impl<M> From<nannou_audio::Stream<M>> for Stream<M> {
+    fn from(stream: nannou_audio::Stream<M>) -> Self {
+        Stream::Device(stream)
+    }
+}
+
+#[test]
+fn virtual_streams_apply_updates_immediately() {
+    let stream = Stream::new_virtual(0);
+    let clone = stream.clone();
+    assert!(stream.play().is_ok());
+    clone.send(|n| *n += 1).unwrap();
+    stream.send(|n| *n *= 10).unwrap();
+    assert_eq!(*stream.lock_virtual().unwrap(), 10);
+    assert_eq!(*clone.lock_virtual().unwrap(), 10);
+}
@@ -0,0 +1,257 @@
+//! An implementation of Vector Base Amplitude Panning as published by Ville Pulkki, 1997.
+//!
+//! Unlike DBAP, VBAP only ever feeds the pair of speakers that enclose the direction of the source
+//! as seen from the listening position. This keeps sources sharply localised within ring and line
+//! arrays, where DBAP tends to smear a source across every nearby speaker.
+//!
+//! The listening position is typically the centroid of all weighted speakers of the installations
+//! in which the source may play, so that the same project may be used without having to specify a
+//! "sweet spot" for each installation. It is given explicitly rather than derived from the speakers
+//! passed to `speaker_gains`, as those are usually only the speakers within proximity of the source
+//! and their centroid would follow the source around the array.
+
+use nannou::glam::DVec2 as Point2;
+use std::f64::consts::PI;
+
+/// The minimum angle in radians that may separate two speakers for them to be considered a valid
+/// VBAP pair.
+///
+/// Pairs that are closer than this are numerically unstable when inverting the base matrix.
+const MIN_PAIR_RADIANS: f64 = 0.001;
+
+/// A speaker that may be used for VBAP.
+#[derive(Copy, Clone, Debug)]
+pub struct Speaker {
+    /// The location of the speaker within the exhibition.
+    pub point: Point2,
+    /// The weight of the speaker. Speakers with a weight of `0.0` are never selected.
+    pub weight: f64,
+}
+
+/// The centroid of the given speakers with a non-zero weight, for use as the listening position.
+///
+/// Returns `None` if none of the speakers are weighted.
+pub fn listening_point<I>(speakers: I) -> Option<Point2>
+where
+    I: IntoIterator<Item = Speaker>,
+{
+    let (sum, count) = speakers
+        .into_iter()
+        .filter(|s| s.weight > 0.0)
+        .fold((Point2::ZERO, 0), |(sum, n), s| (sum + s.point, n + 1));
+    if count == 0 {
+        None
+    } else {
+        Some(sum / count as f64)
+    }
+}
+
+/// Calculate the gain for each of the given speakers for a source at the given point, as heard
+/// from the given listening point.
+///
+/// The resulting gains are written to `gains` in the same order as `speakers`. `gains` is cleared
+/// first, and will not allocate as long as it has a capacity of at least `speakers.len()`.
+///
+/// At most two speakers will have a non-zero gain and the gains are normalised for constant power.
+pub fn speaker_gains(
+    source: Point2,
+    listener: Point2,
+    speakers: &[Speaker],
+    gains: &mut Vec<f64>,
+) {
+    gains.clear();
+    gains.extend(speakers.iter().map(|_| 0.0));
+
+    // Only weighted speakers are considered for selection.
+    let count = speakers.iter().filter(|s| s.weight > 0.0).count();
+    if count == 0 {
+        return;
+    }
+    if count == 1 {
+        let i = speakers.iter().position(|s| s.weight > 0.0).unwrap();
+        gains[i] = speakers[i].weight;
+        return;
+    }
+
+    // If the source sits on the listening position there is no direction to pan towards, so
+    // spread the source evenly across all weighted speakers.
+    let source_direction = source - listener;
+    if source_direction.length_squared() <= ::std::f64::EPSILON {
+        let gain = 1.0 / (count as f64).sqrt();
+        for (g, s) in gains.iter_mut().zip(speakers) {
+            if s.weight > 0.0 {
+                *g = gain * s.weight;
+            }
+        }
+        return;
+    }
+    let source_radians = source_direction.y.atan2(source_direction.x);
+
+    // Find the closest speaker in each rotational direction from the source.
+    //
+    // Together these two speakers are the active pair that encloses the source.
+    let mut ccw: Option<(usize, f64)> = None;
+    let mut cw: Option<(usize, f64)> = None;
+    for (i, s) in speakers.iter().enumerate() {
+        if s.weight <= 0.0 {
+            continue;
+        }
+        let direction = s.point - listener;
+        if direction.length_squared() <= ::std::f64::EPSILON {
+            continue;
+        }
+        let radians = direction.y.atan2(direction.x);
+        let delta = wrap_radians(radians - source_radians);
+        if delta >= 0.0 {
+            if ccw.map(|(_, d)| delta < d).unwrap_or(true) {
+                ccw = Some((i, delta));
+            }
+        } else if cw.map(|(_, d)| delta > d).unwrap_or(true) {
+            cw = Some((i, delta));
+        }
+    }
+
+    let pair = match (ccw, cw) {
+        (Some((a, da)), Some((b, db))) => {
+            let span = da - db;
+            if span < PI && span > MIN_PAIR_RADIANS {
+                Some((a, b))
+            } else {
+                None
+            }
+        }
+        _ => None,
+    };
+
+    match pair {
+        Some((a, b)) => {
+            let la = (speakers[a].point - listener).normalize();
+            let lb = (speakers[b].point - listener).normalize();
+            let p = source_direction.normalize();
+            match pair_gains(p, la, lb) {
+                Some((ga, gb)) => {
+                    gains[a] = ga * speakers[a].weight;
+                    gains[b] = gb * speakers[b].weight;
+                }
+                None => nearest_pair_gains(source, speakers, gains),
+            }
+        }
+        // Either the source lies outside of the array (e.g. a line array or an arc) or the only
+        // enclosing pair is wider than 180 degrees. In both cases fall back to panning between the
+        // two nearest speakers.
+        None => nearest_pair_gains(source, speakers, gains),
+    }
+}
+
+/// Solve the 2D VBAP base matrix for the pair of speaker directions `la` and `lb` and normalise
+/// the result for constant power.
+///
+/// Returns `None` if the speaker directions are collinear.
+fn pair_gains(p: Point2, la: Point2, lb: Point2) -> Option<(f64, f64)> {
+    let det = la.x * lb.y - la.y * lb.x;
+    if det.abs() <= ::std::f64::EPSILON {
+        return None;
+    }
+    let ga = ((p.x * lb.y - p.y * lb.x) / det).max(0.0);
+    let gb = ((la.x * p.y - la.y * p.x) / det).max(0.0);
+    let norm = (ga * ga + gb * gb).sqrt();
+    if norm <= 0.0 {
+        return None;
+    }
+    Some((ga / norm, gb / norm))
+}
+
+/// Pan between the two weighted speakers nearest to the source.
+///
+/// The source is projected onto the line between the two speakers and panned with a constant
+/// power law.
+fn nearest_pair_gains(source: Point2, speakers: &[Speaker], gains: &mut [f64]) {
+    let mut nearest: Option<(usize, f64)> = None;
+    let mut second: Option<(usize, f64)> = None;
+    for (i, s) in speakers.iter().enumerate() {
+        if s.weight <= 0.0 {
+            continue;
+        }
+        let d = s.point.distance_squared(source);
+        if nearest.map(|(_, nd)| d < nd).unwrap_or(true) {
+            second = nearest;
+            nearest = Some((i, d));
+        } else if second.map(|(_, sd)| d < sd).unwrap_or(true) {
+            second = Some((i, d));
+        }
+    }
+    match (nearest, second) {
+        (Some((a, _)), Some((b, _))) => {
+            let ab = speakers[b].point - speakers[a].point;
+            let len_2 = ab.length_squared();
+            let t = if len_2 > 0.0 {
+                ((source - speakers[a].point).dot(ab) / len_2).max(0.0).min(1.0)
+            } else {
+                0.0
+            };
+            let radians = t * PI * 0.5;
+            gains[a] = radians.cos() * speakers[a].weight;
+            gains[b] = radians.sin() * speakers[b].weight;
+        }
+        (Some((a, _)), None) => gains[a] = speakers[a].weight,
+        _ => (),
+    }
+}
+
+/// Wrap the given angle to the range `-PI..PI`.
+fn wrap_radians(radians: f64) -> f64 {
+    let wrapped = (radians + PI) % (2.0 * PI);
+    if wrapped < 0.0 {
+        wrapped + PI
+    } else {
+        wrapped - PI
+    }
+}
+
+#[test]
+fn speaker_gains_ring() {
+    let speaker = |x, y| Speaker {
+        point: Point2::new(x, y),
+        weight: 1.0,
+    };
+    let spkrs = vec![
+        speaker(1.0, 0.0),
+        speaker(0.0, 1.0),
+        speaker(-1.0, 0.0),
+        speaker(0.0, -1.0),
+    ];
+    let mut gains = Vec::with_capacity(spkrs.len());
+    let listener = listening_point(spkrs.iter().cloned()).unwrap();
+
+    // A source directly in front of a speaker should only be heard from that speaker.
+    speaker_gains(Point2::new(0.5, 0.0), listener, &spkrs, &mut gains);
+    assert!((gains[0] - 1.0).abs() < 1e-9);
+    assert!(gains[1..].iter().all(|&g| g.abs() < 1e-9));
+
+    // A source between two speakers should be shared equally between only those two.
+    speaker_gains(Point2::new(0.5, 0.5), listener, &spkrs, &mut gains);
+    assert!((gains[0] - gains[1]).abs() < 1e-9);
+    assert!(gains[2].abs() < 1e-9 && gains[3].abs() < 1e-9);
+    let power = gains.iter().map(|g| g * g).sum::<f64>();
+    assert!((power - 1.0).abs() < 1e-9);
+
+    // The direction is measured from the listener even when only nearby speakers are given.
+    let nearby = vec![spkrs[0], spkrs[1], spkrs[3]];
+    speaker_gains(Point2::new(0.9, 0.1), listener, &nearby, &mut gains);
+    assert!(gains[0] > gains[1] && gains[1] > 0.0);
+    assert!(gains[2].abs() < 1e-9);
+}
+
+#[test]
+fn speaker_gains_line() {
+    let speaker = |x| Speaker {
+        point: Point2::new(x, 0.0),
+        weight: 1.0,
+    };
+    let spkrs = vec![speaker(0.0), speaker(2.0), speaker(4.0)];
+    let mut gains = Vec::with_capacity(spkrs.len());
+    let listener = listening_point(spkrs.iter().cloned()).unwrap();
+    speaker_gains(Point2::new(3.0, 1.0), listener, &spkrs, &mut gains);
+    assert!(gains[0].abs() < 1e-9);
+    assert!((gains[1] - gains[2]).abs() < 1e-9);
+}
@@ -0,0 +1,317 @@
+//! A virtual audio device for running the server without a sound card.
+//!
+//! The virtual device drives the `Virtual` input and output streams from a timer-driven thread,
+//! capturing and rendering one buffer at a time at the same rate as a real device would. The input
+//! is fed from looped WAV files and the output may be written to a WAV file or discarded.
+
+use audio::buffer::Buffer;
+use audio::{input, output, FRAMES_PER_BUFFER, SAMPLE_RATE};
+use hound;
+use nannou_audio::dasp_sample::{FromSample, Sample};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::Arc;
+use std::{cmp, thread, time};
+
+/// Selects the virtual device when used as the `target_output_device_name`.
+pub const DEVICE_NAME: &'static str = "virtual";
+
+/// The type of WAV writer used for the output.
+type WavWriter = hound::WavWriter<io::BufWriter<fs::File>>;
+
+/// Configuration for the virtual device.
+///
+/// Paths are relative to the assets directory.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Config {
+    /// The number of input channels.
+    #[serde(default = "default_input_channels")]
+    pub input_channels: usize,
+    /// The number of output channels.
+    #[serde(default = "default_output_channels")]
+    pub output_channels: usize,
+    /// Looped WAVs whose channels are fed to the input channels in order.
+    #[serde(default)]
+    pub input_wavs: Vec<PathBuf>,
+    /// If specified, the output is written to this WAV file. Otherwise it is discarded.
+    #[serde(default)]
+    pub output_wav: Option<PathBuf>,
+}
+
+/// A handle to the thread driving the virtual device.
+///
+/// The thread is stopped and the output WAV finalised when the handle is dropped.
+pub struct Device {
+    is_running: Arc<AtomicBool>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+// A WAV loaded into memory for feeding the input.
+struct InputWav {
+    samples: Vec<f32>,
+    channels: usize,
+    // The next frame to be read.
+    frame: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            input_channels: default_input_channels(),
+            output_channels: default_output_channels(),
+            input_wavs: Default::default(),
+            output_wav: Default::default(),
+        }
+    }
+}
+
+impl Drop for Device {
+    fn drop(&mut self) {
+        self.is_running.store(false, atomic::Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            thread.join().ok();
+        }
+    }
+}
+
+impl InputWav {
+    // Load the WAV at the given path into memory.
+    fn load(path: &Path) -> Result<Self, hound::Error> {
+        let mut reader = hound::WavReader::open(path)?;
+        let spec = reader.spec();
+        let samples = match (spec.sample_format, spec.bits_per_sample) {
+            (hound::SampleFormat::Float, 32) => read_samples::<f32>(&mut reader)?,
+            (hound::SampleFormat::Int, 8) => read_samples::<i8>(&mut reader)?,
+            (hound::SampleFormat::Int, 16) => read_samples::<i16>(&mut reader)?,
+            (hound::SampleFormat::Int, 32) => read_samples::<i32>(&mut reader)?,
+            _ => return Err(hound::Error::Unsupported),
+        };
+        let channels = spec.channels as usize;
+        Ok(InputWav { samples, channels, frame: 0 })
+    }
+
+    // The number of frames within the WAV.
+    fn len_frames(&self) -> usize {
+        self.samples.len() / self.channels
+    }
+}
+
+/// The number of input and output channels of the virtual device, limited to `MAX_CHANNELS`.
+pub fn channels(config: &Config) -> (usize, usize) {
+    let clamp = |n: usize| cmp::max(1, cmp::min(n, super::MAX_CHANNELS));
+    (clamp(config.input_channels), clamp(config.output_channels))
+}
+
+/// Spawn the thread driving the given `Virtual` input and output streams.
+pub fn spawn(
+    assets: &Path,
+    config: &Config,
+    input_stream: input::Stream,
+    output_stream: output::Stream,
+) -> io::Result<Device> {
+    let (input_channels, output_channels) = channels(config);
+
+    // Load the input WAVs, skipping those that cannot be read.
+    let inputs = config
+        .input_wavs
+        .iter()
+        .filter_map(|path| {
+            let path = assets.join(path);
+            match InputWav::load(&path) {
+                Ok(wav) if wav.len_frames() > 0 => Some(wav),
+                Ok(_) => None,
+                Err(err) => {
+                    eprintln!("failed to load virtual input \"{}\": {}", path.display(), err);
+                    None
+                }
+            }
+        })
+        .collect();
+
+    // Create the output WAV if necessary.
+    let writer = match config.output_wav {
+        None => None,
+        Some(ref path) => {
+            let path = assets.join(path);
+            if let Some(directory) = path.parent() {
+                fs::create_dir_all(directory)?;
+            }
+            let spec = hound::WavSpec {
+                channels: output_channels as u16,
+                sample_rate: SAMPLE_RATE as u32,
+                bits_per_sample: 32,
+                sample_format: hound::SampleFormat::Float,
+            };
+            let writer = hound::WavWriter::create(&path, spec)
+                .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+            Some(writer)
+        }
+    };
+
+    let is_running = Arc::new(AtomicBool::new(true));
+    let is_running_2 = is_running.clone();
+    let thread = thread::Builder::new()
+        .name("virtual_audio_device".into())
+        .spawn(move || {
+            run(
+                inputs,
+                input_channels,
+                output_channels,
+                writer,
+                input_stream,
+                output_stream,
+                is_running_2,
+            )
+        })?;
+
+    Ok(Device {
+        is_running,
+        thread: Some(thread),
+    })
+}
+
+// The loop run on the virtual device thread.
+fn run(
+    mut inputs: Vec<InputWav>,
+    input_channels: usize,
+    output_channels: usize,
+    mut writer: Option<WavWriter>,
+    input_stream: input::Stream,
+    output_stream: output::Stream,
+    is_running: Arc<AtomicBool>,
+) {
+    let buffer_duration = time::Duration::from_secs_f64(FRAMES_PER_BUFFER as f64 / SAMPLE_RATE);
+    let mut input_samples = vec![0.0; FRAMES_PER_BUFFER * input_channels];
+    let mut output_samples = vec![0.0; FRAMES_PER_BUFFER * output_channels];
+    let mut next_buffer = time::Instant::now();
+
+    while is_running.load(atomic::Ordering::Relaxed) {
+        // Capture the input.
+        fill_input(&mut inputs, &mut input_samples, input_channels);
+        if let Some(mut model) = input_stream.lock_virtual() {
+            let buffer = Buffer::new(&input_samples[..], input_channels);
+            input::capture_buffer(&mut model, &buffer);
+        }
+
+        // Render the output.
+        if let Some(mut model) = output_stream.lock_virtual() {
+            let mut buffer = Buffer::new(&mut output_samples[..], output_channels);
+            output::render_buffer(&mut model, &mut buffer);
+        }
+
+        // Write the output, abandoning the file on failure.
+        let result = match writer {
+            None => Ok(()),
+            Some(ref mut writer) => output_samples
+                .iter()
+                .map(|&sample| writer.write_sample(sample))
+                .collect(),
+        };
+        if let Err(err) = result {
+            eprintln!("failed to write virtual output: {}", err);
+            writer = None;
+        }
+
+        // Wait until the next buffer is due.
+        next_buffer += buffer_duration;
+        let now = time::Instant::now();
+        if next_buffer > now {
+            thread::sleep(next_buffer - now);
+        }
+    }
+
+    if let Some(writer) = writer {
+        if let Err(err) = writer.finalize() {
+            eprintln!("failed to finalise virtual output: {}", err);
+        }
+    }
+}
+
+// Fill the interleaved input buffer with the next frames of each input WAV.
+//
+// The channels of each WAV are assigned to the input channels in order. Any remaining channels are
+// silent.
+fn fill_input(inputs: &mut [InputWav], samples: &mut [f32], channels: usize) {
+    for frame in samples.chunks_mut(channels) {
+        let mut channel = 0;
+        for wav in inputs.iter_mut() {
+            let start = wav.frame * wav.channels;
+            for &sample in &wav.samples[start..start + wav.channels] {
+                if channel < channels {
+                    frame[channel] = sample;
+                }
+                channel += 1;
+            }
+            wav.frame = (wav.frame + 1) % wav.len_frames();
+        }
+        for sample in frame.iter_mut().skip(channel) {
+            *sample = 0.0;
+        }
+    }
+}
+
+// Read all samples from the given reader, converted to `f32`.
+fn read_samples<H>(
+    reader: &mut hound::WavReader<io::BufReader<fs::File>>,
+) -> Result<Vec<f32>, hound::Error>
+where
+    H: hound::Sample + Sample,
+    f32: FromSample<H>,
+{
+    reader
+        .samples::<H>()
+        .map(|result| result.map(Sample::to_sample))
+        .collect()
+}
+
+fn default_input_channels() -> usize {
+    2
+}
+
+fn default_output_channels() -> usize {
+    8
+}
+
+#[test]
+fn virtual_input_reaches_the_realtime_source_channel() {
+    use audio::source;
+    use crossbeam::queue::SegQueue;
+    use time_calc::Ms;
+
+    // A stereo input WAV whose second channel carries a known ramp.
+    let frames = FRAMES_PER_BUFFER;
+    let samples = (0..frames).flat_map(|i| vec![0.0, i as f32]).collect();
+    let mut inputs = vec![InputWav { samples, channels: 2, frame: 0 }];
+
+    // A realtime source reading only the second input channel, with a single active sound.
+    let id = source::Id(0);
+    let mut model = input::Model::new();
+    let realtime = source::Realtime { duration: Ms(1_000.0), channels: 1..2 };
+    model.sources.insert(id, realtime);
+    let sound_buffers = Arc::new(SegQueue::new());
+    let sound = input::ActiveSound {
+        duration: input::Duration::Infinite,
+        is_capturing: Arc::new(AtomicBool::new(true)),
+        is_closed: Arc::new(AtomicBool::new(false)),
+        buffer_tx: sound_buffers.clone(),
+        buffer_rx: Arc::new(SegQueue::new()),
+    };
+    model.active_sounds.insert(id, vec![sound]);
+    let stream = input::Stream::new_virtual(model);
+
+    // Capture a single buffer from a virtual device with three input channels.
+    let channels = 3;
+    let mut samples = vec![1.0; frames * channels];
+    fill_input(&mut inputs, &mut samples, channels);
+    assert!(samples.chunks(channels).all(|frame| frame[2] == 0.0));
+    {
+        let mut model = stream.lock_virtual().expect("failed to lock the virtual input model");
+        input::capture_buffer(&mut model, &Buffer::new(&samples[..], channels));
+    }
+
+    let captured = sound_buffers.pop().expect("no samples were captured");
+    let expected: Vec<f32> = (0..frames).map(|i| i as f32).collect();
+    assert_eq!(captured, expected);
+}
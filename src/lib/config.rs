@@ -1,3 +1,4 @@
+use audio;
 use project;
 use std::ops::Deref;
 
@@ -24,8 +25,13 @@ pub struct Config {
     ///
     /// If the device cannot be found, or if the string is empty, the default output device will be
     /// selected.
+    ///
+    /// If this is "virtual", the virtual audio device is used instead of a real device.
     #[serde(default)]
     pub target_output_device_name: String,
+    /// The configuration of the virtual audio device, used when running without a sound card.
+    #[serde(default)]
+    pub virtual_audio_device: audio::virtual_device::Config,
 }
 
 impl Default for Config {
@@ -35,12 +41,14 @@ impl Default for Config {
         let cpu_saving_mode = Default::default();
         let target_input_device_name = Default::default();
         let target_output_device_name = Default::default();
+        let virtual_audio_device = Default::default();
         Config {
             project_default,
             selected_project_slug,
             cpu_saving_mode,
             target_input_device_name,
             target_output_device_name,
+            virtual_audio_device,
         }
     }
 }
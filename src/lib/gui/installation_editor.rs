@@ -1,3 +1,4 @@
+use audio;
 use gui::{self, collapsible_area, Channels, Gui, ProjectState, State};
 use gui::{ITEM_HEIGHT, SMALL_FONT_SIZE};
 use installation;
@@ -6,8 +7,10 @@ use nannou_osc::Connected;
 use nannou::ui::prelude::*;
 use osc;
 use project::{self, Project};
+use soundscape;
 use std::{io, net};
 use std::sync::Arc;
+use time_calc::Ms;
 
 /// Runtime state relevant to the installation editor GUI.
 #[derive(Default)]
@@ -22,6 +25,13 @@ pub struct Selected {
     selected_computer: Option<SelectedComputer>,
 }
 
+impl Selected {
+    /// The unique identifier of the selected installation.
+    pub fn id(&self) -> installation::Id {
+        self.id
+    }
+}
+
 /// The currently selected installation computer.
 pub struct SelectedComputer {
     computer: installation::computer::Id,
@@ -38,6 +48,7 @@ pub fn set(
     let Gui {
         ref mut ui,
         ref ids,
+        ref audio_monitor,
         channels,
         state: &mut State {
             ref mut is_open,
@@ -76,12 +87,16 @@ pub fn set(
     // - Music Data OSC Output (Text and TextBox)
     let osc_canvas_h = PAD + ITEM_HEIGHT * 3.0 + PAD;
     let computer_canvas_h = ITEM_HEIGHT + PAD + ITEM_HEIGHT + PAD + COMPUTER_LIST_HEIGHT;
-    let soundscape_canvas_h = PAD + PAD * 3.0 + PAD + SLIDER_H + PAD;
+    let soundscape_canvas_h = PAD + PAD * 3.0 + PAD + SLIDER_H + (PAD + SLIDER_H) * 3.0 + PAD;
+    let compressor_canvas_h = PAD + PAD * 3.0 + (PAD + SLIDER_H) * 4.0 + PAD;
+    let zone_canvas_h = PAD + PAD * 3.0 + PAD + ITEM_HEIGHT + PAD;
     let selected_canvas_h = PAD
         + NAME_H + PAD
         + computer_canvas_h + PAD
         + osc_canvas_h + PAD
-        + soundscape_canvas_h + PAD;
+        + soundscape_canvas_h + PAD
+        + compressor_canvas_h + PAD
+        + zone_canvas_h + PAD;
 
     // The total height of the installation editor as a sum of the previous heights plus necessary
     // padding.
@@ -438,6 +453,253 @@ pub fn set(
             .expect("failed to send installation update to soundscape thread");
     }
 
+    ///////////////
+    // OCCUPANCY //
+    ///////////////
+
+    // How strongly the reported visitor occupancy determines the target number of sounds.
+    let weight = installations[&id].soundscape.occupancy_weight;
+    let label = format!("Follow Occupancy: {:.0}%", weight * 100.0);
+    for new_weight in widget::Slider::new(weight, 0.0, 1.0)
+        .kid_area_w_of(ids.installation_editor_soundscape_canvas)
+        .h(SLIDER_H)
+        .label(&label)
+        .label_font_size(SMALL_FONT_SIZE)
+        .color(ui::color::LIGHT_CHARCOAL)
+        .align_left()
+        .down(PAD)
+        .set(ids.installation_editor_soundscape_occupancy_weight_slider, ui)
+    {
+        // Update the local copy.
+        installations.get_mut(&id).unwrap().soundscape.occupancy_weight = new_weight;
+
+        // Update the soundscape copy.
+        channels
+            .soundscape
+            .send(move |soundscape| {
+                soundscape.update_installation(&id, |installation| {
+                    installation.occupancy_weight = new_weight;
+                });
+            })
+            .expect("failed to send installation update to soundscape thread");
+    }
+
+    // The duration over which changes in reported occupancy are smoothed.
+    let smoothing_ms = installations[&id].soundscape.occupancy_smoothing.ms();
+    let label = format!("Occupancy Smoothing: {:.1} secs", smoothing_ms / 1_000.0);
+    for new_smoothing_ms in widget::Slider::new(smoothing_ms, 0.0, 300_000.0)
+        .skew(0.5)
+        .kid_area_w_of(ids.installation_editor_soundscape_canvas)
+        .h(SLIDER_H)
+        .label(&label)
+        .label_font_size(SMALL_FONT_SIZE)
+        .color(ui::color::LIGHT_CHARCOAL)
+        .align_left()
+        .down(PAD)
+        .set(ids.installation_editor_soundscape_occupancy_smoothing_slider, ui)
+    {
+        let smoothing = Ms(new_smoothing_ms);
+
+        // Update the local copy.
+        installations.get_mut(&id).unwrap().soundscape.occupancy_smoothing = smoothing;
+
+        // Update the soundscape copy.
+        channels
+            .soundscape
+            .send(move |soundscape| {
+                soundscape.update_installation(&id, |installation| {
+                    installation.occupancy_smoothing = smoothing;
+                });
+            })
+            .expect("failed to send installation update to soundscape thread");
+    }
+
+    // The number of visitors at which the installation is considered full.
+    const MAX_CAPACITY: f64 = 500.0;
+    let capacity = installations[&id].soundscape.capacity;
+    let label = format!("Capacity: {} visitors", capacity);
+    for new_capacity in widget::Slider::new(capacity as f64, 1.0, MAX_CAPACITY)
+        .skew(0.5)
+        .kid_area_w_of(ids.installation_editor_soundscape_canvas)
+        .h(SLIDER_H)
+        .label(&label)
+        .label_font_size(SMALL_FONT_SIZE)
+        .color(ui::color::LIGHT_CHARCOAL)
+        .align_left()
+        .down(PAD)
+        .set(ids.installation_editor_soundscape_capacity_slider, ui)
+    {
+        let new_capacity = new_capacity.round() as usize;
+        if new_capacity == capacity {
+            continue;
+        }
+
+        // Update the local copy.
+        installations.get_mut(&id).unwrap().soundscape.capacity = new_capacity;
+
+        // Update the soundscape copy.
+        channels
+            .soundscape
+            .send(move |soundscape| {
+                soundscape.update_installation(&id, |installation| {
+                    installation.capacity = new_capacity;
+                });
+            })
+            .expect("failed to send installation update to soundscape thread");
+    }
+
+    ////////////////
+    // COMPRESSOR //
+    ////////////////
+
+    // The canvas for displaying the installation's compressor.
+    widget::Canvas::new()
+        .align_middle_x_of(ids.installation_editor_selected_canvas)
+        .down_from(ids.installation_editor_soundscape_canvas, PAD)
+        .parent(ids.installation_editor_selected_canvas)
+        .color(color::CHARCOAL)
+        .w(selected_canvas_kid_area.w())
+        .h(compressor_canvas_h)
+        .pad(PAD)
+        .set(ids.installation_editor_compressor_canvas, ui);
+
+    // Compressor header, displaying the current gain reduction.
+    let gain_reduction_db = audio_monitor
+        .compressor_gain_reduction_db
+        .get(&id)
+        .cloned()
+        .unwrap_or(0.0);
+    let text = format!("Speaker Compressor - GR {:.1} dB", gain_reduction_db);
+    widget::Text::new(&text)
+        .font_size(SMALL_FONT_SIZE)
+        .top_left_of(ids.installation_editor_compressor_canvas)
+        .set(ids.installation_editor_compressor_text, ui);
+
+    // Sends the installation's compressor parameters to the audio output thread.
+    let send_compressor = |compressor: audio::dynamics::CompressorParams| {
+        channels
+            .audio_output
+            .send(move |audio| {
+                audio.set_installation_compressor(id, compressor);
+            })
+            .expect("failed to send installation compressor to audio output thread");
+    };
+
+    // Toggle the compressor on or off.
+    let enabled = installations[&id].compressor.enabled;
+    let label = if enabled { "ENABLED" } else { "DISABLED" };
+    for new_enabled in widget::Toggle::new(enabled)
+        .kid_area_w_of(ids.installation_editor_compressor_canvas)
+        .h(SLIDER_H)
+        .label(label)
+        .label_font_size(SMALL_FONT_SIZE)
+        .color(ui::color::LIGHT_CHARCOAL)
+        .align_left()
+        .down(PAD * 2.0)
+        .set(ids.installation_editor_compressor_enabled, ui)
+    {
+        let installation = installations.get_mut(&id).unwrap();
+        installation.compressor.enabled = new_enabled;
+        send_compressor(installation.compressor);
+    }
+
+    // The level above which the speakers are compressed.
+    let threshold_db = installations[&id].compressor.threshold_db;
+    let label = format!("Threshold: {:.1} dB", threshold_db);
+    for new_threshold_db in widget::Slider::new(threshold_db, -48.0, 0.0)
+        .kid_area_w_of(ids.installation_editor_compressor_canvas)
+        .h(SLIDER_H)
+        .label(&label)
+        .label_font_size(SMALL_FONT_SIZE)
+        .color(ui::color::LIGHT_CHARCOAL)
+        .align_left()
+        .down(PAD)
+        .set(ids.installation_editor_compressor_threshold, ui)
+    {
+        let installation = installations.get_mut(&id).unwrap();
+        installation.compressor.threshold_db = new_threshold_db;
+        send_compressor(installation.compressor);
+    }
+
+    // The compression ratio.
+    let ratio = installations[&id].compressor.ratio;
+    let label = format!("Ratio: {:.1}:1", ratio);
+    for new_ratio in widget::Slider::new(ratio, 1.0, 20.0)
+        .skew(0.5)
+        .kid_area_w_of(ids.installation_editor_compressor_canvas)
+        .h(SLIDER_H)
+        .label(&label)
+        .label_font_size(SMALL_FONT_SIZE)
+        .color(ui::color::LIGHT_CHARCOAL)
+        .align_left()
+        .down(PAD)
+        .set(ids.installation_editor_compressor_ratio, ui)
+    {
+        let installation = installations.get_mut(&id).unwrap();
+        installation.compressor.ratio = new_ratio;
+        send_compressor(installation.compressor);
+    }
+
+    // The time taken for the gain to recover.
+    let release_ms = installations[&id].compressor.release.ms();
+    let label = format!("Release: {:.0} ms", release_ms);
+    for new_release_ms in widget::Slider::new(release_ms, 10.0, 2_000.0)
+        .skew(0.5)
+        .kid_area_w_of(ids.installation_editor_compressor_canvas)
+        .h(SLIDER_H)
+        .label(&label)
+        .label_font_size(SMALL_FONT_SIZE)
+        .color(ui::color::LIGHT_CHARCOAL)
+        .align_left()
+        .down(PAD)
+        .set(ids.installation_editor_compressor_release, ui)
+    {
+        let installation = installations.get_mut(&id).unwrap();
+        installation.compressor.release = Ms(new_release_ms);
+        send_compressor(installation.compressor);
+    }
+
+    //////////
+    // ZONE //
+    //////////
+
+    // The canvas for displaying the installation's zone.
+    widget::Canvas::new()
+        .align_middle_x_of(ids.installation_editor_selected_canvas)
+        .down_from(ids.installation_editor_compressor_canvas, PAD)
+        .parent(ids.installation_editor_selected_canvas)
+        .color(color::CHARCOAL)
+        .w(selected_canvas_kid_area.w())
+        .h(zone_canvas_h)
+        .pad(PAD)
+        .set(ids.installation_editor_zone_canvas, ui);
+
+    // Zone header, displaying the number of vertices.
+    let num_vertices = installations[&id].zone.len();
+    let text = if num_vertices < soundscape::movement::zone::MIN_VERTICES {
+        format!("Zone - {} vertices (using speaker bounds)", num_vertices)
+    } else {
+        format!("Zone - {} vertices", num_vertices)
+    };
+    widget::Text::new(&text)
+        .font_size(SMALL_FONT_SIZE)
+        .top_left_of(ids.installation_editor_zone_canvas)
+        .set(ids.installation_editor_zone_text, ui);
+
+    // Remove all vertices, returning the installation to the area of its speakers.
+    for _click in widget::Button::new()
+        .kid_area_w_of(ids.installation_editor_zone_canvas)
+        .h(ITEM_HEIGHT)
+        .label("CLEAR ZONE")
+        .label_font_size(SMALL_FONT_SIZE)
+        .color(ui::color::LIGHT_CHARCOAL)
+        .align_left()
+        .down(PAD * 2.0)
+        .set(ids.installation_editor_zone_clear, ui)
+    {
+        update_zone(installations, channels, id, Vec::new());
+    }
+
     ///////////////
     // COMPUTERS //
     ///////////////
@@ -445,7 +707,7 @@ pub fn set(
     // The canvas for displaying the computer selection / editor.
     widget::Canvas::new()
         .middle_of(ids.installation_editor_selected_canvas)
-        .down_from(ids.installation_editor_soundscape_canvas, PAD)
+        .down_from(ids.installation_editor_zone_canvas, PAD)
         .color(color::CHARCOAL)
         .w(selected_canvas_kid_area.w())
         .h(computer_canvas_h)
@@ -717,3 +979,24 @@ pub fn set(
 
     area.id
 }
+
+/// Update the zone of the installation with the given `Id`, both locally and on the soundscape
+/// thread.
+pub fn update_zone(
+    installations: &mut project::Installations,
+    channels: &Channels,
+    id: installation::Id,
+    zone: Vec<nannou::glam::DVec2>,
+) {
+    let installation = match installations.get_mut(&id) {
+        None => return,
+        Some(installation) => installation,
+    };
+    installation.zone = zone.clone();
+    channels
+        .soundscape
+        .send(move |soundscape| {
+            soundscape.update_installation_zone(id, zone);
+        })
+        .expect("failed to send installation zone to soundscape thread");
+}
@@ -1,12 +1,14 @@
 //! A "Master" side-bar widget providing control over master volume and input latency.
 
-use gui::{collapsible_area, Gui};
+use gui::{collapsible_area, Channels, Gui, Recorder};
 use gui::{ITEM_HEIGHT, SMALL_FONT_SIZE};
+use audio;
 use project::{self, Project};
 use nannou::ui;
 use nannou::ui::prelude::*;
 use time_calc::Ms;
 use metres::Metres;
+use std::path::Path;
 
 pub fn set(last_area_id: widget::Id, gui: &mut Gui, project: &mut Project) -> widget::Id {
     let Gui {
@@ -15,10 +17,12 @@ pub fn set(last_area_id: widget::Id, gui: &mut Gui, project: &mut Project) -> wi
         ref ids,
         ref channels,
         ref mut state,
+        ref assets,
         ..
     } = *gui;
     let Project {
         state: project::State {
+            ref camera,
             ref mut master,
             ..
         },
@@ -31,7 +35,14 @@ pub fn set(last_area_id: widget::Id, gui: &mut Gui, project: &mut Project) -> wi
     const LATENCY_H: Scalar = ITEM_HEIGHT;
     const DECIBEL_H: Scalar = ITEM_HEIGHT;
     const PROXIMITY_H: Scalar = ITEM_HEIGHT;
-    const MASTER_H: Scalar = PAD + MASTER_VOLUME_H + PAD + LATENCY_H + PAD + DECIBEL_H + PAD + PROXIMITY_H + PAD;
+    const PANNING_LAW_H: Scalar = ITEM_HEIGHT;
+    const AMBISONIC_ORDER_H: Scalar = ITEM_HEIGHT;
+    const LISTENING_POINT_H: Scalar = ITEM_HEIGHT;
+    const CROSSOVER_H: Scalar = ITEM_HEIGHT;
+    const LIMITER_H: Scalar = ITEM_HEIGHT;
+    const REVERB_H: Scalar = ITEM_HEIGHT;
+    const RECORD_H: Scalar = ITEM_HEIGHT;
+    const MASTER_H: Scalar = PAD + MASTER_VOLUME_H + PAD + LATENCY_H + PAD + DECIBEL_H + PAD + PROXIMITY_H + PAD + PANNING_LAW_H + PAD + AMBISONIC_ORDER_H + PAD + LISTENING_POINT_H + PAD + CROSSOVER_H + PAD + LIMITER_H + PAD + LIMITER_H + PAD + REVERB_H + PAD + REVERB_H + PAD + REVERB_H + PAD + RECORD_H + PAD;
 
     // The collapsible area widget.
     let is_open = state.is_open.master;
@@ -181,6 +192,308 @@ pub fn set(last_area_id: widget::Id, gui: &mut Gui, project: &mut Project) -> wi
             .expect("failed to send updated proximity limit to audio output thread");
         }
 
+    // The panning law selection.
+    let labels = (0..audio::PanningLaw::VARIANT_COUNT)
+        .map(|i| audio::PanningLaw::label_from_index(i))
+        .collect::<Vec<_>>();
+    let selected = Some(master.panning_law.to_index());
+    for new_index in widget::DropDownList::new(&labels, selected)
+        .label("Panning Law")
+        .label_font_size(SMALL_FONT_SIZE)
+        .h(PANNING_LAW_H)
+        .kid_area_w_of(area.id)
+        .align_middle_x_of(area.id)
+        .down(PAD)
+        .set(ids.master_panning_law, ui)
+    {
+        let new_law = match audio::PanningLaw::from_index(new_index) {
+            Some(law) => law,
+            None => continue,
+        };
+
+        // Update the local copy.
+        master.panning_law = new_law;
+
+        // Update the audio output thread's panning law.
+        channels
+            .audio_output
+            .send(move |audio| {
+                audio.panning_law = new_law;
+            })
+            .expect("failed to send updated panning law to audio output thread");
+    }
+
+    // The order of the ambisonic bus when using the ambisonics panning law.
+    let labels = (1..audio::ambisonics::MAX_ORDER + 1)
+        .map(|order| format!("Ambisonic Order: {}", order))
+        .collect::<Vec<_>>();
+    let selected = Some(master.ambisonic_order.max(1) - 1);
+    for new_index in widget::DropDownList::new(&labels, selected)
+        .label("Ambisonic Order")
+        .label_font_size(SMALL_FONT_SIZE)
+        .h(AMBISONIC_ORDER_H)
+        .kid_area_w_of(area.id)
+        .align_middle_x_of(area.id)
+        .down(PAD)
+        .set(ids.master_ambisonic_order, ui)
+    {
+        let new_order = new_index + 1;
+
+        // Update the local copy.
+        master.ambisonic_order = new_order;
+
+        // Update the audio output thread's decoders.
+        channels
+            .audio_output
+            .send(move |audio| {
+                audio.set_ambisonic_order(new_order);
+            })
+            .expect("failed to send updated ambisonic order to audio output thread");
+    }
+
+    // Move the listening point used for automatic speaker delays to the centre of the floorplan.
+    let label = format!(
+        "Set Listening Point To View ({:.2}, {:.2})",
+        master.listening_point.x, master.listening_point.y
+    );
+    if widget::Button::new()
+        .label(&label)
+        .label_font_size(SMALL_FONT_SIZE)
+        .h(LISTENING_POINT_H)
+        .kid_area_w_of(area.id)
+        .align_middle_x_of(area.id)
+        .down(PAD)
+        .color(ui::color::LIGHT_CHARCOAL)
+        .set(ids.master_listening_point, ui)
+        .was_clicked()
+    {
+        // Update the local copy.
+        master.listening_point = camera.position;
+
+        // Update the audio output thread's speaker delays.
+        let listening_point = master.listening_point.extend(master.listening_elevation);
+        channels
+            .audio_output
+            .send(move |audio| {
+                audio.set_listening_point(listening_point);
+            })
+            .expect("failed to send updated listening point to audio output thread");
+    }
+
+    // The frequency at which full-range speakers are crossed over to subwoofers.
+    let label = format!("Subwoofer Crossover: {:.0} Hz", master.crossover_hz);
+    for new_hz in widget::Slider::new(master.crossover_hz, 40.0, 200.0)
+        .label(&label)
+        .label_font_size(SMALL_FONT_SIZE)
+        .h(CROSSOVER_H)
+        .kid_area_w_of(area.id)
+        .align_middle_x_of(area.id)
+        .down(PAD)
+        .set(ids.master_crossover, ui)
+    {
+        // Update the local copy.
+        master.crossover_hz = new_hz;
+
+        // Update the audio output thread's crossovers.
+        channels
+            .audio_output
+            .send(move |audio| {
+                audio.set_crossover_hz(new_hz);
+            })
+            .expect("failed to send updated crossover to audio output thread");
+    }
+
+    // The limiter threshold slider, labelled with the current gain reduction.
+    let label = format!(
+        "Limiter Threshold: {:.1} dB (GR {:.1} dB)",
+        master.limiter.threshold_db, audio_monitor.limiter_gain_reduction_db
+    );
+    for new_threshold_db in widget::Slider::new(master.limiter.threshold_db, -24.0, 0.0)
+        .label(&label)
+        .label_font_size(SMALL_FONT_SIZE)
+        .h(LIMITER_H)
+        .kid_area_w_of(area.id)
+        .align_middle_x_of(area.id)
+        .down(PAD)
+        .set(ids.master_limiter_threshold, ui)
+    {
+        // Update the local copy.
+        master.limiter.threshold_db = new_threshold_db;
+
+        // Update the audio output thread's limiter.
+        let limiter = master.limiter;
+        channels
+            .audio_output
+            .send(move |audio| {
+                audio.set_limiter(limiter);
+            })
+            .expect("failed to send updated limiter to audio output thread");
+    }
+
+    // The limiter release slider.
+    let label = format!("Limiter Release: {:.0} ms", master.limiter.release.ms());
+    for new_release_ms in widget::Slider::new(master.limiter.release.ms(), 10.0, 1_000.0)
+        .skew(0.5)
+        .label(&label)
+        .label_font_size(SMALL_FONT_SIZE)
+        .h(LIMITER_H)
+        .kid_area_w_of(area.id)
+        .align_middle_x_of(area.id)
+        .down(PAD)
+        .set(ids.master_limiter_release, ui)
+    {
+        // Update the local copy.
+        master.limiter.release = Ms(new_release_ms);
+
+        // Update the audio output thread's limiter.
+        let limiter = master.limiter;
+        channels
+            .audio_output
+            .send(move |audio| {
+                audio.set_limiter(limiter);
+            })
+            .expect("failed to send updated limiter to audio output thread");
+    }
+
+    // Sends the master reverb parameters to the audio output thread.
+    let send_reverb = |reverb: audio::reverb::Params| {
+        channels
+            .audio_output
+            .send(move |audio| {
+                audio.set_reverb(reverb);
+            })
+            .expect("failed to send updated reverb to audio output thread");
+    };
+
+    // The reverb wet/dry slider.
+    let label = format!("Reverb Wet/Dry: {:.2}", master.reverb.mix);
+    for new_mix in widget::Slider::new(master.reverb.mix, 0.0, 1.0)
+        .label(&label)
+        .label_font_size(SMALL_FONT_SIZE)
+        .h(REVERB_H)
+        .kid_area_w_of(area.id)
+        .align_middle_x_of(area.id)
+        .down(PAD)
+        .set(ids.master_reverb_mix, ui)
+    {
+        master.reverb.mix = new_mix;
+        send_reverb(master.reverb);
+    }
+
+    // The reverb decay slider.
+    let label = format!("Reverb Decay: {:.2} s", master.reverb.decay.ms() / 1_000.0);
+    for new_decay_ms in widget::Slider::new(master.reverb.decay.ms(), 100.0, 10_000.0)
+        .skew(0.5)
+        .label(&label)
+        .label_font_size(SMALL_FONT_SIZE)
+        .h(REVERB_H)
+        .kid_area_w_of(area.id)
+        .align_middle_x_of(area.id)
+        .down(PAD)
+        .set(ids.master_reverb_decay, ui)
+    {
+        master.reverb.decay = Ms(new_decay_ms);
+        send_reverb(master.reverb);
+    }
+
+    // The reverb pre-delay slider.
+    let label = format!("Reverb Pre-Delay: {:.0} ms", master.reverb.pre_delay.ms());
+    let max_pre_delay_ms = audio::reverb::MAX_PRE_DELAY.ms();
+    for new_pre_delay_ms in widget::Slider::new(master.reverb.pre_delay.ms(), 0.0, max_pre_delay_ms)
+        .label(&label)
+        .label_font_size(SMALL_FONT_SIZE)
+        .h(REVERB_H)
+        .kid_area_w_of(area.id)
+        .align_middle_x_of(area.id)
+        .down(PAD)
+        .set(ids.master_reverb_pre_delay, ui)
+    {
+        master.reverb.pre_delay = Ms(new_pre_delay_ms);
+        send_reverb(master.reverb);
+    }
+
+    // Toggles for starting and stopping recording of the master output.
+    let kid_area = ui.kid_area_of(area.id).unwrap();
+    let button_w = kid_area.w() / 2.0 - PAD / 2.0;
+    let is_recording = state.recorder.directory.is_some();
+    let label = match is_recording {
+        true => "RECORDING",
+        false => "RECORD",
+    };
+    for new_is_recording in widget::Toggle::new(is_recording)
+        .label(label)
+        .label_font_size(SMALL_FONT_SIZE)
+        .color(ui::color::DARK_RED)
+        .w(button_w)
+        .h(RECORD_H)
+        .align_left_of(area.id)
+        .down(PAD)
+        .set(ids.master_record, ui)
+    {
+        match new_is_recording {
+            true => start_recording(assets, project, &mut state.recorder, channels),
+            false => stop_recording(&mut state.recorder, channels),
+        }
+    }
+
+    // Whether new recordings write a single multichannel file or a file per speaker.
+    let label = match state.recorder.per_speaker {
+        true => "PER SPEAKER",
+        false => "MULTICHANNEL",
+    };
+    for new_per_speaker in widget::Toggle::new(state.recorder.per_speaker)
+        .label(label)
+        .label_font_size(SMALL_FONT_SIZE)
+        .color(ui::color::LIGHT_CHARCOAL)
+        .w(button_w)
+        .h(RECORD_H)
+        .right(PAD)
+        .set(ids.master_record_per_speaker, ui)
+    {
+        state.recorder.per_speaker = new_per_speaker;
+    }
 
     area.id
 }
+
+/// Begin recording the master output to a new directory within the assets `recordings` directory.
+pub fn start_recording(
+    assets: &Path,
+    project: &Project,
+    recorder: &mut Recorder,
+    channels: &Channels,
+) {
+    let directory = project::recording_directory_path(assets, &project.name);
+    let mode = match recorder.per_speaker {
+        false => audio::recorder::Mode::Multichannel,
+        true => {
+            let speakers = project
+                .speakers
+                .values()
+                .map(|s| audio::recorder::SpeakerFile {
+                    channel: s.audio.channel,
+                    name: s.name.clone(),
+                })
+                .collect();
+            audio::recorder::Mode::PerSpeaker(speakers)
+        }
+    };
+    let recording = audio::recorder::Recording {
+        directory: directory.clone(),
+        mode,
+    };
+    channels
+        .audio_output
+        .send(move |audio| audio.start_recording(recording))
+        .expect("failed to send start recording message to audio output thread");
+    recorder.directory = Some(directory);
+}
+
+/// Finalise the current recording of the master output.
+pub fn stop_recording(recorder: &mut Recorder, channels: &Channels) {
+    channels
+        .audio_output
+        .send(move |audio| audio.stop_recording())
+        .expect("failed to send stop recording message to audio output thread");
+    recorder.directory = None;
+}
@@ -2,6 +2,7 @@ use audio;
 use camera::Camera;
 use config::Config;
 use fxhash::FxHashMap;
+use installation;
 use metres::Metres;
 use nannou;
 use nannou::prelude::*;
@@ -11,7 +12,8 @@ use osc;
 use osc::input::Log as OscInputLog;
 use osc::output::Log as OscOutputLog;
 use project::{self, Project};
-use soundscape::Soundscape;
+use scene;
+use soundscape::{self, Soundscape};
 use slug::slugify;
 use std::collections::VecDeque;
 use std::path::{Path, PathBuf};
@@ -23,6 +25,8 @@ use utils::{self, HumanReadableTime, SEC_MS, MIN_MS, HR_MS};
 
 use self::installation_editor::InstallationEditor;
 use self::project_editor::ProjectEditor;
+use self::scene_editor::SceneEditor;
+use self::schedule_editor::ScheduleEditor;
 use self::soundscape_editor::SoundscapeEditor;
 use self::source_editor::{SourceEditor, SourcePreviewMode};
 use self::speaker_editor::SpeakerEditor;
@@ -35,12 +39,15 @@ pub mod monitor;
 pub mod osc_in_log;
 pub mod osc_out_log;
 pub mod project_editor;
+pub mod scene_editor;
+pub mod schedule_editor;
 pub mod source_editor;
 pub mod soundscape_editor;
 pub mod speaker_editor;
 mod theme;
 
 type ActiveSoundMap = FxHashMap<audio::sound::Id, ActiveSound>;
+type Point3 = nannou::glam::DVec3;
 
 /// The structure of the GUI.
 ///
@@ -88,6 +95,10 @@ pub struct ProjectState {
     installation_editor: InstallationEditor,
     /// Runtime state related to the source editor GUI panel.
     soundscape_editor: SoundscapeEditor,
+    /// Runtime state related to the schedule editor GUI panel.
+    schedule_editor: ScheduleEditor,
+    /// Runtime state related to the scene editor GUI panel.
+    scene_editor: SceneEditor,
     /// Runtime state related to the speaker editor GUI panel.
     speaker_editor: SpeakerEditor,
     /// Runtime state related to the source editor GUI panel.
@@ -108,6 +119,17 @@ pub struct State {
     project_editor: ProjectEditor,
     /// Whether or not each of the collapsible areas are open within the sidebar.
     is_open: IsOpen,
+    /// State related to recording the master output.
+    recorder: Recorder,
+}
+
+/// State related to recording the master output.
+#[derive(Default)]
+pub struct Recorder {
+    /// The directory of the current recording, if the master output is being recorded.
+    directory: Option<PathBuf>,
+    /// Whether or not new recordings write a mono file per speaker.
+    per_speaker: bool,
 }
 
 /// The state of each collapsible area in the sidebar.
@@ -116,6 +138,8 @@ struct IsOpen {
     master: bool,
     installation_editor: bool,
     soundscape_editor: bool,
+    schedule_editor: bool,
+    scene_editor: bool,
     speaker_editor: bool,
     source_editor: bool,
     side_menu: bool,
@@ -171,6 +195,10 @@ type ControlLog = Log<osc::input::Control>;
 #[derive(Default)]
 struct AudioMonitor {
     master_peak: f32,
+    // The gain reduction applied by the master limiter in decibels.
+    limiter_gain_reduction_db: f32,
+    // The gain reduction applied by each installation's compressor in decibels.
+    compressor_gain_reduction_db: FxHashMap<installation::Id, f32>,
     pub active_sounds: ActiveSoundMap,
     speakers: FxHashMap<audio::speaker::Id, ChannelLevels>,
 }
@@ -179,6 +207,8 @@ impl AudioMonitor {
     /// Clears all state and resets the last received master peak volume.
     pub fn clear(&mut self) {
         self.master_peak = 0.0;
+        self.limiter_gain_reduction_db = 0.0;
+        self.compressor_gain_reduction_db.clear();
         self.active_sounds.clear();
         self.speakers.clear();
     }
@@ -191,6 +221,7 @@ impl AudioMonitor {
     pub fn clear_invalid(&mut self, project: &Project) {
         self.active_sounds.retain(|_, s| project.sources.contains_key(&s.source_id));
         self.speakers.retain(|id, _| project.speakers.contains_key(id));
+        self.compressor_gain_reduction_db.retain(|id, _| project.installations.contains_key(id));
     }
 }
 
@@ -212,9 +243,11 @@ struct ChannelLevels {
 
 /// A message sent from the audio thread with some audio levels.
 pub enum AudioMonitorMessage {
-    Master { peak: f32 },
+    Master { peak: f32, gain_reduction_db: f32 },
+    Compressor { installation: installation::Id, gain_reduction_db: f32 },
     ActiveSound(audio::sound::Id, ActiveSoundMessage),
     Speaker(audio::speaker::Id, SpeakerMessage),
+    RecordingFailed(audio::recorder::Failure),
 }
 
 /// A message related to an active sound.
@@ -257,6 +290,8 @@ impl Default for IsOpen {
             master: false,
             installation_editor: false,
             soundscape_editor: false,
+            schedule_editor: false,
+            scene_editor: false,
             speaker_editor: false,
             source_editor: false,
             osc_in_log: false,
@@ -396,75 +431,8 @@ impl Model {
 
         // Handle control messages.
         for control in channels.control_rx.try_iter() {
-            match &control {
-                &osc::input::Control::MasterVolume(osc::input::MasterVolume(volume)) => {
-                    // Update local copy.
-                    if let Some((ref mut project, _)) = *project {
-                        project.master.volume = volume;
-                    }
-
-                    // Update the audio output copy.
-                    channels
-                        .audio_output
-                        .send(move |audio| audio.master_volume = volume)
-                        .expect("failed to send updated master volume to audio output thread");
-                },
-
-                &osc::input::Control::SourceVolume(ref source_volume) => {
-                    let osc::input::SourceVolume { ref name, volume } = *source_volume;
-
-                    let project = match *project {
-                        None => continue,
-                        Some((ref mut proj, _)) => proj,
-                    };
-
-                    // Update local copy.
-                    let id = match project
-                        .state
-                        .sources
-                        .iter_mut()
-                        .find(|&(_, ref s)| &s.name[..] == name)
-                    {
-                        None => continue,
-                        Some((&id, ref mut source)) => {
-                            source.volume = volume;
-                            id
-                        },
-                    };
-
-                    // Update the soundscape copy.
-                    channels
-                        .soundscape
-                        .send(move |soundscape| {
-                            soundscape.update_source(&id, |source| source.volume = volume);
-                        })
-                        .expect("failed to send updated source volume to soundscape thread");
-
-                    // Update the audio output copies.
-                    channels
-                        .audio_output
-                        .send(move |audio| {
-                            audio.update_sounds_with_source(&id, move |_, sound| {
-                                sound.volume = volume;
-                            });
-                        })
-                        .expect("failed to send updated source volume to audio output thread");
-                }
-
-                &osc::input::Control::PlaySoundscape => {
-                    channels
-                        .soundscape
-                        .play()
-                        .expect("failed to send `Play` message to soundscape thread");
-                }
-
-                &osc::input::Control::PauseSoundscape => {
-                    channels
-                        .soundscape
-                        .pause()
-                        .expect("failed to send `Pause` message to soundscape thread");
-                }
-            }
+            let project = project.as_mut().map(|&mut (ref mut project, _)| project);
+            handle_control(&control, project, assets, &mut state.recorder, channels);
 
             // Log the message.
             state.control_log.push_msg(control);
@@ -478,8 +446,12 @@ impl Model {
             };
 
             match msg {
-                AudioMonitorMessage::Master { peak } => {
+                AudioMonitorMessage::Master { peak, gain_reduction_db } => {
                     audio_monitor.master_peak = peak;
+                    audio_monitor.limiter_gain_reduction_db = gain_reduction_db;
+                },
+                AudioMonitorMessage::Compressor { installation, gain_reduction_db } => {
+                    audio_monitor.compressor_gain_reduction_db.insert(installation, gain_reduction_db);
                 },
                 AudioMonitorMessage::ActiveSound(id, msg) => match msg {
                     ActiveSoundMessage::Start {
@@ -546,6 +518,9 @@ impl Model {
                         audio_monitor.speakers.remove(&id);
                     }
                 },
+                AudioMonitorMessage::RecordingFailed(failure) => {
+                    handle_recording_failure(&failure, &mut state.recorder, channels);
+                }
             }
         }
 
@@ -635,6 +610,7 @@ impl State {
         let control_log = Log::with_limit(config.control_log_limit);
         let is_open = Default::default();
         let project_editor = ProjectEditor::default();
+        let recorder = Recorder::default();
         State {
             osc_in_log,
             osc_out_log,
@@ -642,6 +618,7 @@ impl State {
             audio_channels,
             project_editor,
             is_open,
+            recorder,
         }
     }
 }
@@ -860,6 +837,17 @@ widget_ids! {
         master_realtime_source_latency,
         master_dbap_rolloff,
         master_proximity_limit,
+        master_panning_law,
+        master_ambisonic_order,
+        master_listening_point,
+        master_crossover,
+        master_limiter_threshold,
+        master_limiter_release,
+        master_reverb_mix,
+        master_reverb_decay,
+        master_reverb_pre_delay,
+        master_record,
+        master_record_per_speaker,
         // OSC input log.
         osc_in_log,
         osc_in_log_text,
@@ -894,6 +882,18 @@ widget_ids! {
         installation_editor_soundscape_canvas,
         installation_editor_soundscape_text,
         installation_editor_soundscape_simultaneous_sounds_slider,
+        installation_editor_soundscape_occupancy_weight_slider,
+        installation_editor_soundscape_occupancy_smoothing_slider,
+        installation_editor_soundscape_capacity_slider,
+        installation_editor_compressor_canvas,
+        installation_editor_compressor_text,
+        installation_editor_compressor_enabled,
+        installation_editor_compressor_threshold,
+        installation_editor_compressor_ratio,
+        installation_editor_compressor_release,
+        installation_editor_zone_canvas,
+        installation_editor_zone_text,
+        installation_editor_zone_clear,
         // Speaker Editor.
         speaker_editor,
         speaker_editor_no_speakers,
@@ -904,6 +904,11 @@ widget_ids! {
         speaker_editor_selected_none,
         speaker_editor_selected_name,
         speaker_editor_selected_channel,
+        speaker_editor_selected_elevation,
+        speaker_editor_selected_gain,
+        speaker_editor_selected_delay_auto,
+        speaker_editor_selected_delay,
+        speaker_editor_selected_role,
         speaker_editor_selected_position,
         speaker_editor_selected_installations_canvas,
         speaker_editor_selected_installations_text,
@@ -913,6 +918,7 @@ widget_ids! {
         // Audio Sources.
         soundscape_editor,
         soundscape_editor_is_playing,
+        soundscape_editor_reseed,
         soundscape_editor_group_canvas,
         soundscape_editor_group_text,
         soundscape_editor_group_add,
@@ -926,6 +932,47 @@ widget_ids! {
         soundscape_editor_occurrence_rate_slider,
         soundscape_editor_simultaneous_sounds_text,
         soundscape_editor_simultaneous_sounds_slider,
+        soundscape_editor_rules_text,
+        soundscape_editor_rule_lists[],
+        soundscape_editor_rule_within_sliders[],
+        // Schedule Editor.
+        schedule_editor,
+        schedule_editor_enabled,
+        schedule_editor_crossfade,
+        schedule_editor_period_canvas,
+        schedule_editor_period_text,
+        schedule_editor_period_add,
+        schedule_editor_period_none,
+        schedule_editor_period_list,
+        schedule_editor_period_remove,
+        schedule_editor_selected_canvas,
+        schedule_editor_selected_text,
+        schedule_editor_selected_name,
+        schedule_editor_days_text,
+        schedule_editor_days[],
+        schedule_editor_hours_text,
+        schedule_editor_start_hour,
+        schedule_editor_end_hour,
+        schedule_editor_volume_text,
+        schedule_editor_volume_toggle,
+        schedule_editor_volume_slider,
+        schedule_editor_simultaneous_sounds_text,
+        schedule_editor_simultaneous_sounds_toggle,
+        schedule_editor_simultaneous_sounds_slider,
+        schedule_editor_groups_text,
+        schedule_editor_groups[],
+        // Scene Editor.
+        scene_editor,
+        scene_editor_add,
+        scene_editor_none,
+        scene_editor_list,
+        scene_editor_remove,
+        scene_editor_selected_canvas,
+        scene_editor_selected_text,
+        scene_editor_selected_name,
+        scene_editor_selected_crossfade,
+        scene_editor_selected_recall,
+        scene_editor_selected_update,
         // Audio Sources.
         source_editor,
         source_editor_no_sources,
@@ -956,6 +1003,14 @@ widget_ids! {
         source_editor_selected_soundscape_release_duration_slider,
         source_editor_selected_soundscape_groups_text,
         source_editor_selected_soundscape_groups_list,
+        source_editor_selected_soundscape_spawn_text,
+        source_editor_selected_soundscape_spawn_list,
+        source_editor_selected_soundscape_spawn_min_distance,
+        source_editor_selected_soundscape_spawn_anchor_x,
+        source_editor_selected_soundscape_spawn_anchor_y,
+        source_editor_selected_soundscape_elevation_text,
+        source_editor_selected_soundscape_elevation_toggle,
+        source_editor_selected_soundscape_elevation_slider,
         source_editor_selected_soundscape_movement_text,
         source_editor_selected_soundscape_movement_mode_list,
         source_editor_selected_soundscape_movement_generative_list,
@@ -966,6 +1021,8 @@ widget_ids! {
         source_editor_selected_soundscape_movement_agent_max_force_slider,
         source_editor_selected_soundscape_movement_agent_max_rotation_text,
         source_editor_selected_soundscape_movement_agent_max_rotation_slider,
+        source_editor_selected_soundscape_movement_agent_activity_text,
+        source_editor_selected_soundscape_movement_agent_activity_slider,
         source_editor_selected_soundscape_movement_agent_directional,
         source_editor_selected_soundscape_movement_ngon_speed_text,
         source_editor_selected_soundscape_movement_ngon_speed_slider,
@@ -978,10 +1035,40 @@ widget_ids! {
         source_editor_selected_soundscape_movement_ngon_height_slider,
         source_editor_selected_soundscape_movement_ngon_radians_text,
         source_editor_selected_soundscape_movement_ngon_radians_slider,
+        source_editor_selected_soundscape_movement_flock_radius_text,
+        source_editor_selected_soundscape_movement_flock_radius_slider,
+        source_editor_selected_soundscape_movement_flock_weights_text,
+        source_editor_selected_soundscape_movement_flock_separation,
+        source_editor_selected_soundscape_movement_flock_alignment,
+        source_editor_selected_soundscape_movement_flock_cohesion,
+        source_editor_selected_soundscape_movement_flock_groups,
+        source_editor_selected_soundscape_movement_path_speed_text,
+        source_editor_selected_soundscape_movement_path_speed_slider,
+        source_editor_selected_soundscape_movement_path_mode_text,
+        source_editor_selected_soundscape_movement_path_mode_list,
+        source_editor_selected_soundscape_movement_path_spline,
+        source_editor_selected_soundscape_movement_path_random_start,
+        source_editor_selected_soundscape_movement_path_waypoints_text,
+        source_editor_selected_soundscape_movement_path_clear,
+        source_editor_selected_soundscape_movement_walk_step_text,
+        source_editor_selected_soundscape_movement_walk_step_slider,
+        source_editor_selected_soundscape_movement_walk_drift_text,
+        source_editor_selected_soundscape_movement_walk_drift_slider,
+        source_editor_selected_soundscape_movement_orbit_centre_text,
+        source_editor_selected_soundscape_movement_orbit_centre_toggle,
+        source_editor_selected_soundscape_movement_orbit_centre_x,
+        source_editor_selected_soundscape_movement_orbit_centre_y,
+        source_editor_selected_soundscape_movement_orbit_radius_text,
+        source_editor_selected_soundscape_movement_orbit_radius_slider,
+        source_editor_selected_soundscape_movement_orbit_speed_text,
+        source_editor_selected_soundscape_movement_orbit_speed_slider,
+        source_editor_selected_soundscape_movement_orbit_eccentricity_text,
+        source_editor_selected_soundscape_movement_orbit_eccentricity_slider,
         source_editor_selected_wav_canvas,
         source_editor_selected_wav_text,
         source_editor_selected_wav_data,
         source_editor_selected_wav_loop_toggle,
+        source_editor_selected_wav_channel_layout,
         source_editor_selected_wav_playback_text,
         source_editor_selected_wav_playback_list,
         source_editor_selected_realtime_canvas,
@@ -992,6 +1079,7 @@ widget_ids! {
         source_editor_selected_common_canvas,
         source_editor_selected_volume_text,
         source_editor_selected_volume_slider,
+        source_editor_selected_reverb_send_slider,
         source_editor_selected_solo,
         source_editor_selected_mute,
         source_editor_selected_channel_layout_text,
@@ -1001,6 +1089,18 @@ widget_ids! {
         source_editor_selected_channel_layout_spread_circle,
         source_editor_selected_channel_layout_channels[],
         source_editor_selected_channel_layout_channel_labels[],
+        source_editor_selected_distance_canvas,
+        source_editor_selected_distance_text,
+        source_editor_selected_distance_enabled,
+        source_editor_selected_distance_reference,
+        source_editor_selected_distance_rolloff,
+        source_editor_selected_distance_min,
+        source_editor_selected_distance_absorption,
+        source_editor_selected_doppler_canvas,
+        source_editor_selected_doppler_text,
+        source_editor_selected_doppler_enabled,
+        source_editor_selected_doppler_reference,
+        source_editor_selected_doppler_scale,
         source_editor_preview_canvas,
         source_editor_preview_text,
         source_editor_preview_one_shot,
@@ -1014,6 +1114,10 @@ widget_ids! {
         floorplan_speaker_labels[],
         floorplan_sounds[],
         floorplan_channel_to_speaker_lines[],
+        floorplan_path_line,
+        floorplan_path_waypoints[],
+        floorplan_zone_line,
+        floorplan_zone_vertices[],
 
         // Text drawn in the CPU-saving mode.
         cpu_saving_mode,
@@ -1021,6 +1125,179 @@ widget_ids! {
     }
 }
 
+/// Stop showing the given recording as active after it failed on the recorder thread.
+pub fn handle_recording_failure(
+    failure: &audio::recorder::Failure,
+    recorder: &mut Recorder,
+    channels: &Channels,
+) {
+    eprintln!(
+        "failed to record to \"{}\": {}",
+        failure.directory.display(),
+        failure.error,
+    );
+    if recorder.directory.as_ref() == Some(&failure.directory) {
+        master::stop_recording(recorder, channels);
+    }
+}
+
+/// Apply a control message received via OSC.
+///
+/// Updates the local copy of the project (if there is one) along with the relevant threads.
+pub fn handle_control(
+    control: &osc::input::Control,
+    project: Option<&mut Project>,
+    assets: &Path,
+    recorder: &mut Recorder,
+    channels: &Channels,
+) {
+    match *control {
+        osc::input::Control::MasterVolume(osc::input::MasterVolume(volume)) => {
+            // Update local copy.
+            if let Some(project) = project {
+                project.master.volume = volume;
+            }
+
+            // Update the audio output copy.
+            channels
+                .audio_output
+                .send(move |audio| audio.master_volume = volume)
+                .expect("failed to send updated master volume to audio output thread");
+        },
+
+        osc::input::Control::SourceVolume(ref source_volume) => {
+            let osc::input::SourceVolume { ref name, volume } = *source_volume;
+
+            let project = match project {
+                None => return,
+                Some(project) => project,
+            };
+
+            // Update local copy.
+            let id = match project
+                .state
+                .sources
+                .iter_mut()
+                .find(|&(_, ref s)| &s.name[..] == name)
+            {
+                None => return,
+                Some((&id, ref mut source)) => {
+                    source.volume = volume;
+                    id
+                },
+            };
+
+            // Update the soundscape copy.
+            channels
+                .soundscape
+                .send(move |soundscape| {
+                    soundscape.update_source(&id, |source| source.volume = volume);
+                })
+                .expect("failed to send updated source volume to soundscape thread");
+
+            // Update the audio output copies.
+            channels
+                .audio_output
+                .send(move |audio| {
+                    audio.update_sounds_with_source(&id, move |_, sound| {
+                        sound.volume = volume;
+                    });
+                })
+                .expect("failed to send updated source volume to audio output thread");
+        }
+
+        osc::input::Control::PlaySoundscape => {
+            channels
+                .soundscape
+                .play()
+                .expect("failed to send `Play` message to soundscape thread");
+        }
+
+        osc::input::Control::PauseSoundscape => {
+            channels
+                .soundscape
+                .pause()
+                .expect("failed to send `Pause` message to soundscape thread");
+        }
+
+        osc::input::Control::StartRecording => {
+            if let Some(project) = project {
+                master::start_recording(assets, project, recorder, channels);
+            }
+        }
+
+        osc::input::Control::StopRecording => {
+            master::stop_recording(recorder, channels);
+        }
+
+        osc::input::Control::RecallScene(ref recall) => {
+            let osc::input::RecallScene { ref name, crossfade_secs } = *recall;
+
+            let project = match project {
+                None => return,
+                Some(project) => project,
+            };
+
+            let scene = match scene::find(&project.scenes, name) {
+                None => return,
+                Some(scene) => scene.clone(),
+            };
+            let crossfade = crossfade_secs
+                .map(|secs| Ms(secs as f64 * SEC_MS))
+                .unwrap_or(scene.crossfade);
+            scene_editor::recall(&scene, crossfade, project, channels);
+        }
+
+        osc::input::Control::InstallationPresence(ref installation_presence) => {
+            let osc::input::InstallationPresence { ref name, presence } = *installation_presence;
+
+            let id = match project.and_then(|p| find_installation(&p.installations, name)) {
+                None => return,
+                Some(id) => id,
+            };
+
+            // Update the soundscape copy.
+            channels
+                .soundscape
+                .send(move |soundscape| {
+                    soundscape.update_installation_presence(id, presence);
+                })
+                .expect("failed to send installation presence to soundscape thread");
+        }
+
+        osc::input::Control::InstallationOccupancy(ref installation_occupancy) => {
+            let osc::input::InstallationOccupancy { ref name, count } = *installation_occupancy;
+
+            let id = match project.and_then(|p| find_installation(&p.installations, name)) {
+                None => return,
+                Some(id) => id,
+            };
+
+            // Update the soundscape copy.
+            channels
+                .soundscape
+                .send(move |soundscape| {
+                    soundscape.update_installation_occupancy(id, count);
+                })
+                .expect("failed to send installation occupancy to soundscape thread");
+        }
+    }
+}
+
+// Find the installation with the given name.
+//
+// The name may also be given in its slugified form.
+fn find_installation(
+    installations: &project::Installations,
+    name: &str,
+) -> Option<installation::Id> {
+    installations
+        .iter()
+        .find(|&(_, ref i)| &i.name[..] == name)
+        .or_else(|| installations.iter().find(|&(_, ref i)| slugify(&i.name) == name))
+        .map(|(&id, _)| id)
+}
+
 // Begin building a `CollapsibleArea` for the sidebar.
 pub fn collapsible_area(
     is_open: bool,
@@ -1117,6 +1394,12 @@ fn set_side_menu_widgets(
         // Soundscape Editor - for playing/pausing and adding, editing and removing groups.
         last_area_id = soundscape_editor::set(last_area_id, gui, project, project_state);
 
+        // Schedule Editor - for overriding the soundscape at certain times of the week.
+        last_area_id = schedule_editor::set(last_area_id, gui, project, project_state);
+
+        // Scene Editor - for capturing and recalling snapshots of the soundscape.
+        last_area_id = scene_editor::set(last_area_id, gui, project, project_state);
+
         // For adding, changing and removing audio sources.
         last_area_id = source_editor::set(last_area_id, gui, project, project_state);
 
@@ -1523,8 +1806,278 @@ fn set_widgets(
         }
     }
 
+    // Draw the path of the selected source over the floorplan if it has path movement.
+    //
+    // Waypoints are added by double-clicking the floorplan, moved by dragging and removed by
+    // right-clicking.
+    if gui.state.is_open.source_editor {
+        let Gui {
+            ref mut ids,
+            ref mut ui,
+            ref channels,
+            ..
+        } = *gui;
+
+        let Project {
+            state: project::State {
+                ref camera,
+                ref mut sources,
+                ..
+            },
+            ..
+        } = *project;
+
+        let selected_path = project_state
+            .source_editor
+            .selected
+            .and_then(|id| sources.get(&id).map(|source| (id, source)))
+            .and_then(|(id, source)| match source.audio.role {
+                Some(audio::source::Role::Soundscape(ref soundscape)) => match soundscape.movement {
+                    audio::source::Movement::Generative(
+                        audio::source::movement::Generative::Path(ref path),
+                    ) => Some((id, path.clone())),
+                    _ => None,
+                },
+                _ => None,
+            });
+
+        if let Some((source_id, mut path)) = selected_path {
+            const WAYPOINT_RADIUS: Scalar = 6.0;
+            let mut changed = false;
+
+            // Append a waypoint for each double-click on the floorplan.
+            let double_clicks = ui
+                .widget_input(ids.floorplan)
+                .events()
+                .filter_map(|event| match event {
+                    ui::event::Widget::DoubleClick(click) => match click.button {
+                        ui::input::MouseButton::Left => Some(click.xy),
+                        _ => None,
+                    },
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            for xy in double_clicks {
+                let x = camera.position.x + camera.scalar_to_metres(xy[0]);
+                let y = camera.position.y + camera.scalar_to_metres(xy[1]);
+                path.points.push(Point2 { x, y });
+                changed = true;
+            }
+
+            // Ensure there are enough IDs available.
+            let num_waypoints = path.points.len();
+            if ids.floorplan_path_waypoints.len() < num_waypoints {
+                let id_gen = &mut ui.widget_id_generator();
+                ids.floorplan_path_waypoints.resize(num_waypoints, id_gen);
+            }
+
+            // Move dragged waypoints and remove any that were right-clicked.
+            let mut maybe_remove_index = None;
+            for (i, point) in path.points.iter_mut().enumerate() {
+                let widget_id = ids.floorplan_path_waypoints[i];
+                let (dragged_x, dragged_y) = ui.widget_input(widget_id)
+                    .drags()
+                    .left()
+                    .fold((0.0, 0.0), |(x, y), drag| {
+                        (x + drag.delta_xy[0], y + drag.delta_xy[1])
+                    });
+                if dragged_x != 0.0 || dragged_y != 0.0 {
+                    point.x = point.x + camera.scalar_to_metres(dragged_x);
+                    point.y = point.y + camera.scalar_to_metres(dragged_y);
+                    changed = true;
+                }
+                if ui.widget_input(widget_id).clicks().right().next().is_some() {
+                    maybe_remove_index = Some(i);
+                }
+            }
+            if let Some(i) = maybe_remove_index {
+                path.points.remove(i);
+                changed = true;
+            }
+
+            // Draw the line traced by sounds travelling along the path.
+            let closed = path.mode == audio::source::movement::PathMode::Loop;
+            let vertices = soundscape::movement::path::polyline(&path.points, path.spline, closed);
+            if vertices.len() > 1 {
+                let points = vertices.iter().map(|&p| {
+                    let (x, y) = position_metres_to_gui(p, camera);
+                    [x, y]
+                });
+                widget::PointPath::new(points)
+                    .thickness(2.0)
+                    .color(color::BLUE.alpha(0.5))
+                    .parent(ids.floorplan)
+                    .graphics_for(ids.floorplan)
+                    .set(ids.floorplan_path_line, ui);
+            }
+
+            // Draw a handle for each waypoint.
+            for (i, &point) in path.points.iter().enumerate() {
+                let widget_id = ids.floorplan_path_waypoints[i];
+                let (x, y) = position_metres_to_gui(point, camera);
+                let color = match ui.widget_input(widget_id).mouse() {
+                    Some(mouse) => if mouse.buttons.left().is_down() {
+                        color::BLUE.clicked()
+                    } else {
+                        color::BLUE.highlighted()
+                    },
+                    None => color::BLUE,
+                };
+                widget::Circle::fill(WAYPOINT_RADIUS)
+                    .x_y(x, y)
+                    .parent(ids.floorplan)
+                    .color(color)
+                    .set(widget_id, ui);
+            }
+
+            if changed {
+                source_editor::update_path(sources, channels, source_id, path);
+            }
+        }
+    }
+
+    // Draw the zone of the selected installation over the floorplan.
+    //
+    // Vertices are added by double-clicking the floorplan, moved by dragging and removed by
+    // right-clicking. The zone is only editable while the source editor is closed so that
+    // double-clicks are not shared with path editing.
+    if gui.state.is_open.installation_editor && !gui.state.is_open.source_editor {
+        let Gui {
+            ref mut ids,
+            ref mut ui,
+            ref channels,
+            ..
+        } = *gui;
+
+        let Project {
+            state: project::State {
+                ref camera,
+                ref mut installations,
+                ..
+            },
+            ..
+        } = *project;
+
+        let selected_zone = project_state
+            .installation_editor
+            .selected
+            .as_ref()
+            .map(|selected| selected.id())
+            .and_then(|id| installations.get(&id).map(|inst| (id, inst.zone.clone())));
+
+        if let Some((installation_id, mut zone)) = selected_zone {
+            const VERTEX_RADIUS: Scalar = 6.0;
+            let mut changed = false;
+
+            // Append a vertex for each double-click on the floorplan.
+            let double_clicks = ui
+                .widget_input(ids.floorplan)
+                .events()
+                .filter_map(|event| match event {
+                    ui::event::Widget::DoubleClick(click) => match click.button {
+                        ui::input::MouseButton::Left => Some(click.xy),
+                        _ => None,
+                    },
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            for xy in double_clicks {
+                let x = camera.position.x + camera.scalar_to_metres(xy[0]);
+                let y = camera.position.y + camera.scalar_to_metres(xy[1]);
+                zone.push(Point2 { x, y });
+                changed = true;
+            }
+
+            // Ensure there are enough IDs available.
+            let num_vertices = zone.len();
+            if ids.floorplan_zone_vertices.len() < num_vertices {
+                let id_gen = &mut ui.widget_id_generator();
+                ids.floorplan_zone_vertices.resize(num_vertices, id_gen);
+            }
+
+            // Move dragged vertices and remove any that were right-clicked.
+            let mut maybe_remove_index = None;
+            for (i, point) in zone.iter_mut().enumerate() {
+                let widget_id = ids.floorplan_zone_vertices[i];
+                let (dragged_x, dragged_y) = ui.widget_input(widget_id)
+                    .drags()
+                    .left()
+                    .fold((0.0, 0.0), |(x, y), drag| {
+                        (x + drag.delta_xy[0], y + drag.delta_xy[1])
+                    });
+                if dragged_x != 0.0 || dragged_y != 0.0 {
+                    point.x = point.x + camera.scalar_to_metres(dragged_x);
+                    point.y = point.y + camera.scalar_to_metres(dragged_y);
+                    changed = true;
+                }
+                if ui.widget_input(widget_id).clicks().right().next().is_some() {
+                    maybe_remove_index = Some(i);
+                }
+            }
+            if let Some(i) = maybe_remove_index {
+                zone.remove(i);
+                changed = true;
+            }
+
+            // Draw the outline of the zone, closing it once it describes an area.
+            if zone.len() > 1 {
+                let closing = match soundscape::movement::zone::is_valid(&zone) {
+                    true => zone.first().cloned(),
+                    false => None,
+                };
+                let points = zone.iter().cloned().chain(closing).map(|p| {
+                    let (x, y) = position_metres_to_gui(p, camera);
+                    [x, y]
+                });
+                widget::PointPath::new(points)
+                    .thickness(2.0)
+                    .color(color::GREEN.alpha(0.5))
+                    .parent(ids.floorplan)
+                    .graphics_for(ids.floorplan)
+                    .set(ids.floorplan_zone_line, ui);
+            }
+
+            // Draw a handle for each vertex.
+            for (i, &point) in zone.iter().enumerate() {
+                let widget_id = ids.floorplan_zone_vertices[i];
+                let (x, y) = position_metres_to_gui(point, camera);
+                let color = match ui.widget_input(widget_id).mouse() {
+                    Some(mouse) => if mouse.buttons.left().is_down() {
+                        color::GREEN.clicked()
+                    } else {
+                        color::GREEN.highlighted()
+                    },
+                    None => color::GREEN,
+                };
+                widget::Circle::fill(VERTEX_RADIUS)
+                    .x_y(x, y)
+                    .parent(ids.floorplan)
+                    .color(color)
+                    .set(widget_id, ui);
+            }
+
+            if changed {
+                installation_editor::update_zone(installations, channels, installation_id, zone);
+            }
+        }
+    }
+
     // Draw the currently active sounds over the floorplan.
     let mut speakers_in_proximity = vec![]; // TODO: Move this to where it can be re-used.
+
+    // When previewing ambisonic panning, decode to all speakers in the order of the project map.
+    let ambisonic_decoder = match project.master.panning_law {
+        audio::PanningLaw::Ambisonics => {
+            let points: Vec<_> = project
+                .speakers
+                .values()
+                .filter(|s| s.audio.is_panned())
+                .map(|s| s.audio.point.extend(s.audio.elevation))
+                .collect();
+            audio::ambisonics::Decoder::allrad(project.master.ambisonic_order, &points)
+        }
+        _ => None,
+    };
     {
         let Gui {
             ref mut ids,
@@ -1596,6 +2149,7 @@ fn set_widgets(
                         audio::sound::Position {
                             point: new_p,
                             radians: 0.0,
+                            elevation: 0.0,
                         }
                     };
 
@@ -1674,22 +2228,27 @@ fn set_widgets(
             // sending audio.
             let mut line_index = 0;
             for channel in 0..channel_count {
-                let point = position.point;
+                let point = position.point.extend(position.elevation);
                 let radians = position.radians + channel_radians;
                 let channel_point_m =
                     audio::output::channel_point(point, channel, channel_count, spread_m, radians);
-                let (ch_x, ch_y) = position_metres_to_gui(channel_point_m, &project.camera);
+                let (ch_x, ch_y) =
+                    position_metres_to_gui(channel_point_m.truncate(), &project.camera);
                 let channel_amp = channel_amplitudes[channel];
                 let speakers = &project.speakers;
 
                 // A function for finding all speakers within proximity of a sound channel.
                 fn find_speakers_in_proximity(
-                    // The location of the source channel.
-                    point: &Point2<Metres>,
+                    // The location of the source channel, including its elevation.
+                    point: &Point3,
                     // Installations that the current sound is applied to.
                     installations: &audio::sound::Installations,
                     // All speakers.
                     speakers: &project::Speakers,
+                    // The law used to calculate speaker gains.
+                    panning_law: audio::PanningLaw,
+                    // The decoder for all speakers if the panning law is `Ambisonics`.
+                    ambisonic_decoder: Option<&audio::ambisonics::Decoder>,
                     // The rolloff attenuation.
                     rolloff_db: f64,
                     // Amp along with the index within the given `Vec`.
@@ -1701,25 +2260,90 @@ fn set_widgets(
                         return;
                     }
 
-                    let (ids, dbap_speakers): (Vec<audio::speaker::Id>, Vec<audio::dbap::Speaker>) = {
-                        // The location of the sound.
-                        let point_f = Point2 {
-                            x: point.x.0,
-                            y: point.y.0,
-                        };
+                    // Subwoofers only receive the low end of other speakers, so are never panned to.
+                    let panned = || speakers.iter().filter(|&(_, s)| s.audio.is_panned());
 
-                        let mut iter = speakers.iter();
+                    // The location of a speaker including its elevation.
+                    fn speaker_point(speaker: &project::Speaker) -> Point3 {
+                        speaker.audio.point.extend(speaker.audio.elevation)
+                    }
+
+                    // Ambisonics decodes to all speakers, so show those that receive some signal.
+                    if let (audio::PanningLaw::Ambisonics, Some(decoder)) =
+                        (panning_law, ambisonic_decoder)
+                    {
+                        let mut coefficients = [0.0; audio::ambisonics::MAX_CHANNELS];
+                        let direction = decoder.direction(*point);
+                        audio::ambisonics::encode(direction, decoder.order(), &mut coefficients);
+                        let mut gains = Vec::with_capacity(decoder.speaker_count());
+                        decoder.speaker_gains(&coefficients, &mut gains);
+                        in_proximity.clear();
+                        for ((&id, speaker), &gain) in panned().zip(&gains) {
+                            let weight = audio::speaker::dbap_weight(
+                                installations,
+                                &speaker.audio.installations,
+                            );
+                            let gain = gain * weight;
+                            if gain > 0.0 {
+                                in_proximity.push((gain.min(1.0) as f32, id));
+                            }
+                        }
+                        return;
+                    }
+
+                    // VBAP only selects between those speakers within proximity, so filter first.
+                    //
+                    // The listening point is the centroid of all weighted speakers, regardless of
+                    // proximity, matching the audio output thread.
+                    if let audio::PanningLaw::Vbap = panning_law {
+                        let listener = audio::vbap::listening_point(panned().map(|(_, s)| {
+                            let weight = audio::speaker::dbap_weight(
+                                installations,
+                                &s.audio.installations,
+                            );
+                            let point = s.audio.point;
+                            audio::vbap::Speaker { point, weight }
+                        }));
+                        let listener = listener.unwrap_or(point.truncate());
+                        let (ids, vbap_speakers): (Vec<_>, Vec<_>) = panned()
+                            .filter(|&(_, s)| {
+                                audio::output::speaker_is_in_proximity(point, &speaker_point(s),
+                                                                       proximity_limit_2)
+                            })
+                            .map(|(&id, s)| {
+                                let weight = audio::speaker::dbap_weight(
+                                    installations,
+                                    &s.audio.installations,
+                                );
+                                let point = s.audio.point;
+                                (id, audio::vbap::Speaker { point, weight })
+                            })
+                            .unzip();
+                        let mut gains = Vec::with_capacity(vbap_speakers.len());
+                        audio::vbap::speaker_gains(
+                            point.truncate(),
+                            listener,
+                            &vbap_speakers,
+                            &mut gains,
+                        );
+                        in_proximity.clear();
+                        for (&id, &gain) in ids.iter().zip(&gains) {
+                            if gain > 0.0 {
+                                in_proximity.push((gain as f32, id));
+                            }
+                        }
+                        return;
+                    }
+
+                    let (ids, dbap_speakers): (Vec<audio::speaker::Id>, Vec<audio::dbap::Speaker>) = {
+                        let mut iter = panned();
                         iter.next()
                             .map(|(&id, speaker)| {
                                 // The function used to create the dbap speakers.
                                 let dbap_speaker = |speaker: &project::Speaker| -> audio::dbap::Speaker {
-                                    let speaker_f = Point2 {
-                                        x: speaker.audio.point.x.0,
-                                        y: speaker.audio.point.y.0,
-                                    };
                                     let distance = audio::dbap::blurred_distance_2(
-                                        point_f,
-                                        speaker_f,
+                                        *point,
+                                        speaker_point(speaker),
                                         audio::DISTANCE_BLUR,
                                     );
                                     let weight = audio::speaker::dbap_weight(
@@ -1742,7 +2366,7 @@ fn set_widgets(
                     in_proximity.clear();
                     for (i, gain) in gains.enumerate() {
                         let id = ids[i];
-                        if audio::output::speaker_is_in_proximity(point, &speakers[&id].audio.point,
+                        if audio::output::speaker_is_in_proximity(point, &speaker_point(&speakers[&id]),
                                                                   proximity_limit_2) {
                             in_proximity.push((gain as f32, id));
                         }
@@ -1753,6 +2377,8 @@ fn set_widgets(
                     &channel_point_m,
                     &installations,
                     speakers,
+                    project.master.panning_law,
+                    ambisonic_decoder.as_ref(),
                     project.master.dbap_rolloff_db,
                     &mut speakers_in_proximity,
                     project.master.proximity_limit_2,
@@ -0,0 +1,349 @@
+//! A `Scenes` panel displaying:
+//!
+//! - Scenes panel for capturing/removing snapshots of the soundscape-relevant parameters.
+//! - Name, crossfade, recall and update controls for the selected scene.
+
+use gui::{collapsible_area, duration_label, Channels, Gui, ProjectState, State};
+use gui::{ITEM_HEIGHT, SMALL_FONT_SIZE};
+use nannou::ui;
+use nannou::ui::prelude::*;
+use project::Project;
+use scene::{self, Scene};
+use std::time;
+use time_calc::Ms;
+use utils::{MIN_MS, SEC_MS};
+
+/// GUI state related to the scene editor area.
+#[derive(Default)]
+pub struct SceneEditor {
+    pub selected: Option<Selected>,
+}
+
+/// The currently selected scene.
+pub struct Selected {
+    name: String,
+    index: usize,
+}
+
+/// The longest crossfade that may be selected via the slider.
+const MAX_CROSSFADE_MS: f64 = MIN_MS * 5.0;
+
+/// Sets all widgets in the scene area and returns the `Id` of the last area.
+pub fn set(
+    last_area_id: widget::Id,
+    gui: &mut Gui,
+    project: &mut Project,
+    project_state: &mut ProjectState,
+) -> widget::Id {
+    let Gui {
+        ref mut ui,
+        ref ids,
+        channels,
+        state: &mut State {
+            ref mut is_open,
+            ..
+        },
+        ..
+    } = *gui;
+
+    let ProjectState {
+        ref mut scene_editor,
+        ..
+    } = *project_state;
+
+    // Constants to use as widget heights.
+    const PAD: Scalar = 6.0;
+    const PLUS_SCENE_H: Scalar = ITEM_HEIGHT;
+    const SCENE_LIST_MAX_H: Scalar = ITEM_HEIGHT * 5.0;
+    const TEXT_BOX_H: Scalar = ITEM_HEIGHT;
+    const SLIDER_H: Scalar = ITEM_HEIGHT;
+    const BUTTON_H: Scalar = ITEM_HEIGHT;
+    const TITLE_H: Scalar = SMALL_FONT_SIZE as Scalar * 1.333;
+    const SELECTED_CANVAS_H: Scalar = PAD + TITLE_H + PAD * 2.0 + TEXT_BOX_H + PAD + SLIDER_H
+        + PAD + BUTTON_H + PAD;
+    const SCENE_EDITOR_CANVAS_H: Scalar = PAD + PLUS_SCENE_H + SCENE_LIST_MAX_H + PAD
+        + SELECTED_CANVAS_H + PAD;
+
+    // The collapsible area.
+    let (area, event) = collapsible_area(is_open.scene_editor, "Scenes", ids.side_menu)
+        .align_middle_x_of(ids.side_menu)
+        .down_from(last_area_id, 0.0)
+        .set(ids.scene_editor, ui);
+    if let Some(event) = event {
+        is_open.scene_editor = event.is_open();
+    }
+
+    // If the area is open, get the area.
+    let area = match area {
+        Some(area) => area,
+        None => return ids.scene_editor,
+    };
+
+    // The canvas on which the scene editor will be placed.
+    let canvas = widget::Canvas::new()
+        .pad(PAD)
+        .h(SCENE_EDITOR_CANVAS_H);
+    area.set(canvas, ui);
+
+    // A button for capturing the current state as a new scene.
+    for _click in widget::Button::new()
+        .label("+")
+        .kid_area_w_of(area.id)
+        .h(PLUS_SCENE_H)
+        .mid_top_of(area.id)
+        .set(ids.scene_editor_add, ui)
+    {
+        let name = format!("Scene {}", project.scenes.len() + 1);
+        let crossfade = project
+            .scenes
+            .last()
+            .map(|scene| scene.crossfade)
+            .unwrap_or(scene::DEFAULT_CROSSFADE);
+        let scene = Scene::capture(name.clone(), crossfade, &project.state);
+        project.scenes.push(scene);
+        let index = project.scenes.len() - 1;
+        scene_editor.selected = Some(Selected { name, index });
+    }
+
+    // If there are no scenes, display some text for adding a scene.
+    if project.scenes.is_empty() {
+        widget::Text::new("Capture the current state with the \"+\" button above!")
+            .font_size(SMALL_FONT_SIZE)
+            .align_middle_x_of(area.id)
+            .down(PAD + ITEM_HEIGHT)
+            .set(ids.scene_editor_none, ui);
+        return area.id;
+    }
+
+    // A list of all scenes in the order in which they were captured.
+    let num_scenes = project.scenes.len();
+    let (mut events, scrollbar) = widget::ListSelect::single(num_scenes)
+        .down(0.0)
+        .flow_down()
+        .item_size(ITEM_HEIGHT)
+        .h(SCENE_LIST_MAX_H)
+        .kid_area_w_of(area.id)
+        .scrollbar_next_to()
+        .set(ids.scene_editor_list, ui);
+
+    let selected_index = scene_editor.selected.as_ref().map(|s| s.index);
+
+    // Track whether or not an item was removed.
+    let mut maybe_remove_index = None;
+    while let Some(event) = events.next(ui, |i| Some(i) == selected_index) {
+        use self::ui::widget::list_select::Event;
+        match event {
+            // Instantiate the widget for this item.
+            Event::Item(item) => {
+                let is_selected = selected_index == Some(item.i);
+
+                // Blue if selected, gray otherwise.
+                let color = if is_selected {
+                    color::BLUE
+                } else {
+                    color::DARK_CHARCOAL
+                };
+
+                // Use the name as the label.
+                let label = &project.scenes[item.i].name;
+
+                // Use a button widget for each item.
+                let label_x = position::Relative::Place(position::Place::Start(Some(10.0)));
+                let button = widget::Button::new()
+                    .label(label)
+                    .label_font_size(SMALL_FONT_SIZE)
+                    .label_x(label_x)
+                    .color(color);
+                item.set(button, ui);
+
+                // If the button or any of its children are capturing the mouse, display
+                // the `remove` button.
+                let show_remove_button = ui.global_input()
+                    .current
+                    .widget_capturing_mouse
+                    .map(|id| {
+                        id == item.widget_id
+                            || ui.widget_graph()
+                                .does_recursive_depth_edge_exist(item.widget_id, id)
+                    })
+                    .unwrap_or(false);
+
+                if !show_remove_button {
+                    continue;
+                }
+
+                if widget::Button::new()
+                    .label("X")
+                    .label_font_size(SMALL_FONT_SIZE)
+                    .color(color::DARK_RED.alpha(0.5))
+                    .w_h(ITEM_HEIGHT, ITEM_HEIGHT)
+                    .align_right_of(item.widget_id)
+                    .align_middle_y_of(item.widget_id)
+                    .parent(item.widget_id)
+                    .set(ids.scene_editor_remove, ui)
+                    .was_clicked()
+                {
+                    maybe_remove_index = Some(item.i);
+                }
+            },
+
+            // Update the selected scene.
+            Event::Selection(index) => {
+                let name = project.scenes[index].name.clone();
+                scene_editor.selected = Some(Selected { name, index });
+            }
+
+            _ => (),
+        }
+    }
+
+    // The scrollbar for the list.
+    if let Some(s) = scrollbar {
+        s.set(ui);
+    }
+
+    // Remove a scene if necessary, shifting the selection to account for the removal.
+    if let Some(i) = maybe_remove_index {
+        project.scenes.remove(i);
+        scene_editor.selected = match scene_editor.selected.take() {
+            Some(ref s) if s.index == i => None,
+            Some(mut s) => {
+                if s.index > i {
+                    s.index -= 1;
+                }
+                Some(s)
+            }
+            None => None,
+        };
+    }
+
+    ////////////////////
+    // SELECTED SCENE //
+    ////////////////////
+
+    // Only continue if there is some selected scene.
+    let selected = match scene_editor.selected.as_mut() {
+        Some(selected) if selected.index < project.scenes.len() => selected,
+        _ => return area.id,
+    };
+
+    // A canvas for parameters specific to the selected scene.
+    widget::Canvas::new()
+        .parent(area.id)
+        .kid_area_w_of(area.id)
+        .h(SELECTED_CANVAS_H)
+        .align_middle_x_of(area.id)
+        .down_from(ids.scene_editor_list, PAD)
+        .pad(PAD)
+        .color(color::CHARCOAL)
+        .set(ids.scene_editor_selected_canvas, ui);
+
+    // A title indicating that the following parameters are for the selected scene.
+    widget::Text::new("Selected Scene")
+        .top_left_of(ids.scene_editor_selected_canvas)
+        .font_size(SMALL_FONT_SIZE)
+        .set(ids.scene_editor_selected_text, ui);
+
+    // Display a textbox for editing the name.
+    for event in widget::TextBox::new(&selected.name)
+        .middle_of(ids.scene_editor_selected_canvas)
+        .down(PAD * 2.0)
+        .h(TEXT_BOX_H)
+        .kid_area_w_of(ids.scene_editor_selected_canvas)
+        .font_size(SMALL_FONT_SIZE)
+        .color(color::BLACK)
+        .set(ids.scene_editor_selected_name, ui)
+    {
+        use self::ui::widget::text_box::Event;
+        match event {
+            // When typing generally, only update the temp selected name.
+            Event::Update(new_name) => {
+                selected.name = new_name;
+            },
+            // Only when enter is pressed do we update the actual name.
+            Event::Enter => {
+                project.scenes[selected.index].name = selected.name.clone();
+            },
+        }
+    }
+
+    // A slider for the duration of the crossfade when recalling the scene.
+    let crossfade = project.scenes[selected.index].crossfade;
+    let label = format!("Crossfade: {}", duration_label(&crossfade));
+    for new_crossfade in widget::Slider::new(crossfade.ms(), 0.0, MAX_CROSSFADE_MS)
+        .skew(0.5)
+        .label(&label)
+        .label_font_size(SMALL_FONT_SIZE)
+        .h(SLIDER_H)
+        .kid_area_w_of(ids.scene_editor_selected_canvas)
+        .align_left()
+        .down(PAD)
+        .color(color::LIGHT_CHARCOAL)
+        .set(ids.scene_editor_selected_crossfade, ui)
+    {
+        // Round to the nearest tenth of a second.
+        let ms = (new_crossfade / 100.0).round() * 100.0;
+        project.scenes[selected.index].crossfade = Ms(ms);
+    }
+
+    // Buttons for recalling the scene and for replacing it with the current state.
+    let button_w = (ui.kid_area_of(ids.scene_editor_selected_canvas).unwrap().w() - PAD) / 2.0;
+    for _click in widget::Button::new()
+        .label("RECALL")
+        .label_font_size(SMALL_FONT_SIZE)
+        .color(color::BLUE)
+        .w_h(button_w, BUTTON_H)
+        .align_left()
+        .down(PAD)
+        .set(ids.scene_editor_selected_recall, ui)
+    {
+        let scene = project.scenes[selected.index].clone();
+        recall(&scene, scene.crossfade, project, channels);
+    }
+
+    for _click in widget::Button::new()
+        .label("UPDATE")
+        .label_font_size(SMALL_FONT_SIZE)
+        .color(color::DARK_CHARCOAL)
+        .w_h(button_w, BUTTON_H)
+        .right(PAD)
+        .set(ids.scene_editor_selected_update, ui)
+    {
+        let scene = &project.scenes[selected.index];
+        let (name, crossfade) = (scene.name.clone(), scene.crossfade);
+        let scene = Scene::capture(name, crossfade, &project.state);
+        project.scenes[selected.index] = scene;
+    }
+
+    area.id
+}
+
+/// Recall the given scene.
+///
+/// The scene is applied to the project immediately while the soundscape and audio output threads
+/// crossfade towards it over the given duration.
+pub fn recall(scene: &Scene, crossfade: Ms, project: &mut Project, channels: &Channels) {
+    // Update the local copy.
+    scene.apply(&mut project.state);
+
+    // Guard against durations that cannot be represented, e.g. from a hand-edited scene.
+    let crossfade_ms = match crossfade.ms().is_finite() {
+        true => crossfade.ms().max(0.0).min(scene::MAX_CROSSFADE.ms()),
+        false => scene::DEFAULT_CROSSFADE.ms(),
+    };
+    let duration = time::Duration::from_secs_f64(crossfade_ms / SEC_MS);
+
+    // Crossfade the soundscape copy.
+    let clone = scene.clone();
+    channels
+        .soundscape
+        .send(move |soundscape| soundscape.fade_to_scene(&clone, duration))
+        .expect("failed to send scene to soundscape thread");
+
+    // Crossfade the audio output copy of the master volume.
+    let volume = scene.master_volume;
+    channels
+        .audio_output
+        .send(move |audio| audio.fade_master_volume(volume, duration))
+        .expect("failed to send scene master volume to audio output thread");
+}
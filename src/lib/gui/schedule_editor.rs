@@ -0,0 +1,587 @@
+//! A `Schedule` panel displaying:
+//!
+//! - Enabled toggle and crossfade slider for the schedule.
+//! - Periods panel for creating/removing periods of the week.
+//! - Days, hours and overrides of the selected period.
+
+use gui::{collapsible_area, duration_label, Channels, Gui, ProjectState, State};
+use gui::{ITEM_HEIGHT, SMALL_FONT_SIZE};
+use nannou::ui;
+use nannou::ui::prelude::*;
+use project::{self, Project};
+use soundscape::schedule::{self, Schedule};
+use time_calc::Ms;
+use utils::{Range, MIN_MS, SEC_MS};
+
+/// GUI state related to the schedule editor area.
+#[derive(Default)]
+pub struct ScheduleEditor {
+    pub selected: Option<Selected>,
+}
+
+/// The currently selected period.
+pub struct Selected {
+    name: String,
+    index: usize,
+}
+
+/// The longest crossfade that may be selected via the slider.
+const MAX_CROSSFADE_MS: f64 = MIN_MS * 30.0;
+
+/// Sets all widgets in the schedule area and returns the `Id` of the last area.
+pub fn set(
+    last_area_id: widget::Id,
+    gui: &mut Gui,
+    project: &mut Project,
+    project_state: &mut ProjectState,
+) -> widget::Id {
+    let Gui {
+        ref mut ui,
+        ref mut ids,
+        channels,
+        state: &mut State {
+            ref mut is_open,
+            ..
+        },
+        ..
+    } = *gui;
+
+    let Project {
+        state: project::State {
+            ref master,
+            ref soundscape_groups,
+            ref mut schedule,
+            ..
+        },
+        ..
+    } = *project;
+
+    let ProjectState {
+        ref mut schedule_editor,
+        ..
+    } = *project_state;
+
+    // Constants to use as widget heights.
+    const PAD: Scalar = 6.0;
+    const ENABLED_H: Scalar = ITEM_HEIGHT;
+    const SLIDER_H: Scalar = ITEM_HEIGHT;
+    const PLUS_PERIOD_H: Scalar = ITEM_HEIGHT;
+    const PERIOD_LIST_MAX_H: Scalar = ITEM_HEIGHT * 5.0;
+    const TEXT_BOX_H: Scalar = ITEM_HEIGHT;
+    const TITLE_H: Scalar = SMALL_FONT_SIZE as Scalar * 1.333;
+    const PERIOD_CANVAS_H: Scalar = PAD + TITLE_H + PAD + PLUS_PERIOD_H + PERIOD_LIST_MAX_H + PAD;
+    const SELECTED_CANVAS_FIXED_H: Scalar = PAD
+        + TITLE_H + PAD * 2.0 + TEXT_BOX_H + PAD
+        + TITLE_H + PAD * 2.0 + ITEM_HEIGHT + PAD
+        + TITLE_H + PAD * 2.0 + SLIDER_H + PAD + SLIDER_H + PAD
+        + TITLE_H + PAD * 2.0 + SLIDER_H + PAD
+        + TITLE_H + PAD * 2.0 + SLIDER_H + PAD
+        + TITLE_H + PAD;
+    let selected_canvas_h =
+        SELECTED_CANVAS_FIXED_H + soundscape_groups.len() as Scalar * (ITEM_HEIGHT + PAD);
+    let schedule_editor_canvas_h = PAD + ENABLED_H + PAD + SLIDER_H + PAD + PERIOD_CANVAS_H
+        + PAD + selected_canvas_h + PAD;
+
+    // The collapsible area.
+    let (area, event) = collapsible_area(is_open.schedule_editor, "Schedule", ids.side_menu)
+        .align_middle_x_of(ids.side_menu)
+        .down_from(last_area_id, 0.0)
+        .set(ids.schedule_editor, ui);
+    if let Some(event) = event {
+        is_open.schedule_editor = event.is_open();
+    }
+
+    // If the area is open, get the area.
+    let area = match area {
+        Some(area) => area,
+        None => return ids.schedule_editor,
+    };
+
+    // The canvas on which the schedule editor will be placed.
+    let canvas = widget::Canvas::new()
+        .pad(PAD)
+        .h(schedule_editor_canvas_h);
+    area.set(canvas, ui);
+
+    // The toggle for whether or not the schedule is applied to the soundscape.
+    let label = match schedule.enabled {
+        true => "SCHEDULE ENABLED",
+        false => "SCHEDULE DISABLED",
+    };
+    for new_enabled in widget::Toggle::new(schedule.enabled)
+        .color(color::BLUE)
+        .h(ENABLED_H)
+        .mid_top_of(area.id)
+        .kid_area_w_of(area.id)
+        .label(label)
+        .label_font_size(SMALL_FONT_SIZE)
+        .set(ids.schedule_editor_enabled, ui)
+    {
+        schedule.enabled = new_enabled;
+        send_schedule(channels, schedule);
+    }
+
+    // A slider for the duration over which periods fade in and out.
+    let label = format!("Crossfade: {}", duration_label(&schedule.crossfade));
+    for new_crossfade in widget::Slider::new(schedule.crossfade.ms(), 0.0, MAX_CROSSFADE_MS)
+        .skew(0.5)
+        .label(&label)
+        .label_font_size(SMALL_FONT_SIZE)
+        .h(SLIDER_H)
+        .kid_area_w_of(area.id)
+        .align_middle_x_of(area.id)
+        .down(PAD)
+        .color(color::LIGHT_CHARCOAL)
+        .set(ids.schedule_editor_crossfade, ui)
+    {
+        // Round to the nearest second.
+        schedule.crossfade = Ms((new_crossfade / SEC_MS).round() * SEC_MS);
+        send_schedule(channels, schedule);
+    }
+
+    ///////////////////
+    // PERIOD EDITOR //
+    ///////////////////
+
+    // A canvas on which period selection and editing takes place.
+    widget::Canvas::new()
+        .parent(area.id)
+        .kid_area_w_of(area.id)
+        .h(PERIOD_CANVAS_H)
+        .align_middle_x_of(area.id)
+        .down(PAD)
+        .pad(PAD)
+        .color(color::CHARCOAL)
+        .set(ids.schedule_editor_period_canvas, ui);
+
+    // A title for the periods canvas.
+    widget::Text::new("Periods")
+        .top_left_of(ids.schedule_editor_period_canvas)
+        .font_size(SMALL_FONT_SIZE)
+        .set(ids.schedule_editor_period_text, ui);
+
+    // A button for adding new periods.
+    for _click in widget::Button::new()
+        .label("+")
+        .kid_area_w_of(ids.schedule_editor_period_canvas)
+        .h(PLUS_PERIOD_H)
+        .align_middle_x_of(ids.schedule_editor_period_canvas)
+        .down(PAD * 2.0)
+        .set(ids.schedule_editor_period_add, ui)
+    {
+        let period = schedule::Period::default();
+        let name = period.name.clone();
+        schedule.periods.push(period);
+        let index = schedule.periods.len() - 1;
+        schedule_editor.selected = Some(Selected { name, index });
+        send_schedule(channels, schedule);
+    }
+
+    // If there are no periods, display some text for adding a period.
+    if schedule.periods.is_empty() {
+        widget::Text::new("Add a period with the \"+\" button above!")
+            .font_size(SMALL_FONT_SIZE)
+            .align_middle_x_of(ids.schedule_editor_period_canvas)
+            .down(PAD + ITEM_HEIGHT)
+            .set(ids.schedule_editor_period_none, ui);
+        return area.id;
+    }
+
+    // A list of all periods in order of precedence.
+    let num_periods = schedule.periods.len();
+    let (mut events, scrollbar) = widget::ListSelect::single(num_periods)
+        .down(0.0)
+        .flow_down()
+        .item_size(ITEM_HEIGHT)
+        .h(PERIOD_LIST_MAX_H)
+        .kid_area_w_of(ids.schedule_editor_period_canvas)
+        .scrollbar_next_to()
+        .set(ids.schedule_editor_period_list, ui);
+
+    let selected_index = schedule_editor.selected.as_ref().map(|s| s.index);
+
+    // Track whether or not an item was removed.
+    let mut maybe_remove_index = None;
+    while let Some(event) = events.next(ui, |i| Some(i) == selected_index) {
+        use self::ui::widget::list_select::Event;
+        match event {
+            // Instantiate the widget for this item.
+            Event::Item(item) => {
+                let is_selected = selected_index == Some(item.i);
+
+                // Blue if selected, gray otherwise.
+                let color = if is_selected {
+                    color::BLUE
+                } else {
+                    color::DARK_CHARCOAL
+                };
+
+                // Label the period with its name and hours.
+                let period = &schedule.periods[item.i];
+                let label = format!(
+                    "{} ({} to {})",
+                    period.name,
+                    schedule::hour_label(period.start_hour),
+                    schedule::hour_label(period.end_hour),
+                );
+
+                // Use a button widget for each item.
+                let label_x = position::Relative::Place(position::Place::Start(Some(10.0)));
+                let button = widget::Button::new()
+                    .label(&label)
+                    .label_font_size(SMALL_FONT_SIZE)
+                    .label_x(label_x)
+                    .color(color);
+                item.set(button, ui);
+
+                // If the button or any of its children are capturing the mouse, display
+                // the `remove` button.
+                let show_remove_button = ui.global_input()
+                    .current
+                    .widget_capturing_mouse
+                    .map(|id| {
+                        id == item.widget_id
+                            || ui.widget_graph()
+                                .does_recursive_depth_edge_exist(item.widget_id, id)
+                    })
+                    .unwrap_or(false);
+
+                if !show_remove_button {
+                    continue;
+                }
+
+                if widget::Button::new()
+                    .label("X")
+                    .label_font_size(SMALL_FONT_SIZE)
+                    .color(color::DARK_RED.alpha(0.5))
+                    .w_h(ITEM_HEIGHT, ITEM_HEIGHT)
+                    .align_right_of(item.widget_id)
+                    .align_middle_y_of(item.widget_id)
+                    .parent(item.widget_id)
+                    .set(ids.schedule_editor_period_remove, ui)
+                    .was_clicked()
+                {
+                    maybe_remove_index = Some(item.i);
+                }
+            },
+
+            // Update the selected period.
+            Event::Selection(index) => {
+                let name = schedule.periods[index].name.clone();
+                schedule_editor.selected = Some(Selected { name, index });
+            }
+
+            _ => (),
+        }
+    }
+
+    // The scrollbar for the list.
+    if let Some(s) = scrollbar {
+        s.set(ui);
+    }
+
+    // Remove a period if necessary, shifting the selection to account for the removal.
+    if let Some(i) = maybe_remove_index {
+        schedule.periods.remove(i);
+        schedule_editor.selected = match schedule_editor.selected.take() {
+            Some(ref s) if s.index == i => None,
+            Some(mut s) => {
+                if s.index > i {
+                    s.index -= 1;
+                }
+                Some(s)
+            }
+            None => None,
+        };
+        send_schedule(channels, schedule);
+    }
+
+    /////////////////////
+    // SELECTED PERIOD //
+    /////////////////////
+
+    // Only continue if there is some selected period.
+    let selected = match schedule_editor.selected.as_mut() {
+        Some(selected) if selected.index < schedule.periods.len() => selected,
+        _ => return area.id,
+    };
+
+    // A canvas for parameters specific to the selected period.
+    widget::Canvas::new()
+        .parent(area.id)
+        .kid_area_w_of(area.id)
+        .h(selected_canvas_h)
+        .align_middle_x_of(area.id)
+        .down_from(ids.schedule_editor_period_canvas, PAD)
+        .pad(PAD)
+        .color(color::CHARCOAL)
+        .set(ids.schedule_editor_selected_canvas, ui);
+    let selected_canvas_kid_area = ui.kid_area_of(ids.schedule_editor_selected_canvas).unwrap();
+
+    // A title indicating that the following parameters are for the selected period.
+    widget::Text::new("Selected Period")
+        .top_left_of(ids.schedule_editor_selected_canvas)
+        .font_size(SMALL_FONT_SIZE)
+        .set(ids.schedule_editor_selected_text, ui);
+
+    // Display a textbox for editing the name.
+    for event in widget::TextBox::new(&selected.name)
+        .middle_of(ids.schedule_editor_selected_canvas)
+        .down(PAD * 2.0)
+        .h(TEXT_BOX_H)
+        .kid_area_w_of(ids.schedule_editor_selected_canvas)
+        .font_size(SMALL_FONT_SIZE)
+        .color(color::BLACK)
+        .set(ids.schedule_editor_selected_name, ui)
+    {
+        use self::ui::widget::text_box::Event;
+        match event {
+            // When typing generally, only update the temp selected name.
+            Event::Update(new_name) => {
+                selected.name = new_name;
+            },
+            // Only when enter is pressed do we update the actual name.
+            Event::Enter => {
+                schedule.periods[selected.index].name = selected.name.clone();
+                send_schedule(channels, schedule);
+            },
+        }
+    }
+
+    //////////
+    // DAYS //
+    //////////
+
+    widget::Text::new("Days")
+        .align_left()
+        .down(PAD)
+        .font_size(SMALL_FONT_SIZE)
+        .set(ids.schedule_editor_days_text, ui);
+
+    // A toggle for each day of the week.
+    if ids.schedule_editor_days.len() < schedule::DAYS_PER_WEEK {
+        let id_gen = &mut ui.widget_id_generator();
+        ids.schedule_editor_days.resize(schedule::DAYS_PER_WEEK, id_gen);
+    }
+    let days_gaps = PAD * (schedule::DAYS_PER_WEEK - 1) as Scalar;
+    let day_w = (selected_canvas_kid_area.w() - days_gaps) / schedule::DAYS_PER_WEEK as Scalar;
+    for (day, &day_name) in schedule::DAY_NAMES.iter().enumerate() {
+        let enabled = schedule.periods[selected.index].days[day];
+        let toggle = widget::Toggle::new(enabled)
+            .label(day_name)
+            .label_font_size(SMALL_FONT_SIZE)
+            .color(color::BLUE)
+            .w_h(day_w, ITEM_HEIGHT);
+        let toggle = match day {
+            0 => toggle.align_left_of(ids.schedule_editor_days_text).down(PAD * 2.0),
+            _ => toggle.right(PAD),
+        };
+        for new_enabled in toggle.set(ids.schedule_editor_days[day], ui) {
+            schedule.periods[selected.index].days[day] = new_enabled;
+            send_schedule(channels, schedule);
+        }
+    }
+
+    ///////////
+    // HOURS //
+    ///////////
+
+    widget::Text::new("Hours")
+        .align_left_of(ids.schedule_editor_days_text)
+        .down_from(ids.schedule_editor_days[0], PAD)
+        .font_size(SMALL_FONT_SIZE)
+        .set(ids.schedule_editor_hours_text, ui);
+
+    let hour_slider = |hour| {
+        widget::Slider::new(hour, 0.0, 24.0)
+            .label_font_size(SMALL_FONT_SIZE)
+            .kid_area_w_of(ids.schedule_editor_selected_canvas)
+            .h(SLIDER_H)
+            .color(color::LIGHT_CHARCOAL)
+    };
+
+    // Hours are rounded to the nearest quarter hour.
+    let round_hour = |hour: f64| (hour * 4.0).round() / 4.0;
+
+    let start_hour = schedule.periods[selected.index].start_hour;
+    let label = format!("Starts at {}", schedule::hour_label(start_hour));
+    for new_hour in hour_slider(start_hour)
+        .label(&label)
+        .align_left()
+        .down(PAD * 2.0)
+        .set(ids.schedule_editor_start_hour, ui)
+    {
+        schedule.periods[selected.index].start_hour = round_hour(new_hour);
+        send_schedule(channels, schedule);
+    }
+
+    let end_hour = schedule.periods[selected.index].end_hour;
+    let label = match end_hour <= start_hour {
+        true => format!("Ends at {} the following day", schedule::hour_label(end_hour)),
+        false => format!("Ends at {}", schedule::hour_label(end_hour)),
+    };
+    for new_hour in hour_slider(end_hour)
+        .label(&label)
+        .align_left()
+        .down(PAD)
+        .set(ids.schedule_editor_end_hour, ui)
+    {
+        schedule.periods[selected.index].end_hour = round_hour(new_hour);
+        send_schedule(channels, schedule);
+    }
+
+    // The overrides are each enabled via a toggle to the left of their slider.
+    let override_toggle_w = selected_canvas_kid_area.w() / 3.0;
+    let override_slider_w = selected_canvas_kid_area.w() - override_toggle_w - PAD;
+    let override_toggle = |is_overridden| {
+        widget::Toggle::new(is_overridden)
+            .label("OVERRIDE")
+            .label_font_size(SMALL_FONT_SIZE)
+            .color(color::BLUE)
+            .w_h(override_toggle_w, SLIDER_H)
+    };
+
+    ////////////
+    // VOLUME //
+    ////////////
+
+    widget::Text::new("Master Volume")
+        .align_left()
+        .down(PAD)
+        .font_size(SMALL_FONT_SIZE)
+        .set(ids.schedule_editor_volume_text, ui);
+
+    let volume = schedule.periods[selected.index].volume;
+    for is_overridden in override_toggle(volume.is_some())
+        .align_left()
+        .down(PAD * 2.0)
+        .set(ids.schedule_editor_volume_toggle, ui)
+    {
+        // Begin from the current master volume when first overriding.
+        schedule.periods[selected.index].volume = match is_overridden {
+            true => Some(master.volume),
+            false => None,
+        };
+        send_schedule(channels, schedule);
+    }
+
+    if let Some(volume) = volume {
+        let label = format!("{:.2}", volume);
+        for new_volume in widget::Slider::new(volume, 0.0, 1.0)
+            .label(&label)
+            .label_font_size(SMALL_FONT_SIZE)
+            .w_h(override_slider_w, SLIDER_H)
+            .right_from(ids.schedule_editor_volume_toggle, PAD)
+            .color(color::LIGHT_CHARCOAL)
+            .set(ids.schedule_editor_volume_slider, ui)
+        {
+            schedule.periods[selected.index].volume = Some(new_volume);
+            send_schedule(channels, schedule);
+        }
+    }
+
+    /////////////////////////
+    // SIMULTANEOUS SOUNDS //
+    /////////////////////////
+
+    widget::Text::new("Simultaneous Sounds Per Installation")
+        .align_left_of(ids.schedule_editor_volume_toggle)
+        .down_from(ids.schedule_editor_volume_toggle, PAD)
+        .font_size(SMALL_FONT_SIZE)
+        .set(ids.schedule_editor_simultaneous_sounds_text, ui);
+
+    let simultaneous_sounds = schedule.periods[selected.index].simultaneous_sounds;
+    for is_overridden in override_toggle(simultaneous_sounds.is_some())
+        .align_left()
+        .down(PAD * 2.0)
+        .set(ids.schedule_editor_simultaneous_sounds_toggle, ui)
+    {
+        schedule.periods[selected.index].simultaneous_sounds = match is_overridden {
+            true => Some(Range { min: 0, max: 0 }),
+            false => None,
+        };
+        send_schedule(channels, schedule);
+    }
+
+    if let Some(range) = simultaneous_sounds {
+        let label = format!("{} to {}", range.min, range.max);
+        let total_min_num = 0.0;
+        let total_max_num = 100.0;
+        let min = range.min as f64;
+        let max = range.max as f64;
+        for (edge, value) in widget::RangeSlider::new(min, max, total_min_num, total_max_num)
+            .skew(0.5)
+            .label(&label)
+            .label_font_size(SMALL_FONT_SIZE)
+            .w_h(override_slider_w, SLIDER_H)
+            .right_from(ids.schedule_editor_simultaneous_sounds_toggle, PAD)
+            .color(color::LIGHT_CHARCOAL)
+            .set(ids.schedule_editor_simultaneous_sounds_slider, ui)
+        {
+            let num = value as usize;
+            let mut new_range = range;
+            match edge {
+                widget::range_slider::Edge::Start => new_range.min = num,
+                widget::range_slider::Edge::End => new_range.max = num,
+            }
+            schedule.periods[selected.index].simultaneous_sounds = Some(new_range);
+            send_schedule(channels, schedule);
+        }
+    }
+
+    ////////////
+    // GROUPS //
+    ////////////
+
+    widget::Text::new("Enabled Groups")
+        .align_left_of(ids.schedule_editor_simultaneous_sounds_toggle)
+        .down_from(ids.schedule_editor_simultaneous_sounds_toggle, PAD)
+        .font_size(SMALL_FONT_SIZE)
+        .set(ids.schedule_editor_groups_text, ui);
+
+    // Collect all groups into alphabetical order.
+    let mut groups_vec: Vec<_> = soundscape_groups
+        .iter()
+        .map(|(&id, group)| (id, group.name.clone()))
+        .collect();
+    groups_vec.sort_by(|a, b| a.1.cmp(&b.1));
+
+    // A toggle for each group, disabling the group for the duration of the period when off.
+    if ids.schedule_editor_groups.len() < groups_vec.len() {
+        let id_gen = &mut ui.widget_id_generator();
+        ids.schedule_editor_groups.resize(groups_vec.len(), id_gen);
+    }
+    for (i, &(id, ref name)) in groups_vec.iter().enumerate() {
+        let enabled = !schedule.periods[selected.index].disabled_groups.contains(&id);
+        let toggle = widget::Toggle::new(enabled)
+            .label(name)
+            .label_font_size(SMALL_FONT_SIZE)
+            .color(color::BLUE)
+            .kid_area_w_of(ids.schedule_editor_selected_canvas)
+            .h(ITEM_HEIGHT)
+            .align_left();
+        let toggle = match i {
+            0 => toggle.down(PAD * 2.0),
+            _ => toggle.down(PAD),
+        };
+        for new_enabled in toggle.set(ids.schedule_editor_groups[i], ui) {
+            let period = &mut schedule.periods[selected.index];
+            period.disabled_groups.retain(|&group| group != id);
+            if !new_enabled {
+                period.disabled_groups.push(id);
+            }
+            send_schedule(channels, schedule);
+        }
+    }
+
+    area.id
+}
+
+// Send a copy of the schedule to the soundscape thread.
+fn send_schedule(channels: &Channels, schedule: &Schedule) {
+    let schedule = schedule.clone();
+    channels
+        .soundscape
+        .send(move |soundscape| soundscape.set_schedule(schedule))
+        .expect("failed to send updated schedule to soundscape thread");
+}
@@ -1,14 +1,17 @@
 //! A `Soundscape` panel displaying:
 //!
 //! - Play/Pause toggle for the soundscape.
+//! - Reseed button for the soundscape's source of randomness.
 //! - Groups panel for creating/removing soundscape source groups.
 
-use gui::{collapsible_area, hz_label, Gui, ProjectState, State};
+use gui::{collapsible_area, hz_label, Channels, Gui, ProjectState, State};
 use gui::{ITEM_HEIGHT, SMALL_FONT_SIZE};
 use project::{self, Project};
+use nannou;
 use nannou::ui;
 use nannou::ui::prelude::*;
 use soundscape;
+use soundscape::group::Rule;
 use time_calc::Ms;
 use utils;
 
@@ -33,7 +36,7 @@ pub fn set(
 ) -> widget::Id {
     let &mut Gui {
         ref mut ui,
-        ref ids,
+        ref mut ids,
         channels,
         state: &mut State {
             ref mut is_open,
@@ -43,11 +46,12 @@ pub fn set(
     } = gui;
 
     let Project {
+        ref mut config,
         state: project::State {
             ref mut soundscape_groups,
+            ref mut schedule,
             ..
         },
-        ..
     } = *project;
 
     let ProjectState {
@@ -58,17 +62,42 @@ pub fn set(
     // Constants to use as widget heights.
     const PAD: Scalar = 6.0;
     const IS_PLAYING_H: Scalar = ITEM_HEIGHT;
+    const RESEED_H: Scalar = ITEM_HEIGHT;
     const PLUS_GROUP_H: Scalar = ITEM_HEIGHT;
     const GROUP_LIST_MAX_H: Scalar = ITEM_HEIGHT * 5.0;
     const TEXT_BOX_H: Scalar = ITEM_HEIGHT;
     const TITLE_H: Scalar = SMALL_FONT_SIZE as Scalar * 1.333;
     const GROUP_CANVAS_H: Scalar = PAD + TITLE_H + PAD + PLUS_GROUP_H + GROUP_LIST_MAX_H + PAD;
     const SLIDER_H: Scalar = ITEM_HEIGHT;
-    const SELECTED_CANVAS_H: Scalar = PAD
+    const RULE_H: Scalar = ITEM_HEIGHT;
+
+    // Each other group has a rule relating it to the selected group, with an extra slider for
+    // each rule that follows another group.
+    let (num_rules, num_follow_rules) = soundscape_editor
+        .selected
+        .as_ref()
+        .and_then(|selected| soundscape_groups.get(&selected.id))
+        .map(|group| {
+            let num_rules = soundscape_groups.len() - 1;
+            let num_follow_rules = group
+                .rules
+                .iter()
+                .filter(|rule| match **rule {
+                    Rule::Follow { .. } => true,
+                    _ => false,
+                })
+                .count();
+            (num_rules, num_follow_rules)
+        })
+        .unwrap_or((0, 0));
+    let rules_h = TITLE_H + PAD + (PAD + RULE_H) * (num_rules + num_follow_rules) as Scalar + PAD;
+    let selected_canvas_h = PAD
         + TITLE_H + PAD * 2.0 + TEXT_BOX_H + PAD
         + TITLE_H + PAD * 2.0 + SLIDER_H + PAD
-        + TITLE_H + PAD + SLIDER_H + PAD;
-    let soundscape_editor_canvas_h = PAD + IS_PLAYING_H + PAD + GROUP_CANVAS_H + PAD + SELECTED_CANVAS_H + PAD;
+        + TITLE_H + PAD + SLIDER_H + PAD
+        + rules_h;
+    let soundscape_editor_canvas_h = PAD + IS_PLAYING_H + PAD + RESEED_H + PAD + GROUP_CANVAS_H
+        + PAD + selected_canvas_h + PAD;
 
     // The collapsible area.
     let (area, event) = collapsible_area(is_open.soundscape_editor, "Soundscape Editor", ids.side_menu)
@@ -119,6 +148,29 @@ pub fn set(
         }
     }
 
+    // A button for re-seeding the soundscape's source of randomness.
+    //
+    // The label shows the beginning of the current seed so that runs may be reproduced.
+    let seed_hex: String = config.seed.iter().take(4).map(|b| format!("{:02x}", b)).collect();
+    let label = format!("RESEED ({})", seed_hex);
+    for _click in widget::Button::new()
+        .color(color::DARK_CHARCOAL)
+        .h(RESEED_H)
+        .align_middle_x_of(area.id)
+        .down(PAD)
+        .kid_area_w_of(area.id)
+        .label(&label)
+        .label_font_size(SMALL_FONT_SIZE)
+        .set(ids.soundscape_editor_reseed, ui)
+    {
+        let seed: utils::Seed = nannou::rand::random();
+        config.seed = seed;
+        channels
+            .soundscape
+            .send(move |soundscape| soundscape.set_seed(seed))
+            .expect("failed to send new seed to soundscape thread");
+    }
+
     //////////////////
     // GROUP EDITOR //
     //////////////////
@@ -282,8 +334,15 @@ pub fn set(
             soundscape_editor.selected = None;
         }
 
-        // Remove the local copy from the map.
+        // Remove the local copy from the map, from any scheduled periods and from the rules of
+        // other groups.
         soundscape_groups.remove(&id);
+        for period in schedule.periods.iter_mut() {
+            period.disabled_groups.retain(|&group| group != id);
+        }
+        for group in soundscape_groups.values_mut() {
+            group.soundscape.rules.retain(|rule| rule.group() != id);
+        }
 
         // Remove this group from any sources on the soundscape thread.
         channels
@@ -313,7 +372,7 @@ pub fn set(
     widget::Canvas::new()
         .parent(area.id)
         .kid_area_w_of(area.id)
-        .h(SELECTED_CANVAS_H)
+        .h(selected_canvas_h)
         .align_middle_x_of(area.id)
         .down_from(ids.soundscape_editor_group_canvas, PAD)
         .pad(PAD)
@@ -470,5 +529,104 @@ pub fn set(
             .expect("failed to send updated simultaneous sounds constraint to soundscape thread");
     }
 
+    ///////////////////
+    // RELATIONSHIPS //
+    ///////////////////
+
+    widget::Text::new("Relationships")
+        .align_left()
+        .down(PAD)
+        .font_size(SMALL_FONT_SIZE)
+        .set(ids.soundscape_editor_rules_text, ui);
+
+    // Ensure there are enough IDs available for each of the other groups.
+    let others: Vec<_> = groups_vec
+        .iter()
+        .filter(|&&(id, _)| id != selected.id)
+        .cloned()
+        .collect();
+    if ids.soundscape_editor_rule_lists.len() < others.len() {
+        let id_gen = &mut ui.widget_id_generator();
+        ids.soundscape_editor_rule_lists.resize(others.len(), id_gen);
+        ids.soundscape_editor_rule_within_sliders.resize(others.len(), id_gen);
+    }
+
+    for (i, &(other_id, ref other_name)) in others.iter().enumerate() {
+        let id = selected.id;
+
+        // A drop down list for choosing how the selected group relates to the other group.
+        let labels: Vec<String> = (0..Rule::VARIANT_COUNT + 1)
+            .map(|index| match index {
+                0 => format!("INDEPENDENT OF {}", other_name),
+                index => format!("{} {}", Rule::label_from_index(index - 1), other_name),
+            })
+            .collect();
+        let rule = soundscape_groups[&id].rule(other_id).cloned();
+        let selected_index = rule.as_ref().map(|rule| rule.to_index() + 1).or(Some(0));
+        let down = if i == 0 { PAD * 2.0 } else { PAD };
+        for new_index in widget::DropDownList::new(&labels, selected_index)
+            .label_font_size(SMALL_FONT_SIZE)
+            .kid_area_w_of(ids.soundscape_editor_selected_canvas)
+            .h(RULE_H)
+            .align_left()
+            .down(down)
+            .set(ids.soundscape_editor_rule_lists[i], ui)
+        {
+            let new_rule = match new_index {
+                0 => None,
+                index => Rule::from_index(index - 1, other_id),
+            };
+            update_rule(soundscape_groups, channels, id, other_id, new_rule);
+        }
+
+        // If following the other group, a slider for the duration within which to follow it.
+        let within = match soundscape_groups[&id].rule(other_id) {
+            Some(&Rule::Follow { within, .. }) => within,
+            _ => continue,
+        };
+        let label = format!("Follow Within: {:.1} secs", within.ms() / 1_000.0);
+        for new_within_ms in widget::Slider::new(within.ms(), 0.0, 300_000.0)
+            .skew(0.5)
+            .kid_area_w_of(ids.soundscape_editor_selected_canvas)
+            .h(RULE_H)
+            .label(&label)
+            .label_font_size(SMALL_FONT_SIZE)
+            .color(ui::color::LIGHT_CHARCOAL)
+            .align_left()
+            .down(PAD)
+            .set(ids.soundscape_editor_rule_within_sliders[i], ui)
+        {
+            let within = Ms(new_within_ms);
+            let new_rule = Some(Rule::Follow { group: other_id, within });
+            update_rule(soundscape_groups, channels, id, other_id, new_rule);
+        }
+    }
+
     area.id
 }
+
+/// Replace the rule relating the group with the given `id` to the `other` group, both locally and
+/// on the soundscape thread.
+pub fn update_rule(
+    soundscape_groups: &mut project::SoundscapeGroups,
+    channels: &Channels,
+    id: soundscape::group::Id,
+    other: soundscape::group::Id,
+    rule: Option<Rule>,
+) {
+    // Update the local copy.
+    match soundscape_groups.get_mut(&id) {
+        None => return,
+        Some(group) => group.soundscape.set_rule(other, rule.clone()),
+    }
+
+    // Update the soundscape copy.
+    channels
+        .soundscape
+        .send(move |soundscape| {
+            soundscape.update_group(&id, |group| {
+                group.set_rule(other, rule);
+            });
+        })
+        .expect("failed to send updated group rule to soundscape thread");
+}
@@ -1,7 +1,7 @@
 use audio;
 use audio::source::Role;
 use audio::source::wav::Playback;
-use gui::{collapsible_area, duration_label, hz_label, Gui, ProjectState, State};
+use gui::{collapsible_area, duration_label, hz_label, info_text, Channels, Gui, ProjectState, State};
 use gui::{DARK_A, ITEM_HEIGHT, SMALL_FONT_SIZE};
 use metres::Metres;
 use nannou::prelude::*;
@@ -107,22 +107,32 @@ pub fn set(
         + TEXT_PAD + PAD + SLIDER_H + PAD
         + TEXT_PAD + PAD + SLIDER_H + PAD
         + TEXT_PAD + PAD * 3.5 + SOUNDSCAPE_GROUP_LIST_H + PAD
+        + TEXT_PAD + PAD * 2.0 + BUTTON_H + PAD
+        + TEXT_PAD + PAD * 2.0 + BUTTON_H + PAD
         + TEXT_PAD + PAD * 2.0 + BUTTON_H + PAD + BUTTON_H + PAD
         + TEXT_PAD + PAD * 2.0 + SLIDER_H + PAD
         + TEXT_PAD + PAD * 2.0 + SLIDER_H + PAD
         + TEXT_PAD + PAD * 2.0 + SLIDER_H + PAD
         + TEXT_PAD + PAD * 2.0 + SLIDER_H * 2.0 + PAD
+        + TEXT_PAD + PAD * 2.0 + SLIDER_H + PAD
+        + TEXT_PAD + PAD * 2.0 + SLIDER_H + PAD
+        + TEXT_PAD + PAD * 2.0 + (SLIDER_H + PAD) * 3.0 + BUTTON_H + PAD
         + TEXT_PAD + PAD * 2.0 + SLIDER_H + PAD;
     const LOOP_TOGGLE_H: Scalar = ITEM_HEIGHT;
     const PLAYBACK_MODE_H: Scalar = ITEM_HEIGHT;
+    const WAV_CHANNEL_LAYOUT_H: Scalar = ITEM_HEIGHT;
     const WAV_CANVAS_H: Scalar =
-        100.0 + PAD + LOOP_TOGGLE_H + PAD * 4.0 + PLAYBACK_MODE_H + PAD;
+        100.0 + PAD + LOOP_TOGGLE_H + PAD + WAV_CHANNEL_LAYOUT_H + PAD * 4.0 + PLAYBACK_MODE_H + PAD;
     const REALTIME_CANVAS_H: Scalar = 94.0;
     const CHANNEL_LAYOUT_H: Scalar = 200.0;
-    const COMMON_CANVAS_H: Scalar = TEXT_PAD + PAD + SLIDER_H + PAD + CHANNEL_LAYOUT_H;
+    const COMMON_CANVAS_H: Scalar =
+        TEXT_PAD + PAD + SLIDER_H + PAD + SLIDER_H + PAD + CHANNEL_LAYOUT_H;
+    const DISTANCE_CANVAS_H: Scalar = TEXT_PAD + PAD + (SLIDER_H + PAD) * 3.0;
+    const DOPPLER_CANVAS_H: Scalar = TEXT_PAD + PAD + (SLIDER_H + PAD) * 2.0;
     let kind_specific_h = WAV_CANVAS_H.max(REALTIME_CANVAS_H);
-    let selected_canvas_h = ITEM_HEIGHT * 2.0 + PAD * 7.0 + PREVIEW_CANVAS_H + kind_specific_h
-        + COMMON_CANVAS_H + INSTALLATIONS_CANVAS_H + PAD + SOUNDSCAPE_CANVAS_H;
+    let selected_canvas_h = ITEM_HEIGHT * 2.0 + PAD * 9.0 + PREVIEW_CANVAS_H + kind_specific_h
+        + COMMON_CANVAS_H + DISTANCE_CANVAS_H + DOPPLER_CANVAS_H + INSTALLATIONS_CANVAS_H + PAD
+        + SOUNDSCAPE_CANVAS_H;
     let source_editor_canvas_h = LIST_HEIGHT + ITEM_HEIGHT + selected_canvas_h;
 
     let (area, event) = collapsible_area(is_open.source_editor, "Source Editor", ids.side_menu)
@@ -366,14 +376,20 @@ pub fn set(
         let spread = audio::source::default::SPREAD;
         let channel_radians = audio::source::default::CHANNEL_RADIANS;
         let volume = audio::source::default::VOLUME;
+        let reverb_send = 0.0;
         let muted = bool::default();
+        let distance = None;
+        let doppler = None;
         let audio = audio::Source {
             kind,
             role,
             spread,
             channel_radians,
             volume,
+            reverb_send,
             muted,
+            distance,
+            doppler,
         };
         let source = project::Source { name, audio };
 
@@ -626,6 +642,7 @@ pub fn set(
                     let position = audio::sound::Position {
                         point: preview.point.unwrap(),
                         radians: 0.0,
+                        elevation: 0.0,
                     };
 
                     // When previewing sounds, remove the role so they play back through all
@@ -770,6 +787,47 @@ pub fn set(
                 // with a looping version.
             }
 
+            // The channel layout selection, only listing the layouts valid for the channel count.
+            let layouts = audio::source::wav::ChannelLayout::valid_for_channels(wav.channels);
+            let labels = layouts.iter().map(|l| l.label()).collect::<Vec<_>>();
+            let selected = layouts.iter().position(|l| *l == wav.channel_layout);
+            for new_index in widget::DropDownList::new(&labels, selected)
+                .label("Channel Layout")
+                .label_font_size(SMALL_FONT_SIZE)
+                .down(PAD)
+                .h(WAV_CHANNEL_LAYOUT_H)
+                .w(canvas_kid_area.w())
+                .align_middle_x_of(ids.source_editor_selected_wav_canvas)
+                .set(ids.source_editor_selected_wav_channel_layout, ui)
+            {
+                let new_layout = layouts[new_index];
+
+                // Update the local copy.
+                wav.channel_layout = new_layout;
+
+                // Update the soundscape thread copy.
+                channels
+                    .soundscape
+                    .send(move |soundscape| {
+                        soundscape.update_source(&id, |source| {
+                            if let audio::source::Kind::Wav(ref mut wav) = source.kind {
+                                wav.channel_layout = new_layout;
+                            }
+                        });
+                    })
+                    .expect("failed to send source channel layout to soundscape thread");
+
+                // Update all audio thread copies.
+                channels
+                    .audio_output
+                    .send(move |audio| {
+                        audio.update_sounds_with_source(&id, move |_, sound| {
+                            sound.channel_layout = new_layout;
+                        });
+                    })
+                    .expect("failed to send source channel layout to audio output thread");
+            }
+
             // The playback mode selection.
             widget::Text::new("Playback Mode")
                 .font_size(SMALL_FONT_SIZE)
@@ -1051,6 +1109,41 @@ pub fn set(
             .expect("failed to send source volume update to audio output thread");
     }
 
+    // Display the reverb send slider.
+    let reverb_send = sources[&id].reverb_send;
+    let label = format!("Reverb Send: {:.3}", reverb_send);
+    for new_reverb_send in widget::Slider::new(reverb_send, 0.0, 1.0)
+        .label(&label)
+        .label_font_size(SMALL_FONT_SIZE)
+        .kid_area_w_of(ids.source_editor_selected_common_canvas)
+        .h(SLIDER_H)
+        .align_left()
+        .down(PAD)
+        .color(color::DARK_BLUE)
+        .set(ids.source_editor_selected_reverb_send_slider, ui)
+    {
+        // Update the local copy.
+        sources.get_mut(&id).unwrap().reverb_send = new_reverb_send;
+
+        // Update the soundscape copy.
+        channels
+            .soundscape
+            .send(move |soundscape| {
+                soundscape.update_source(&id, |source| source.reverb_send = new_reverb_send);
+            })
+            .expect("failed to send source reverb send update to soundscape thread");
+
+        // Update the audio output copies.
+        channels
+            .audio_output
+            .send(move |audio| {
+                audio.update_sounds_with_source(&id, move |_, sound| {
+                    sound.reverb_send = new_reverb_send;
+                });
+            })
+            .expect("failed to send source reverb send update to audio output thread");
+    }
+
     // Buttons for solo and mute behaviour.
     let channel_layout_kid_area = ui.kid_area_of(ids.source_editor_selected_common_canvas)
         .unwrap();
@@ -1298,6 +1391,236 @@ pub fn set(
             .set(label_id, ui);
     }
 
+    //////////////
+    // Distance //
+    //////////////
+
+    widget::Canvas::new()
+        .down_from(ids.source_editor_selected_common_canvas, PAD)
+        .h(DISTANCE_CANVAS_H)
+        .w(selected_canvas_kid_area.w())
+        .pad(PAD)
+        .parent(ids.source_editor_selected_canvas)
+        .color(color::CHARCOAL)
+        .set(ids.source_editor_selected_distance_canvas, ui);
+
+    widget::Text::new("DISTANCE")
+        .font_size(SMALL_FONT_SIZE)
+        .top_left_of(ids.source_editor_selected_distance_canvas)
+        .set(ids.source_editor_selected_distance_text, ui);
+
+    // Sends the source's distance model to the soundscape and audio output threads.
+    let send_distance = |distance: Option<audio::distance::Model>| {
+        channels
+            .soundscape
+            .send(move |soundscape| {
+                soundscape.update_source(&id, |source| source.distance = distance);
+            })
+            .expect("failed to send source distance model to soundscape thread");
+        channels
+            .audio_output
+            .send(move |audio| {
+                audio.update_sounds_with_source(&id, move |_, sound| {
+                    sound.distance = distance;
+                });
+            })
+            .expect("failed to send source distance model to audio output thread");
+    };
+
+    // Toggle the distance model on or off.
+    let distance = sources[&id].audio.distance;
+    let label = match distance {
+        Some(_) => "ENABLED",
+        None => "DISABLED",
+    };
+    for new_enabled in toggle(distance.is_some())
+        .label(label)
+        .color(color::LIGHT_CHARCOAL)
+        .align_left()
+        .down(PAD * 1.5)
+        .set(ids.source_editor_selected_distance_enabled, ui)
+    {
+        let new_distance = match new_enabled {
+            true => Some(Default::default()),
+            false => None,
+        };
+        sources.get_mut(&id).unwrap().audio.distance = new_distance;
+        send_distance(new_distance);
+    }
+
+    // The remaining parameters are only shown when the distance model is enabled.
+    if let Some(model) = distance {
+        // The point from which distance is measured.
+        let labels = (0..audio::distance::Reference::VARIANT_COUNT)
+            .map(|i| audio::distance::Reference::label_from_index(i))
+            .collect::<Vec<_>>();
+        let selected = Some(model.reference.to_index());
+        for new_index in widget::DropDownList::new(&labels, selected)
+            .label_font_size(SMALL_FONT_SIZE)
+            .w(button_w)
+            .h(ITEM_HEIGHT)
+            .align_top()
+            .right(PAD)
+            .set(ids.source_editor_selected_distance_reference, ui)
+        {
+            let reference = match audio::distance::Reference::from_index(new_index) {
+                Some(reference) => reference,
+                None => continue,
+            };
+            let new_distance = Some(audio::distance::Model { reference, ..model });
+            sources.get_mut(&id).unwrap().audio.distance = new_distance;
+            send_distance(new_distance);
+        }
+
+        // The distance within which sounds are unaffected.
+        let label = format!("Min: {:.2} metres", model.min_distance.0);
+        for new_min in widget::Slider::new(model.min_distance.0, 0.1, 10.0)
+            .label_font_size(SMALL_FONT_SIZE)
+            .w(slider_w)
+            .skew(0.5)
+            .label(&label)
+            .align_left_of(ids.source_editor_selected_distance_enabled)
+            .down_from(ids.source_editor_selected_distance_enabled, PAD)
+            .set(ids.source_editor_selected_distance_min, ui)
+        {
+            let min_distance = Metres(new_min);
+            let new_distance = Some(audio::distance::Model { min_distance, ..model });
+            sources.get_mut(&id).unwrap().audio.distance = new_distance;
+            send_distance(new_distance);
+        }
+
+        // The attenuation per doubling of distance.
+        let label = format!("Rolloff: {:.1} dB", model.rolloff_db);
+        for new_rolloff in widget::Slider::new(model.rolloff_db, 0.0, 12.0)
+            .label_font_size(SMALL_FONT_SIZE)
+            .w(slider_w)
+            .label(&label)
+            .align_top_of(ids.source_editor_selected_distance_min)
+            .right(PAD)
+            .set(ids.source_editor_selected_distance_rolloff, ui)
+        {
+            let new_distance = Some(audio::distance::Model { rolloff_db: new_rolloff, ..model });
+            sources.get_mut(&id).unwrap().audio.distance = new_distance;
+            send_distance(new_distance);
+        }
+
+        // The distance over which the air absorption cutoff halves.
+        let label = format!("Air Absorption: cutoff halves every {:.1} metres", model.absorption_distance.0);
+        for new_absorption in widget::Slider::new(model.absorption_distance.0, 1.0, 100.0)
+            .skew(0.5)
+            .label(&label)
+            .label_font_size(SMALL_FONT_SIZE)
+            .kid_area_w_of(ids.source_editor_selected_distance_canvas)
+            .h(SLIDER_H)
+            .align_left_of(ids.source_editor_selected_distance_min)
+            .down_from(ids.source_editor_selected_distance_min, PAD)
+            .set(ids.source_editor_selected_distance_absorption, ui)
+        {
+            let absorption_distance = Metres(new_absorption);
+            let new_distance = Some(audio::distance::Model { absorption_distance, ..model });
+            sources.get_mut(&id).unwrap().audio.distance = new_distance;
+            send_distance(new_distance);
+        }
+    }
+
+    /////////////
+    // Doppler //
+    /////////////
+
+    widget::Canvas::new()
+        .down_from(ids.source_editor_selected_distance_canvas, PAD)
+        .h(DOPPLER_CANVAS_H)
+        .w(selected_canvas_kid_area.w())
+        .pad(PAD)
+        .parent(ids.source_editor_selected_canvas)
+        .color(color::CHARCOAL)
+        .set(ids.source_editor_selected_doppler_canvas, ui);
+
+    widget::Text::new("DOPPLER")
+        .font_size(SMALL_FONT_SIZE)
+        .top_left_of(ids.source_editor_selected_doppler_canvas)
+        .set(ids.source_editor_selected_doppler_text, ui);
+
+    // Sends the source's Doppler model to the soundscape and audio output threads.
+    let send_doppler = |doppler: Option<audio::doppler::Model>| {
+        channels
+            .soundscape
+            .send(move |soundscape| {
+                soundscape.update_source(&id, |source| source.doppler = doppler);
+            })
+            .expect("failed to send source doppler model to soundscape thread");
+        channels
+            .audio_output
+            .send(move |audio| {
+                audio.update_sounds_with_source(&id, move |_, sound| {
+                    sound.doppler = doppler;
+                });
+            })
+            .expect("failed to send source doppler model to audio output thread");
+    };
+
+    // Toggle the Doppler effect on or off.
+    let doppler = sources[&id].audio.doppler;
+    let label = match doppler {
+        Some(_) => "ENABLED",
+        None => "DISABLED",
+    };
+    for new_enabled in toggle(doppler.is_some())
+        .label(label)
+        .color(color::LIGHT_CHARCOAL)
+        .align_left()
+        .down(PAD * 1.5)
+        .set(ids.source_editor_selected_doppler_enabled, ui)
+    {
+        let new_doppler = match new_enabled {
+            true => Some(Default::default()),
+            false => None,
+        };
+        sources.get_mut(&id).unwrap().audio.doppler = new_doppler;
+        send_doppler(new_doppler);
+    }
+
+    // The remaining parameters are only shown when the Doppler effect is enabled.
+    if let Some(model) = doppler {
+        // The point relative to which velocity is measured.
+        let labels = (0..audio::distance::Reference::VARIANT_COUNT)
+            .map(|i| audio::distance::Reference::label_from_index(i))
+            .collect::<Vec<_>>();
+        let selected = Some(model.reference.to_index());
+        for new_index in widget::DropDownList::new(&labels, selected)
+            .label_font_size(SMALL_FONT_SIZE)
+            .w(button_w)
+            .h(ITEM_HEIGHT)
+            .align_top()
+            .right(PAD)
+            .set(ids.source_editor_selected_doppler_reference, ui)
+        {
+            let reference = match audio::distance::Reference::from_index(new_index) {
+                Some(reference) => reference,
+                None => continue,
+            };
+            let new_doppler = Some(audio::doppler::Model { reference, ..model });
+            sources.get_mut(&id).unwrap().audio.doppler = new_doppler;
+            send_doppler(new_doppler);
+        }
+
+        // The strength of the effect.
+        let label = format!("Scale: {:.2}", model.scale);
+        for new_scale in widget::Slider::new(model.scale, 0.0, 4.0)
+            .label(&label)
+            .label_font_size(SMALL_FONT_SIZE)
+            .kid_area_w_of(ids.source_editor_selected_doppler_canvas)
+            .h(SLIDER_H)
+            .align_left_of(ids.source_editor_selected_doppler_enabled)
+            .down_from(ids.source_editor_selected_doppler_enabled, PAD)
+            .set(ids.source_editor_selected_doppler_scale, ui)
+        {
+            let new_doppler = Some(audio::doppler::Model { scale: new_scale, ..model });
+            sources.get_mut(&id).unwrap().audio.doppler = new_doppler;
+            send_doppler(new_doppler);
+        }
+    }
+
     ///////////////////
     // Role-specific //
     ///////////////////
@@ -1315,6 +1638,8 @@ pub fn set(
                 attack_duration,
                 release_duration,
                 movement,
+                spawn,
+                elevation,
             } = soundscape;
 
             // A canvas on which installation selection widgets are instantiated.
@@ -1323,7 +1648,7 @@ pub fn set(
                 .h(INSTALLATIONS_CANVAS_H)
                 .align_middle_x_of(ids.source_editor_selected_canvas)
                 .parent(ids.source_editor_selected_canvas)
-                .down_from(ids.source_editor_selected_common_canvas, PAD)
+                .down_from(ids.source_editor_selected_doppler_canvas, PAD)
                 .pad(PAD)
                 .color(color::CHARCOAL)
                 .set(ids.source_editor_selected_installations_canvas, ui);
@@ -1890,13 +2215,170 @@ pub fn set(
                 scrollbar.set(ui);
             }
 
+            //////////////////////
+            // Soundscape Spawn //
+            //////////////////////
+
+            widget::Text::new("Spawn")
+                .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                .down(PAD * 2.0)
+                .font_size(SMALL_FONT_SIZE)
+                .set(ids.source_editor_selected_soundscape_spawn_text, ui);
+
+            // Update the local and soundscape thread copies of the spawn strategy.
+            let mut send_spawn = |new_spawn: audio::source::Spawn| {
+                let soundscape = expect_soundscape_mut(sources, &id);
+                soundscape.spawn = new_spawn.clone();
+                channels
+                    .soundscape
+                    .send(move |soundscape| {
+                        soundscape.update_source(&id, move |source| source.spawn = new_spawn);
+                    })
+                    .expect("failed to send source spawn update to soundscape thread");
+            };
+
+            // A drop down list for selecting the strategy used to position new sounds.
+            //
+            // Strategies with parameters display them to the right of the list.
+            let spawn_kid_area = ui.kid_area_of(ids.source_editor_selected_soundscape_canvas).unwrap();
+            let spawn_w = (spawn_kid_area.w() - PAD) / 2.0;
+            let labels = (0..audio::source::Spawn::VARIANT_COUNT)
+                .map(|i| audio::source::Spawn::label_from_index(i))
+                .collect::<Vec<_>>();
+            let selected = Some(spawn.to_index());
+            for new_index in widget::DropDownList::new(&labels, selected)
+                .label_font_size(SMALL_FONT_SIZE)
+                .down(PAD * 2.0)
+                .align_left()
+                .w(spawn_w)
+                .h(BUTTON_H)
+                .set(ids.source_editor_selected_soundscape_spawn_list, ui)
+            {
+                if let Some(new_spawn) = audio::source::Spawn::from_index(new_index) {
+                    send_spawn(new_spawn);
+                }
+            }
+
+            let spawn_slider = |value, min, max| {
+                widget::Slider::new(value, min, max)
+                    .h(BUTTON_H)
+                    .label_font_size(SMALL_FONT_SIZE)
+                    .color(ui::color::LIGHT_CHARCOAL)
+            };
+
+            match spawn {
+                audio::source::Spawn::PoissonDisc { min_distance } => {
+                    let label = format!("{:.2} metres apart", min_distance);
+                    let max = audio::source::movement::MAX_SPAWN_DISTANCE;
+                    for new_distance in spawn_slider(min_distance, 0.0, max)
+                        .skew(audio::source::movement::SPAWN_DISTANCE_SKEW)
+                        .label(&label)
+                        .w(spawn_w)
+                        .right(PAD)
+                        .set(ids.source_editor_selected_soundscape_spawn_min_distance, ui)
+                    {
+                        send_spawn(audio::source::Spawn::PoissonDisc { min_distance: new_distance });
+                    }
+                },
+                audio::source::Spawn::Anchor(anchor) => {
+                    let anchor_w = (spawn_w - PAD) / 2.0;
+                    let label = format!("X: {:.2}", anchor.x);
+                    for new_x in spawn_slider(anchor.x, 0.0, 1.0)
+                        .label(&label)
+                        .w(anchor_w)
+                        .right(PAD)
+                        .set(ids.source_editor_selected_soundscape_spawn_anchor_x, ui)
+                    {
+                        send_spawn(audio::source::Spawn::Anchor(pt2(new_x, anchor.y)));
+                    }
+                    let label = format!("Y: {:.2}", anchor.y);
+                    for new_y in spawn_slider(anchor.y, 0.0, 1.0)
+                        .label(&label)
+                        .w(anchor_w)
+                        .right(PAD)
+                        .set(ids.source_editor_selected_soundscape_spawn_anchor_y, ui)
+                    {
+                        send_spawn(audio::source::Spawn::Anchor(pt2(anchor.x, new_y)));
+                    }
+                },
+                _ => (),
+            }
+
+            //////////////////////////
+            // Soundscape Elevation //
+            //////////////////////////
+
+            widget::Text::new("Elevation")
+                .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                .down_from(ids.source_editor_selected_soundscape_spawn_list, PAD * 2.0)
+                .font_size(SMALL_FONT_SIZE)
+                .set(ids.source_editor_selected_soundscape_elevation_text, ui);
+
+            // Update the local and soundscape thread copies of the elevation range.
+            let mut send_elevation = |new_elevation: Option<utils::Range<f64>>| {
+                let soundscape = expect_soundscape_mut(sources, &id);
+                soundscape.elevation = new_elevation;
+                channels
+                    .soundscape
+                    .send(move |soundscape| {
+                        soundscape.update_source(&id, move |source| {
+                            source.elevation = new_elevation;
+                        });
+                    })
+                    .expect("failed to send source elevation update to soundscape thread");
+            };
+
+            // Toggle between following the average elevation of the installation's speakers and
+            // choosing the elevation of each sound from a range, displayed to the right.
+            let label = match elevation {
+                None => "INSTALLATION AVERAGE",
+                Some(_) => "RANGE",
+            };
+            for new_is_range in widget::Toggle::new(elevation.is_some())
+                .label(label)
+                .label_font_size(SMALL_FONT_SIZE)
+                .color(ui::color::LIGHT_CHARCOAL)
+                .down(PAD * 2.0)
+                .align_left()
+                .w(spawn_w)
+                .h(BUTTON_H)
+                .set(ids.source_editor_selected_soundscape_elevation_toggle, ui)
+            {
+                let new_elevation = match new_is_range {
+                    true => Some(audio::source::default::ELEVATION),
+                    false => None,
+                };
+                send_elevation(new_elevation);
+            }
+
+            if let Some(range) = elevation {
+                let label = format!("{:.2} to {:.2} metres", range.min, range.max);
+                let max = audio::source::movement::MAX_ELEVATION;
+                for (edge, value) in widget::RangeSlider::new(range.min, range.max, 0.0, max)
+                    .label(&label)
+                    .label_font_size(SMALL_FONT_SIZE)
+                    .color(ui::color::LIGHT_CHARCOAL)
+                    .w(spawn_w)
+                    .h(BUTTON_H)
+                    .right(PAD)
+                    .set(ids.source_editor_selected_soundscape_elevation_slider, ui)
+                {
+                    let mut new_range = range;
+                    match edge {
+                        widget::range_slider::Edge::Start => new_range.min = value,
+                        widget::range_slider::Edge::End => new_range.max = value,
+                    }
+                    send_elevation(Some(new_range));
+                }
+            }
+
             /////////////////////////
             // Soundscape Movement //
             /////////////////////////
 
             widget::Text::new("Movement")
                 .mid_left_of(ids.source_editor_selected_soundscape_canvas)
-                .down(PAD * 2.0)
+                .down_from(ids.source_editor_selected_soundscape_elevation_toggle, PAD * 2.0)
                 .font_size(SMALL_FONT_SIZE)
                 .set(ids.source_editor_selected_soundscape_movement_text, ui);
 
@@ -2051,267 +2533,293 @@ pub fn set(
                 }
             }
 
-            // Depending on the selected generative movement, display the relevant widgets.
-            match generative {
-                // Agent-specific widgets.
-                audio::source::movement::Generative::Agent(mut agent) => {
-                    /////////////////
-                    // Directional //
-                    /////////////////
-
-                    let on_off = if agent.directional { "ON" } else { "OFF" };
-                    let label = format!("Directional: {}", on_off);
-                    for new_directional in widget::Toggle::new(agent.directional)
-                        .label(&label)
-                        .label_font_size(SMALL_FONT_SIZE)
-                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
-                        .down(PAD * 2.0)
-                        .h(ITEM_HEIGHT)
-                        .w(canvas_kid_area.w())
-                        .color(ui::color::LIGHT_CHARCOAL)
-                        .set(ids.source_editor_selected_soundscape_movement_agent_directional, ui)
-                    {
-                        // Update local copy.
-                        agent.directional = new_directional;
-                        let generative = audio::source::movement::Generative::Agent(agent.clone());
-                        let movement = audio::source::Movement::Generative(generative);
-                        let soundscape = expect_soundscape_mut(sources, &id);
-                        soundscape.movement = movement.clone();
-
-                        // Update the soundsape thread copy.
-                        channels
-                            .soundscape
-                            .send(move |soundscape| {
-                                // Update all active sounds.
-                                soundscape.update_active_sounds_with_source(id, |_, sound| {
-                                    let gen = match sound.movement {
-                                        soundscape::Movement::Generative(ref mut gen) => gen,
-                                        _ => return,
-                                    };
-                                    let agent = match *gen {
-                                        soundscape::movement::Generative::Agent(ref mut agent) => agent,
-                                        _ => return,
-                                    };
-                                    agent.directional = new_directional;
-                                });
+            // Agent-specific widgets, shared by all movements that steer an agent.
+            if let Some(mut agent) = generative.agent().cloned() {
+                /////////////////
+                // Directional //
+                /////////////////
 
-                                // Update the source.
-                                soundscape.update_source(&id, |source| {
-                                    let gen = match source.movement {
-                                        audio::source::Movement::Generative(ref mut gen) => gen,
-                                        _ => return,
-                                    };
-                                    let agent = match *gen {
-                                        audio::source::movement::Generative::Agent(ref mut agent) => agent,
-                                        _ => return,
-                                    };
-                                    agent.directional = new_directional;
-                                });
-                            })
-                            .expect("failed to send source movement update to soundscape thread");
+                let on_off = if agent.directional { "ON" } else { "OFF" };
+                let label = format!("Directional: {}", on_off);
+                for new_directional in widget::Toggle::new(agent.directional)
+                    .label(&label)
+                    .label_font_size(SMALL_FONT_SIZE)
+                    .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                    .down(PAD * 2.0)
+                    .h(ITEM_HEIGHT)
+                    .w(canvas_kid_area.w())
+                    .color(ui::color::LIGHT_CHARCOAL)
+                    .set(ids.source_editor_selected_soundscape_movement_agent_directional, ui)
+                {
+                    // Update local copy.
+                    agent.directional = new_directional;
+                    let soundscape = expect_soundscape_mut(sources, &id);
+                    if let Some(local_agent) = soundscape.movement.agent_mut() {
+                        *local_agent = agent.clone();
                     }
 
-                    ///////////////
-                    // Max Speed //
-                    ///////////////
+                    // Update the soundsape thread copy.
+                    channels
+                        .soundscape
+                        .send(move |soundscape| {
+                            // Update all active sounds.
+                            soundscape.update_active_sounds_with_source(id, |_, sound| {
+                                let agent = match sound.movement.agent_mut() {
+                                    Some(agent) => agent,
+                                    None => return,
+                                };
+                                agent.directional = new_directional;
+                            });
 
-                    widget::Text::new("Max Speed")
-                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
-                        .down(PAD * 2.0)
-                        .font_size(SMALL_FONT_SIZE)
-                        .set(ids.source_editor_selected_soundscape_movement_agent_max_speed_text, ui);
+                            // Update the source.
+                            soundscape.update_source(&id, |source| {
+                                let agent = match source.movement.agent_mut() {
+                                    Some(agent) => agent,
+                                    None => return,
+                                };
+                                agent.directional = new_directional;
+                            });
+                        })
+                        .expect("failed to send source movement update to soundscape thread");
+                }
 
-                    let min = agent.max_speed.min;
-                    let max = agent.max_speed.max;
-                    let total_min = 0.0;
-                    let total_max = audio::source::movement::MAX_SPEED;
-                    let label = format!("{:.2} to {:.2} metres per second", min, max);
-                    for (edge, value) in range_slider(min, max, total_min, total_max)
-                        .skew(audio::source::movement::MAX_SPEED_SKEW)
-                        .align_left()
-                        .label(&label)
-                        .down(PAD * 2.0)
-                        .set(ids.source_editor_selected_soundscape_movement_agent_max_speed_slider, ui)
-                    {
-                        match edge {
-                            widget::range_slider::Edge::Start => agent.max_speed.min = value,
-                            widget::range_slider::Edge::End => agent.max_speed.max = value,
-                        }
+                ///////////////
+                // Max Speed //
+                ///////////////
+
+                widget::Text::new("Max Speed")
+                    .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                    .down(PAD * 2.0)
+                    .font_size(SMALL_FONT_SIZE)
+                    .set(ids.source_editor_selected_soundscape_movement_agent_max_speed_text, ui);
+
+                let min = agent.max_speed.min;
+                let max = agent.max_speed.max;
+                let total_min = 0.0;
+                let total_max = audio::source::movement::MAX_SPEED;
+                let label = format!("{:.2} to {:.2} metres per second", min, max);
+                for (edge, value) in range_slider(min, max, total_min, total_max)
+                    .skew(audio::source::movement::MAX_SPEED_SKEW)
+                    .align_left()
+                    .label(&label)
+                    .down(PAD * 2.0)
+                    .set(ids.source_editor_selected_soundscape_movement_agent_max_speed_slider, ui)
+                {
+                    match edge {
+                        widget::range_slider::Edge::Start => agent.max_speed.min = value,
+                        widget::range_slider::Edge::End => agent.max_speed.max = value,
+                    }
 
-                        // Update local copy.
-                        let generative = audio::source::movement::Generative::Agent(agent.clone());
-                        let movement = audio::source::Movement::Generative(generative);
-                        let soundscape = expect_soundscape_mut(sources, &id);
-                        soundscape.movement = movement.clone();
+                    // Update local copy.
+                    let soundscape = expect_soundscape_mut(sources, &id);
+                    if let Some(local_agent) = soundscape.movement.agent_mut() {
+                        *local_agent = agent.clone();
+                    }
 
-                        // Update the soundsape thread copy.
-                        let new_max_speed = agent.max_speed;
-                        channels
-                            .soundscape
-                            .send(move |soundscape| {
-                                // Update all active sounds.
-                                soundscape.update_active_sounds_with_source(id, |_, sound| {
-                                    let gen = match sound.movement {
-                                        soundscape::Movement::Generative(ref mut gen) => gen,
-                                        _ => return,
-                                    };
-                                    let agent = match *gen {
-                                        soundscape::movement::Generative::Agent(ref mut agent) => agent,
-                                        _ => return,
-                                    };
-                                    agent.max_speed = new_max_speed.clamp(agent.max_speed);
-                                });
+                    // Update the soundsape thread copy.
+                    let new_max_speed = agent.max_speed;
+                    channels
+                        .soundscape
+                        .send(move |soundscape| {
+                            // Update all active sounds.
+                            soundscape.update_active_sounds_with_source(id, |_, sound| {
+                                let agent = match sound.movement.agent_mut() {
+                                    Some(agent) => agent,
+                                    None => return,
+                                };
+                                agent.max_speed = new_max_speed.clamp(agent.max_speed);
+                            });
 
-                                // Update the source.
-                                soundscape.update_source(&id, |source| {
-                                    let gen = match source.movement {
-                                        audio::source::Movement::Generative(ref mut gen) => gen,
-                                        _ => return,
-                                    };
-                                    let agent = match *gen {
-                                        audio::source::movement::Generative::Agent(ref mut agent) => agent,
-                                        _ => return,
-                                    };
-                                    agent.max_speed = new_max_speed;
-                                });
-                            })
-                            .expect("failed to send movement update to soundscape thread");
-                    }
+                            // Update the source.
+                            soundscape.update_source(&id, |source| {
+                                let agent = match source.movement.agent_mut() {
+                                    Some(agent) => agent,
+                                    None => return,
+                                };
+                                agent.max_speed = new_max_speed;
+                            });
+                        })
+                        .expect("failed to send movement update to soundscape thread");
+                }
 
-                    ///////////////
-                    // Max Force //
-                    ///////////////
+                ///////////////
+                // Max Force //
+                ///////////////
+
+                widget::Text::new("Max Force")
+                    .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                    .down(PAD * 2.0)
+                    .font_size(SMALL_FONT_SIZE)
+                    .set(ids.source_editor_selected_soundscape_movement_agent_max_force_text, ui);
+
+                let min = agent.max_force.min;
+                let max = agent.max_force.max;
+                let total_min = 0.0;
+                let total_max = audio::source::movement::MAX_FORCE;
+                let label = format!("{:.2} to {:.2} metres per second squared", min, max);
+                for (edge, value) in range_slider(min, max, total_min, total_max)
+                    .skew(audio::source::movement::MAX_FORCE_SKEW)
+                    .align_left()
+                    .label(&label)
+                    .down(PAD * 2.0)
+                    .set(ids.source_editor_selected_soundscape_movement_agent_max_force_slider, ui)
+                {
+                    match edge {
+                        widget::range_slider::Edge::Start => agent.max_force.min = value,
+                        widget::range_slider::Edge::End => agent.max_force.max = value,
+                    }
 
-                    widget::Text::new("Max Force")
-                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
-                        .down(PAD * 2.0)
-                        .font_size(SMALL_FONT_SIZE)
-                        .set(ids.source_editor_selected_soundscape_movement_agent_max_force_text, ui);
+                    // Update local copy.
+                    let soundscape = expect_soundscape_mut(sources, &id);
+                    if let Some(local_agent) = soundscape.movement.agent_mut() {
+                        *local_agent = agent.clone();
+                    }
 
-                    let min = agent.max_force.min;
-                    let max = agent.max_force.max;
-                    let total_min = 0.0;
-                    let total_max = audio::source::movement::MAX_FORCE;
-                    let label = format!("{:.2} to {:.2} metres per second squared", min, max);
-                    for (edge, value) in range_slider(min, max, total_min, total_max)
-                        .skew(audio::source::movement::MAX_FORCE_SKEW)
-                        .align_left()
-                        .label(&label)
-                        .down(PAD * 2.0)
-                        .set(ids.source_editor_selected_soundscape_movement_agent_max_force_slider, ui)
-                    {
-                        match edge {
-                            widget::range_slider::Edge::Start => agent.max_force.min = value,
-                            widget::range_slider::Edge::End => agent.max_force.max = value,
-                        }
+                    // Update the soundsape thread copy.
+                    let new_max_force = agent.max_force;
+                    channels
+                        .soundscape
+                        .send(move |soundscape| {
+                            // Update all active sounds.
+                            soundscape.update_active_sounds_with_source(id, |_, sound| {
+                                let agent = match sound.movement.agent_mut() {
+                                    Some(agent) => agent,
+                                    None => return,
+                                };
+                                agent.max_force = new_max_force.clamp(agent.max_force);
+                            });
 
-                        // Update local copy.
-                        let soundscape = expect_soundscape_mut(sources, &id);
-                        let generative = audio::source::movement::Generative::Agent(agent.clone());
-                        let movement = audio::source::Movement::Generative(generative);
-                        soundscape.movement = movement.clone();
+                            // Update the source.
+                            soundscape.update_source(&id, |source| {
+                                let agent = match source.movement.agent_mut() {
+                                    Some(agent) => agent,
+                                    None => return,
+                                };
+                                agent.max_force = new_max_force;
+                            });
+                        })
+                        .expect("failed to send movement update to soundscape thread");
+                }
 
-                        // Update the soundsape thread copy.
-                        let new_max_force = agent.max_force;
-                        channels
-                            .soundscape
-                            .send(move |soundscape| {
-                                // Update all active sounds.
-                                soundscape.update_active_sounds_with_source(id, |_, sound| {
-                                    let gen = match sound.movement {
-                                        soundscape::Movement::Generative(ref mut gen) => gen,
-                                        _ => return,
-                                    };
-                                    let agent = match *gen {
-                                        soundscape::movement::Generative::Agent(ref mut agent) => agent,
-                                        _ => return,
-                                    };
-                                    agent.max_force = new_max_force.clamp(agent.max_force);
-                                });
+                //////////////////
+                // Max Rotation //
+                //////////////////
+
+                widget::Text::new("Max Rotation")
+                    .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                    .down(PAD * 2.0)
+                    .font_size(SMALL_FONT_SIZE)
+                    .set(ids.source_editor_selected_soundscape_movement_agent_max_rotation_text, ui);
+
+                let min = agent.max_rotation.min;
+                let max = agent.max_rotation.max;
+                let total_min = 0.0;
+                let total_max = audio::source::movement::MAX_ROTATION;
+                let label = format!("{:.2} to {:.2} radians per second", min, max);
+                for (edge, value) in range_slider(min, max, total_min, total_max)
+                    .skew(audio::source::movement::MAX_ROTATION_SKEW)
+                    .align_left()
+                    .label(&label)
+                    .down(PAD * 2.0)
+                    .set(ids.source_editor_selected_soundscape_movement_agent_max_rotation_slider, ui)
+                {
+                    match edge {
+                        widget::range_slider::Edge::Start => agent.max_rotation.min = value,
+                        widget::range_slider::Edge::End => agent.max_rotation.max = value,
+                    }
 
-                                // Update the source.
-                                soundscape.update_source(&id, |source| {
-                                    let gen = match source.movement {
-                                        audio::source::Movement::Generative(ref mut gen) => gen,
-                                        _ => return,
-                                    };
-                                    let agent = match *gen {
-                                        audio::source::movement::Generative::Agent(ref mut agent) => agent,
-                                        _ => return,
-                                    };
-                                    agent.max_force = new_max_force;
-                                });
-                            })
-                            .expect("failed to send movement update to soundscape thread");
+                    // Update local copy.
+                    let soundscape = expect_soundscape_mut(sources, &id);
+                    if let Some(local_agent) = soundscape.movement.agent_mut() {
+                        *local_agent = agent.clone();
                     }
 
-                    //////////////////
-                    // Max Rotation //
-                    //////////////////
+                    // Update the soundsape thread copy.
+                    let new_max_rotation = agent.max_rotation;
+                    channels
+                        .soundscape
+                        .send(move |soundscape| {
+                            // Update all active sounds.
+                            soundscape.update_active_sounds_with_source(id, |_, sound| {
+                                let agent = match sound.movement.agent_mut() {
+                                    Some(agent) => agent,
+                                    None => return,
+                                };
+                                agent.max_rotation = new_max_rotation.clamp(agent.max_rotation);
+                            });
 
-                    widget::Text::new("Max Rotation")
-                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
-                        .down(PAD * 2.0)
-                        .font_size(SMALL_FONT_SIZE)
-                        .set(ids.source_editor_selected_soundscape_movement_agent_max_rotation_text, ui);
+                            // Update the source.
+                            soundscape.update_source(&id, |source| {
+                                let agent = match source.movement.agent_mut() {
+                                    Some(agent) => agent,
+                                    None => return,
+                                };
+                                agent.max_rotation = new_max_rotation;
+                            });
+                        })
+                        .expect("failed to send movement update to soundscape thread");
+                }
 
-                    let min = agent.max_rotation.min;
-                    let max = agent.max_rotation.max;
-                    let total_min = 0.0;
-                    let total_max = audio::source::movement::MAX_ROTATION;
-                    let label = format!("{:.2} to {:.2} radians per second", min, max);
-                    for (edge, value) in range_slider(min, max, total_min, total_max)
-                        .skew(audio::source::movement::MAX_ROTATION_SKEW)
-                        .align_left()
-                        .label(&label)
-                        .down(PAD * 2.0)
-                        .set(ids.source_editor_selected_soundscape_movement_agent_max_rotation_slider, ui)
-                    {
-                        match edge {
-                            widget::range_slider::Edge::Start => agent.max_rotation.min = value,
-                            widget::range_slider::Edge::End => agent.max_rotation.max = value,
-                        }
+                /////////////////////
+                // Follow Activity //
+                /////////////////////
+
+                // Without presence reported via OSC, activity is measured from the output RMS of
+                // each installation, which includes the soundscape's own sounds, so following it
+                // is self-reinforcing.
+                widget::Text::new("Follow Activity (output RMS is self-reinforcing)")
+                    .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                    .down(PAD * 2.0)
+                    .font_size(SMALL_FONT_SIZE)
+                    .set(ids.source_editor_selected_soundscape_movement_agent_activity_text, ui);
+
+                let label = format!("{:.0}% of targets follow activity", agent.activity_weight * 100.0);
+                for new_activity_weight in widget::Slider::new(agent.activity_weight, 0.0, 1.0)
+                    .kid_area_w_of(ids.source_editor_selected_soundscape_canvas)
+                    .h(SLIDER_H)
+                    .label_font_size(SMALL_FONT_SIZE)
+                    .color(ui::color::LIGHT_CHARCOAL)
+                    .align_left()
+                    .label(&label)
+                    .down(PAD * 2.0)
+                    .set(ids.source_editor_selected_soundscape_movement_agent_activity_slider, ui)
+                {
+                    // Update local copy.
+                    agent.activity_weight = new_activity_weight;
+                    let soundscape = expect_soundscape_mut(sources, &id);
+                    if let Some(local_agent) = soundscape.movement.agent_mut() {
+                        *local_agent = agent.clone();
+                    }
 
-                        // Update local copy.
-                        let soundscape = expect_soundscape_mut(sources, &id);
-                        let generative = audio::source::movement::Generative::Agent(agent.clone());
-                        let movement = audio::source::Movement::Generative(generative);
-                        soundscape.movement = movement.clone();
+                    // Update the soundsape thread copy.
+                    channels
+                        .soundscape
+                        .send(move |soundscape| {
+                            // Update all active sounds.
+                            soundscape.update_active_sounds_with_source(id, |_, sound| {
+                                let agent = match sound.movement.agent_mut() {
+                                    Some(agent) => agent,
+                                    None => return,
+                                };
+                                agent.activity_weight = new_activity_weight;
+                            });
 
-                        // Update the soundsape thread copy.
-                        let new_max_rotation = agent.max_rotation;
-                        channels
-                            .soundscape
-                            .send(move |soundscape| {
-                                // Update all active sounds.
-                                soundscape.update_active_sounds_with_source(id, |_, sound| {
-                                    let gen = match sound.movement {
-                                        soundscape::Movement::Generative(ref mut gen) => gen,
-                                        _ => return,
-                                    };
-                                    let agent = match *gen {
-                                        soundscape::movement::Generative::Agent(ref mut agent) => agent,
-                                        _ => return,
-                                    };
-                                    agent.max_rotation = new_max_rotation.clamp(agent.max_rotation);
-                                });
+                            // Update the source.
+                            soundscape.update_source(&id, |source| {
+                                let agent = match source.movement.agent_mut() {
+                                    Some(agent) => agent,
+                                    None => return,
+                                };
+                                agent.activity_weight = new_activity_weight;
+                            });
+                        })
+                        .expect("failed to send movement update to soundscape thread");
+                }
+            }
 
-                                // Update the source.
-                                soundscape.update_source(&id, |source| {
-                                    let gen = match source.movement {
-                                        audio::source::Movement::Generative(ref mut gen) => gen,
-                                        _ => return,
-                                    };
-                                    let agent = match *gen {
-                                        audio::source::movement::Generative::Agent(ref mut agent) => agent,
-                                        _ => return,
-                                    };
-                                    agent.max_rotation = new_max_rotation;
-                                });
-                            })
-                            .expect("failed to send movement update to soundscape thread");
-                    }
-                },
+            // Depending on the selected generative movement, display the relevant widgets.
+            match generative {
+                audio::source::movement::Generative::Agent(_) => (),
 
                 // Ngon-specific widgets.
                 audio::source::movement::Generative::Ngon(mut ngon) => {
@@ -2706,6 +3214,463 @@ pub fn set(
                             .expect("failed to send movement update to soundscape thread");
                     }
                 },
+
+                // Flock-specific widgets.
+                audio::source::movement::Generative::Flock(mut flock) => {
+
+                    ////////////
+                    // Radius //
+                    ////////////
+
+                    widget::Text::new("Flocking Radius")
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .font_size(SMALL_FONT_SIZE)
+                        .set(ids.source_editor_selected_soundscape_movement_flock_radius_text, ui);
+
+                    let flock_slider = |value, min, max| {
+                        widget::Slider::new(value, min, max)
+                            .w(canvas_kid_area.w())
+                            .h(SLIDER_H)
+                            .label_font_size(SMALL_FONT_SIZE)
+                            .color(ui::color::LIGHT_CHARCOAL)
+                    };
+
+                    let label = format!("{:.2} metres", flock.radius);
+                    let max = audio::source::movement::MAX_FLOCK_RADIUS;
+                    for new_radius in flock_slider(flock.radius, 0.0, max)
+                        .label(&label)
+                        .align_left()
+                        .down(PAD * 2.0)
+                        .set(ids.source_editor_selected_soundscape_movement_flock_radius_slider, ui)
+                    {
+                        flock.radius = new_radius;
+                        update_flock(sources, channels, id, flock.clone());
+                    }
+
+                    /////////////
+                    // Weights //
+                    /////////////
+
+                    widget::Text::new("Flocking Weights")
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .font_size(SMALL_FONT_SIZE)
+                        .set(ids.source_editor_selected_soundscape_movement_flock_weights_text, ui);
+
+                    let max = audio::source::movement::MAX_FLOCK_WEIGHT;
+                    let label = format!("Separation: {:.2}", flock.separation);
+                    for new_separation in flock_slider(flock.separation, 0.0, max)
+                        .label(&label)
+                        .align_left()
+                        .down(PAD * 2.0)
+                        .set(ids.source_editor_selected_soundscape_movement_flock_separation, ui)
+                    {
+                        flock.separation = new_separation;
+                        update_flock(sources, channels, id, flock.clone());
+                    }
+
+                    let label = format!("Alignment: {:.2}", flock.alignment);
+                    for new_alignment in flock_slider(flock.alignment, 0.0, max)
+                        .label(&label)
+                        .align_left()
+                        .down(PAD)
+                        .set(ids.source_editor_selected_soundscape_movement_flock_alignment, ui)
+                    {
+                        flock.alignment = new_alignment;
+                        update_flock(sources, channels, id, flock.clone());
+                    }
+
+                    let label = format!("Cohesion: {:.2}", flock.cohesion);
+                    for new_cohesion in flock_slider(flock.cohesion, 0.0, max)
+                        .label(&label)
+                        .align_left()
+                        .down(PAD)
+                        .set(ids.source_editor_selected_soundscape_movement_flock_cohesion, ui)
+                    {
+                        flock.cohesion = new_cohesion;
+                        update_flock(sources, channels, id, flock.clone());
+                    }
+
+                    ////////////
+                    // Groups //
+                    ////////////
+
+                    let on_off = if flock.groups { "ON" } else { "OFF" };
+                    let label = format!("Flock With Groups: {}", on_off);
+                    for new_groups in widget::Toggle::new(flock.groups)
+                        .label(&label)
+                        .label_font_size(SMALL_FONT_SIZE)
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .h(ITEM_HEIGHT)
+                        .w(canvas_kid_area.w())
+                        .color(ui::color::LIGHT_CHARCOAL)
+                        .set(ids.source_editor_selected_soundscape_movement_flock_groups, ui)
+                    {
+                        flock.groups = new_groups;
+                        update_flock(sources, channels, id, flock.clone());
+                    }
+                },
+
+                // Path-specific widgets.
+                audio::source::movement::Generative::Path(mut path) => {
+
+                    ///////////
+                    // Speed //
+                    ///////////
+
+                    widget::Text::new("Speed")
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .font_size(SMALL_FONT_SIZE)
+                        .set(ids.source_editor_selected_soundscape_movement_path_speed_text, ui);
+
+                    let min = path.speed.min;
+                    let max = path.speed.max;
+                    let total_min = 0.0;
+                    let total_max = audio::source::movement::MAX_SPEED;
+                    let label = format!("{:.2} to {:.2} metres per second", min, max);
+                    for (edge, value) in range_slider(min, max, total_min, total_max)
+                        .skew(audio::source::movement::MAX_SPEED_SKEW)
+                        .align_left()
+                        .label(&label)
+                        .down(PAD * 2.0)
+                        .set(ids.source_editor_selected_soundscape_movement_path_speed_slider, ui)
+                    {
+                        match edge {
+                            widget::range_slider::Edge::Start => path.speed.min = value,
+                            widget::range_slider::Edge::End => path.speed.max = value,
+                        }
+                        update_path(sources, channels, id, path.clone());
+                    }
+
+                    //////////
+                    // Mode //
+                    //////////
+
+                    widget::Text::new("At The End Of The Path")
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .font_size(SMALL_FONT_SIZE)
+                        .set(ids.source_editor_selected_soundscape_movement_path_mode_text, ui);
+
+                    let n_items = audio::source::movement::PathMode::VARIANT_COUNT;
+                    let item_w = canvas_kid_area.w() / n_items as Scalar;
+                    let (mut events, _scrollbar) = widget::ListSelect::single(n_items)
+                        .flow_right()
+                        .down(PAD * 2.0)
+                        .align_left()
+                        .w(canvas_kid_area.w())
+                        .h(BUTTON_H)
+                        .item_size(item_w)
+                        .set(ids.source_editor_selected_soundscape_movement_path_mode_list, ui);
+                    let selected_index = path.mode.to_index();
+                    let is_selected = |i| i == selected_index;
+                    while let Some(event) = events.next(ui, &is_selected) {
+                        use nannou::ui::widget::list_select::Event;
+                        match event {
+                            Event::Item(item) => {
+                                let index = item.i;
+                                let color = if is_selected(index) { color::BLUE } else { color::DARK_CHARCOAL };
+                                let label = audio::source::movement::PathMode::label_from_index(index);
+                                let button = widget::Button::new()
+                                    .label(&label)
+                                    .label_font_size(SMALL_FONT_SIZE)
+                                    .color(color);
+                                for _click in item.set(button, ui) {
+                                    if let Some(mode) = audio::source::movement::PathMode::from_index(index) {
+                                        path.mode = mode;
+                                        update_path(sources, channels, id, path.clone());
+                                    }
+                                }
+                            },
+                            _ => (),
+                        }
+                    }
+
+                    /////////////////////////////
+                    // Spline and Random Start //
+                    /////////////////////////////
+
+                    let toggle_w = (canvas_kid_area.w() - PAD) / 2.0;
+                    let on_off = if path.spline { "ON" } else { "OFF" };
+                    let label = format!("Spline: {}", on_off);
+                    for new_spline in widget::Toggle::new(path.spline)
+                        .label(&label)
+                        .label_font_size(SMALL_FONT_SIZE)
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD)
+                        .h(ITEM_HEIGHT)
+                        .w(toggle_w)
+                        .color(ui::color::LIGHT_CHARCOAL)
+                        .set(ids.source_editor_selected_soundscape_movement_path_spline, ui)
+                    {
+                        path.spline = new_spline;
+                        update_path(sources, channels, id, path.clone());
+                    }
+
+                    let on_off = if path.random_start { "ON" } else { "OFF" };
+                    let label = format!("Random Start: {}", on_off);
+                    for new_random_start in widget::Toggle::new(path.random_start)
+                        .label(&label)
+                        .label_font_size(SMALL_FONT_SIZE)
+                        .right(PAD)
+                        .h(ITEM_HEIGHT)
+                        .w(toggle_w)
+                        .color(ui::color::LIGHT_CHARCOAL)
+                        .set(ids.source_editor_selected_soundscape_movement_path_random_start, ui)
+                    {
+                        path.random_start = new_random_start;
+                        update_path(sources, channels, id, path.clone());
+                    }
+
+                    ///////////////
+                    // Waypoints //
+                    ///////////////
+
+                    let text = format!(
+                        "{} waypoints. Double-click the floorplan to add a waypoint, drag a \
+                         waypoint to move it or right-click a waypoint to remove it.",
+                        path.points.len(),
+                    );
+                    info_text(&text)
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .w(canvas_kid_area.w())
+                        .set(ids.source_editor_selected_soundscape_movement_path_waypoints_text, ui);
+
+                    for _click in widget::Button::new()
+                        .label("CLEAR WAYPOINTS")
+                        .label_font_size(SMALL_FONT_SIZE)
+                        .color(ui::color::DARK_CHARCOAL)
+                        .align_left()
+                        .down(PAD * 2.0)
+                        .w(canvas_kid_area.w())
+                        .h(BUTTON_H)
+                        .set(ids.source_editor_selected_soundscape_movement_path_clear, ui)
+                    {
+                        path.points.clear();
+                        update_path(sources, channels, id, path.clone());
+                    }
+                },
+
+                // Random walk specific widgets.
+                audio::source::movement::Generative::RandomWalk(mut walk) => {
+
+                    ///////////////
+                    // Step Size //
+                    ///////////////
+
+                    widget::Text::new("Step Size")
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .font_size(SMALL_FONT_SIZE)
+                        .set(ids.source_editor_selected_soundscape_movement_walk_step_text, ui);
+
+                    let min = walk.step.min;
+                    let max = walk.step.max;
+                    let total_min = 0.0;
+                    let total_max = audio::source::movement::MAX_STEP;
+                    let label = format!("{:.2} to {:.2} metres per step", min, max);
+                    for (edge, value) in range_slider(min, max, total_min, total_max)
+                        .align_left()
+                        .label(&label)
+                        .down(PAD * 2.0)
+                        .set(ids.source_editor_selected_soundscape_movement_walk_step_slider, ui)
+                    {
+                        match edge {
+                            widget::range_slider::Edge::Start => walk.step.min = value,
+                            widget::range_slider::Edge::End => walk.step.max = value,
+                        }
+                        update_random_walk(sources, channels, id, walk.clone());
+                    }
+
+                    ///////////
+                    // Drift //
+                    ///////////
+
+                    widget::Text::new("Drift")
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .font_size(SMALL_FONT_SIZE)
+                        .set(ids.source_editor_selected_soundscape_movement_walk_drift_text, ui);
+
+                    let min = walk.drift.min;
+                    let max = walk.drift.max;
+                    let total_min = 0.0;
+                    let total_max = audio::source::movement::MAX_DRIFT;
+                    let label = format!("{:.2} to {:.2} metres per second", min, max);
+                    for (edge, value) in range_slider(min, max, total_min, total_max)
+                        .align_left()
+                        .label(&label)
+                        .down(PAD * 2.0)
+                        .set(ids.source_editor_selected_soundscape_movement_walk_drift_slider, ui)
+                    {
+                        match edge {
+                            widget::range_slider::Edge::Start => walk.drift.min = value,
+                            widget::range_slider::Edge::End => walk.drift.max = value,
+                        }
+                        update_random_walk(sources, channels, id, walk.clone());
+                    }
+                },
+
+                // Orbit-specific widgets.
+                audio::source::movement::Generative::Orbit(mut orbit) => {
+
+                    ////////////
+                    // Centre //
+                    ////////////
+
+                    widget::Text::new("Orbit Centre")
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .font_size(SMALL_FONT_SIZE)
+                        .set(ids.source_editor_selected_soundscape_movement_orbit_centre_text, ui);
+
+                    let centre_w = (canvas_kid_area.w() - PAD) / 2.0;
+                    let fixed = match orbit.centre {
+                        audio::source::movement::OrbitCentre::Fixed(_) => true,
+                        audio::source::movement::OrbitCentre::Installation => false,
+                    };
+                    let label = if fixed { "FIXED POINT" } else { "INSTALLATION" };
+                    for new_fixed in widget::Toggle::new(fixed)
+                        .label(label)
+                        .label_font_size(SMALL_FONT_SIZE)
+                        .align_left()
+                        .down(PAD * 2.0)
+                        .h(ITEM_HEIGHT)
+                        .w(centre_w)
+                        .color(ui::color::LIGHT_CHARCOAL)
+                        .set(ids.source_editor_selected_soundscape_movement_orbit_centre_toggle, ui)
+                    {
+                        orbit.centre = match new_fixed {
+                            true => {
+                                let centre = audio::source::default::FIXED;
+                                audio::source::movement::OrbitCentre::Fixed(centre)
+                            },
+                            false => audio::source::movement::OrbitCentre::Installation,
+                        };
+                        update_orbit(sources, channels, id, orbit.clone());
+                    }
+
+                    if let audio::source::movement::OrbitCentre::Fixed(centre) = orbit.centre {
+                        let centre_slider = |value| {
+                            widget::Slider::new(value, 0.0, 1.0)
+                                .w((centre_w - PAD) / 2.0)
+                                .h(ITEM_HEIGHT)
+                                .label_font_size(SMALL_FONT_SIZE)
+                                .color(ui::color::LIGHT_CHARCOAL)
+                        };
+
+                        let label = format!("X: {:.2}", centre.x);
+                        for new_x in centre_slider(centre.x)
+                            .label(&label)
+                            .right(PAD)
+                            .set(ids.source_editor_selected_soundscape_movement_orbit_centre_x, ui)
+                        {
+                            let point = pt2(new_x, centre.y);
+                            orbit.centre = audio::source::movement::OrbitCentre::Fixed(point);
+                            update_orbit(sources, channels, id, orbit.clone());
+                        }
+
+                        let label = format!("Y: {:.2}", centre.y);
+                        for new_y in centre_slider(centre.y)
+                            .label(&label)
+                            .right(PAD)
+                            .set(ids.source_editor_selected_soundscape_movement_orbit_centre_y, ui)
+                        {
+                            let point = pt2(centre.x, new_y);
+                            orbit.centre = audio::source::movement::OrbitCentre::Fixed(point);
+                            update_orbit(sources, channels, id, orbit.clone());
+                        }
+                    }
+
+                    ////////////
+                    // Radius //
+                    ////////////
+
+                    widget::Text::new("Radius")
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .font_size(SMALL_FONT_SIZE)
+                        .set(ids.source_editor_selected_soundscape_movement_orbit_radius_text, ui);
+
+                    let min = orbit.radius.min;
+                    let max = orbit.radius.max;
+                    let total_min = 0.0;
+                    let total_max = audio::source::movement::MAX_ORBIT_RADIUS;
+                    let label = format!("{:.2} to {:.2} metres", min, max);
+                    for (edge, value) in range_slider(min, max, total_min, total_max)
+                        .align_left()
+                        .label(&label)
+                        .down(PAD * 2.0)
+                        .set(ids.source_editor_selected_soundscape_movement_orbit_radius_slider, ui)
+                    {
+                        match edge {
+                            widget::range_slider::Edge::Start => orbit.radius.min = value,
+                            widget::range_slider::Edge::End => orbit.radius.max = value,
+                        }
+                        update_orbit(sources, channels, id, orbit.clone());
+                    }
+
+                    ///////////////////
+                    // Angular Speed //
+                    ///////////////////
+
+                    widget::Text::new("Angular Speed")
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .font_size(SMALL_FONT_SIZE)
+                        .set(ids.source_editor_selected_soundscape_movement_orbit_speed_text, ui);
+
+                    let min = orbit.angular_speed.min;
+                    let max = orbit.angular_speed.max;
+                    let total_min = 0.0;
+                    let total_max = audio::source::movement::MAX_ANGULAR_SPEED;
+                    let label = format!("{:.2} to {:.2} radians per second", min, max);
+                    for (edge, value) in range_slider(min, max, total_min, total_max)
+                        .skew(audio::source::movement::ANGULAR_SPEED_SKEW)
+                        .align_left()
+                        .label(&label)
+                        .down(PAD * 2.0)
+                        .set(ids.source_editor_selected_soundscape_movement_orbit_speed_slider, ui)
+                    {
+                        match edge {
+                            widget::range_slider::Edge::Start => orbit.angular_speed.min = value,
+                            widget::range_slider::Edge::End => orbit.angular_speed.max = value,
+                        }
+                        update_orbit(sources, channels, id, orbit.clone());
+                    }
+
+                    //////////////////
+                    // Eccentricity //
+                    //////////////////
+
+                    widget::Text::new("Eccentricity")
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .font_size(SMALL_FONT_SIZE)
+                        .set(ids.source_editor_selected_soundscape_movement_orbit_eccentricity_text, ui);
+
+                    let min = orbit.eccentricity.min;
+                    let max = orbit.eccentricity.max;
+                    let total_min = 0.0;
+                    let total_max = audio::source::movement::MAX_ECCENTRICITY;
+                    let label = format!("{:.2} to {:.2}", min, max);
+                    for (edge, value) in range_slider(min, max, total_min, total_max)
+                        .align_left()
+                        .label(&label)
+                        .down(PAD * 2.0)
+                        .set(ids.source_editor_selected_soundscape_movement_orbit_eccentricity_slider, ui)
+                    {
+                        match edge {
+                            widget::range_slider::Edge::Start => orbit.eccentricity.min = value,
+                            widget::range_slider::Edge::End => orbit.eccentricity.max = value,
+                        }
+                        update_orbit(sources, channels, id, orbit.clone());
+                    }
+                },
             }
         },
 
@@ -2724,3 +3689,219 @@ pub fn set(
 
     area.id
 }
+
+/// Update the local and soundscape thread copies of the given source's path movement.
+///
+/// Active sounds spawned via the source follow the new path from their current progress along it.
+pub fn update_path(
+    sources: &mut project::SourcesMap,
+    channels: &Channels,
+    id: audio::source::Id,
+    path: audio::source::movement::Path,
+) {
+    // Update the local copy.
+    let soundscape = match sources
+        .get_mut(&id)
+        .and_then(|source| source.audio.role.as_mut())
+        .and_then(|role| role.soundscape_mut())
+    {
+        Some(soundscape) => soundscape,
+        None => return,
+    };
+    let generative = audio::source::movement::Generative::Path(path.clone());
+    soundscape.movement = audio::source::Movement::Generative(generative);
+
+    // Update the soundscape thread copy.
+    channels
+        .soundscape
+        .send(move |soundscape| {
+            // Update all active sounds.
+            soundscape.update_active_sounds_with_source(id, |_, sound| {
+                let gen = match sound.movement {
+                    soundscape::Movement::Generative(ref mut gen) => gen,
+                    _ => return,
+                };
+                let sound_path = match *gen {
+                    soundscape::movement::Generative::Path(ref mut path) => path,
+                    _ => return,
+                };
+                sound_path.speed = path.speed.clamp(sound_path.speed);
+                sound_path.mode = path.mode;
+                sound_path.set_points(&path.points, path.spline);
+            });
+
+            // Update the source.
+            soundscape.update_source(&id, |source| {
+                let generative = audio::source::movement::Generative::Path(path);
+                source.movement = audio::source::Movement::Generative(generative);
+            });
+        })
+        .expect("failed to send path movement update to soundscape thread");
+}
+
+/// Update the local and soundscape thread copies of the given source's flocking parameters.
+///
+/// The agent constraints of the flock are left untouched, as they are updated via the agent
+/// widgets.
+pub fn update_flock(
+    sources: &mut project::SourcesMap,
+    channels: &Channels,
+    id: audio::source::Id,
+    flock: audio::source::movement::Flock,
+) {
+    // Apply the flocking parameters to the given source flock, preserving its agent.
+    fn set_params(
+        source_flock: &mut audio::source::movement::Flock,
+        flock: &audio::source::movement::Flock,
+    ) {
+        let agent = source_flock.agent.clone();
+        *source_flock = audio::source::movement::Flock { agent, ..flock.clone() };
+    }
+
+    // Update the local copy.
+    let soundscape = match sources
+        .get_mut(&id)
+        .and_then(|source| source.audio.role.as_mut())
+        .and_then(|role| role.soundscape_mut())
+    {
+        Some(soundscape) => soundscape,
+        None => return,
+    };
+    if let audio::source::Movement::Generative(ref mut gen) = soundscape.movement {
+        if let audio::source::movement::Generative::Flock(ref mut source_flock) = *gen {
+            set_params(source_flock, &flock);
+        }
+    }
+
+    // Update the soundscape thread copy.
+    channels
+        .soundscape
+        .send(move |soundscape| {
+            // Update all active sounds.
+            soundscape.update_active_sounds_with_source(id, |_, sound| {
+                if let soundscape::Movement::Generative(ref mut gen) = sound.movement {
+                    if let soundscape::movement::Generative::Flock(ref mut sound_flock) = *gen {
+                        sound_flock.radius = flock.radius;
+                        sound_flock.separation = flock.separation;
+                        sound_flock.alignment = flock.alignment;
+                        sound_flock.cohesion = flock.cohesion;
+                        sound_flock.groups = flock.groups;
+                    }
+                }
+            });
+
+            // Update the source.
+            soundscape.update_source(&id, |source| {
+                if let audio::source::Movement::Generative(ref mut gen) = source.movement {
+                    if let audio::source::movement::Generative::Flock(ref mut source_flock) = *gen {
+                        set_params(source_flock, &flock);
+                    }
+                }
+            });
+        })
+        .expect("failed to send flock movement update to soundscape thread");
+}
+
+/// Update the local and soundscape thread copies of the given source's random walk movement.
+///
+/// The step size and drift speed of active sounds are clamped to the new ranges.
+pub fn update_random_walk(
+    sources: &mut project::SourcesMap,
+    channels: &Channels,
+    id: audio::source::Id,
+    walk: audio::source::movement::RandomWalk,
+) {
+    // Update the local copy.
+    let soundscape = match sources
+        .get_mut(&id)
+        .and_then(|source| source.audio.role.as_mut())
+        .and_then(|role| role.soundscape_mut())
+    {
+        Some(soundscape) => soundscape,
+        None => return,
+    };
+    let generative = audio::source::movement::Generative::RandomWalk(walk.clone());
+    soundscape.movement = audio::source::Movement::Generative(generative);
+
+    // Update the soundscape thread copy.
+    channels
+        .soundscape
+        .send(move |soundscape| {
+            // Update all active sounds.
+            soundscape.update_active_sounds_with_source(id, |_, sound| {
+                let gen = match sound.movement {
+                    soundscape::Movement::Generative(ref mut gen) => gen,
+                    _ => return,
+                };
+                let sound_walk = match *gen {
+                    soundscape::movement::Generative::RandomWalk(ref mut walk) => walk,
+                    _ => return,
+                };
+                sound_walk.step = walk.step.clamp(sound_walk.step);
+                let drift_speed = sound_walk.drift.length();
+                if drift_speed > 0.0 {
+                    sound_walk.drift *= walk.drift.clamp(drift_speed) / drift_speed;
+                }
+            });
+
+            // Update the source.
+            soundscape.update_source(&id, |source| {
+                let generative = audio::source::movement::Generative::RandomWalk(walk);
+                source.movement = audio::source::Movement::Generative(generative);
+            });
+        })
+        .expect("failed to send random walk movement update to soundscape thread");
+}
+
+/// Update the local and soundscape thread copies of the given source's orbit movement.
+///
+/// Active sounds take on the new centre, while their radius, angular speed and eccentricity are
+/// clamped to the new ranges.
+pub fn update_orbit(
+    sources: &mut project::SourcesMap,
+    channels: &Channels,
+    id: audio::source::Id,
+    orbit: audio::source::movement::Orbit,
+) {
+    // Update the local copy.
+    let soundscape = match sources
+        .get_mut(&id)
+        .and_then(|source| source.audio.role.as_mut())
+        .and_then(|role| role.soundscape_mut())
+    {
+        Some(soundscape) => soundscape,
+        None => return,
+    };
+    let generative = audio::source::movement::Generative::Orbit(orbit.clone());
+    soundscape.movement = audio::source::Movement::Generative(generative);
+
+    // Update the soundscape thread copy.
+    channels
+        .soundscape
+        .send(move |soundscape| {
+            // Update all active sounds.
+            soundscape.update_active_sounds_with_source(id, |_, sound| {
+                let gen = match sound.movement {
+                    soundscape::Movement::Generative(ref mut gen) => gen,
+                    _ => return,
+                };
+                let sound_orbit = match *gen {
+                    soundscape::movement::Generative::Orbit(ref mut orbit) => orbit,
+                    _ => return,
+                };
+                sound_orbit.centre = orbit.centre;
+                sound_orbit.radius = orbit.radius.clamp(sound_orbit.radius);
+                let direction = sound_orbit.angular_speed.signum();
+                let angular_speed = orbit.angular_speed.clamp(sound_orbit.angular_speed.abs());
+                sound_orbit.angular_speed = direction * angular_speed;
+                sound_orbit.eccentricity = orbit.eccentricity.clamp(sound_orbit.eccentricity);
+            });
+
+            // Update the source.
+            soundscape.update_source(&id, |source| {
+                let generative = audio::source::movement::Generative::Orbit(orbit);
+                source.movement = audio::source::Movement::Generative(generative);
+            });
+        })
+        .expect("failed to send orbit movement update to soundscape thread");
+}
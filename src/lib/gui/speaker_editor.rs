@@ -5,6 +5,7 @@ use nannou::ui;
 use nannou::ui::prelude::*;
 use project::{self, Project};
 use soundscape;
+use time_calc::Ms;
 
 /// Runtime state related to the speaker editor GUI panel.
 #[derive(Default)]
@@ -58,7 +59,7 @@ pub fn set(
     const INSTALLATION_LIST_H: Scalar = ITEM_HEIGHT * 3.0;
     const INSTALLATIONS_CANVAS_H: Scalar =
         PAD + ITEM_HEIGHT * 2.0 + PAD + INSTALLATION_LIST_H + PAD;
-    const SELECTED_CANVAS_H: Scalar = ITEM_HEIGHT * 2.0 + PAD * 4.0 + INSTALLATIONS_CANVAS_H;
+    const SELECTED_CANVAS_H: Scalar = ITEM_HEIGHT * 6.0 + PAD * 8.0 + INSTALLATIONS_CANVAS_H;
     let speaker_editor_canvas_h = LIST_HEIGHT + ITEM_HEIGHT + SELECTED_CANVAS_H;
 
     let (area, event) = collapsible_area(is_open, "Speaker Editor", ids.side_menu)
@@ -250,8 +251,12 @@ pub fn set(
             let channel = project::next_available_speaker_channel(speakers);
             let audio = audio::Speaker {
                 point: camera.position,
+                elevation: 0.0,
                 channel: channel,
                 installations: Default::default(),
+                gain_db: 0.0,
+                delay: Default::default(),
+                role: Default::default(),
             };
 
             // Update the audio output copy.
@@ -390,6 +395,170 @@ pub fn set(
         }
     }
 
+    // The elevation of the speaker above the floor.
+    const MAX_ELEVATION_M: f64 = 10.0;
+    let elevation = speakers[&id].audio.elevation;
+    let label = format!("Elevation: {:.2} metres", elevation);
+    for new_elevation in widget::Slider::new(elevation, 0.0, MAX_ELEVATION_M)
+        .down_from(ids.speaker_editor_selected_channel, PAD)
+        .align_middle_x_of(ids.side_menu)
+        .kid_area_w_of(ids.speaker_editor_selected_canvas)
+        .h(ITEM_HEIGHT)
+        .parent(ids.speaker_editor_selected_canvas)
+        .label(&label)
+        .label_font_size(SMALL_FONT_SIZE)
+        .color(color::LIGHT_CHARCOAL)
+        .set(ids.speaker_editor_selected_elevation, ui)
+    {
+        // Update the local copy.
+        speakers.get_mut(&id).unwrap().audio.elevation = new_elevation;
+
+        // Update the audio output copy.
+        gui.channels
+            .audio_output
+            .send(move |audio| {
+                audio.set_speaker_elevation(id, new_elevation);
+            })
+            .expect("failed to send speaker elevation to audio output thread");
+
+        // Update the soundscape copy.
+        gui.channels
+            .soundscape
+            .send(move |soundscape| {
+                soundscape.update_speaker(&id, |speaker| speaker.elevation = new_elevation);
+            })
+            .expect("failed to send speaker update to soundscape thread");
+    }
+
+    // The level trim applied to the speaker's output.
+    const MIN_GAIN_DB: f64 = -24.0;
+    const MAX_GAIN_DB: f64 = 12.0;
+    let gain_db = speakers[&id].audio.gain_db;
+    let label = format!("Trim: {:.1} dB", gain_db);
+    for new_gain_db in widget::Slider::new(gain_db, MIN_GAIN_DB, MAX_GAIN_DB)
+        .down_from(ids.speaker_editor_selected_elevation, PAD)
+        .align_middle_x_of(ids.side_menu)
+        .kid_area_w_of(ids.speaker_editor_selected_canvas)
+        .h(ITEM_HEIGHT)
+        .parent(ids.speaker_editor_selected_canvas)
+        .label(&label)
+        .label_font_size(SMALL_FONT_SIZE)
+        .color(color::LIGHT_CHARCOAL)
+        .set(ids.speaker_editor_selected_gain, ui)
+    {
+        // Update the local copy.
+        speakers.get_mut(&id).unwrap().audio.gain_db = new_gain_db;
+
+        // Update the audio output copy.
+        gui.channels
+            .audio_output
+            .send(move |audio| {
+                audio.set_speaker_gain_db(id, new_gain_db);
+            })
+            .expect("failed to send speaker trim to audio output thread");
+    }
+
+    // Toggle between automatic and manual delay compensation.
+    let delay = speakers[&id].audio.delay;
+    let is_auto = delay == audio::speaker::Delay::Auto;
+    let delay_w = ui.kid_area_of(ids.speaker_editor_selected_canvas).unwrap().w() / 2.0 - PAD / 2.0;
+    let label = if is_auto { "Delay: AUTO" } else { "Delay: MANUAL" };
+    for new_is_auto in widget::Toggle::new(is_auto)
+        .down_from(ids.speaker_editor_selected_gain, PAD)
+        .align_left_of(ids.speaker_editor_selected_gain)
+        .w(delay_w)
+        .h(ITEM_HEIGHT)
+        .parent(ids.speaker_editor_selected_canvas)
+        .label(label)
+        .label_font_size(SMALL_FONT_SIZE)
+        .color(color::LIGHT_CHARCOAL)
+        .set(ids.speaker_editor_selected_delay_auto, ui)
+    {
+        let new_delay = match new_is_auto {
+            true => audio::speaker::Delay::Auto,
+            false => audio::speaker::Delay::default(),
+        };
+
+        // Update the local copy.
+        speakers.get_mut(&id).unwrap().audio.delay = new_delay;
+
+        // Update the audio output copy.
+        gui.channels
+            .audio_output
+            .send(move |audio| {
+                audio.set_speaker_delay(id, new_delay);
+            })
+            .expect("failed to send speaker delay to audio output thread");
+    }
+
+    // The manual delay in milliseconds, only shown when not automatically calculated.
+    if let audio::speaker::Delay::Manual(ms) = delay {
+        let label = format!("{:.2} ms", ms.ms());
+        for new_ms in widget::Slider::new(ms.ms(), 0.0, audio::MAX_SPEAKER_DELAY.ms())
+            .right_from(ids.speaker_editor_selected_delay_auto, PAD)
+            .align_top_of(ids.speaker_editor_selected_delay_auto)
+            .w(delay_w)
+            .h(ITEM_HEIGHT)
+            .parent(ids.speaker_editor_selected_canvas)
+            .label(&label)
+            .label_font_size(SMALL_FONT_SIZE)
+            .color(color::LIGHT_CHARCOAL)
+            .set(ids.speaker_editor_selected_delay, ui)
+        {
+            // Update the local copy.
+            let new_delay = audio::speaker::Delay::Manual(Ms(new_ms));
+            speakers.get_mut(&id).unwrap().audio.delay = new_delay;
+
+            // Update the audio output copy.
+            gui.channels
+                .audio_output
+                .send(move |audio| {
+                    audio.set_speaker_delay(id, new_delay);
+                })
+                .expect("failed to send speaker delay to audio output thread");
+        }
+    }
+
+    // Toggle between a full-range speaker and a subwoofer.
+    let is_sub = speakers[&id].audio.role == audio::speaker::Role::Subwoofer;
+    let label = if is_sub { "Role: SUBWOOFER" } else { "Role: FULL-RANGE" };
+    for new_is_sub in widget::Toggle::new(is_sub)
+        .down_from(ids.speaker_editor_selected_delay_auto, PAD)
+        .align_middle_x_of(ids.side_menu)
+        .kid_area_w_of(ids.speaker_editor_selected_canvas)
+        .h(ITEM_HEIGHT)
+        .parent(ids.speaker_editor_selected_canvas)
+        .label(label)
+        .label_font_size(SMALL_FONT_SIZE)
+        .color(color::LIGHT_CHARCOAL)
+        .set(ids.speaker_editor_selected_role, ui)
+    {
+        let new_role = match new_is_sub {
+            true => audio::speaker::Role::Subwoofer,
+            false => audio::speaker::Role::FullRange,
+        };
+
+        // Update the local copy.
+        speakers.get_mut(&id).unwrap().audio.role = new_role;
+
+        // Update the audio output copy.
+        let speaker = speakers[&id].audio.clone();
+        gui.channels
+            .audio_output
+            .send(move |audio| {
+                audio.insert_speaker(id, speaker);
+            })
+            .expect("failed to send speaker to audio output thread");
+
+        // Update the soundscape copy.
+        gui.channels
+            .soundscape
+            .send(move |soundscape| {
+                soundscape.update_speaker(&id, |speaker| speaker.role = new_role);
+            })
+            .expect("failed to send speaker update to soundscape thread");
+    }
+
     // A canvas on which installation selection widgets are instantiated.
     widget::Canvas::new()
         .kid_area_w_of(ids.speaker_editor_selected_canvas)
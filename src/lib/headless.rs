@@ -0,0 +1,191 @@
+//! Run the audio server without a window or GUI.
+//!
+//! The audio, soundscape, detection and OSC threads are run just as they are with the GUI, however
+//! the project is loaded once at startup and is controlled entirely via OSC. This is suitable for
+//! running the server as a service, where a crashed or hung GUI should never take down the audio.
+
+use audio;
+use config::Config;
+use crossbeam::queue::SegQueue;
+use gui;
+use nannou;
+use nannou_osc;
+use osc;
+use project::Project;
+use soundscape;
+use std::sync::atomic::AtomicUsize;
+use std::sync::{mpsc, Arc};
+use std::time;
+use utils;
+
+/// The interval at which pending control messages, logs and monitoring data are handled.
+const UPDATE_INTERVAL_MS: u64 = 16;
+
+/// Options for running the headless server.
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    /// The slug of the project to run. Defaults to the project selected in the config.
+    pub project_slug: Option<String>,
+}
+
+impl Options {
+    /// Parse the options from the given command line arguments.
+    ///
+    /// Recognises `--project <slug>`. The `--headless` flag itself is ignored, as is the
+    /// `--virtual-audio` flag which is handled when creating the audio streams.
+    pub fn from_args<I>(args: I) -> Result<Self, String>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut options = Options::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match &arg[..] {
+                "--headless" | super::VIRTUAL_AUDIO_FLAG => (),
+                "--project" => {
+                    let slug = args
+                        .next()
+                        .ok_or_else(|| format!("missing value for `{}`", arg))?;
+                    options.project_slug = Some(slug);
+                }
+                _ => return Err(format!("unrecognised argument `{}`", arg)),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Run the server without a GUI until the OSC input thread closes.
+pub fn run(options: Options) -> Result<(), String> {
+    // Find the assets directory and load the configuration.
+    let assets = nannou::app::find_assets_path()
+        .map_err(|err| format!("could not find assets directory: {}", err))?;
+    let config: Config = utils::load_from_json_or_default(&super::config_path(&assets));
+
+    // Load the project.
+    let slug = options
+        .project_slug
+        .clone()
+        .unwrap_or_else(|| config.selected_project_slug.clone());
+    let mut project = Project::load_from_slug(&assets, &slug, &config.project_default)
+        .ok_or_else(|| format!("no project found with the slug \"{}\"", slug))?;
+
+    // Spawn the OSC input thread.
+    let osc_receiver = nannou_osc::receiver(config.osc_input_port).map_err(|err| {
+        format!("failed to create OSC receiver bound to port {}: {}", config.osc_input_port, err)
+    })?;
+    let (_osc_in_thread_handle, osc_in_log_rx, control_rx) = osc::input::spawn(osc_receiver);
+
+    // Spawn the OSC output thread.
+    let (_osc_out_thread_handle, osc_out_msg_tx, osc_out_log_rx) = osc::output::spawn();
+
+    // Without a GUI there is no need for the intermediary monitoring thread, so the audio output
+    // thread pushes directly onto the queue that is drained below.
+    let audio_monitor_queue: gui::monitor::Sender = Arc::new(SegQueue::new());
+
+    // Spawn the thread used for reading wavs.
+    let wav_reader = audio::source::wav::reader::spawn();
+
+    // A channel for sending and receiving on the soundscape thread.
+    let (soundscape_tx, soundscape_rx) = mpsc::channel();
+
+    // The playhead frame count shared between soundscape and audio output thread.
+    let frame_count = Arc::new(AtomicUsize::new(0));
+
+    // The RMS of each installation measured by the detection thread and sampled by the soundscape.
+    let installation_rms = audio::detection::InstallationRms::default();
+
+    // Create the audio input and output streams.
+    let audio_host = audio::host();
+    let (audio_input_stream, _) = super::build_audio_input_stream(&audio_host, &config);
+    let audio_output_model = audio::output::Model::new(
+        frame_count.clone(),
+        audio_monitor_queue.clone(),
+        osc_out_msg_tx.clone(),
+        soundscape_tx.clone(),
+        installation_rms.clone(),
+        wav_reader.clone(),
+    );
+    let (audio_output_stream, _) =
+        super::build_audio_output_stream(&audio_host, &config, audio_output_model);
+    let _virtual_audio_device = super::spawn_virtual_audio_device(
+        &assets,
+        &config,
+        &audio_input_stream,
+        &audio_output_stream,
+    );
+
+    // Spawn the soundscape thread.
+    let sound_id_gen = audio::sound::IdGenerator::new();
+    let soundscape = soundscape::spawn(
+        frame_count.clone(),
+        config.seed,
+        soundscape_tx,
+        soundscape_rx,
+        wav_reader.clone(),
+        audio_input_stream.clone(),
+        audio_output_stream.clone(),
+        sound_id_gen,
+        installation_rms,
+    );
+
+    // Sync the project with all threads.
+    let channels = gui::Channels::new(
+        frame_count,
+        osc_in_log_rx,
+        osc_out_log_rx,
+        osc_out_msg_tx,
+        control_rx,
+        soundscape.clone(),
+        wav_reader.clone(),
+        audio_input_stream.clone(),
+        audio_output_stream.clone(),
+        audio_monitor_queue,
+    );
+    project.reset_and_sync_all_threads(&channels);
+
+    if let Err(err) = audio_input_stream.play() {
+        eprintln!("Failed to start playing the audio input stream: {}", err);
+    }
+    if let Err(err) = audio_output_stream.play() {
+        eprintln!("Failed to start playing the audio output stream: {}", err);
+    }
+
+    println!(
+        "Running \"{}\" headless, listening for OSC on port {}",
+        project.name, config.osc_input_port,
+    );
+
+    // Handle control messages until the OSC input thread closes.
+    let mut recorder = gui::Recorder::default();
+    let interval = time::Duration::from_millis(UPDATE_INTERVAL_MS);
+    loop {
+        match channels.control_rx.recv_timeout(interval) {
+            Ok(control) => {
+                let project = Some(&mut project);
+                gui::handle_control(&control, project, &assets, &mut recorder, &channels);
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => (),
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+
+        // Discard the logs and monitoring data that would otherwise be displayed by the GUI.
+        for _ in channels.osc_in_log_rx.try_iter() {}
+        for _ in channels.osc_out_log_rx.try_iter() {}
+        while let Some(msg) = channels.audio_monitor_msg_rx.pop() {
+            if let gui::AudioMonitorMessage::RecordingFailed(failure) = msg {
+                gui::handle_recording_failure(&failure, &mut recorder, &channels);
+            }
+        }
+    }
+
+    // Stop the soundscape and wav reader threads.
+    if let Some(thread) = soundscape.exit() {
+        thread.join().ok();
+    }
+    if let Some(thread) = wav_reader.exit() {
+        thread.join().ok();
+    }
+
+    Ok(())
+}
@@ -4,8 +4,11 @@
 //! hard-coded and rather identified via dynamically generated unique IDs. Otherwise, most
 //! of the logic should remain the same.
 
+use audio;
+use nannou::glam::DVec2 as Point2;
 use serde::{Deserialize, Deserializer};
 use slug::slugify;
+use time_calc::Ms;
 use utils::Range;
 
 /// All known beyond perception installations (used by default).
@@ -74,6 +77,14 @@ pub struct Installation {
     /// Constraints related to the soundscape.
     #[serde(default)]
     pub soundscape: Soundscape,
+    /// The optional compressor applied to the installation's speakers.
+    #[serde(default)]
+    pub compressor: audio::dynamics::CompressorParams,
+    /// The vertices of the user-drawn polygon to which the installation's area is restricted.
+    ///
+    /// With fewer than three vertices, the area is described by the installation's speakers.
+    #[serde(default)]
+    pub zone: Vec<Point2>,
 }
 
 impl Default for Installation {
@@ -81,21 +92,40 @@ impl Default for Installation {
         let name = default::name().into();
         let computers = Default::default();
         let soundscape = Default::default();
-        Installation { name, computers, soundscape }
+        let compressor = Default::default();
+        let zone = Default::default();
+        Installation { name, computers, soundscape, compressor, zone }
     }
 }
 
 /// Constraints related to the soundscape.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 pub struct Soundscape {
     #[serde(default = "default::simultaneous_sounds")]
     pub simultaneous_sounds: Range<usize>,
+    /// How strongly the visitor occupancy reported via OSC determines where the target number of
+    /// sounds lies within the `simultaneous_sounds` range.
+    ///
+    /// `0.0` ignores occupancy while `1.0` ignores the soundscape's slow random walk.
+    #[serde(default = "default::occupancy_weight")]
+    pub occupancy_weight: f64,
+    /// The duration over which changes in reported occupancy are smoothed.
+    #[serde(default = "default::occupancy_smoothing")]
+    pub occupancy_smoothing: Ms,
+    /// The number of visitors reported via OSC at which the installation is considered full.
+    ///
+    /// The default of `1` allows for occupancy to be reported as a fraction of the capacity.
+    #[serde(default = "default::capacity")]
+    pub capacity: usize,
 }
 
 impl Default for Soundscape {
     fn default() -> Self {
         let simultaneous_sounds = default::SIMULTANEOUS_SOUNDS;
-        Soundscape { simultaneous_sounds }
+        let occupancy_weight = default::OCCUPANCY_WEIGHT;
+        let occupancy_smoothing = default::OCCUPANCY_SMOOTHING;
+        let capacity = default::CAPACITY;
+        Soundscape { simultaneous_sounds, occupancy_weight, occupancy_smoothing, capacity }
     }
 }
 
@@ -123,9 +153,13 @@ pub fn beyond_perception_default_num_computers(name: &str) -> Option<usize> {
 
 /// Default soundscape constraints.
 pub mod default {
+    use time_calc::Ms;
     use utils::Range;
 
     pub const SIMULTANEOUS_SOUNDS: Range<usize> = Range { min: 1, max: 8 };
+    pub const OCCUPANCY_WEIGHT: f64 = 1.0;
+    pub const OCCUPANCY_SMOOTHING: Ms = Ms(30_000.0);
+    pub const CAPACITY: usize = 1;
 
     pub fn name() -> &'static str {
         "<unnamed>"
@@ -138,6 +172,18 @@ pub mod default {
     pub fn simultaneous_sounds() -> Range<usize> {
         SIMULTANEOUS_SOUNDS
     }
+
+    pub fn occupancy_weight() -> f64 {
+        OCCUPANCY_WEIGHT
+    }
+
+    pub fn occupancy_smoothing() -> Ms {
+        OCCUPANCY_SMOOTHING
+    }
+
+    pub fn capacity() -> usize {
+        CAPACITY
+    }
 }
 
 /// State related to the computers available to an installation.
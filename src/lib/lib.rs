@@ -1,6 +1,7 @@
 // Extend the macro recursion limit to allow for many GUI widget IDs.
 #![recursion_limit = "256"]
 
+extern crate chrono;
 #[macro_use]
 extern crate crossbeam;
 extern crate dasp;
@@ -39,11 +40,14 @@ mod audio;
 mod camera;
 mod config;
 mod gui;
+pub mod headless;
 mod installation;
 mod master;
 mod metres;
+pub mod offline;
 mod project;
 mod osc;
+mod scene;
 mod soundscape;
 mod utils;
 
@@ -64,8 +68,13 @@ struct Model {
     config: Config,
     audio_monitor: gui::monitor::Monitor,
     wav_reader: audio::source::wav::reader::Handle,
+    // Drives the audio streams when running without a sound card.
+    _virtual_audio_device: Option<audio::virtual_device::Device>,
 }
 
+// The command line flag used to select the virtual audio device.
+const VIRTUAL_AUDIO_FLAG: &'static str = "--virtual-audio";
+
 // The path to the server's config file.
 fn config_path<P>(assets: P) -> PathBuf
 where
@@ -120,48 +129,34 @@ fn model(app: &App) -> Model {
     // synchronising continuous WAV soures.
     let frame_count = Arc::new(AtomicUsize::new(0));
 
+    // The RMS of each installation measured by the detection thread and sampled by the soundscape.
+    let installation_rms = audio::detection::InstallationRms::default();
+
     // Retrieve the audio host.
     let audio_host = audio::host();
 
     // Initialise the audio input model and create the input stream.
-    let input_device = audio::find_input_device(&audio_host, &config.target_input_device_name)
-        .expect("no input devices available on the system");
-    let max_supported_input_channels = input_device.max_supported_input_channels();
-    let audio_input_channels = std::cmp::min(max_supported_input_channels, audio::MAX_CHANNELS);
-    println!("Selected Input Device: {:?}", input_device.name());
-    let audio_input_model = audio::input::Model::new();
-    let audio_input_stream = audio_host
-        .new_input_stream(audio_input_model)
-        .capture(audio::input::capture)
-        .sample_rate(audio::SAMPLE_RATE as u32)
-        .frames_per_buffer(audio::FRAMES_PER_BUFFER)
-        .channels(audio_input_channels)
-        .device(input_device)
-        .build()
-        .expect("failed to build audio input stream");
+    let (audio_input_stream, audio_input_channels) = build_audio_input_stream(&audio_host, &config);
 
     // Initialise the audio output model and create the output stream.
-    let output_device = audio::find_output_device(&audio_host, &config.target_output_device_name)
-        .expect("no output devices available on the system");
-    println!("Selected Output Device: {:?}", output_device.name());
-    let max_supported_output_channels = output_device.max_supported_output_channels();
-    let audio_output_channels = std::cmp::min(max_supported_output_channels, audio::MAX_CHANNELS);
     let audio_output_model = audio::output::Model::new(
         frame_count.clone(),
         audio_monitor_tx,
         osc_out_msg_tx.clone(),
         soundscape_tx.clone(),
+        installation_rms.clone(),
         wav_reader.clone(),
     );
-    let audio_output_stream = audio_host
-        .new_output_stream(audio_output_model)
-        .render(audio::output::render)
-        .sample_rate(audio::SAMPLE_RATE as u32)
-        .frames_per_buffer(audio::FRAMES_PER_BUFFER)
-        .channels(audio_output_channels)
-        .device(output_device)
-        .build()
-        .expect("failed to build audio output stream");
+    let (audio_output_stream, audio_output_channels) =
+        build_audio_output_stream(&audio_host, &config, audio_output_model);
+
+    // Drive the streams via the virtual device if it was selected.
+    let _virtual_audio_device = spawn_virtual_audio_device(
+        &assets,
+        &config,
+        &audio_input_stream,
+        &audio_output_stream,
+    );
 
     // To be shared between the `Composer` and `GUI` threads as both are responsible for creating
     // sounds and sending them to the audio thread.
@@ -177,6 +172,7 @@ fn model(app: &App) -> Model {
         audio_input_stream.clone(),
         audio_output_stream.clone(),
         sound_id_gen.clone(),
+        installation_rms,
     );
 
     // Create a window.
@@ -228,7 +224,103 @@ fn model(app: &App) -> Model {
         gui,
         audio_monitor,
         wav_reader,
+        _virtual_audio_device,
+    }
+}
+
+// Whether or not the virtual audio device should be used in place of the system's devices.
+//
+// This is the case if either the target output device is named "virtual" or if the server was run
+// with the `--virtual-audio` flag.
+fn virtual_audio_enabled(config: &Config) -> bool {
+    config.target_output_device_name == audio::virtual_device::DEVICE_NAME
+        || std::env::args().any(|arg| arg == VIRTUAL_AUDIO_FLAG)
+}
+
+// Spawn the virtual audio device to drive the given streams if it was selected.
+fn spawn_virtual_audio_device(
+    assets: &Path,
+    config: &Config,
+    audio_input_stream: &audio::input::Stream,
+    audio_output_stream: &audio::output::Stream,
+) -> Option<audio::virtual_device::Device> {
+    if !virtual_audio_enabled(config) {
+        return None;
+    }
+    let device = audio::virtual_device::spawn(
+        assets,
+        &config.virtual_audio_device,
+        audio_input_stream.clone(),
+        audio_output_stream.clone(),
+    )
+    .expect("failed to spawn the virtual audio device");
+    Some(device)
+}
+
+// Create the audio input stream for the device specified within the config.
+//
+// Returns the stream along with its number of channels.
+fn build_audio_input_stream(
+    audio_host: &nannou_audio::Host,
+    config: &Config,
+) -> (audio::input::Stream, usize) {
+    if virtual_audio_enabled(config) {
+        let (audio_input_channels, _) =
+            audio::virtual_device::channels(&config.virtual_audio_device);
+        println!("Selected Input Device: \"{}\"", audio::virtual_device::DEVICE_NAME);
+        let audio_input_stream = audio::input::Stream::new_virtual(audio::input::Model::new());
+        return (audio_input_stream, audio_input_channels);
+    }
+    let input_device = audio::find_input_device(audio_host, &config.target_input_device_name)
+        .expect("no input devices available on the system");
+    let max_supported_input_channels = input_device.max_supported_input_channels();
+    let audio_input_channels = std::cmp::min(max_supported_input_channels, audio::MAX_CHANNELS);
+    println!("Selected Input Device: {:?}", input_device.name());
+    let audio_input_model = audio::input::Model::new();
+    let audio_input_stream = audio_host
+        .new_input_stream(audio_input_model)
+        .capture(audio::input::capture)
+        .sample_rate(audio::SAMPLE_RATE as u32)
+        .frames_per_buffer(audio::FRAMES_PER_BUFFER)
+        .channels(audio_input_channels)
+        .device(input_device)
+        .build()
+        .map(audio::input::Stream::from)
+        .expect("failed to build audio input stream");
+    (audio_input_stream, audio_input_channels)
+}
+
+// Create the audio output stream for the device specified within the config.
+//
+// Returns the stream along with its number of channels.
+fn build_audio_output_stream(
+    audio_host: &nannou_audio::Host,
+    config: &Config,
+    audio_output_model: audio::output::Model,
+) -> (audio::output::Stream, usize) {
+    if virtual_audio_enabled(config) {
+        let (_, audio_output_channels) =
+            audio::virtual_device::channels(&config.virtual_audio_device);
+        println!("Selected Output Device: \"{}\"", audio::virtual_device::DEVICE_NAME);
+        let audio_output_stream = audio::output::Stream::new_virtual(audio_output_model);
+        return (audio_output_stream, audio_output_channels);
     }
+    let output_device = audio::find_output_device(audio_host, &config.target_output_device_name)
+        .expect("no output devices available on the system");
+    println!("Selected Output Device: {:?}", output_device.name());
+    let max_supported_output_channels = output_device.max_supported_output_channels();
+    let audio_output_channels = std::cmp::min(max_supported_output_channels, audio::MAX_CHANNELS);
+    let audio_output_stream = audio_host
+        .new_output_stream(audio_output_model)
+        .render(audio::output::render)
+        .sample_rate(audio::SAMPLE_RATE as u32)
+        .frames_per_buffer(audio::FRAMES_PER_BUFFER)
+        .channels(audio_output_channels)
+        .device(output_device)
+        .build()
+        .map(audio::output::Stream::from)
+        .expect("failed to build audio output stream");
+    (audio_output_stream, audio_output_channels)
 }
 
 // Update the application in accordance with the given event.
@@ -2,6 +2,8 @@ use audio;
 use time_calc::Ms;
 use metres::Metres;
 
+type Point2 = nannou::glam::DVec2;
+
 /// Master state of the project.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Master {
@@ -19,6 +21,27 @@ pub struct Master {
     /// It is stored as a square for faster calculations
     #[serde(default = "default_proximity_limit")]
     pub proximity_limit_2: Metres,
+    /// The law used to distribute sounds across speakers.
+    #[serde(default)]
+    pub panning_law: audio::PanningLaw,
+    /// The order of the ambisonic bus used by the `Ambisonics` panning law.
+    #[serde(default = "default_ambisonic_order")]
+    pub ambisonic_order: usize,
+    /// The reference listening position used to calculate automatic speaker delays.
+    #[serde(default)]
+    pub listening_point: Point2,
+    /// The elevation of the reference listening position in metres.
+    #[serde(default = "default_listening_elevation")]
+    pub listening_elevation: f64,
+    /// The frequency at which full-range speaker feeds are crossed over to the subwoofers.
+    #[serde(default = "default_crossover_hz")]
+    pub crossover_hz: f64,
+    /// The lookahead limiter applied to the master output.
+    #[serde(default)]
+    pub limiter: audio::dynamics::LimiterParams,
+    /// The reverb shared by all sources.
+    #[serde(default)]
+    pub reverb: audio::reverb::Params,
 }
 
 impl Default for Master {
@@ -27,8 +50,16 @@ impl Default for Master {
         let realtime_source_latency = default_realtime_source_latency();
         let dbap_rolloff_db = default_dbap_rolloff_db();
         let proximity_limit_2 = default_proximity_limit();
+        let panning_law = Default::default();
+        let ambisonic_order = default_ambisonic_order();
+        let listening_point = Point2::ZERO;
+        let listening_elevation = default_listening_elevation();
+        let crossover_hz = default_crossover_hz();
+        let limiter = Default::default();
+        let reverb = Default::default();
         Master { volume, realtime_source_latency, 
-            dbap_rolloff_db, proximity_limit_2 }
+            dbap_rolloff_db, proximity_limit_2, panning_law, ambisonic_order,
+            listening_point, listening_elevation, crossover_hz, limiter, reverb }
     }
 }
 
@@ -47,3 +78,15 @@ fn default_dbap_rolloff_db() -> f64 {
 fn default_proximity_limit() -> Metres {
     audio::DEFAULT_PROXIMITY_LIMIT_2
 }
+
+fn default_ambisonic_order() -> usize {
+    audio::DEFAULT_AMBISONIC_ORDER
+}
+
+fn default_listening_elevation() -> f64 {
+    audio::DEFAULT_LISTENING_ELEVATION
+}
+
+fn default_crossover_hz() -> f64 {
+    audio::DEFAULT_CROSSOVER_HZ
+}
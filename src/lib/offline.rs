@@ -0,0 +1,349 @@
+//! Render a project's soundscape to a multichannel WAV faster than realtime.
+//!
+//! No audio device is used. Instead, the soundscape is ticked on a simulated clock and the audio
+//! output is rendered one buffer at a time, interleaving the two in the same way that they would
+//! be when running in realtime. WAV sources are read synchronously so that they never run dry.
+//!
+//! Realtime sources have no input device to read from and are silent in the rendered output.
+//!
+//! The RMS of each installation is measured from each rendered buffer and handed to the soundscape
+//! before its next tick, rather than via the detection thread, so that agents following activity
+//! behave identically for every render.
+//!
+//! Renders that exceed the size limit of a WAV are continued within numbered files, e.g.
+//! `render.wav`, `render-2.wav`.
+
+use audio;
+use config::Config;
+use crossbeam::queue::SegQueue;
+use fxhash::FxHashMap;
+use gui;
+use installation;
+use nannou;
+use osc;
+use project::{self, Project};
+use soundscape;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicUsize;
+use std::sync::{mpsc, Arc};
+use std::{cmp, time};
+use utils::{self, Seed};
+
+/// The duration rendered when none is specified.
+const DEFAULT_DURATION_SECS: f64 = 60.0;
+
+/// Options for an offline render.
+#[derive(Clone, Debug)]
+pub struct Options {
+    /// The slug of the project to render. Defaults to the project selected in the config.
+    pub project_slug: Option<String>,
+    /// The duration of the rendered file.
+    pub duration: time::Duration,
+    /// Overrides the seed used for the soundscape's randomness.
+    pub seed: Option<u64>,
+    /// The number of output channels. Defaults to the highest speaker channel in the project.
+    pub channels: Option<usize>,
+    /// The path of the first rendered WAV. Defaults to a new file within the recordings directory.
+    pub output: Option<PathBuf>,
+    /// The moment within the week at which the render starts, used to evaluate the project's
+    /// schedule. Defaults to midnight at the beginning of Monday so that renders never depend on
+    /// the time at which they are run.
+    pub start: soundscape::schedule::Moment,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            project_slug: None,
+            duration: time::Duration::from_secs_f64(DEFAULT_DURATION_SECS),
+            seed: None,
+            channels: None,
+            output: None,
+            start: soundscape::schedule::Moment::new(0, 0.0),
+        }
+    }
+}
+
+impl Options {
+    /// Parse the options from the given command line arguments.
+    ///
+    /// Recognises `--project <slug>`, `--duration <secs>`, `--seed <u64>`, `--channels <n>`,
+    /// `--output <path>` and `--start <day> <hour>`, where day `0` is Monday. The `--offline` flag
+    /// itself is ignored.
+    ///
+    /// Without `--start`, the render begins at midnight on Monday rather than the current time so
+    /// that the project's schedule is evaluated identically for every render.
+    pub fn from_args<I>(args: I) -> Result<Self, String>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut options = Options::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("missing value for `{}`", arg))
+            };
+            match &arg[..] {
+                "--offline" => (),
+                "--project" => options.project_slug = Some(value()?),
+                "--duration" => {
+                    let secs: f64 = parse(&value()?)?;
+                    options.duration = time::Duration::from_secs_f64(secs.max(0.0));
+                }
+                "--seed" => options.seed = Some(parse(&value()?)?),
+                "--channels" => options.channels = Some(parse(&value()?)?),
+                "--output" => options.output = Some(PathBuf::from(value()?)),
+                "--start" => {
+                    let day = parse(&value()?)?;
+                    let hour = parse(&value()?)?;
+                    options.start = soundscape::schedule::Moment::new(day, hour);
+                }
+                _ => return Err(format!("unrecognised argument `{}`", arg)),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Render the project described by the given options, returning the path of the first rendered
+/// WAV.
+pub fn run(options: Options) -> Result<PathBuf, String> {
+    // Find the assets directory and load the configuration.
+    let assets = nannou::app::find_assets_path()
+        .map_err(|err| format!("could not find assets directory: {}", err))?;
+    let config: Config = utils::load_from_json_or_default(&super::config_path(&assets));
+
+    // Load the project.
+    let slug = options
+        .project_slug
+        .clone()
+        .unwrap_or_else(|| config.selected_project_slug.clone());
+    let project = Project::load_from_slug(&assets, &slug, &config.project_default)
+        .ok_or_else(|| format!("no project found with the slug \"{}\"", slug))?;
+
+    let seed = options.seed.map(seed_from_u64).unwrap_or(project.config.seed);
+    let channels = options.channels.unwrap_or_else(|| output_channels(&project));
+    let channels = cmp::max(1, cmp::min(channels, audio::MAX_CHANNELS));
+    let path = options.output.clone().unwrap_or_else(|| {
+        project::recording_directory_path(&assets, &project.name).with_extension("wav")
+    });
+
+    // Queues that would otherwise be drained by the GUI and OSC output threads.
+    let audio_monitor_queue: gui::monitor::Sender = Arc::new(SegQueue::new());
+    let osc_out_queue: osc::output::Tx = Arc::new(SegQueue::new());
+
+    // WAV sources must never run dry when rendering faster than realtime.
+    let wav_reader = audio::source::wav::reader::spawn_synchronous();
+
+    let (soundscape_tx, soundscape_rx) = mpsc::channel();
+    let frame_count = Arc::new(AtomicUsize::new(0));
+
+    // The RMS measured by the detection thread is ignored in favour of that measured below.
+    let detection_rms = audio::detection::InstallationRms::default();
+    let installation_rms = audio::detection::InstallationRms::default();
+
+    // The input and output streams are driven here rather than by a device.
+    let audio_input_stream = audio::input::Stream::new_virtual(audio::input::Model::new());
+    let audio_output_model = audio::output::Model::new(
+        frame_count.clone(),
+        audio_monitor_queue.clone(),
+        osc_out_queue.clone(),
+        soundscape_tx.clone(),
+        detection_rms,
+        wav_reader.clone(),
+    );
+    let audio_output_stream = audio::output::Stream::new_virtual(audio_output_model);
+
+    let sound_id_gen = audio::sound::IdGenerator::new();
+    let start = options.start;
+    let (soundscape, mut offline) = soundscape::offline(
+        frame_count.clone(),
+        seed,
+        start,
+        soundscape_tx,
+        soundscape_rx,
+        wav_reader.clone(),
+        audio_input_stream.clone(),
+        audio_output_stream.clone(),
+        sound_id_gen,
+        installation_rms.clone(),
+    );
+
+    // Sync the project with the soundscape and audio models.
+    let (_osc_in_log_tx, osc_in_log_rx) = mpsc::channel();
+    let (_osc_out_log_tx, osc_out_log_rx) = mpsc::channel();
+    let (_control_tx, control_rx) = mpsc::channel();
+    let gui_channels = gui::Channels::new(
+        frame_count,
+        osc_in_log_rx,
+        osc_out_log_rx,
+        osc_out_queue.clone(),
+        control_rx,
+        soundscape,
+        wav_reader.clone(),
+        audio_input_stream,
+        audio_output_stream.clone(),
+        audio_monitor_queue.clone(),
+    );
+    project.reset_and_sync_all_threads(&gui_channels);
+
+    // Apply the seed override, as syncing the project applies the project's seed.
+    gui_channels
+        .soundscape
+        .send(move |soundscape| soundscape.set_seed(seed))
+        .expect("failed to send seed to soundscape");
+
+    // Create the WAV file.
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)
+            .map_err(|err| format!("failed to create \"{}\": {}", directory.display(), err))?;
+    }
+    let mut writer = audio::recorder::Writer::create(path.clone(), channels)
+        .map_err(|err| format!("failed to create \"{}\": {}", path.display(), err))?;
+
+    println!(
+        "Rendering {:.1} secs of \"{}\" across {} channels to \"{}\"",
+        options.duration.as_secs_f64(),
+        project.name,
+        channels,
+        path.display(),
+    );
+
+    // Interleave soundscape ticks with rendering the output one buffer at a time.
+    let total_frames = (options.duration.as_secs_f64() * audio::SAMPLE_RATE) as usize;
+    let buffer_duration =
+        time::Duration::from_secs_f64(audio::FRAMES_PER_BUFFER as f64 / audio::SAMPLE_RATE);
+    let mut samples = vec![0.0; audio::FRAMES_PER_BUFFER * channels];
+    let mut speakers: Vec<_> = project
+        .speakers
+        .values()
+        .filter(|speaker| speaker.audio.channel < channels)
+        .map(|speaker| (speaker.audio.clone(), audio::EnvDetector::new()))
+        .collect();
+    let mut rms_sums = FxHashMap::default();
+    let mut frames_written = 0;
+    while frames_written < total_frames {
+        offline.advance(buffer_duration);
+
+        {
+            let mut model = audio_output_stream
+                .lock_virtual()
+                .expect("failed to lock the audio output model");
+            let mut buffer = audio::buffer::Buffer::new(&mut samples[..], channels);
+            model.wait_for_ambisonic_buses();
+            audio::output::render_buffer(&mut model, &mut buffer);
+        }
+
+        let frames = cmp::min(audio::FRAMES_PER_BUFFER, total_frames - frames_written);
+        for frame in samples[..frames * channels].chunks(channels) {
+            writer.write_frame(frame).map_err(|err| {
+                format!("failed to write \"{}\": {}", writer.current_path().display(), err)
+            })?;
+        }
+        frames_written += frames;
+
+        // Measure the RMS of each installation for the soundscape's next tick.
+        measure_installation_rms(&samples, channels, &mut speakers, &mut rms_sums);
+        for (&id, &(sum, count)) in &rms_sums {
+            installation_rms.set(id, sum / count as f32);
+        }
+
+        // Discard the monitoring data as there is no GUI or OSC output.
+        while audio_monitor_queue.pop().is_some() {}
+        while osc_out_queue.pop().is_some() {}
+    }
+
+    let last_path = writer.current_path();
+    writer
+        .finalize()
+        .map_err(|err| format!("failed to finalise \"{}\": {}", last_path.display(), err))?;
+
+    // Stop the wav reader thread.
+    if let Some(thread) = wav_reader.exit() {
+        thread.join().ok();
+    }
+
+    Ok(path)
+}
+
+// Feed the rendered samples into each speaker's detector and sum the resulting RMS of the
+// speakers assigned to each installation, along with the number of speakers summed.
+fn measure_installation_rms(
+    samples: &[f32],
+    channels: usize,
+    speakers: &mut [(audio::Speaker, audio::EnvDetector)],
+    rms_sums: &mut FxHashMap<installation::Id, (f32, usize)>,
+) {
+    rms_sums.clear();
+    for &mut (ref speaker, ref mut env_detector) in speakers.iter_mut() {
+        for frame in samples.chunks(channels) {
+            env_detector.next(frame[speaker.channel]);
+        }
+        let (rms, _peak) = env_detector.current();
+        for &id in &speaker.installations {
+            let sum = rms_sums.entry(id).or_insert((0.0, 0));
+            sum.0 += rms;
+            sum.1 += 1;
+        }
+    }
+}
+
+// The number of output channels required to reach every speaker within the project.
+fn output_channels(project: &Project) -> usize {
+    project
+        .speakers
+        .values()
+        .map(|speaker| speaker.audio.channel + 1)
+        .max()
+        .unwrap_or(1)
+}
+
+// Produce a soundscape seed from the given integer.
+fn seed_from_u64(n: u64) -> Seed {
+    let mut seed = Seed::default();
+    for (i, byte) in seed.iter_mut().enumerate() {
+        *byte = (n >> ((i % 8) * 8)) as u8;
+    }
+    seed
+}
+
+// Parse a single command line value.
+fn parse<T>(value: &str) -> Result<T, String>
+where
+    T: ::std::str::FromStr,
+    T::Err: ::std::fmt::Display,
+{
+    value
+        .parse()
+        .map_err(|err| format!("invalid value `{}`: {}", value, err))
+}
+
+#[test]
+fn options_from_args() {
+    let args = [
+        "--offline", "--project", "my-project", "--duration", "2.5", "--seed", "42",
+        "--channels", "4", "--output", "render.wav", "--start", "5", "21.5",
+    ];
+    let options = Options::from_args(args.iter().map(|s| s.to_string())).unwrap();
+    assert_eq!(options.project_slug, Some("my-project".to_string()));
+    assert_eq!(options.duration, time::Duration::from_secs_f64(2.5));
+    assert_eq!(options.seed, Some(42));
+    assert_eq!(options.channels, Some(4));
+    assert_eq!(options.output, Some(PathBuf::from("render.wav")));
+    assert_eq!(options.start, soundscape::schedule::Moment::new(5, 21.5));
+
+    // Defaults are used for any omitted options.
+    let options = Options::from_args(vec!["--offline".to_string()]).unwrap();
+    assert_eq!(options.duration, time::Duration::from_secs_f64(DEFAULT_DURATION_SECS));
+    assert!(options.project_slug.is_none() && options.seed.is_none());
+    assert_eq!(options.start, soundscape::schedule::Moment::new(0, 0.0));
+
+    // Unknown arguments, missing values and invalid values are errors.
+    let from_args = |args: &[&str]| Options::from_args(args.iter().map(|s| s.to_string()));
+    assert!(from_args(&["--unknown"]).is_err());
+    assert!(from_args(&["--seed"]).is_err());
+    assert!(from_args(&["--channels", "many"]).is_err());
+    assert!(from_args(&["--start", "5"]).is_err());
+}
@@ -1,4 +1,5 @@
-use nannou_osc::{self as osc, Type::Float};
+use nannou_osc::{self as osc, Type::{Float, Int}};
+use scene;
 use std;
 use std::net::SocketAddr;
 use std::sync::mpsc;
@@ -8,6 +9,12 @@ const SOURCE_VOLUME_ADDR: &'static str = "/source_volume";
 const MASTER_VOLUME_ADDR: &'static str = "/master_volume";
 const PLAY_SOUNDSCAPE: &'static str = "/play_soundscape";
 const PAUSE_SOUNDSCAPE: &'static str = "/pause_soundscape";
+const START_RECORDING: &'static str = "/start_recording";
+const STOP_RECORDING: &'static str = "/stop_recording";
+const SCENE_ADDR: &'static str = "/scene";
+const PRESENCE_ADDR: &'static str = "/presence";
+const INSTALLATION_ADDR: &'static str = "/installation";
+const OCCUPANCY_ADDR: &'static str = "/occupancy";
 
 /// A record of a received message.
 #[derive(Debug)]
@@ -25,6 +32,11 @@ pub enum Control {
     MasterVolume(MasterVolume),
     PauseSoundscape,
     PlaySoundscape,
+    StartRecording,
+    StopRecording,
+    RecallScene(RecallScene),
+    InstallationPresence(InstallationPresence),
+    InstallationOccupancy(InstallationOccupancy),
 }
 
 /// An OSC input message that was parsed as the master volume for the exhibition.
@@ -48,6 +60,49 @@ pub struct SourceVolume {
     pub volume: f32,
 }
 
+/// An OSC input message that was parsed as a request to recall a scene.
+///
+/// Expects the following OSC message:
+///
+/// - Address: "/bp/scene/<scene_name>"
+/// - Arguments: An optional `Float` specifying the crossfade duration in seconds, limited to
+///   `scene::MAX_CROSSFADE`. Messages with a non-finite duration are ignored.
+#[derive(Clone, Debug)]
+pub struct RecallScene {
+    /// The name of the scene to recall, either as named in the scene editor or slugified.
+    pub name: String,
+    /// Overrides the scene's crossfade duration.
+    pub crossfade_secs: Option<f32>,
+}
+
+/// An OSC input message that was parsed as the visitor presence at an installation.
+///
+/// Expects the following OSC message:
+///
+/// - Address: "/bp/presence/<installation_name>"
+/// - Arguments: `Float` where `0.0` is an empty installation and `1.0` is a busy one.
+#[derive(Clone, Debug)]
+pub struct InstallationPresence {
+    /// The name of the installation, either as named in the installation editor or slugified.
+    pub name: String,
+    /// How busy the installation is.
+    pub presence: f32,
+}
+
+/// An OSC input message that was parsed as the visitor occupancy of an installation.
+///
+/// Expects the following OSC message:
+///
+/// - Address: "/bp/installation/<installation_name>/occupancy"
+/// - Arguments: `Int` or `Float` visitor count, relative to the installation's `capacity`.
+#[derive(Clone, Debug)]
+pub struct InstallationOccupancy {
+    /// The name of the installation, either as named in the installation editor or slugified.
+    pub name: String,
+    /// The number of visitors currently within the installation.
+    pub count: f32,
+}
+
 impl From<MasterVolume> for Control {
     fn from(mv: MasterVolume) -> Self {
         Control::MasterVolume(mv)
@@ -60,6 +115,24 @@ impl From<SourceVolume> for Control {
     }
 }
 
+impl From<RecallScene> for Control {
+    fn from(rs: RecallScene) -> Self {
+        Control::RecallScene(rs)
+    }
+}
+
+impl From<InstallationPresence> for Control {
+    fn from(ip: InstallationPresence) -> Self {
+        Control::InstallationPresence(ip)
+    }
+}
+
+impl From<InstallationOccupancy> for Control {
+    fn from(io: InstallationOccupancy) -> Self {
+        Control::InstallationOccupancy(io)
+    }
+}
+
 // Finds the "/bp" string and returns the remainder if any.
 fn parse_bp(s: &str) -> Option<&str> {
     if s.starts_with(BEYOND_PERCEPTION_ADDR) {
@@ -83,6 +156,49 @@ fn parse_source_volume(s: &str) -> Option<&str> {
     }
 }
 
+// Finds the "/scene" string and returns the name of the scene.
+fn parse_scene(s: &str) -> Option<&str> {
+    if s.starts_with(SCENE_ADDR) {
+        let slice_start = SCENE_ADDR.len() + "/".len();
+        if slice_start < s.len() {
+            Some(&s[slice_start..])
+        } else {
+            None
+        }
+    } else {
+        None
+    }
+}
+
+// Finds the "/presence" string and returns the name of the installation.
+fn parse_presence(s: &str) -> Option<&str> {
+    if s.starts_with(PRESENCE_ADDR) {
+        let slice_start = PRESENCE_ADDR.len() + "/".len();
+        if slice_start < s.len() {
+            Some(&s[slice_start..])
+        } else {
+            None
+        }
+    } else {
+        None
+    }
+}
+
+// Finds the "/installation/<name>/occupancy" string and returns the name of the installation.
+fn parse_installation_occupancy(s: &str) -> Option<&str> {
+    if s.starts_with(INSTALLATION_ADDR) && s.ends_with(OCCUPANCY_ADDR) {
+        let slice_start = INSTALLATION_ADDR.len() + "/".len();
+        let slice_end = s.len() - OCCUPANCY_ADDR.len();
+        if slice_start < slice_end {
+            Some(&s[slice_start..slice_end])
+        } else {
+            None
+        }
+    } else {
+        None
+    }
+}
+
 // Finds the "/master_volume" string. Returns `true` if found.
 fn parse_master_volume(s: &str) -> bool {
     s == MASTER_VOLUME_ADDR
@@ -98,6 +214,16 @@ fn parse_pause_soundscape(s: &str) -> bool {
     s == PAUSE_SOUNDSCAPE
 }
 
+// Finds the "/start_recording" string. Returns `true` if found.
+fn parse_start_recording(s: &str) -> bool {
+    s == START_RECORDING
+}
+
+// Finds the "/stop_recording" string. Returns `true` if found.
+fn parse_stop_recording(s: &str) -> bool {
+    s == STOP_RECORDING
+}
+
 impl Control {
     fn from_osc_msg(msg: &osc::Message) -> Option<Self> {
         parse_bp(&msg.addr)
@@ -119,6 +245,44 @@ impl Control {
                     _ => (),
                 }
 
+                if let Some(name) = parse_scene(s) {
+                    let crossfade_secs = match msg.args.as_ref().and_then(|args| args.get(0)) {
+                        Some(&Float(secs)) if !secs.is_finite() => return None,
+                        Some(&Float(secs)) => {
+                            let max_secs = (scene::MAX_CROSSFADE.ms() / 1_000.0) as f32;
+                            Some(secs.max(0.0).min(max_secs))
+                        }
+                        _ => None,
+                    };
+                    let name = name.into();
+                    let recall_scene = RecallScene { name, crossfade_secs };
+                    return Some(recall_scene.into());
+                }
+
+                match (parse_presence(s), msg.args.as_ref().and_then(|args| args.get(0))) {
+                    (Some(name), Some(&Float(presence))) => {
+                        let name = name.into();
+                        let presence = presence.min(1.0).max(0.0);
+                        let installation_presence = InstallationPresence { name, presence };
+                        return Some(installation_presence.into())
+                    }
+                    _ => (),
+                }
+
+                if let Some(name) = parse_installation_occupancy(s) {
+                    let count = match msg.args.as_ref().and_then(|args| args.get(0)) {
+                        Some(&Float(count)) if count.is_finite() => Some(count),
+                        Some(&Int(count)) => Some(count as f32),
+                        _ => None,
+                    };
+                    if let Some(count) = count {
+                        let name = name.into();
+                        let count = count.max(0.0);
+                        let installation_occupancy = InstallationOccupancy { name, count };
+                        return Some(installation_occupancy.into());
+                    }
+                }
+
                 if parse_play_soundscape(s) {
                     return Some(Control::PlaySoundscape);
                 }
@@ -127,6 +291,14 @@ impl Control {
                     return Some(Control::PauseSoundscape);
                 }
 
+                if parse_start_recording(s) {
+                    return Some(Control::StartRecording);
+                }
+
+                if parse_stop_recording(s) {
+                    return Some(Control::StopRecording);
+                }
+
                 None
             })
     }
@@ -181,3 +353,33 @@ fn run(
         }
     }
 }
+
+#[test]
+fn scene_crossfade_is_finite() {
+    let msg = |secs: f32| osc::Message {
+        addr: "/bp/scene/night".into(),
+        args: Some(vec![Float(secs)]),
+    };
+    assert!(Control::from_osc_msg(&msg(std::f32::INFINITY)).is_none());
+    assert!(Control::from_osc_msg(&msg(std::f32::NAN)).is_none());
+    match Control::from_osc_msg(&msg(1e30)) {
+        Some(Control::RecallScene(recall)) => {
+            let max_secs = (scene::MAX_CROSSFADE.ms() / 1_000.0) as f32;
+            assert_eq!(recall.crossfade_secs, Some(max_secs));
+        }
+        _ => panic!("expected a scene recall"),
+    }
+}
+
+#[test]
+fn installation_occupancy_is_finite() {
+    let msg = |arg| osc::Message {
+        addr: "/bp/installation/foyer/occupancy".into(),
+        args: Some(vec![arg]),
+    };
+    assert!(Control::from_osc_msg(&msg(Float(std::f32::INFINITY))).is_none());
+    match Control::from_osc_msg(&msg(Int(-3))) {
+        Some(Control::InstallationOccupancy(occupancy)) => assert_eq!(occupancy.count, 0.0),
+        _ => panic!("expected an installation occupancy"),
+    }
+}
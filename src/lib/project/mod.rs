@@ -18,9 +18,10 @@ use gui;
 use installation::{self, Installation};
 use master::Master;
 use osc;
+use scene::Scenes;
 use slug::slugify;
 use soundscape;
-use std::{cmp, fs, io};
+use std::{cmp, fs, io, time};
 use std::ffi::OsStr;
 use std::mem;
 use std::ops::{Deref, DerefMut};
@@ -51,6 +52,9 @@ const CONFIG_EXTENSION: &'static str = "json";
 /// The name of the directory where the WAVs are stored.
 const AUDIO_DIRECTORY_STEM: &'static str = "audio";
 
+/// The assets sub-directory in which recordings of the master output are stored.
+const RECORDINGS_DIRECTORY_STEM: &'static str = "recordings";
+
 /// All state related to a single project including configuration.
 ///
 /// A single project describes a particular configuration of the audio server.
@@ -99,6 +103,12 @@ pub struct State {
     /// The state of the camera over the floorplan.
     #[serde(default)]
     pub camera: Camera,
+    /// The weekly schedule overriding the density, volume and groups of the soundscape.
+    #[serde(default)]
+    pub schedule: soundscape::schedule::Schedule,
+    /// Named snapshots of the soundscape-relevant parameters that may be recalled at any time.
+    #[serde(default)]
+    pub scenes: Scenes,
 }
 
 /// A map of all installations within the exhibition to their soundscape constraints.
@@ -162,6 +172,8 @@ impl State {
         let speakers = Default::default();
         let sources = Default::default();
         let camera = Default::default();
+        let schedule = Default::default();
+        let scenes = Default::default();
         State {
             name,
             master,
@@ -170,6 +182,8 @@ impl State {
             speakers,
             sources,
             camera,
+            schedule,
+            scenes,
         }
     }
 
@@ -264,6 +278,13 @@ impl Project {
         let dbap_rolloff_db = self.master.dbap_rolloff_db;
         let realtime_source_latency = self.master.realtime_source_latency;
         let proximity_limit_2 = self.master.proximity_limit_2;
+        let panning_law = self.master.panning_law;
+        let ambisonic_order = self.master.ambisonic_order;
+        let listening_point = self.master.listening_point.extend(self.master.listening_elevation);
+        let crossover_hz = self.master.crossover_hz;
+        let limiter = self.master.limiter;
+        let reverb = self.master.reverb;
+        let seed = self.config.seed;
         channels
             .audio_output
             .send(move |audio| {
@@ -271,23 +292,32 @@ impl Project {
                 audio.dbap_rolloff_db = dbap_rolloff_db;
                 // Square for efficiency
                 audio.proximity_limit_2 = proximity_limit_2;
+                audio.panning_law = panning_law;
+                audio.set_ambisonic_order(ambisonic_order);
+                audio.set_listening_point(listening_point);
+                audio.set_crossover_hz(crossover_hz);
+                audio.set_limiter(limiter);
+                audio.set_reverb(reverb);
             })
             .expect("failed to send loaded master volume and dbap rolloff");
         channels
             .soundscape
             .send(move |soundscape| {
                 soundscape.realtime_source_latency = realtime_source_latency;
+                soundscape.set_seed(seed);
             })
-            .expect("failed to send loaded realtime source latency");
+            .expect("failed to send loaded realtime source latency and seed");
 
         // Installations to soundscape, osc output and audio output.
         for (&id, installation) in self.installations.iter() {
             // Soundscape.
             let clone = installation.soundscape.clone();
+            let zone = installation.zone.clone();
             channels
                 .soundscape
                 .send(move |soundscape| {
                     soundscape.insert_installation(id, clone);
+                    soundscape.update_installation_zone(id, zone);
                 })
                 .expect("failed to send loaded installation soundscape state");
 
@@ -306,10 +336,12 @@ impl Project {
 
             // Audio output thread.
             let computers = installation.computers.len();
+            let compressor = installation.compressor;
             channels
                 .audio_output
                 .send(move |audio| {
                     audio.insert_installation(id, computers);
+                    audio.set_installation_compressor(id, compressor);
                 })
                 .expect("failed to send loaded installation to audio output thread");
         }
@@ -364,7 +396,12 @@ impl Project {
             }
         }
 
-
+        // The schedule to the soundscape thread.
+        let schedule = self.schedule.clone();
+        channels
+            .soundscape
+            .send(move |soundscape| soundscape.set_schedule(schedule))
+            .expect("failed to send schedule to soundscape thread");
     }
 
     /// Create a new project with a unique, default name.
@@ -583,6 +620,7 @@ where
                     };
                     new_wav.should_loop = wav.should_loop;
                     new_wav.playback = wav.playback;
+                    new_wav.channel_layout = wav.channel_layout;
                     mem::swap(wav, &mut new_wav);
                     continue;
                 }
@@ -671,14 +709,20 @@ where
             let spread = audio::source::default::SPREAD;
             let channel_radians = audio::source::default::CHANNEL_RADIANS;
             let volume = audio::source::default::VOLUME;
+            let reverb_send = 0.0;
             let muted = bool::default();
+            let distance = None;
+            let doppler = None;
             let audio = audio::Source {
                 kind,
                 role,
                 spread,
                 channel_radians,
                 volume,
+                reverb_send,
                 muted,
+                distance,
+                doppler,
             };
             let source = Source { name, audio };
             sources.map.insert(next_id, source);
@@ -807,8 +851,10 @@ pub fn default_beyond_perception_installations() -> Installations {
                 })
                 .collect();
             let soundscape = Default::default();
+            let compressor = Default::default();
+            let zone = Default::default();
             let name = name.into();
-            let installation = Installation { name, computers, soundscape };
+            let installation = Installation { name, computers, soundscape, compressor, zone };
             (id, installation)
         })
         .collect()
@@ -837,6 +883,25 @@ where
     projects_directory.join(directory_stem)
 }
 
+/// The directory path for a new recording of the master output for the project with the given
+/// name.
+///
+/// The directory is named after the project and the number of seconds since the unix epoch.
+pub fn recording_directory_path<P>(assets: P, name: &str) -> PathBuf
+where
+    P: AsRef<Path>,
+{
+    let secs = time::SystemTime::now()
+        .duration_since(time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let directory_stem = format!("{}-{}", slugify(name), secs);
+    assets
+        .as_ref()
+        .join(RECORDINGS_DIRECTORY_STEM)
+        .join(directory_stem)
+}
+
 /// The file path for the "config.json" file holding all human-friendly config for this project.
 pub fn project_config_path<P>(project_directory: P) -> PathBuf
 where
@@ -0,0 +1,228 @@
+//! Named snapshots of all soundscape-relevant parameters of a project.
+//!
+//! A scene captures the installation and group constraints, source volumes and mutes and the master
+//! volume. Recalling a scene applies it to the project immediately, while the soundscape and audio
+//! output threads crossfade from their current values to those of the scene.
+
+use audio;
+use fxhash::FxHashMap;
+use installation;
+use project;
+use slug::slugify;
+use soundscape;
+use std::time;
+use time_calc::Ms;
+use utils::Range;
+
+/// The crossfade used when recalling a scene unless otherwise specified.
+pub const DEFAULT_CROSSFADE: Ms = Ms(5_000.0);
+
+/// The longest crossfade that may be used when recalling a scene.
+pub const MAX_CROSSFADE: Ms = Ms(60.0 * 60.0 * 1_000.0);
+
+/// All scenes within a project.
+pub type Scenes = Vec<Scene>;
+
+/// A named snapshot of the soundscape-relevant parameters of a project.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Scene {
+    pub name: String,
+    /// The duration of the crossfade used when recalling the scene, unless otherwise specified.
+    #[serde(default = "default_crossfade")]
+    pub crossfade: Ms,
+    #[serde(default = "default_master_volume")]
+    pub master_volume: f32,
+    #[serde(default)]
+    pub installations: FxHashMap<installation::Id, installation::Soundscape>,
+    #[serde(default)]
+    pub groups: FxHashMap<soundscape::group::Id, soundscape::Group>,
+    #[serde(default)]
+    pub sources: FxHashMap<audio::source::Id, SourceLevel>,
+}
+
+/// The volume and mute state of a source within a scene.
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct SourceLevel {
+    pub volume: f32,
+    pub muted: bool,
+}
+
+/// Tracks the progress of a crossfade.
+#[derive(Copy, Clone, Debug)]
+pub struct Crossfade {
+    duration: time::Duration,
+    elapsed: time::Duration,
+}
+
+impl Scene {
+    /// Capture the current state of the given project.
+    pub fn capture(name: String, crossfade: Ms, state: &project::State) -> Self {
+        let installations = state
+            .installations
+            .iter()
+            .map(|(&id, installation)| (id, installation.soundscape.clone()))
+            .collect();
+        let groups = state
+            .soundscape_groups
+            .iter()
+            .map(|(&id, group)| (id, group.soundscape.clone()))
+            .collect();
+        let sources = state
+            .sources
+            .iter()
+            .map(|(&id, source)| {
+                let level = SourceLevel {
+                    volume: source.volume,
+                    muted: source.muted,
+                };
+                (id, level)
+            })
+            .collect();
+        Scene {
+            name,
+            crossfade,
+            master_volume: state.master.volume,
+            installations,
+            groups,
+            sources,
+        }
+    }
+
+    /// Apply the scene to the given project.
+    ///
+    /// Installations, groups and sources that have been removed since the scene was captured are
+    /// ignored, as are those that have been added.
+    pub fn apply(&self, state: &mut project::State) {
+        state.master.volume = self.master_volume;
+        for (id, soundscape) in self.installations.iter() {
+            if let Some(installation) = state.installations.get_mut(id) {
+                installation.soundscape = soundscape.clone();
+            }
+        }
+        for (id, soundscape) in self.groups.iter() {
+            if let Some(group) = state.soundscape_groups.get_mut(id) {
+                group.soundscape = soundscape.clone();
+            }
+        }
+        for (id, level) in self.sources.iter() {
+            if let Some(source) = state.sources.get_mut(id) {
+                source.volume = level.volume;
+                source.muted = level.muted;
+            }
+        }
+    }
+}
+
+impl SourceLevel {
+    /// Interpolate towards the given level.
+    ///
+    /// Muted sources are treated as silent so that muting and unmuting is also faded. The source
+    /// only remains muted throughout the fade if it is muted at both ends.
+    pub fn lerp(&self, other: &Self, t: f64) -> Self {
+        if t >= 1.0 {
+            return *other;
+        }
+        let audible = |level: &Self| if level.muted { 0.0 } else { level.volume };
+        let volume = lerp(audible(self) as f64, audible(other) as f64, t) as f32;
+        let muted = self.muted && other.muted;
+        SourceLevel { volume, muted }
+    }
+}
+
+impl Crossfade {
+    /// Begin a crossfade over the given duration.
+    pub fn new(duration: time::Duration) -> Self {
+        let elapsed = time::Duration::from_secs(0);
+        Crossfade { duration, elapsed }
+    }
+
+    /// Step the crossfade forward, returning its progress between `0.0` and `1.0`.
+    pub fn step(&mut self, delta: time::Duration) -> f64 {
+        self.elapsed = ::std::cmp::min(self.elapsed + delta, self.duration);
+        self.progress()
+    }
+
+    /// The progress of the crossfade between `0.0` and `1.0`.
+    pub fn progress(&self) -> f64 {
+        if self.duration == time::Duration::from_secs(0) {
+            return 1.0;
+        }
+        self.elapsed.as_secs_f64() / self.duration.as_secs_f64()
+    }
+
+    /// Whether or not the crossfade has reached its end.
+    pub fn is_complete(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+}
+
+/// Find the scene with the given name.
+///
+/// The name may also be given in its slugified form, e.g. `"night-mode"` for `"Night Mode"`.
+pub fn find<'a>(scenes: &'a [Scene], name: &str) -> Option<&'a Scene> {
+    scenes
+        .iter()
+        .find(|scene| scene.name == name)
+        .or_else(|| scenes.iter().find(|scene| slugify(&scene.name) == name))
+}
+
+/// Interpolate between the constraints of two installations.
+pub fn lerp_installation(
+    a: &installation::Soundscape,
+    b: &installation::Soundscape,
+    t: f64,
+) -> installation::Soundscape {
+    installation::Soundscape {
+        simultaneous_sounds: lerp_usize_range(&a.simultaneous_sounds, &b.simultaneous_sounds, t),
+        occupancy_weight: lerp(a.occupancy_weight, b.occupancy_weight, t),
+        occupancy_smoothing: Ms(lerp(a.occupancy_smoothing.ms(), b.occupancy_smoothing.ms(), t)),
+        capacity: lerp(a.capacity as f64, b.capacity as f64, t).round() as usize,
+    }
+}
+
+/// Interpolate between the constraints of two groups.
+pub fn lerp_group(a: &soundscape::Group, b: &soundscape::Group, t: f64) -> soundscape::Group {
+    let lerp_ms = |a: Ms, b: Ms| Ms(lerp(a.ms(), b.ms(), t));
+    let occurrence_rate = Range {
+        min: lerp_ms(a.occurrence_rate.min, b.occurrence_rate.min),
+        max: lerp_ms(a.occurrence_rate.max, b.occurrence_rate.max),
+    };
+    let simultaneous_sounds = lerp_usize_range(&a.simultaneous_sounds, &b.simultaneous_sounds, t);
+    // Rules cannot be interpolated, so those of the destination apply immediately.
+    let rules = b.rules.clone();
+    soundscape::Group {
+        occurrence_rate,
+        simultaneous_sounds,
+        rules,
+    }
+}
+
+fn lerp_usize_range(a: &Range<usize>, b: &Range<usize>, t: f64) -> Range<usize> {
+    let lerp_usize = |a: usize, b: usize| lerp(a as f64, b as f64, t).round() as usize;
+    Range {
+        min: lerp_usize(a.min, b.min),
+        max: lerp_usize(a.max, b.max),
+    }
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
+
+fn default_crossfade() -> Ms {
+    DEFAULT_CROSSFADE
+}
+
+fn default_master_volume() -> f32 {
+    audio::DEFAULT_MASTER_VOLUME
+}
+
+#[test]
+fn source_level_lerp() {
+    let muted = SourceLevel { volume: 0.8, muted: true };
+    let unmuted = SourceLevel { volume: 0.8, muted: false };
+    let half = muted.lerp(&unmuted, 0.5);
+    assert_eq!(half, SourceLevel { volume: 0.4, muted: false });
+    assert_eq!(muted.lerp(&unmuted, 1.0), unmuted);
+    assert_eq!(unmuted.lerp(&muted, 1.0), muted);
+}
@@ -2,6 +2,7 @@
 //!
 //! Soundscape groups allow for describing rules/constraints for multiple sounds at once.
 
+use fxhash::{FxHashMap, FxHashSet};
 use time_calc::Ms;
 use utils::Range;
 
@@ -18,6 +19,79 @@ pub struct Id(pub usize);
 pub struct Group {
     pub occurrence_rate: Range<Ms>,
     pub simultaneous_sounds: Range<usize>,
+    /// Relationships constraining when the group may play in relation to other groups.
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+}
+
+/// A relationship between a group and some other group.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub enum Rule {
+    /// Never play at the same time as a sound from the other group.
+    ///
+    /// Exclusion is mutual, so the other group also does not play while this group is active.
+    Exclude(Id),
+    /// Only begin playing while a sound from the other group is active.
+    Require(Id),
+    /// After a sound from the other group ends, trigger a sound from this group within the given
+    /// duration.
+    Follow { group: Id, within: Ms },
+}
+
+impl Group {
+    /// The rule relating this group to the given group, if any.
+    pub fn rule(&self, other: Id) -> Option<&Rule> {
+        self.rules.iter().find(|rule| rule.group() == other)
+    }
+
+    /// Replace the rule relating this group to the given group.
+    ///
+    /// Each group may have at most one rule relating it to each other group.
+    pub fn set_rule(&mut self, other: Id, rule: Option<Rule>) {
+        self.rules.retain(|r| r.group() != other);
+        self.rules.extend(rule);
+    }
+}
+
+impl Rule {
+    pub const VARIANT_COUNT: usize = 3;
+
+    /// The group to which the rule relates.
+    pub fn group(&self) -> Id {
+        match *self {
+            Rule::Exclude(id) | Rule::Require(id) => id,
+            Rule::Follow { group, .. } => group,
+        }
+    }
+
+    /// Produce the index of the Rule variant.
+    pub fn to_index(&self) -> usize {
+        match *self {
+            Rule::Exclude(_) => 0,
+            Rule::Require(_) => 1,
+            Rule::Follow { .. } => 2,
+        }
+    }
+
+    /// Produce a label for the variant at the given index.
+    pub fn label_from_index(i: usize) -> &'static str {
+        match i {
+            0 => "NEVER WITH",
+            1 => "ONLY WHILE",
+            2 => "FOLLOWS",
+            _ => "",
+        }
+    }
+
+    /// Produce a default Rule variant relating to the given group for the given variant index.
+    pub fn from_index(i: usize, group: Id) -> Option<Self> {
+        match i {
+            0 => Some(Rule::Exclude(group)),
+            1 => Some(Rule::Require(group)),
+            2 => Some(Rule::Follow { group, within: default::FOLLOW_WITHIN }),
+            _ => None,
+        }
+    }
 }
 
 pub mod default {
@@ -28,15 +102,70 @@ pub mod default {
         max: Ms(HR_MS as _),
     };
     pub const SIMULTANEOUS_SOUNDS: Range<usize> = Range { min: 1, max: 10 };
+    pub const FOLLOW_WITHIN: Ms = Ms(10_000.0);
 }
 
 impl Default for Group {
     fn default() -> Self {
         let occurrence_rate = default::OCCURRENCE_RATE;
         let simultaneous_sounds = default::SIMULTANEOUS_SOUNDS;
+        let rules = Vec::new();
         Group {
             occurrence_rate,
             simultaneous_sounds,
+            rules,
+        }
+    }
+}
+
+/// Whether or not the rules of the given groups permit a sound from the group with the given `Id`
+/// to begin playing while the `active` groups are playing.
+///
+/// A group is not permitted to play if:
+///
+/// - it excludes an active group or is excluded by one, or
+/// - it requires a group that is not active.
+pub fn rules_permit(id: Id, groups: &FxHashMap<Id, Group>, active: &FxHashSet<Id>) -> bool {
+    if let Some(group) = groups.get(&id) {
+        for rule in &group.rules {
+            match *rule {
+                Rule::Exclude(other) if active.contains(&other) => return false,
+                Rule::Require(other) if !active.contains(&other) => return false,
+                _ => (),
+            }
         }
     }
+    let excluded_by_active = active
+        .iter()
+        .filter_map(|other| groups.get(other))
+        .any(|other| other.rules.contains(&Rule::Exclude(id)));
+    !excluded_by_active
+}
+
+#[test]
+fn rules_permit_exclusion_and_requirement() {
+    let (a, b, c) = (Id(0), Id(1), Id(2));
+    let mut groups = FxHashMap::default();
+    let mut group_a = Group::default();
+    group_a.set_rule(b, Some(Rule::Exclude(b)));
+    group_a.set_rule(c, Some(Rule::Require(c)));
+    groups.insert(a, group_a);
+    groups.insert(b, Group::default());
+    groups.insert(c, Group::default());
+
+    let active = |ids: &[Id]| ids.iter().cloned().collect::<FxHashSet<_>>();
+
+    // `a` requires `c` and never plays alongside `b`.
+    assert!(!rules_permit(a, &groups, &active(&[])));
+    assert!(rules_permit(a, &groups, &active(&[c])));
+    assert!(!rules_permit(a, &groups, &active(&[b, c])));
+
+    // Exclusion is mutual.
+    assert!(rules_permit(b, &groups, &active(&[c])));
+    assert!(!rules_permit(b, &groups, &active(&[a])));
+
+    // Replacing a rule leaves only one rule per group.
+    groups.get_mut(&a).unwrap().set_rule(b, None);
+    assert!(rules_permit(a, &groups, &active(&[b, c])));
+    assert_eq!(groups[&a].rules, vec![Rule::Require(c)]);
 }
@@ -7,6 +7,7 @@ use nannou;
 use nannou::prelude::*;
 use nannou::rand::{Rng, SeedableRng};
 use rand_xorshift::XorShiftRng;
+use scene;
 use std::cmp;
 use std::ops;
 use std::sync::atomic::{AtomicBool, AtomicUsize};
@@ -22,6 +23,7 @@ use self::movement::BoundingRect;
 
 pub mod group;
 pub mod movement;
+pub mod schedule;
 
 const TICK_RATE_MS: u64 = 16;
 
@@ -31,10 +33,17 @@ type Sources = FxHashMap<audio::source::Id, Source>;
 type Speakers = FxHashMap<audio::speaker::Id, Speaker>;
 type GroupsLastUsed = FxHashMap<group::Id, time::Instant>;
 type SourcesLastUsed = FxHashMap<audio::source::Id, time::Instant>;
+type GroupsLastEnded = FxHashMap<group::Id, time::Instant>;
 type InstallationAreas = FxHashMap<installation::Id, movement::Area>;
 type InstallationSpeakers = FxHashMap<installation::Id, Vec<audio::speaker::Id>>;
 type ActiveSounds = FxHashMap<audio::sound::Id, ActiveSound>;
 type ActiveSoundPositions = FxHashMap<audio::sound::Id, ActiveSoundPosition>;
+type FlockBoids = Vec<(audio::sound::Id, audio::source::Id, movement::flock::Boid)>;
+type InstallationActivity = FxHashMap<installation::Id, Activity>;
+type InstallationZones = FxHashMap<installation::Id, Vec<nannou::glam::DVec2>>;
+type InstallationOccupancy = FxHashMap<installation::Id, Occupancy>;
+type ActiveGroups = FxHashSet<group::Id>;
+type TriggeredGroups = FxHashMap<group::Id, Ms>;
 type ActiveSoundsPerInstallation = FxHashMap<installation::Id, Vec<audio::sound::Id>>;
 type TargetSoundsPerInstallation = FxHashMap<installation::Id, usize>;
 type AvailableGroups = Vec<AvailableGroup>;
@@ -63,6 +72,8 @@ pub struct Tick {
     ///
     /// This does not increase when the stream is paused.
     playback_duration: time::Duration,
+    /// The moment within the week at which the tick occurred, used to evaluate the schedule.
+    moment: schedule::Moment,
 }
 
 /// The update function applied to a source.
@@ -87,8 +98,12 @@ pub struct Soundscape {
 pub struct Speaker {
     /// The position of the speaker in metres.
     pub point: Point2,
+    /// The elevation of the speaker in metres.
+    pub elevation: f64,
     /// All installations assigned to the speaker.
     pub installations: FxHashSet<installation::Id>,
+    /// Whether the speaker is full-range or a subwoofer.
+    pub role: audio::speaker::Role,
 }
 
 /// Properties of an audio source that are relevant to the soundscape thread.
@@ -98,7 +113,10 @@ pub struct Source {
     pub spread: Metres,
     pub channel_radians: f32,
     pub volume: f32,
+    pub reverb_send: f32,
     pub muted: bool,
+    pub distance: Option<audio::distance::Model>,
+    pub doppler: Option<audio::doppler::Model>,
     /// The time at which the source was last used to create a sound.
     pub last_sound_created: Option<time::Instant>,
 }
@@ -109,10 +127,46 @@ pub struct ActiveSound {
     pub initial_installation: installation::Id,
     /// State related to active sound's assigned movement.
     pub movement: Movement,
+    /// The elevation chosen for the sound from its source's elevation range, if any.
+    chosen_elevation: Option<f64>,
+    /// The current elevation of the sound in metres.
+    ///
+    /// Movement is 2D, so this is either the chosen elevation or that of the sound's installation.
+    elevation: f64,
     /// The handle associated with this sound.
     handle: audio::sound::Handle,
 }
 
+/// How busy an installation currently is.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Activity {
+    /// The average RMS across the speakers of the installation, measured on the output buffer.
+    pub rms: f32,
+    /// The visitor presence most recently reported for the installation via OSC, if any.
+    pub presence: Option<f32>,
+}
+
+/// The largest number of visitors that may be reported for a single installation.
+pub const MAX_OCCUPANCY: f32 = 1_000_000.0;
+
+/// The visitor occupancy of an installation, as reported by people counters via OSC.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Occupancy {
+    /// The most recently reported number of visitors.
+    pub reported: f32,
+    /// The reported number of visitors smoothed over the installation's `occupancy_smoothing`
+    /// duration.
+    pub smoothed: f32,
+}
+
+impl Occupancy {
+    /// The smoothed occupancy as a fraction of the given capacity, where `0.0` is empty and `1.0`
+    /// is full.
+    pub fn fraction(&self, capacity: usize) -> f64 {
+        (self.smoothed as f64 / capacity.max(1) as f64).min(1.0).max(0.0)
+    }
+}
+
 // The current positioning of an active sound.
 struct ActiveSoundPosition {
     // The source from which this active sound was produced.
@@ -129,6 +183,8 @@ pub struct Model {
     pub realtime_source_latency: Ms,
     /// The soundscape's deterministic source of randomness.
     seed: Seed,
+    /// The source of every random decision made on the soundscape thread, seeded via `seed`.
+    rng: XorShiftRng,
     /// For generating unique IDs for each new sound.
     sound_id_gen: audio::sound::IdGenerator,
     /// How long the soundscape has been actively playing (in an un-paused state).
@@ -147,8 +203,24 @@ pub struct Model {
     groups_last_used: GroupsLastUsed,
     /// The moment at which each `Source` was last used to spawn a sound.
     sources_last_used: SourcesLastUsed,
+    /// The moment at which a sound spawned via each `Group` last ended.
+    groups_last_ended: GroupsLastEnded,
+    /// Groups whose sounds have ended since the last tick, yet to be stamped in `groups_last_ended`.
+    ended_groups: FxHashSet<group::Id>,
     /// All sounds currently being played that were spawned by the soundscape thread.
     active_sounds: ActiveSounds,
+    /// The weekly schedule overriding the density, volume and groups of the soundscape.
+    schedule: schedule::Schedule,
+    /// The crossfade towards the most recently recalled scene, if one is in progress.
+    scene_fade: Option<SceneFade>,
+    /// How busy each installation currently is, used to attract agents to busy installations.
+    installation_activity: InstallationActivity,
+    /// The RMS of each installation measured on the output, sampled into the activity each tick.
+    installation_rms: audio::detection::InstallationRms,
+    /// The user-drawn polygon zone to which each installation's area is restricted, if any.
+    installation_zones: InstallationZones,
+    /// The visitor occupancy reported for each installation, used to scale its target sounds.
+    installation_occupancy: InstallationOccupancy,
 
     // The following are intermediary buffers re-used between calculations.
     //
@@ -166,10 +238,19 @@ pub struct Model {
     active_sounds_per_installation: ActiveSoundsPerInstallation,
     /// Tracks the position of all active sounds.
     active_sound_positions: ActiveSoundPositions,
+    /// Tracks the location and velocity of all flocking sounds at the beginning of each tick.
+    flock_boids: FlockBoids,
+    /// Groups with at least one active sound, used to enforce the rules between groups.
+    active_groups: ActiveGroups,
+    /// Groups awaiting a sound following the end of a sound from a group that they follow, along
+    /// with the time remaining in which to do so.
+    triggered_groups: TriggeredGroups,
     /// A buffer to re-use when calculating currently available groups.
     available_groups: AvailableGroups,
     /// A buffer to re-use when calculating currently available sources.
     available_sources: AvailableSources,
+    /// The result of evaluating the schedule at the beginning of each tick.
+    schedule_state: schedule::State,
 
     // Communication channels.
 
@@ -180,7 +261,36 @@ pub struct Model {
     /// A handle for submitting new sounds to the output stream.
     audio_output_stream: audio::output::Stream,
     // A handle to the ticker thread.
-    _tick_thread: thread::JoinHandle<()>,
+    //
+    // This is `None` for `Offline` soundscapes, which are ticked manually.
+    _tick_thread: Option<thread::JoinHandle<()>>,
+}
+
+/// A soundscape that is stepped manually on a simulated clock rather than on its own thread.
+///
+/// This allows for rendering the soundscape faster than realtime.
+pub struct Offline {
+    model: Model,
+    rx: mpsc::Receiver<Message>,
+    is_playing: Arc<AtomicBool>,
+    /// The moment from which the simulated clock starts.
+    start: time::Instant,
+    /// The total simulated time that has elapsed.
+    elapsed: time::Duration,
+    /// Simulated time that has not yet been consumed by a `Tick`.
+    pending: time::Duration,
+    /// The moment within the week at which the simulated clock starts.
+    start_moment: schedule::Moment,
+}
+
+// The values between which installations, groups and sources are crossfaded when recalling a scene.
+//
+// Only those whose values differ between the start and end of the crossfade are included.
+struct SceneFade {
+    installations: Vec<(installation::Id, installation::Soundscape, installation::Soundscape)>,
+    groups: Vec<(group::Id, Group, Group)>,
+    sources: Vec<(audio::source::Id, scene::SourceLevel, scene::SourceLevel)>,
+    crossfade: scene::Crossfade,
 }
 
 // Data related to the suitability of a group or source for selection of use within the soundscape.
@@ -237,7 +347,9 @@ struct AvailableSource {
 impl ActiveSound {
     /// The current location and orientation of the active sound.
     pub fn position(&self) -> audio::sound::Position {
-        self.movement.position()
+        let mut position = self.movement.position();
+        position.elevation = self.elevation;
+        position
     }
 
     /// A simplified view of the active sound's position.
@@ -255,7 +367,9 @@ impl Speaker {
     pub fn from_audio_speaker(s: &audio::Speaker) -> Self {
         Speaker {
             point: s.point,
+            elevation: s.elevation,
             installations: s.installations.clone(),
+            role: s.role,
         }
     }
 }
@@ -273,7 +387,10 @@ impl Source {
         let spread = source.spread;
         let channel_radians = source.channel_radians;
         let volume = source.volume;
+        let reverb_send = source.reverb_send;
         let muted = source.muted;
+        let distance = source.distance;
+        let doppler = source.doppler;
         let last_sound_created = None;
         Some(Source {
             constraints,
@@ -281,7 +398,10 @@ impl Source {
             spread,
             channel_radians,
             volume,
+            reverb_send,
             muted,
+            distance,
+            doppler,
             last_sound_created,
         })
     }
@@ -293,14 +413,20 @@ impl Source {
         let spread = self.spread;
         let channel_radians = self.channel_radians;
         let volume = self.volume;
+        let reverb_send = self.reverb_send;
         let muted = self.muted;
+        let distance = self.distance;
+        let doppler = self.doppler;
         audio::Source {
             kind,
             role,
             spread,
             channel_radians,
             volume,
+            reverb_send,
             muted,
+            distance,
+            doppler,
         }
     }
 }
@@ -355,7 +481,189 @@ impl Soundscape {
     }
 }
 
+impl Offline {
+    /// Advance the simulated clock by the given duration.
+    ///
+    /// Pending messages are handled and the soundscape is ticked at the same rate as it would be
+    /// when running in realtime.
+    pub fn advance(&mut self, duration: time::Duration) {
+        let tick_rate = time::Duration::from_millis(TICK_RATE_MS);
+        self.handle_pending_messages();
+        self.pending += duration;
+        while self.pending >= tick_rate {
+            self.pending -= tick_rate;
+            self.elapsed += tick_rate;
+            if !self.is_playing.load(atomic::Ordering::Relaxed) {
+                continue;
+            }
+            let playback_duration = self.model.playback_duration + tick_rate;
+            let t = Tick {
+                instant: self.start + self.elapsed,
+                since_last_tick: tick_rate,
+                playback_duration,
+                moment: self.start_moment.after(self.elapsed),
+            };
+            tick(&mut self.model, t);
+            self.handle_pending_messages();
+        }
+    }
+
+    /// The total simulated time that has elapsed.
+    pub fn elapsed(&self) -> time::Duration {
+        self.elapsed + self.pending
+    }
+
+    // Handle all messages that have been sent since the last call.
+    fn handle_pending_messages(&mut self) {
+        for msg in self.rx.try_iter() {
+            handle_message(&mut self.model, msg);
+        }
+    }
+}
+
 impl Model {
+    // Initialise the `Model`.
+    fn new(
+        frame_count: Arc<AtomicUsize>,
+        seed: Seed,
+        wav_reader: audio::source::wav::reader::Handle,
+        audio_input_stream: audio::input::Stream,
+        audio_output_stream: audio::output::Stream,
+        sound_id_gen: audio::sound::IdGenerator,
+        installation_rms: audio::detection::InstallationRms,
+        _tick_thread: Option<thread::JoinHandle<()>>,
+    ) -> Self {
+        let realtime_source_latency = audio::DEFAULT_REALTIME_SOURCE_LATENCY;
+        let rng = seeded_rng(seed);
+        let playback_duration = time::Duration::from_secs(0);
+        let installations = Default::default();
+        let groups = Default::default();
+        let sources = Default::default();
+        let speakers = Default::default();
+        let active_sounds = Default::default();
+        let installation_speakers = Default::default();
+        let installation_areas = Default::default();
+        let groups_last_used = Default::default();
+        let sources_last_used = Default::default();
+        let groups_last_ended = Default::default();
+        let ended_groups = Default::default();
+        let target_sounds_per_installation = Default::default();
+        let active_sound_positions = Default::default();
+        let flock_boids = Default::default();
+        let active_groups = Default::default();
+        let triggered_groups = Default::default();
+        let active_sounds_per_installation = Default::default();
+        let available_groups = Default::default();
+        let available_sources = Default::default();
+        let schedule = Default::default();
+        let scene_fade = None;
+        let installation_activity = Default::default();
+        let installation_zones = Default::default();
+        let installation_occupancy = Default::default();
+        let schedule_state = Default::default();
+        Model {
+            frame_count,
+            realtime_source_latency,
+            seed,
+            rng,
+            playback_duration,
+            installations,
+            groups,
+            sources,
+            speakers,
+            active_sounds,
+            schedule,
+            scene_fade,
+            installation_activity,
+            installation_rms,
+            installation_zones,
+            installation_occupancy,
+            groups_last_used,
+            sources_last_used,
+            groups_last_ended,
+            ended_groups,
+            installation_speakers,
+            installation_areas,
+            target_sounds_per_installation,
+            active_sounds_per_installation,
+            active_sound_positions,
+            flock_boids,
+            active_groups,
+            triggered_groups,
+            available_groups,
+            available_sources,
+            schedule_state,
+            wav_reader,
+            audio_input_stream,
+            audio_output_stream,
+            sound_id_gen,
+            _tick_thread,
+        }
+    }
+
+    /// Re-seed the soundscape's source of randomness.
+    ///
+    /// All random decisions made from this point onwards are reproducible from the given seed.
+    pub fn set_seed(&mut self, seed: Seed) {
+        self.seed = seed;
+        self.rng = seeded_rng(seed);
+    }
+
+    /// Replace the weekly schedule.
+    pub fn set_schedule(&mut self, schedule: schedule::Schedule) {
+        self.schedule = schedule;
+    }
+
+    /// Begin crossfading the installations, groups and sources towards those of the given scene.
+    ///
+    /// The crossfade progresses upon each `Tick` and replaces any crossfade already in progress.
+    pub fn fade_to_scene(&mut self, scene: &scene::Scene, duration: time::Duration) {
+        let installations = scene
+            .installations
+            .iter()
+            .filter_map(|(&id, to)| {
+                let from = self.installations.get(&id)?;
+                if from == to {
+                    return None;
+                }
+                Some((id, from.clone(), to.clone()))
+            })
+            .collect();
+        let groups = scene
+            .groups
+            .iter()
+            .filter_map(|(&id, to)| {
+                let from = self.groups.get(&id)?;
+                if from == to {
+                    return None;
+                }
+                Some((id, from.clone(), to.clone()))
+            })
+            .collect();
+        let sources = scene
+            .sources
+            .iter()
+            .filter_map(|(&id, &to)| {
+                let source = self.sources.get(&id)?;
+                let from = scene::SourceLevel {
+                    volume: source.volume,
+                    muted: source.muted,
+                };
+                if from == to {
+                    return None;
+                }
+                Some((id, from, to))
+            })
+            .collect();
+        let crossfade = scene::Crossfade::new(duration);
+        self.scene_fade = Some(SceneFade {
+            installations,
+            groups,
+            sources,
+            crossfade,
+        });
+    }
+
     /// Insert a new installation.
     pub fn insert_installation(
         &mut self,
@@ -385,6 +693,9 @@ impl Model {
             source.installations.remove(id);
         }
 
+        self.installation_activity.remove(id);
+        self.installation_zones.remove(id);
+        self.installation_occupancy.remove(id);
         self.installations.remove(id)
     }
 
@@ -404,6 +715,49 @@ impl Model {
         }
     }
 
+    /// Update the visitor presence most recently reported for the installation.
+    ///
+    /// Once reported, presence takes precedence over the installation's RMS as its activity.
+    pub fn update_installation_presence(&mut self, installation: installation::Id, presence: f32) {
+        self.installation_activity
+            .entry(installation)
+            .or_insert_with(Default::default)
+            .presence = Some(presence);
+    }
+
+    /// Update the number of visitors most recently reported for the installation.
+    ///
+    /// The first report for an installation is applied immediately, while subsequent reports are
+    /// smoothed over the installation's `occupancy_smoothing` duration.
+    ///
+    /// Non-finite counts are ignored and all others are limited to `0.0..=MAX_OCCUPANCY`.
+    pub fn update_installation_occupancy(&mut self, installation: installation::Id, count: f32) {
+        if !count.is_finite() {
+            return;
+        }
+        let count = count.max(0.0).min(MAX_OCCUPANCY);
+        self.installation_occupancy
+            .entry(installation)
+            .or_insert(Occupancy { reported: count, smoothed: count })
+            .reported = count;
+    }
+
+    /// Update the polygon zone to which the installation's area is restricted.
+    ///
+    /// Zones with fewer than three vertices are ignored in favour of the area described by the
+    /// installation's speakers.
+    pub fn update_installation_zone(
+        &mut self,
+        installation: installation::Id,
+        zone: Vec<nannou::glam::DVec2>,
+    ) {
+        if movement::zone::is_valid(&zone) {
+            self.installation_zones.insert(installation, zone);
+        } else {
+            self.installation_zones.remove(&installation);
+        }
+    }
+
     /// Insert a new soundscape group.
     pub fn insert_group(&mut self, id: group::Id, group: Group) -> Option<Group> {
         self.groups.insert(id, group)
@@ -426,7 +780,16 @@ impl Model {
     }
 
     /// Remove the given soundscape group.
+    ///
+    /// The group is also removed from all periods of the schedule.
     pub fn remove_group(&mut self, id: &group::Id) -> Option<Group> {
+        for period in self.schedule.periods.iter_mut() {
+            period.disabled_groups.retain(|group| group != id);
+        }
+        for group in self.groups.values_mut() {
+            group.rules.retain(|rule| rule.group() != *id);
+        }
+        self.groups_last_ended.remove(id);
         self.groups.remove(id)
     }
 
@@ -491,28 +854,40 @@ impl Model {
         // Now update all active sounds that use this source.
         let Model {
             seed,
+            ref mut rng,
             ref playback_duration,
             ref sources,
             ref speakers,
             ref installations,
             ref mut active_sounds,
+            ref installation_activity,
+            ref installation_zones,
+            ref installation_occupancy,
 
             // Intermediary buffers.
             ref mut installation_speakers,
             ref mut installation_areas,
             ref mut target_sounds_per_installation,
             ref mut active_sound_positions,
+            ref schedule_state,
             ..
         } = *self;
 
         // Collect the necessary data for generating a `Movement` instance from the constraints.
         update_installation_speakers(speakers, installation_speakers);
-        update_installation_areas(speakers, installation_speakers, installation_areas);
+        update_installation_areas(
+            speakers,
+            installation_speakers,
+            installation_zones,
+            installation_areas,
+        );
         update_target_sounds_per_installation(
             seed,
             playback_duration,
             installations,
+            schedule_state,
             &installation_areas,
+            installation_occupancy,
             target_sounds_per_installation,
         );
         update_active_sound_positions(active_sounds, active_sound_positions);
@@ -533,10 +908,13 @@ impl Model {
                         *source_id,
                         sources,
                         installation,
+                        &sound.position,
                         installations,
                         &installation_areas,
                         &target_sounds_per_installation,
+                        &installation_activity,
                         &active_sounds,
+                        rng,
                     );
                     // Update the sound.
                     active_sounds.get_mut(&sound_id).unwrap().movement = movement;
@@ -554,7 +932,14 @@ impl Model {
 
     /// Remove an active sound from the hashmap.
     pub fn remove_active_sound(&mut self, id: &audio::sound::Id) -> Option<ActiveSound> {
-        self.active_sounds.remove(id)
+        let sound = self.active_sounds.remove(id);
+        // Track the end of the sound's groups so that groups that follow them may be triggered.
+        if let Some(ref sound) = sound {
+            if let Some(source) = self.sources.get(&sound.source_id()) {
+                self.ended_groups.extend(source.groups.iter().cloned());
+            }
+        }
+        sound
     }
 
     /// Update the state of all active sounds spawned via the source with the given `Id`.
@@ -588,14 +973,32 @@ impl Model {
         self.speakers.clear();
         self.groups_last_used.clear();
         self.sources_last_used.clear();
+        self.groups_last_ended.clear();
+        self.ended_groups.clear();
         self.active_sounds.clear();
         self.installation_speakers.clear();
         self.installation_areas.clear();
         self.target_sounds_per_installation.clear();
         self.active_sounds_per_installation.clear();
         self.active_sound_positions.clear();
+        self.flock_boids.clear();
+        self.active_groups.clear();
+        self.triggered_groups.clear();
         self.available_groups.clear();
         self.available_sources.clear();
+        self.schedule = Default::default();
+        self.schedule_state = Default::default();
+        self.scene_fade = None;
+        self.installation_activity.clear();
+        self.installation_zones.clear();
+        self.installation_occupancy.clear();
+    }
+}
+
+impl Activity {
+    /// The level of activity, preferring the reported visitor presence over the measured RMS.
+    pub fn level(&self) -> f32 {
+        self.presence.unwrap_or(self.rms)
     }
 }
 
@@ -673,6 +1076,7 @@ pub fn spawn(
     audio_input_stream: audio::input::Stream,
     audio_output_stream: audio::output::Stream,
     sound_id_gen: audio::sound::IdGenerator,
+    installation_rms: audio::detection::InstallationRms,
 ) -> Soundscape {
     let is_playing = Arc::new(AtomicBool::new(true));
 
@@ -697,6 +1101,7 @@ pub fn spawn(
                     instant,
                     since_last_tick,
                     playback_duration,
+                    moment: schedule::Moment::now(),
                 };
                 if tick_tx.send(Message::Tick(tick)).is_err() {
                     break;
@@ -706,47 +1111,16 @@ pub fn spawn(
         .unwrap();
 
     // The model maintaining state between messages.
-    let realtime_source_latency = audio::DEFAULT_REALTIME_SOURCE_LATENCY;
-    let playback_duration = time::Duration::from_secs(0);
-    let installations = Default::default();
-    let groups = Default::default();
-    let sources = Default::default();
-    let speakers = Default::default();
-    let active_sounds = Default::default();
-    let installation_speakers = Default::default();
-    let installation_areas = Default::default();
-    let groups_last_used = Default::default();
-    let sources_last_used = Default::default();
-    let target_sounds_per_installation = Default::default();
-    let active_sound_positions = Default::default();
-    let active_sounds_per_installation = Default::default();
-    let available_groups = Default::default();
-    let available_sources = Default::default();
-    let model = Model {
+    let model = Model::new(
         frame_count,
-        realtime_source_latency,
         seed,
-        playback_duration,
-        installations,
-        groups,
-        sources,
-        speakers,
-        active_sounds,
-        groups_last_used,
-        sources_last_used,
-        installation_speakers,
-        installation_areas,
-        target_sounds_per_installation,
-        active_sounds_per_installation,
-        active_sound_positions,
-        available_groups,
-        available_sources,
         wav_reader,
         audio_input_stream,
         audio_output_stream,
         sound_id_gen,
-        _tick_thread,
-    };
+        installation_rms,
+        Some(_tick_thread),
+    );
 
     // Spawn the soundscape thread.
     let thread = thread::Builder::new()
@@ -761,34 +1135,90 @@ pub fn spawn(
     }
 }
 
+/// Create a soundscape that is stepped manually via the returned `Offline` rather than on its own
+/// thread.
+///
+/// The returned `Soundscape` handle may be used to update the model as usual. Messages are handled
+/// upon each call to `Offline::advance`. The schedule is evaluated from the given `start_moment`.
+pub fn offline(
+    frame_count: Arc<AtomicUsize>,
+    seed: Seed,
+    start_moment: schedule::Moment,
+    tx: mpsc::Sender<Message>,
+    rx: mpsc::Receiver<Message>,
+    wav_reader: audio::source::wav::reader::Handle,
+    audio_input_stream: audio::input::Stream,
+    audio_output_stream: audio::output::Stream,
+    sound_id_gen: audio::sound::IdGenerator,
+    installation_rms: audio::detection::InstallationRms,
+) -> (Soundscape, Offline) {
+    let is_playing = Arc::new(AtomicBool::new(true));
+    let model = Model::new(
+        frame_count,
+        seed,
+        wav_reader,
+        audio_input_stream,
+        audio_output_stream,
+        sound_id_gen,
+        installation_rms,
+        None,
+    );
+    let offline = Offline {
+        model,
+        rx,
+        is_playing: is_playing.clone(),
+        start: time::Instant::now(),
+        elapsed: time::Duration::from_secs(0),
+        pending: time::Duration::from_secs(0),
+        start_moment,
+    };
+    let thread = Arc::new(Mutex::new(None));
+    let soundscape = Soundscape {
+        tx,
+        thread,
+        is_playing,
+    };
+    (soundscape, offline)
+}
+
 // A blocking function that is run on the unique soundscape thread (called by spawn).
 fn run(mut model: Model, msg_rx: mpsc::Receiver<Message>) {
     // Wait for messages.
     for msg in msg_rx {
-        match msg {
-            // An update from another thread.
-            Message::Update(update) => update.call(&mut model),
-
-            // Break from the loop and finish the thread.
-            Message::Exit => break,
-
-            // Step forward the state of the soundscape.
-            Message::Tick(t) => tick(&mut model, t),
+        // Break from the loop and finish the thread.
+        if let Message::Exit = msg {
+            break;
+        }
+        handle_message(&mut model, msg);
+    }
+}
 
-            // Play all active sounds.
-            Message::Play => {
-                for sound in model.active_sounds.values() {
-                    sound.handle.play();
-                }
+// Apply the given message to the model.
+//
+// `Message::Exit` is ignored as it is the responsibility of the caller.
+fn handle_message(model: &mut Model, msg: Message) {
+    match msg {
+        // An update from another thread.
+        Message::Update(update) => update.call(model),
+
+        // Step forward the state of the soundscape.
+        Message::Tick(t) => tick(model, t),
+
+        // Play all active sounds.
+        Message::Play => {
+            for sound in model.active_sounds.values() {
+                sound.handle.play();
             }
+        }
 
-            // Pause all active sounds.
-            Message::Pause => {
-                for sound in model.active_sounds.values() {
-                    sound.handle.pause();
-                }
+        // Pause all active sounds.
+        Message::Pause => {
+            for sound in model.active_sounds.values() {
+                sound.handle.pause();
             }
         }
+
+        Message::Exit => (),
     }
 }
 
@@ -804,6 +1234,9 @@ fn update_active_sound_positions(
 
 // Find the sound's closest assigned installation.
 //
+// An assigned installation whose zone contains the sound is preferred. Otherwise the installation
+// with the closest centroid is used.
+//
 // Returns `None` if:
 //
 // - installation_areas is empty
@@ -817,6 +1250,19 @@ fn closest_assigned_installation(
 {
     if let Some(source) = sources.get(&sound.source_id) {
         let sound_point = Point2::new(sound.position.point.x, sound.position.point.y) ;
+        let containing = source
+            .constraints
+            .installations
+            .iter()
+            .find(|&i| {
+                installation_areas
+                    .get(i)
+                    .map(|a| a.zone_contains(sound.position.point))
+                    .unwrap_or(false)
+            });
+        if let Some(&installation) = containing {
+            return Some(installation);
+        }
         let mut distances = source
             .constraints
             .installations
@@ -843,6 +1289,40 @@ fn closest_assigned_installation(
     None
 }
 
+// Collect the location and velocity of each flocking sound.
+fn update_flock_boids(active_sounds: &ActiveSounds, flock_boids: &mut FlockBoids) {
+    flock_boids.clear();
+    let extension = active_sounds.iter().filter_map(|(&id, sound)| match sound.movement {
+        Movement::Generative(movement::Generative::Flock(ref flock)) => {
+            Some((id, sound.handle.source_id(), flock.boid()))
+        },
+        _ => None,
+    });
+    flock_boids.extend(extension);
+}
+
+// Whether or not the sounds of the two given sources flock together.
+//
+// Sounds of the same source always flock together. Sounds of different sources only do so if
+// `groups` is enabled and the sources share a group.
+fn flocks_with(
+    sources: &Sources,
+    source_id: audio::source::Id,
+    other_id: audio::source::Id,
+    groups: bool,
+) -> bool {
+    if source_id == other_id {
+        return true;
+    }
+    if !groups {
+        return false;
+    }
+    match (sources.get(&source_id), sources.get(&other_id)) {
+        (Some(source), Some(other)) => !source.groups.is_disjoint(&other.groups),
+        _ => false,
+    }
+}
+
 // Group the active sounds via the installation they are currently closest to.
 fn update_active_sounds_per_installation(
     active_sound_positions: &ActiveSoundPositions,
@@ -870,6 +1350,7 @@ fn agent_installation_data(
     installations: &Installations,
     installation_areas: &InstallationAreas,
     target_sounds_per_installation: &TargetSoundsPerInstallation,
+    installation_activity: &InstallationActivity,
     active_sound_positions: &ActiveSoundPositions,
 ) -> movement::agent::InstallationDataMap
 {
@@ -925,11 +1406,16 @@ fn agent_installation_data(
             } else {
                 0
             };
+            let activity = installation_activity
+                .get(inst)
+                .map(Activity::level)
+                .unwrap_or(0.0);
             let data = movement::agent::InstallationData {
                 area,
                 num_sounds_needed_to_reach_target,
                 num_sounds_needed,
                 num_available_sounds,
+                activity,
             };
             Some((*inst, data))
         })
@@ -937,14 +1423,19 @@ fn agent_installation_data(
 }
 
 // Generate a movement for some source within some given installation.
+//
+// Generative movements begin from the given initial position of the sound.
 fn generate_movement(
     source_id: audio::source::Id,
     sources: &Sources,
     installation: installation::Id,
+    initial_position: &audio::sound::Position,
     installations: &Installations,
     installation_areas: &InstallationAreas,
     target_sounds_per_installation: &TargetSoundsPerInstallation,
+    installation_activity: &InstallationActivity,
     active_sounds: &ActiveSounds,
+    rng: &mut XorShiftRng,
 ) -> Movement {
     match sources[&source_id].movement {
         audio::source::Movement::Fixed(ref pos) => {
@@ -953,47 +1444,58 @@ fn generate_movement(
                 .expect("no area for the given installation");
             let x = area.bounding_rect.left + area.bounding_rect.width() * pos.x;
             let y = area.bounding_rect.bottom + area.bounding_rect.height() * pos.y;
-            let point = pt2(x, y);
+            let point = area.clamp(pt2(x, y));
             let radians = 0.0;
-            let position = audio::sound::Position { point, radians };
+            let elevation = initial_position.elevation;
+            let position = audio::sound::Position { point, radians, elevation };
             Movement::Fixed(position)
         },
         audio::source::Movement::Generative(ref gen) => match *gen {
             audio::source::movement::Generative::Agent(ref agent) => {
-                let mut rng = nannou::rand::thread_rng();
-                // TODO: Should these be skewed?
-                let r = &agent.max_speed;
-                let max_speed = map_range(rng.gen(), 0f64, 1.0, r.min, r.max);
-                let r = &agent.max_force;
-                let max_force = map_range(rng.gen(), 0f64, 1.0, r.min, r.max);
-                let r = &agent.max_rotation;
-                let max_rotation = map_range(rng.gen(), 0f64, 1.0, r.min, r.max);
-                let mut active_sound_positions = ActiveSoundPositions::default();
-                update_active_sound_positions(active_sounds, &mut active_sound_positions);
-                let installation_data = agent_installation_data(
+                let agent = generate_agent(
+                    agent,
                     source_id,
                     sources,
+                    initial_position,
                     installations,
                     installation_areas,
-                    &target_sounds_per_installation,
-                    &active_sound_positions,
-                );
-                let agent = movement::Agent::generate(
+                    target_sounds_per_installation,
+                    installation_activity,
+                    active_sounds,
                     rng,
-                    installation,
-                    &installation_data,
-                    max_speed,
-                    max_force,
-                    max_rotation,
-                    agent.directional,
                 );
                 let generative = movement::Generative::Agent(agent);
                 let movement = Movement::Generative(generative);
                 movement
             },
 
+            audio::source::movement::Generative::Flock(ref flock) => {
+                let agent = generate_agent(
+                    &flock.agent,
+                    source_id,
+                    sources,
+                    initial_position,
+                    installations,
+                    installation_areas,
+                    target_sounds_per_installation,
+                    installation_activity,
+                    active_sounds,
+                    rng,
+                );
+                let flock = movement::Flock {
+                    agent,
+                    radius: flock.radius,
+                    separation: flock.separation,
+                    alignment: flock.alignment,
+                    cohesion: flock.cohesion,
+                    groups: flock.groups,
+                };
+                let generative = movement::Generative::Flock(flock);
+                let movement = Movement::Generative(generative);
+                movement
+            },
+
             audio::source::movement::Generative::Ngon(ref ngon) => {
-                let mut rng = nannou::rand::thread_rng();
                 // TODO: Should these be skewed?
                 let r = &ngon.vertices;
                 let vertices = map_range(rng.gen(), 0f64, 1.0, r.min, r.max);
@@ -1015,11 +1517,128 @@ fn generate_movement(
                 let generative = movement::Generative::Ngon(ngon);
                 let movement = Movement::Generative(generative);
                 movement
-            }
+            },
+
+            audio::source::movement::Generative::Path(ref path) => {
+                let r = &path.speed;
+                let speed = map_range(rng.gen(), 0f64, 1.0, r.min, r.max);
+                let start = if path.random_start { rng.gen() } else { 0.0 };
+                // Without any waypoints, the sound remains where it was spawned.
+                let path = match path.points.is_empty() {
+                    true => movement::Path::new(
+                        &[initial_position.point],
+                        false,
+                        path.mode,
+                        speed,
+                        start,
+                    ),
+                    false => movement::Path::new(
+                        &path.points,
+                        path.spline,
+                        path.mode,
+                        speed,
+                        start,
+                    ),
+                };
+                let generative = movement::Generative::Path(path);
+                let movement = Movement::Generative(generative);
+                movement
+            },
+
+            audio::source::movement::Generative::RandomWalk(ref walk) => {
+                let r = &walk.step;
+                let step = map_range(rng.gen(), 0f64, 1.0, r.min, r.max);
+                let r = &walk.drift;
+                let drift_speed = map_range(rng.gen(), 0f64, 1.0, r.min, r.max);
+                let drift_radians = rng.gen::<f64>() * 2.0 * PI_F64;
+                let direction = nannou::glam::DVec2::new(drift_radians.cos(), drift_radians.sin());
+                let drift = direction * drift_speed;
+                let walk = movement::RandomWalk::new(initial_position.point, step, drift);
+                let generative = movement::Generative::RandomWalk(walk);
+                let movement = Movement::Generative(generative);
+                movement
+            },
+
+            audio::source::movement::Generative::Orbit(ref orbit) => {
+                let r = &orbit.radius;
+                let radius = map_range(rng.gen(), 0f64, 1.0, r.min, r.max);
+                let r = &orbit.angular_speed;
+                let angular_speed = map_range(rng.gen(), 0f64, 1.0, r.min, r.max);
+                let angular_speed = if rng.gen() { angular_speed } else { -angular_speed };
+                let r = &orbit.eccentricity;
+                let eccentricity = map_range(rng.gen(), 0f64, 1.0, r.min, r.max);
+                let rotation = rng.gen::<f64>() * 2.0 * PI_F64;
+                let phase = rng.gen::<f64>() * 2.0 * PI_F64;
+                let area = &installation_areas[&installation];
+                let orbit = movement::Orbit::new(
+                    orbit.centre,
+                    radius,
+                    angular_speed,
+                    eccentricity,
+                    rotation,
+                    phase,
+                    area,
+                );
+                let generative = movement::Generative::Orbit(orbit);
+                let movement = Movement::Generative(generative);
+                movement
+            },
         },
     }
 }
 
+// Generate an agent for some source, beginning from the given initial position of the sound.
+fn generate_agent(
+    agent: &audio::source::movement::Agent,
+    source_id: audio::source::Id,
+    sources: &Sources,
+    initial_position: &audio::sound::Position,
+    installations: &Installations,
+    installation_areas: &InstallationAreas,
+    target_sounds_per_installation: &TargetSoundsPerInstallation,
+    installation_activity: &InstallationActivity,
+    active_sounds: &ActiveSounds,
+    rng: &mut XorShiftRng,
+) -> movement::Agent {
+    // TODO: Should these be skewed?
+    let r = &agent.max_speed;
+    let max_speed = map_range(rng.gen(), 0f64, 1.0, r.min, r.max);
+    let r = &agent.max_force;
+    let max_force = map_range(rng.gen(), 0f64, 1.0, r.min, r.max);
+    let r = &agent.max_rotation;
+    let max_rotation = map_range(rng.gen(), 0f64, 1.0, r.min, r.max);
+    let mut active_sound_positions = ActiveSoundPositions::default();
+    update_active_sound_positions(active_sounds, &mut active_sound_positions);
+    let installation_data = agent_installation_data(
+        source_id,
+        sources,
+        installations,
+        installation_areas,
+        &target_sounds_per_installation,
+        installation_activity,
+        &active_sound_positions,
+    );
+    movement::Agent::generate(
+        &mut *rng,
+        initial_position.point,
+        &installation_data,
+        max_speed,
+        max_force,
+        max_rotation,
+        agent.directional,
+        agent.activity_weight,
+    )
+}
+
+// Create the random number generator used by the soundscape from the given seed.
+fn seeded_rng(mut seed: Seed) -> XorShiftRng {
+    // An all-zero seed would cause the generator to yield only zeroes.
+    if seed == [0; 16] {
+        seed[0] = 1;
+    }
+    XorShiftRng::from_seed(seed)
+}
+
 // A unique, constant seed associated with the installation.
 fn installation_seed(installation: &installation::Id) -> Seed {
     // Convert the installation to its integer representation.
@@ -1048,26 +1667,51 @@ fn update_installation_speakers(
 
 // Update the map from installations to their areas.
 //
-// An installations `Area` is determined via the assigned speaker locations.
+// An installations `Area` is determined via the assigned speaker locations. If the installation
+// has a zone, the area's bounds and centroid are instead determined via the zone's vertices.
 fn update_installation_areas(
     speakers: &Speakers,
     installation_speakers: &InstallationSpeakers,
+    installation_zones: &InstallationZones,
     installation_areas: &mut InstallationAreas,
 ) {
     installation_areas.clear();
     for (&installation, installation_speakers) in installation_speakers {
         let speaker_points = || installation_speakers.iter().map(|id| speakers[id].point);
-        let bounding_rect = match BoundingRect::from_points(speaker_points()) {
+        let mut bounding_rect = match BoundingRect::from_points(speaker_points()) {
             None => continue,
             Some(rect) => rect,
         };
-        let centroid = match nannou::geom::centroid(speaker_points().map(|p| pt2(p.x.0, p.y.0))) {
+        let mut centroid = match nannou::geom::centroid(speaker_points().map(|p| pt2(p.x.0, p.y.0))) {
             None => continue,
             Some(p) => pt2(Metres(p.x), Metres(p.y)),
         };
+        // Subwoofers are excluded as sounds are never panned to them.
+        let elevations = || {
+            installation_speakers
+                .iter()
+                .map(|id| &speakers[id])
+                .filter(|s| s.role != audio::speaker::Role::Subwoofer)
+                .map(|s| s.elevation)
+        };
+        let elevation = match elevations().count() {
+            0 => 0.0,
+            n => elevations().sum::<f64>() / n as f64,
+        };
+        let zone = installation_zones.get(&installation).cloned();
+        if let Some(ref zone) = zone {
+            if let Some(rect) = BoundingRect::from_points(zone.iter().cloned()) {
+                bounding_rect = rect;
+            }
+            if let Some(p) = movement::zone::centroid(zone) {
+                centroid = p;
+            }
+        }
         let area = movement::Area {
             bounding_rect,
             centroid,
+            elevation,
+            zone,
         };
         installation_areas.insert(installation, area);
     }
@@ -1078,6 +1722,10 @@ fn update_installation_areas(
 // We can determine this in a purely functional manner by using the playback duration as the phase
 // for a noise_walk signal.
 //
+// If occupancy has been reported for the installation, the position within the range is blended
+// towards the smoothed occupancy (as a fraction of the installation's `capacity`) by the
+// installation's `occupancy_weight`.
+//
 // Note that an installation may not generate any sounds if it has no area (i.e. there are no
 // speakers assigned).
 fn installation_target_sounds(
@@ -1085,7 +1733,9 @@ fn installation_target_sounds(
     playback_duration: &time::Duration,
     installation: &installation::Id,
     constraints: &installation::Soundscape,
+    schedule_state: &schedule::State,
     installation_areas: &InstallationAreas,
+    occupancy: Option<&Occupancy>,
 ) -> usize {
     if !installation_areas.contains_key(installation) {
         return 0;
@@ -1095,17 +1745,21 @@ fn installation_target_sounds(
     let hr_secs = 1.0 * 60.0 * 60.0;
     let hz = 1.0 / hr_secs;
     // Offset the phase using the `installation::Id` as a unique seed.
-    let mut noise_walk_seed = utils::add_seeds(&seed, &installation_seed(&installation));
-    if noise_walk_seed == [0; 16] {
-        noise_walk_seed[0] = 1;
-    }
-    let mut rng = XorShiftRng::from_seed(noise_walk_seed);
+    let noise_walk_seed = utils::add_seeds(&seed, &installation_seed(&installation));
+    let mut rng = seeded_rng(noise_walk_seed);
     let phase_offset: f64 = rng.gen();
     let phase = phase_offset + playback_secs * hz;
     // Amplify the noise_walk slightly so that it occasionally reaches min and max.
     let amp = (noise_walk(phase) * 1.5).min(1.0).max(-1.0);
     let normalised_amp = amp * 0.5 + 0.5;
-    let range = &constraints.simultaneous_sounds;
+    let normalised_amp = match occupancy {
+        None => normalised_amp,
+        Some(occupancy) => {
+            let weight = constraints.occupancy_weight.min(1.0).max(0.0);
+            normalised_amp * (1.0 - weight) + occupancy.fraction(constraints.capacity) * weight
+        }
+    };
+    let range = schedule_state.simultaneous_sounds(&constraints.simultaneous_sounds);
     let range_diff = range.max - range.min;
     (range.min as f64 + normalised_amp * range_diff as f64) as usize
 }
@@ -1113,12 +1767,14 @@ fn installation_target_sounds(
 // Determine the target number of sounds per installation.
 //
 // We can determine this in a purely functional manner by using the playback duration as the phase
-// for a noise_walk signal.
+// for a noise_walk signal. The range of each installation is first overridden by the schedule.
 fn update_target_sounds_per_installation(
     seed: Seed,
     playback_duration: &time::Duration,
     installations: &Installations,
+    schedule_state: &schedule::State,
     installation_areas: &InstallationAreas,
+    installation_occupancy: &InstallationOccupancy,
     target_sounds_per_installation: &mut TargetSoundsPerInstallation,
 ) {
     target_sounds_per_installation.clear();
@@ -1128,19 +1784,51 @@ fn update_target_sounds_per_installation(
             playback_duration,
             installation,
             installation_constraints,
+            schedule_state,
             installation_areas,
+            installation_occupancy.get(installation),
         );
         target_sounds_per_installation.insert(*installation, target_num_sounds);
     }
 }
 
+// Move the smoothed occupancy of each installation towards its most recently reported occupancy.
+//
+// The smoothing is exponential, with the installation's `occupancy_smoothing` as the time constant.
+fn smooth_installation_occupancy(
+    since_last_tick: &time::Duration,
+    installations: &Installations,
+    installation_occupancy: &mut InstallationOccupancy,
+) {
+    let delta_secs = duration_to_secs(since_last_tick);
+    for (installation, occupancy) in installation_occupancy.iter_mut() {
+        let smoothing_secs = match installations.get(installation) {
+            None => continue,
+            Some(constraints) => constraints.occupancy_smoothing.ms() / 1_000.0,
+        };
+        let t = if smoothing_secs > 0.0 {
+            1.0 - (-delta_secs / smoothing_secs).exp()
+        } else {
+            1.0
+        };
+        occupancy.smoothed += (occupancy.reported - occupancy.smoothed) * t as f32;
+    }
+}
+
 // Collect available groups of sources (based on occurrence rate and simultaneous sounds) for the
 // given installation at the given moment in time.
+//
+// Groups disabled by the schedule or forbidden by the rules between groups are never available.
+//
+// Triggered groups bypass their occurrence rate and are considered to need a sound.
 fn update_available_groups(
     tick: &Tick,
     sources: &Sources,
     groups: &Groups,
+    disabled_groups: &FxHashSet<group::Id>,
     active_sounds: &ActiveSounds,
+    active_groups: &ActiveGroups,
+    triggered_groups: &TriggeredGroups,
     groups_last_used: &GroupsLastUsed,
     available_groups: &mut AvailableGroups,
 ) {
@@ -1148,6 +1836,14 @@ fn update_available_groups(
     let extension = groups
         .iter()
         .filter_map(|(group_id, group)| {
+            if disabled_groups.contains(group_id) {
+                return None;
+            }
+
+            if !group::rules_permit(*group_id, groups, active_groups) {
+                return None;
+            }
+
             // The total number of active sounds spawned via this group across all installations.
             let num_active_sounds = active_sounds
                 .values()
@@ -1173,10 +1869,21 @@ fn update_available_groups(
             } else {
                 0
             };
+            let triggered = triggered_groups.get(group_id).cloned();
+            let num_sounds_needed = match triggered {
+                Some(_) => cmp::max(num_sounds_needed, 1),
+                None => num_sounds_needed,
+            };
 
             // Find the duration since the last time a sound was spawned using a source from
             // this group.
-            let timing = if let Some(&last_used) = groups_last_used.get(group_id) {
+            let timing = if let Some(duration_until_sound_needed) = triggered {
+                let duration_since_min_interval = Ms(0.0);
+                Some(Timing {
+                    duration_since_min_interval,
+                    duration_until_sound_needed,
+                })
+            } else if let Some(&last_used) = groups_last_used.get(group_id) {
                 let duration_since_last: time::Duration =
                     tick.instant.duration_since(last_used);
                 let duration_since_last_ms =
@@ -1214,11 +1921,18 @@ fn update_available_groups(
     available_groups.extend(extension);
 }
 
+// Collect the sources available for spawning a sound within the given installation.
+//
+// Sources belonging to any group that is forbidden by the rules between groups are unavailable.
+// Sources belonging to a triggered group bypass their occurrence rate.
 fn update_available_sources(
     installation: &installation::Id,
     tick: &Tick,
     sources: &Sources,
+    groups: &Groups,
     active_sounds: &ActiveSounds,
+    active_groups: &ActiveGroups,
+    triggered_groups: &TriggeredGroups,
     sources_last_used: &SourcesLastUsed,
     available_groups: &AvailableGroups,
     available_sources: &mut AvailableSources,
@@ -1236,6 +1950,11 @@ fn update_available_sources(
             return None;
         }
 
+        // Ensure the sound would not break the rules of any of the source's groups.
+        if source.groups.iter().any(|&g| !group::rules_permit(g, groups, active_groups)) {
+            return None;
+        }
+
         // How many instances of this sound are already playing.
         let num_sounds = active_sounds
             .values()
@@ -1256,9 +1975,29 @@ fn update_available_sources(
             0
         };
 
+        // The least time remaining for any triggered group to which the source belongs.
+        let triggered = source
+            .groups
+            .iter()
+            .filter_map(|g| triggered_groups.get(g).cloned())
+            .fold(None, |min: Option<Ms>, remaining| match min {
+                Some(min) if min <= remaining => Some(min),
+                _ => Some(remaining),
+            });
+        let num_sounds_needed = match triggered {
+            Some(_) => cmp::max(num_sounds_needed, 1),
+            None => num_sounds_needed,
+        };
+
         // Find the duration since the last time a sound was spawned using a source
         // from this group.
-        let timing = if let Some(&last_use) = sources_last_used.get(source_id) {
+        let timing = if let Some(duration_until_sound_needed) = triggered {
+            let duration_since_min_interval = Ms(0.0);
+            Some(Timing {
+                duration_since_min_interval,
+                duration_until_sound_needed,
+            })
+        } else if let Some(&last_use) = sources_last_used.get(source_id) {
             let duration_since_last = tick.instant.duration_since(last_use);
             let duration_since_last_ms =
                 Ms(duration_to_secs(&duration_since_last) * 1_000.0);
@@ -1298,6 +2037,64 @@ fn update_available_sources(
 }
 
 
+// Collect the groups that currently have at least one active sound.
+fn update_active_groups(
+    sources: &Sources,
+    active_sounds: &ActiveSounds,
+    active_groups: &mut ActiveGroups,
+) {
+    active_groups.clear();
+    for sound in active_sounds.values() {
+        if let Some(source) = sources.get(&sound.source_id()) {
+            active_groups.extend(source.groups.iter().cloned());
+        }
+    }
+}
+
+// Collect the groups awaiting a sound following the end of a sound from a group that they follow.
+//
+// A group is triggered if it has not been used since the followed group's sound ended and the
+// duration within which it must follow has not yet passed. The time remaining is stored with each
+// triggered group.
+fn update_triggered_groups(
+    tick: &Tick,
+    groups: &Groups,
+    disabled_groups: &FxHashSet<group::Id>,
+    groups_last_used: &GroupsLastUsed,
+    groups_last_ended: &GroupsLastEnded,
+    triggered_groups: &mut TriggeredGroups,
+) {
+    triggered_groups.clear();
+    for (group_id, group) in groups {
+        if disabled_groups.contains(group_id) {
+            continue;
+        }
+        for rule in &group.rules {
+            let (followed, within) = match *rule {
+                group::Rule::Follow { group, within } => (group, within),
+                _ => continue,
+            };
+            let ended = match groups_last_ended.get(&followed) {
+                None => continue,
+                Some(&ended) => ended,
+            };
+            if groups_last_used.get(group_id).map(|&used| used >= ended).unwrap_or(false) {
+                continue;
+            }
+            let since_ended = tick.instant.duration_since(ended);
+            let since_ended_ms = Ms(duration_to_secs(&since_ended) * 1_000.0);
+            if since_ended_ms > within {
+                continue;
+            }
+            let remaining = within - since_ended_ms;
+            let entry = triggered_groups.entry(*group_id).or_insert(remaining);
+            if remaining < *entry {
+                *entry = remaining;
+            }
+        }
+    }
+}
+
 // Order the two sets or properties by their suitability for use as the next sound.
 fn suitability(a: &Suitability, b: &Suitability) -> cmp::Ordering {
     match b.num_sounds_needed.cmp(&a.num_sounds_needed) {
@@ -1313,12 +2110,61 @@ fn suitability(a: &Suitability, b: &Suitability) -> cmp::Ordering {
     }
 }
 
+// Step the crossfade towards the most recently recalled scene, if any.
+//
+// The volume and mute state of the sources are also updated for their active sounds on the audio
+// output thread.
+fn step_scene_fade(model: &mut Model, delta: time::Duration) {
+    let mut fade = match model.scene_fade.take() {
+        None => return,
+        Some(fade) => fade,
+    };
+    let t = fade.crossfade.step(delta);
+
+    for &(ref id, ref from, ref to) in &fade.installations {
+        if let Some(installation) = model.installations.get_mut(id) {
+            *installation = scene::lerp_installation(from, to, t);
+        }
+    }
+
+    for &(ref id, ref from, ref to) in &fade.groups {
+        if let Some(group) = model.groups.get_mut(id) {
+            *group = scene::lerp_group(from, to, t);
+        }
+    }
+
+    for &(id, from, to) in &fade.sources {
+        let level = from.lerp(&to, t);
+        if let Some(source) = model.sources.get_mut(&id) {
+            source.volume = level.volume;
+            source.muted = level.muted;
+        }
+        model
+            .audio_output_stream
+            .send(move |audio| {
+                audio.update_sounds_with_source(&id, move |_, sound| {
+                    sound.volume = level.volume;
+                    sound.muted = level.muted;
+                });
+            })
+            .expect("failed to send faded source level to audio output thread");
+    }
+
+    if !fade.crossfade.is_complete() {
+        model.scene_fade = Some(fade);
+    }
+}
+
 // Called each time the soundscape thread receives a tick.
 fn tick(model: &mut Model, tick: Tick) {
+    // Progress the crossfade towards the current scene before using any of the faded values.
+    step_scene_fade(model, tick.since_last_tick);
+
     let Model {
         ref frame_count,
         realtime_source_latency,
         seed,
+        ref mut rng,
         ref mut playback_duration,
         ref installations,
         ref groups,
@@ -1326,14 +2172,25 @@ fn tick(model: &mut Model, tick: Tick) {
         ref sources,
         ref mut groups_last_used,
         ref mut sources_last_used,
+        ref mut groups_last_ended,
+        ref mut ended_groups,
         ref mut active_sounds,
+        ref schedule,
+        ref mut installation_activity,
+        ref installation_rms,
+        ref installation_zones,
+        ref mut installation_occupancy,
         ref mut installation_speakers,
         ref mut installation_areas,
         ref mut target_sounds_per_installation,
         ref mut active_sounds_per_installation,
         ref mut active_sound_positions,
+        ref mut flock_boids,
+        ref mut active_groups,
+        ref mut triggered_groups,
         ref mut available_groups,
         ref mut available_sources,
+        ref mut schedule_state,
         ref mut sound_id_gen,
         ref wav_reader,
         ref audio_input_stream,
@@ -1344,32 +2201,68 @@ fn tick(model: &mut Model, tick: Tick) {
     // Update the playback duration so far.
     *playback_duration = tick.playback_duration;
 
+    // Evaluate the schedule, notifying the audio output thread if the volume override has changed.
+    let previous_volume = schedule_state.volume;
+    schedule.evaluate(&tick.moment, schedule_state);
+    if schedule_state.volume != previous_volume {
+        let volume = schedule_state.volume;
+        audio_output_stream
+            .send(move |audio| audio.scheduled_volume = volume)
+            .expect("failed to send scheduled volume to audio output thread");
+    }
+
+    // Stamp the end of any groups whose sounds have ended since the last tick.
+    for group_id in ended_groups.drain() {
+        groups_last_ended.insert(group_id, tick.instant);
+    }
+
     // Update the map from installations to speakers.
     update_installation_speakers(speakers, installation_speakers);
 
     // Create the map from installations to their areas.
     //
-    // An installations `Area` is determined via the assigned speaker locations.
-    update_installation_areas(speakers, installation_speakers, installation_areas);
+    // An installations `Area` is determined via the assigned speaker locations, or its zone.
+    update_installation_areas(
+        speakers,
+        installation_speakers,
+        installation_zones,
+        installation_areas,
+    );
+
+    // Sample the RMS most recently measured across the speakers of each installation.
+    //
+    // This is sampled once per tick rather than received per output buffer. Note that the RMS
+    // includes the soundscape's own sounds, so activity measured this way is self-reinforcing.
+    for id in installations.keys() {
+        if let Some(rms) = installation_rms.get(id) {
+            installation_activity.entry(*id).or_insert_with(Default::default).rms = rms;
+        }
+    }
+
+    // Smooth the reported occupancy of each installation.
+    smooth_installation_occupancy(&tick.since_last_tick, installations, installation_occupancy);
 
     // Determine the target number of sounds per installation.
     //
     // We can determine this in a purely functional manner by using the playback duration as the
-    // phase for a noise_walk signal.
+    // phase for a noise_walk signal, blended with the smoothed occupancy of the installation.
     update_target_sounds_per_installation(
         seed,
         &tick.playback_duration,
         installations,
+        schedule_state,
         installation_areas,
+        installation_occupancy,
         target_sounds_per_installation,
     );
 
     // Update the movement of each active sound.
     {
-        let mut rng = nannou::rand::thread_rng();
         update_active_sound_positions(active_sounds, active_sound_positions);
+        update_flock_boids(active_sounds, flock_boids);
         for (&sound_id, sound) in active_sounds.iter_mut() {
             let initial_installation_area = installation_areas.get(&sound.initial_installation);
+            let previous_position = sound.position();
             match sound.movement {
                 Movement::Fixed(_) => (),
                 Movement::Generative(ref mut generative) => match *generative {
@@ -1381,15 +2274,55 @@ fn tick(model: &mut Model, tick: Tick) {
                             installations,
                             installation_areas,
                             &target_sounds_per_installation,
+                            installation_activity,
                             &active_sound_positions,
                         );
-                        agent.update(&mut rng, &tick.since_last_tick, &installation_data);
+                        agent.update(&mut *rng, &tick.since_last_tick, &installation_data);
+                    },
+                    movement::Generative::Flock(ref mut flock) => {
+                        let source_id = sound.handle.source_id();
+                        let installation_data = agent_installation_data(
+                            source_id,
+                            sources,
+                            installations,
+                            installation_areas,
+                            &target_sounds_per_installation,
+                            installation_activity,
+                            &active_sound_positions,
+                        );
+                        let groups = flock.groups;
+                        let flockmates = flock_boids
+                            .iter()
+                            .filter(|&&(id, _, _)| id != sound_id)
+                            .filter(|&&(_, other_id, _)| {
+                                flocks_with(sources, source_id, other_id, groups)
+                            })
+                            .map(|&(_, _, ref boid)| boid);
+                        flock.update(
+                            &mut *rng,
+                            &tick.since_last_tick,
+                            &installation_data,
+                            flockmates,
+                        );
                     },
                     movement::Generative::Ngon(ref mut ngon) => {
                         if let Some(area) = initial_installation_area {
                             ngon.update(&tick.since_last_tick, &area.bounding_rect);
                         }
                     },
+                    movement::Generative::Path(ref mut path) => {
+                        path.update(&tick.since_last_tick);
+                    },
+                    movement::Generative::RandomWalk(ref mut walk) => {
+                        if let Some(area) = initial_installation_area {
+                            walk.update(&mut *rng, &tick.since_last_tick, area);
+                        }
+                    },
+                    movement::Generative::Orbit(ref mut orbit) => {
+                        if let Some(area) = initial_installation_area {
+                            orbit.update(&tick.since_last_tick, area);
+                        }
+                    },
                 },
             }
 
@@ -1397,11 +2330,26 @@ fn tick(model: &mut Model, tick: Tick) {
             //
             // The audio thread will then notify the GUI of the new position upon the next rendered
             // buffer.
+            //
+            // Movement is 2D, so sounds keep the elevation chosen when they were spawned or
+            // otherwise follow the elevation of their installation.
+            if let (None, Some(area)) = (sound.chosen_elevation, initial_installation_area) {
+                sound.elevation = area.elevation;
+            }
             let position = sound.position();
+
+            // The velocity is sent along with the position for the Doppler effect.
+            let delta_secs = tick.since_last_tick.as_secs_f64();
+            let velocity = if delta_secs > 0.0 {
+                ((position.point - previous_position.point) / delta_secs).extend(0.0)
+            } else {
+                Default::default()
+            };
             audio_output_stream
                 .send(move |audio| {
                     audio.update_sound(&sound_id, move |sound| {
                         sound.position = position;
+                        sound.velocity = velocity;
                     });
                 })
                 .expect("failed to send audio output thread updated sound position");
@@ -1428,8 +2376,27 @@ fn tick(model: &mut Model, tick: Tick) {
             None => 0,
             Some(sounds) => sounds.len(),
         };
-        let sounds_to_add = if num_target_sounds > num_active_sounds {
-            num_target_sounds - num_active_sounds
+
+        // Check for groups that are awaiting a sound from one of this installation's sources.
+        update_triggered_groups(
+            &tick,
+            groups,
+            &schedule_state.disabled_groups,
+            groups_last_used,
+            groups_last_ended,
+            triggered_groups,
+        );
+        let is_triggered = sources.values().any(|source| {
+            source.installations.contains(installation)
+                && source.groups.iter().any(|g| triggered_groups.contains_key(g))
+        });
+
+        // If the installation has already reached its target, only triggered groups may add a
+        // sound to it.
+        let (sounds_to_add, triggered_only) = if num_target_sounds > num_active_sounds {
+            (num_target_sounds - num_active_sounds, false)
+        } else if is_triggered {
+            (1, true)
         } else {
             // If there are no sounds to add, move on to the next installation.
             continue 'installations;
@@ -1449,15 +2416,32 @@ fn tick(model: &mut Model, tick: Tick) {
         // updated and the vec should be re-sorted.
         for _ in 0..sounds_to_add {
             {
+                // Update the active and triggered groups for enforcing the rules between groups.
+                update_active_groups(sources, active_sounds, active_groups);
+                update_triggered_groups(
+                    &tick,
+                    groups,
+                    &schedule_state.disabled_groups,
+                    groups_last_used,
+                    groups_last_ended,
+                    triggered_groups,
+                );
+
                 // Collect available groups of sounds (based on occurrence rate and simultaneous sounds).
                 update_available_groups(
                     &tick,
                     sources,
                     groups,
+                    &schedule_state.disabled_groups,
                     active_sounds,
+                    active_groups,
+                    triggered_groups,
                     groups_last_used,
                     available_groups,
                 );
+                if triggered_only {
+                    available_groups.retain(|g| triggered_groups.contains_key(&g.id));
+                }
 
                 // If there are no available groups, go to the next installation.
                 if available_groups.is_empty() {
@@ -1469,7 +2453,10 @@ fn tick(model: &mut Model, tick: Tick) {
                     installation,
                     &tick,
                     sources,
+                    groups,
                     active_sounds,
+                    active_groups,
+                    triggered_groups,
                     sources_last_used,
                     available_groups,
                     available_sources,
@@ -1498,7 +2485,7 @@ fn tick(model: &mut Model, tick: Tick) {
                     let num_equal = utils::count_equal(&*available_groups, |a, b| {
                         suitability(&a.suitability, &b.suitability)
                     });
-                    nannou::rand::thread_rng().gen_range(0, num_equal)
+                    rng.gen_range(0, num_equal)
                 };
 
                 // Retrieve one of the most suitable sources.
@@ -1506,7 +2493,7 @@ fn tick(model: &mut Model, tick: Tick) {
                     let num_equal = utils::count_equal(&*available_sources, |a, b| {
                         suitability(&a.suitability, &b.suitability)
                     });
-                    nannou::rand::thread_rng().gen_range(0, num_equal)
+                    rng.gen_range(0, num_equal)
                 };
 
                 // Pick one of the most suitable sources.
@@ -1514,59 +2501,44 @@ fn tick(model: &mut Model, tick: Tick) {
                     //let source = find_equally_suitable(available_sources.iter().map(|s| &s.suitability));
                     let source = &available_sources[source_index];
 
-                    // TODO: Determine the initial position of the sound based on:
-                    //
-                    // 1. Installation for which we're triggereing a sound.
-                    // 2. Movement properties and constraints of the source and group.
-                    let initial_position = {
-                        let mut rng = nannou::rand::thread_rng();
-                        let left: bool = rng.gen();
-                        let x_mag: f64 = rng.gen();
-                        let x = match left {
-                            true => {
-                                Metres(x_mag)
-                                    * (installation_area.centroid.x
-                                        - installation_area.bounding_rect.left)
-                                    + installation_area.centroid.x
-                            }
-                            false => {
-                                Metres(x_mag)
-                                    * (installation_area.centroid.x
-                                        - installation_area.bounding_rect.right)
-                                    + installation_area.centroid.x
-                            }
-                        };
-                        let down: bool = rng.gen();
-                        let y_mag: f64 = rng.gen();
-                        let y = match down {
-                            true => {
-                                Metres(y_mag)
-                                    * (installation_area.centroid.y
-                                        - installation_area.bounding_rect.bottom)
-                                    + installation_area.centroid.y
-                            }
-                            false => {
-                                Metres(y_mag)
-                                    * (installation_area.centroid.y
-                                        - installation_area.bounding_rect.top)
-                                    + installation_area.centroid.y
-                            }
-                        };
-                        let point = Point2 { x, y };
-                        let radians = rng.gen::<f32>() * 2.0 * ::std::f32::consts::PI;
-                        audio::sound::Position { point, radians }
+                    // Choose the elevation of the sound from the source's range, if it has one.
+                    let chosen_elevation = sources[&source.id]
+                        .constraints
+                        .elevation
+                        .map(|r| map_range(rng.gen(), 0f64, 1.0, r.min, r.max));
+
+                    // Determine the initial position of the sound via the source's spawn
+                    // strategy, spacing it relative to the speakers and all other active sounds.
+                    let mut initial_position = {
+                        let speaker_points: Vec<_> = installation_speakers
+                            .get(installation)
+                            .map(|ids| ids.iter().map(|id| speakers[id].point).collect())
+                            .unwrap_or_default();
+                        let sound_points: Vec<_> = active_sound_positions
+                            .values()
+                            .map(|sound| sound.position.point)
+                            .collect();
+                        movement::spawn::position(
+                            &mut *rng,
+                            &sources[&source.id].constraints.spawn,
+                            installation_area,
+                            &speaker_points,
+                            &sound_points,
+                        )
                     };
+                    if let Some(elevation) = chosen_elevation {
+                        initial_position.elevation = elevation;
+                    }
 
                     // Generate the attack and release durations based on their source ranges.
-                    let mut rng = nannou::rand::thread_rng();
                     let attack_duration_frames =
-                        audio::source::random_playback_duration(&mut rng, source.attack_duration)
+                        audio::source::random_playback_duration(&mut *rng, source.attack_duration)
                             .to_samples(audio::SAMPLE_RATE);
                     let release_duration_frames =
-                        audio::source::random_playback_duration(&mut rng, source.release_duration)
+                        audio::source::random_playback_duration(&mut *rng, source.release_duration)
                             .to_samples(audio::SAMPLE_RATE);
                     let duration_frames =
-                        audio::source::random_playback_duration(&mut rng, source.playback_duration)
+                        audio::source::random_playback_duration(&mut *rng, source.playback_duration)
                             .to_samples(audio::SAMPLE_RATE);
 
                     // This is not a continuous preview (this is only used for GUI sounds).
@@ -1577,10 +2549,13 @@ fn tick(model: &mut Model, tick: Tick) {
                         source.id,
                         &sources,
                         *installation,
+                        &initial_position,
                         installations,
                         installation_areas,
                         &target_sounds_per_installation,
+                        &installation_activity,
                         &active_sounds,
+                        rng,
                     );
 
                     // Spawn the sound from this source
@@ -1607,13 +2582,26 @@ fn tick(model: &mut Model, tick: Tick) {
                     groups_last_used.insert(available_groups[group_index].id, tick.instant);
                     sources_last_used.insert(source_id, tick.instant);
 
+                    // Satisfy any triggers awaiting a sound from the source's groups.
+                    for group_id in sources[&source_id].groups.iter() {
+                        if triggered_groups.contains_key(group_id) {
+                            groups_last_used.insert(*group_id, tick.instant);
+                        }
+                    }
+
                     // Create the active sound for out use.
                     let active_sound = ActiveSound {
                         initial_installation: *installation,
                         handle: sound,
                         movement,
+                        chosen_elevation,
+                        elevation: initial_position.elevation,
                     };
 
+                    // Track the position so that later spawns this tick may be spaced from it.
+                    let position = ActiveSoundPosition { source_id, position: initial_position };
+                    active_sound_positions.insert(sound_id, position);
+
                     // Store the new active sound.
                     active_sounds.insert(sound_id, active_sound);
                 }
@@ -1621,3 +2609,53 @@ fn tick(model: &mut Model, tick: Tick) {
         }
     }
 }
+
+#[test]
+fn occupancy_scales_target_sounds() {
+    let id = installation::Id(0);
+    let constraints = installation::Soundscape {
+        simultaneous_sounds: Range { min: 2, max: 12 },
+        occupancy_weight: 1.0,
+        occupancy_smoothing: Ms(10_000.0),
+        capacity: 40,
+    };
+    let mut installations = Installations::default();
+    installations.insert(id, constraints.clone());
+    let bounding_rect = BoundingRect {
+        left: -1.0,
+        right: 1.0,
+        bottom: -1.0,
+        top: 1.0,
+    };
+    let area = movement::Area {
+        bounding_rect,
+        centroid: Default::default(),
+        elevation: 0.0,
+        zone: None,
+    };
+    let mut areas = InstallationAreas::default();
+    areas.insert(id, area);
+    let schedule_state = schedule::State::default();
+    let playback = time::Duration::from_secs(0);
+    let target = |constraints: &installation::Soundscape, occupancy: Option<&Occupancy>| {
+        installation_target_sounds([0; 16], &playback, &id, constraints, &schedule_state, &areas,
+                                   occupancy)
+    };
+
+    // Visitor counts are relative to the installation's capacity.
+    let mut occupancy = InstallationOccupancy::default();
+    occupancy.insert(id, Occupancy { reported: 20.0, smoothed: 20.0 });
+    assert_eq!(target(&constraints, occupancy.get(&id)), 7);
+    occupancy.insert(id, Occupancy { reported: 80.0, smoothed: 80.0 });
+    assert_eq!(target(&constraints, occupancy.get(&id)), 12);
+
+    // Changes in occupancy are smoothed exponentially.
+    occupancy.insert(id, Occupancy { reported: 0.0, smoothed: 40.0 });
+    smooth_installation_occupancy(&time::Duration::from_secs(10), &installations, &mut occupancy);
+    assert!((occupancy[&id].smoothed - 40.0 * (-1.0f32).exp()).abs() < 1e-3);
+    assert_eq!(target(&constraints, occupancy.get(&id)), 5);
+
+    // Without any weight, occupancy is ignored.
+    let ignored = installation::Soundscape { occupancy_weight: 0.0, ..constraints.clone() };
+    assert_eq!(target(&ignored, occupancy.get(&id)), target(&ignored, None));
+}
@@ -21,6 +21,7 @@ type Vector = glam::DVec2;
 ///
 /// - A desired target location within one of the assigned installations.
 /// - A user-defined movement weight affecting the max velocity and rotation speeds.
+/// - How strongly the choice of target is drawn towards the currently busiest installations.
 #[derive(Debug)]
 pub struct Agent {
     /// The current location of the agent.
@@ -38,6 +39,9 @@ pub struct Agent {
     /// Specifies whether or not the orientation of the agent should be summed onto the channel
     /// radians.
     pub directional: bool,
+    /// The probability with which each new target is chosen in proportion to the activity of each
+    /// installation, rather than by the number of sounds each installation needs.
+    pub activity_weight: f64,
 }
 
 /// Information about an installation required by the Agent.
@@ -52,30 +56,29 @@ pub struct InstallationData {
     /// The number of sounds that can be added to the installation before the constraints would be
     /// exceeded.
     pub num_available_sounds: usize,
+    /// How busy the installation currently is, either via its RMS or reported visitor presence.
+    pub activity: f32,
 }
 
 /// A map of installation data relevant to the agent.
 pub type InstallationDataMap = FxHashMap<installation::Id, InstallationData>;
 
 impl Agent {
-    /// Generate a new agent starting in the given installation area.
+    /// Generate a new agent starting at the given location.
     pub fn generate<R>(
         mut rng: R,
-        start_installation: installation::Id,
+        location: Point,
         installations: &InstallationDataMap,
         max_speed: f64,
         max_force: f64,
         max_rotation: f64,
         directional: bool,
+        activity_weight: f64,
     ) -> Self
     where
         R: Rng,
     {
-        let installation_data = installations
-            .get(&start_installation)
-            .expect("no `InstallationData` for given for `start_installation`");
-        let location = generate_installation_target(&mut rng, &installation_data.area);
-        let target_location = generate_target(&mut rng, installations);
+        let target_location = generate_target(&mut rng, installations, activity_weight);
         // Generate these based on "weight" or whatever user params are decided upon.
         let start_magnitude = rng.gen::<f64>() * max_speed;
         let desired_velocity = desired_velocity(location, target_location);
@@ -94,6 +97,7 @@ impl Agent {
             max_force,
             max_rotation,
             directional,
+            activity_weight,
         };
         agent
     }
@@ -108,7 +112,18 @@ impl Agent {
         } else {
             0.0
         };
-        audio::sound::Position { point, radians }
+        let elevation = 0.0;
+        audio::sound::Position { point, radians, elevation }
+    }
+
+    /// The current location of the agent.
+    pub fn location(&self) -> Point {
+        self.location
+    }
+
+    /// The current velocity of the agent in metres per second.
+    pub fn velocity(&self) -> Vector {
+        self.velocity
     }
 
     /// Produce the agent's target seeking force for its current state.
@@ -157,28 +172,46 @@ impl Agent {
 
     /// Update the agent for the given past amount of time.
     pub fn update<R>(
+        &mut self,
+        rng: R,
+        delta_time: &time::Duration,
+        installations: &InstallationDataMap,
+    ) where
+        R: Rng,
+    {
+        let force = Vector::ZERO;
+        self.update_with_force(rng, delta_time, installations, force);
+    }
+
+    /// Update the agent for the given past amount of time, applying the given force along with the
+    /// agent's target seeking force.
+    pub fn update_with_force<R>(
         &mut self,
         mut rng: R,
         delta_time: &time::Duration,
         installations: &InstallationDataMap,
+        force: Vector,
     ) where
         R: Rng,
     {
         // We can't know where to go if there are no assigned installations.
         if !installations.is_empty() {
             if should_pick_new_target(self.location, self.target_location, &installations) {
-                self.target_location = generate_target(&mut rng, &installations);
+                self.target_location =
+                    generate_target(&mut rng, &installations, self.activity_weight);
             }
         }
 
         // Determine the steering force to apply based on how much time has passed.
-        let force = self.seek_force();
+        //
+        // The sum is limited so that additional forces (e.g. flocking) respect the max force.
+        let force = limit_magnitude(self.seek_force() + force, self.max_force);
         self.apply_force(force, delta_time);
 
         // If we've reached the target, pick a new one.
         if reached_target(self.location, self.target_location) {
             if !installations.is_empty() {
-                self.target_location = generate_target(rng, installations);
+                self.target_location = generate_target(rng, installations, self.activity_weight);
             }
         }
     }
@@ -213,11 +246,16 @@ fn should_pick_new_target(
 
 /// Find and return the installation cloest to the given point.
 ///
+/// An installation whose zone contains the point is preferred over those with closer centroids.
+///
 /// Returns `None` if there are no installations in the given map.
 fn closest_installation(
     p: Point,
     installations: &InstallationDataMap,
 ) -> Option<(&installation::Id, &InstallationData)> {
+    if let Some(inst) = installations.iter().find(|(_, data)| data.area.zone_contains(p)) {
+        return Some(inst);
+    }
     let mut iter = installations.iter();
     iter.next()
         .map(|first| {
@@ -261,10 +299,20 @@ fn installation_suitability_order(a: &InstallationData, b: &InstallationData) ->
 }
 
 /// Generate a new target within one of the given installations.
-fn generate_target<R>(mut rng: R, installations: &InstallationDataMap) -> Point
+///
+/// With a probability of `activity_weight`, the installation is chosen in proportion to its
+/// activity so that sounds follow the activity around the exhibition.
+fn generate_target<R>(mut rng: R, installations: &InstallationDataMap, activity_weight: f64) -> Point
 where
     R: Rng,
 {
+    // Only consult the rng when following activity so that existing soundscapes are unchanged.
+    if activity_weight > 0.0 && rng.gen::<f64>() < activity_weight {
+        if let Some(data) = choose_active_installation(&mut rng, installations) {
+            return generate_installation_target(rng, &data.area);
+        }
+    }
+
     // Collect references to installation data into a `Vec` that we can sort by target suitability.
     let mut vec: Vec<_> = installations.values().collect();
     vec.sort_by(|a, b| installation_suitability_order(a, b));
@@ -275,16 +323,48 @@ where
     generate_installation_target(rng, &data.area)
 }
 
+/// Randomly choose an installation in proportion to its activity.
+///
+/// Installations without any available sounds are only considered if all installations are full.
+///
+/// Returns `None` if none of the considered installations have any activity.
+fn choose_active_installation<R>(
+    mut rng: R,
+    installations: &InstallationDataMap,
+) -> Option<&InstallationData>
+where
+    R: Rng,
+{
+    let any_available = installations.values().any(|data| data.num_available_sounds > 0);
+    let candidates = || {
+        installations
+            .values()
+            .filter(move |data| !any_available || data.num_available_sounds > 0)
+    };
+    let total_activity: f32 = candidates().map(|data| data.activity.max(0.0)).sum();
+    if total_activity <= 0.0 {
+        return None;
+    }
+    let mut remaining = rng.gen::<f32>() * total_activity;
+    let mut chosen = None;
+    for data in candidates().filter(|data| data.activity > 0.0) {
+        chosen = Some(data);
+        remaining -= data.activity;
+        if remaining < 0.0 {
+            break;
+        }
+    }
+    chosen
+}
+
 /// Generate a target location within the given installation.
-fn generate_installation_target<R>(mut rng: R, installation_area: &super::Area) -> Point
+///
+/// If the installation has a zone, the target lies within it.
+fn generate_installation_target<R>(rng: R, installation_area: &super::Area) -> Point
 where
     R: Rng,
 {
-    let x_len = installation_area.bounding_rect.right - installation_area.bounding_rect.left;
-    let y_len = installation_area.bounding_rect.top - installation_area.bounding_rect.bottom;
-    let x = installation_area.bounding_rect.left + x_len * rng.gen::<f64>();
-    let y = installation_area.bounding_rect.bottom + y_len * rng.gen::<f64>();
-    Point2::new(x, y)
+    installation_area.random_point(rng)
 }
 
 /// Whether or not the current point has reached the target.
@@ -337,3 +417,58 @@ fn seek_force(
     let steering_limited = limit_magnitude(steering_force, max_force);
     steering_limited
 }
+
+#[test]
+fn choose_active_installation_by_activity() {
+    use nannou::rand::SeedableRng;
+    use super::{Area, BoundingRect};
+
+    let data = |activity: f32, num_available_sounds: usize| {
+        let bounding_rect = BoundingRect {
+            left: -1.0,
+            right: 1.0,
+            bottom: -1.0,
+            top: 1.0,
+        };
+        let area = Area {
+            bounding_rect,
+            centroid: Point::ZERO,
+            elevation: 0.0,
+            zone: None,
+        };
+        InstallationData {
+            area,
+            num_sounds_needed_to_reach_target: 0,
+            num_sounds_needed: 0,
+            num_available_sounds,
+            activity,
+        }
+    };
+    let mut rng = rand_xorshift::XorShiftRng::from_seed([1; 16]);
+    let mut installations = InstallationDataMap::default();
+    installations.insert(installation::Id(0), data(0.0, 1));
+    installations.insert(installation::Id(1), data(1.0, 1));
+    installations.insert(installation::Id(2), data(3.0, 0));
+
+    // Full installations are skipped while others have sounds available.
+    for _ in 0..100 {
+        let chosen = choose_active_installation(&mut rng, &installations).unwrap();
+        assert_eq!(chosen.activity, 1.0);
+    }
+
+    // Without any activity among the candidates there is nothing to follow.
+    installations.get_mut(&installation::Id(1)).unwrap().activity = 0.0;
+    assert!(choose_active_installation(&mut rng, &installations).is_none());
+
+    // Once all are full, installations are chosen in proportion to their activity.
+    installations.get_mut(&installation::Id(0)).unwrap().activity = 1.0;
+    for data in installations.values_mut() {
+        data.num_available_sounds = 0;
+    }
+    let draws = 4_000;
+    let busiest = (0..draws)
+        .filter(|_| choose_active_installation(&mut rng, &installations).unwrap().activity == 3.0)
+        .count();
+    let ratio = busiest as f64 / draws as f64;
+    assert!(ratio > 0.7 && ratio < 0.8);
+}
@@ -0,0 +1,194 @@
+use crate::audio;
+use nannou::glam;
+use nannou::rand::Rng;
+use std::time;
+use super::agent::{Agent, InstallationDataMap};
+
+// Flockmates closer than this fraction of the flock radius are steered away from.
+const SEPARATION_RADIUS_SCALE: f64 = 0.5;
+
+// The point and vector types in exhibition space.
+type Point = glam::DVec2;
+type Vector = glam::DVec2;
+
+/// A flocking movement kind, steering an agent in relation to its flockmates.
+///
+/// The flock applies the classic separation, alignment and cohesion forces of Craig Reynolds'
+/// "boids" on top of the agent's own target seeking force.
+#[derive(Debug)]
+pub struct Flock {
+    /// The agent steered by the flock.
+    pub agent: Agent,
+    /// The distance in metres within which other sounds are considered flockmates.
+    pub radius: f64,
+    /// The weight of the force steering away from flockmates that are too close.
+    pub separation: f64,
+    /// The weight of the force steering towards the average heading of flockmates.
+    pub alignment: f64,
+    /// The weight of the force steering towards the average location of flockmates.
+    pub cohesion: f64,
+    /// Whether the sound also flocks with sounds spawned via other sources that share a group.
+    pub groups: bool,
+}
+
+/// The state of a flocking sound that is relevant to its flockmates.
+#[derive(Copy, Clone, Debug)]
+pub struct Boid {
+    pub location: Point,
+    pub velocity: Vector,
+}
+
+impl Flock {
+    /// The current location and orientation of the flocking agent.
+    pub fn position(&self) -> audio::sound::Position {
+        self.agent.position()
+    }
+
+    /// The state of this sound that is relevant to its flockmates.
+    pub fn boid(&self) -> Boid {
+        Boid {
+            location: self.agent.location(),
+            velocity: self.agent.velocity(),
+        }
+    }
+
+    /// Produce the sum of the weighted separation, alignment and cohesion forces for the given
+    /// flockmates.
+    ///
+    /// Flockmates further than the flock's `radius` are ignored.
+    pub fn flocking_force<'a, I>(&self, flockmates: I) -> Vector
+    where
+        I: IntoIterator<Item = &'a Boid>,
+    {
+        let boid = self.boid();
+        let separation_radius = self.radius * SEPARATION_RADIUS_SCALE;
+        let mut away = Vector::ZERO;
+        let mut heading = Vector::ZERO;
+        let mut centre = Point::ZERO;
+        let mut count = 0;
+        for mate in flockmates {
+            let offset = boid.location - mate.location;
+            let distance = offset.length();
+            if distance > self.radius {
+                continue;
+            }
+            // Steer away more strongly from closer flockmates.
+            if distance > 0.0 && distance < separation_radius {
+                away += offset / (distance * distance);
+            }
+            heading += mate.velocity;
+            centre += mate.location;
+            count += 1;
+        }
+
+        if count == 0 {
+            return Vector::ZERO;
+        }
+
+        let separation = self.steer(away);
+        let alignment = self.steer(heading);
+        let cohesion = self.steer(centre / count as f64 - boid.location);
+        separation * self.separation + alignment * self.alignment + cohesion * self.cohesion
+    }
+
+    /// Update the flocking agent for the given past amount of time.
+    pub fn update<'a, R, I>(
+        &mut self,
+        rng: R,
+        delta_time: &time::Duration,
+        installations: &InstallationDataMap,
+        flockmates: I,
+    ) where
+        R: Rng,
+        I: IntoIterator<Item = &'a Boid>,
+    {
+        let force = self.flocking_force(flockmates);
+        self.agent.update_with_force(rng, delta_time, installations, force);
+    }
+
+    // The steering force that would turn the agent towards the given desired direction at its max
+    // speed, limited by its max force.
+    fn steer(&self, desired: Vector) -> Vector {
+        if desired == Vector::ZERO {
+            return Vector::ZERO;
+        }
+        let desired_velocity = desired.normalize() * self.agent.max_speed;
+        (desired_velocity - self.agent.velocity()).clamp_length_max(self.agent.max_force)
+    }
+}
+
+#[test]
+fn flocking_respects_max_force() {
+    use nannou::rand::SeedableRng;
+    use super::agent::InstallationData;
+    use super::{Area, BoundingRect};
+
+    let bounding_rect = BoundingRect {
+        left: -5.0,
+        right: 5.0,
+        bottom: -5.0,
+        top: 5.0,
+    };
+    let area = Area {
+        bounding_rect,
+        centroid: Point::ZERO,
+        elevation: 0.0,
+        zone: None,
+    };
+    let data = InstallationData {
+        area,
+        num_sounds_needed_to_reach_target: 0,
+        num_sounds_needed: 0,
+        num_available_sounds: 1,
+        activity: 0.0,
+    };
+    let mut installations = InstallationDataMap::default();
+    installations.insert(crate::installation::Id(0), data);
+
+    let mut rng = rand_xorshift::XorShiftRng::from_seed([1; 16]);
+    let max_force = 0.1;
+    let agent = Agent::generate(
+        &mut rng,
+        Point::ZERO,
+        &installations,
+        1.0,
+        max_force,
+        100.0,
+        false,
+        0.0,
+    );
+    let mut flock = Flock {
+        agent,
+        radius: 2.0,
+        separation: 5.0,
+        alignment: 5.0,
+        cohesion: 5.0,
+        groups: false,
+    };
+
+    // No flockmates within the radius produce no force.
+    let far = Boid { location: Point::new(4.0, 4.0), velocity: Vector::ZERO };
+    assert_eq!(flock.flocking_force(&[far]), Vector::ZERO);
+
+    // A close flockmate steers the agent away.
+    let near = Boid { location: Point::new(0.1, 0.0), velocity: flock.agent.velocity() };
+    flock.separation = 1.0;
+    flock.alignment = 0.0;
+    flock.cohesion = 0.0;
+    assert!(flock.flocking_force(&[near]).x < 0.0);
+
+    // However strongly weighted, the summed force never exceeds the agent's max force.
+    flock.alignment = 5.0;
+    flock.cohesion = 5.0;
+    let mates = [
+        near,
+        Boid { location: Point::new(-1.5, 0.5), velocity: Vector::new(0.0, -1.0) },
+        Boid { location: Point::new(0.5, 1.5), velocity: Vector::new(-1.0, 0.0) },
+    ];
+    let delta = time::Duration::from_millis(16);
+    for _ in 0..100 {
+        let velocity = flock.agent.velocity();
+        flock.update(&mut rng, &delta, &installations, &mates);
+        assert!((flock.agent.velocity() - velocity).length() <= max_force + 1e-9);
+    }
+}
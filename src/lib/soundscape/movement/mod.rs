@@ -1,12 +1,23 @@
 use audio;
 use metres::Metres;
 use nannou::prelude::*;
+use nannou::rand::Rng;
 
 pub use self::agent::Agent;
+pub use self::flock::Flock;
 pub use self::ngon::Ngon;
+pub use self::orbit::Orbit;
+pub use self::path::Path;
+pub use self::random_walk::RandomWalk;
 
 pub mod agent;
+pub mod flock;
 pub mod ngon;
+pub mod orbit;
+pub mod path;
+pub mod random_walk;
+pub mod spawn;
+pub mod zone;
 
 type Point2 = nannou::glam::DVec2;
 
@@ -26,6 +37,14 @@ pub enum Generative {
     Agent(Agent),
     /// A 2D N-sided, symmetrical polygon path tracing movement implementation.
     Ngon(Ngon),
+    /// Travels along a path of user-drawn waypoints.
+    Path(Path),
+    /// An agent steered in relation to its flockmates.
+    Flock(Flock),
+    /// A bounded random walk with some constant drift.
+    RandomWalk(RandomWalk),
+    /// Circles some centre point along an ellipse.
+    Orbit(Orbit),
 }
 
 /// The bounding box for an iterator yielding points.
@@ -38,10 +57,16 @@ pub struct BoundingRect {
 }
 
 /// Includes the bounding box and
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub struct Area {
     pub bounding_rect: BoundingRect,
     pub centroid: Point2,
+    /// The average elevation of the speakers within the area in metres.
+    pub elevation: f64,
+    /// The user-drawn polygon to which the area is restricted, if any.
+    ///
+    /// When present, the `bounding_rect` and `centroid` are those of the polygon.
+    pub zone: Option<Vec<Point2>>,
 }
 
 impl Generative {
@@ -50,18 +75,97 @@ impl Generative {
         match *self {
             Generative::Agent(ref agent) => agent.position(),
             Generative::Ngon(ref ngon) => ngon.position(),
+            Generative::Path(ref path) => path.position(),
+            Generative::Flock(ref flock) => flock.position(),
+            Generative::RandomWalk(ref walk) => walk.position(),
+            Generative::Orbit(ref orbit) => orbit.position(),
+        }
+    }
+
+    /// The agent steered by the movement, if any.
+    pub fn agent_mut(&mut self) -> Option<&mut Agent> {
+        match *self {
+            Generative::Agent(ref mut agent) => Some(agent),
+            Generative::Flock(ref mut flock) => Some(&mut flock.agent),
+            _ => None,
         }
     }
 }
 
 impl Movement {
     /// Determine the position and orientation of the sound.
+    ///
+    /// Generative movements are 2D and leave the elevation at `0.0`. The active sound applies the
+    /// elevation chosen from its source or that of its installation.
     pub fn position(&self) -> audio::sound::Position {
         match *self {
             Movement::Fixed(position) => position,
             Movement::Generative(ref generative) => generative.position(),
         }
     }
+
+    /// The agent steered by the movement, if any.
+    pub fn agent_mut(&mut self) -> Option<&mut Agent> {
+        match *self {
+            Movement::Generative(ref mut generative) => generative.agent_mut(),
+            Movement::Fixed(_) => None,
+        }
+    }
+}
+
+impl Area {
+    /// Whether or not the given point lies within the area.
+    pub fn contains(&self, p: Point2) -> bool {
+        match self.zone {
+            Some(ref zone) => zone::contains(zone, p),
+            None => self.bounding_rect.contains(p),
+        }
+    }
+
+    /// Whether or not the given point lies within the area's user-drawn zone.
+    ///
+    /// Always `false` for areas without a zone.
+    pub fn zone_contains(&self, p: Point2) -> bool {
+        self.zone.as_ref().map(|zone| zone::contains(zone, p)).unwrap_or(false)
+    }
+
+    /// The closest point to `p` that lies within the area.
+    pub fn clamp(&self, p: Point2) -> Point2 {
+        match self.zone {
+            Some(ref zone) if !zone::contains(zone, p) => zone::closest_point(zone, p),
+            Some(_) => p,
+            None => self.bounding_rect.clamp(p),
+        }
+    }
+
+    /// A uniformly distributed random point within the area.
+    ///
+    /// Points within a zone are found by rejection sampling its bounding rect. If no point is
+    /// found within a reasonable number of attempts, the closest point within the zone is used.
+    pub fn random_point<R>(&self, mut rng: R) -> Point2
+    where
+        R: Rng,
+    {
+        let rect = &self.bounding_rect;
+        let mut gen_point = || {
+            let x = rect.left + rect.width() * rng.gen::<f64>();
+            let y = rect.bottom + rect.height() * rng.gen::<f64>();
+            Point2::new(x, y)
+        };
+        match self.zone {
+            None => gen_point(),
+            Some(ref zone) => {
+                let mut point = gen_point();
+                for _ in 1..zone::MAX_SAMPLE_ATTEMPTS {
+                    if zone::contains(zone, point) {
+                        return point;
+                    }
+                    point = gen_point();
+                }
+                self.clamp(point)
+            }
+        }
+    }
 }
 
 impl BoundingRect {
@@ -106,6 +210,19 @@ impl BoundingRect {
         Point2::new(x, y)
     }
 
+    /// Whether or not the given point lies within the bounding box.
+    pub fn contains(&self, p: Point2) -> bool {
+        p.x >= self.left && p.x <= self.right && p.y >= self.bottom && p.y <= self.top
+    }
+
+    /// The closest point to `p` that lies within the bounding box.
+    pub fn clamp(&self, p: Point2) -> Point2 {
+        Point2::new(
+            p.x.max(self.left).min(self.right),
+            p.y.max(self.bottom).min(self.top),
+        )
+    }
+
     pub fn width(&self) -> Metres {
         self.right - self.left
     }
@@ -0,0 +1,144 @@
+use audio;
+use audio::source::movement::OrbitCentre;
+use nannou::glam::DVec2 as Vector2;
+use std::f64::consts::PI;
+use std::time;
+use super::Area;
+use utils::duration_to_secs;
+
+// The point and vector types in exhibition space.
+type Point = Vector2;
+type Vector = Vector2;
+
+/// An elliptical orbit movement implementation.
+///
+/// The centre of the orbit is resolved against the installation area upon each update so that the
+/// orbit follows changes to the installation's speakers.
+#[derive(Debug)]
+pub struct Orbit {
+    /// The point around which the sound orbits.
+    pub centre: OrbitCentre,
+    /// The radius of the orbit's major axis in metres.
+    pub radius: f64,
+    /// The rate of rotation in radians per second. Negative values rotate clockwise.
+    pub angular_speed: f64,
+    /// The eccentricity of the orbit's ellipse, where `0.0` is a circle.
+    pub eccentricity: f64,
+    /// The rotation of the ellipse's major axis in radians.
+    rotation: f64,
+    /// The current angle of the sound around the ellipse in radians.
+    phase: f64,
+    /// The current position of the sound, clamped to the installation area.
+    position: audio::sound::Position,
+}
+
+impl Orbit {
+    /// Create a new **Orbit** movement type.
+    pub fn new(
+        centre: OrbitCentre,
+        radius: f64,
+        angular_speed: f64,
+        eccentricity: f64,
+        rotation: f64,
+        phase: f64,
+        area: &Area,
+    ) -> Self {
+        let point = Point::ZERO;
+        let radians = 0.0;
+        let elevation = 0.0;
+        let position = audio::sound::Position { point, radians, elevation };
+        let mut orbit = Orbit {
+            centre,
+            radius,
+            angular_speed,
+            eccentricity,
+            rotation,
+            phase,
+            position,
+        };
+        orbit.update_position(area);
+        orbit
+    }
+
+    /// The current location and orientation of the sound.
+    ///
+    /// The sound faces the direction in which it is travelling around the orbit.
+    pub fn position(&self) -> audio::sound::Position {
+        self.position
+    }
+
+    /// Update the orbit for the given past amount of time, keeping it within the given area.
+    pub fn update(&mut self, delta_time: &time::Duration, area: &Area) {
+        self.phase += self.angular_speed * duration_to_secs(delta_time);
+        self.phase %= 2.0 * PI;
+        self.update_position(area);
+    }
+
+    // Update the position for the current phase, clamping it to the area.
+    fn update_position(&mut self, area: &Area) {
+        let rect = &area.bounding_rect;
+        let centre = match self.centre {
+            OrbitCentre::Installation => area.centroid,
+            OrbitCentre::Fixed(p) => Point::new(
+                rect.left + rect.width() * p.x,
+                rect.bottom + rect.height() * p.y,
+            ),
+        };
+        let (major, minor) = self.radii();
+        let (sin, cos) = self.phase.sin_cos();
+        let point = centre + rotate(Vector::new(major * cos, minor * sin), self.rotation);
+        let point = area.clamp(point);
+
+        // The tangent to the ellipse in the direction of rotation.
+        let tangent = Vector::new(-major * sin, minor * cos) * self.angular_speed.signum();
+        let tangent = rotate(tangent, self.rotation);
+        self.position.point = point;
+        self.position.radians = tangent.y.atan2(tangent.x) as f32;
+    }
+
+    // The radii of the major and minor axes of the orbit's ellipse.
+    fn radii(&self) -> (f64, f64) {
+        let eccentricity = self.eccentricity.max(0.0).min(1.0);
+        let minor = self.radius * (1.0 - eccentricity * eccentricity).sqrt();
+        (self.radius, minor)
+    }
+}
+
+// Rotate the given vector anti-clockwise by the given radians.
+fn rotate(v: Vector, radians: f64) -> Vector {
+    let (sin, cos) = radians.sin_cos();
+    Vector::new(v.x * cos - v.y * sin, v.x * sin + v.y * cos)
+}
+
+#[test]
+fn orbit_stays_within_area() {
+    use super::BoundingRect;
+    let bounding_rect = BoundingRect {
+        left: -1.0,
+        right: 1.0,
+        bottom: -1.0,
+        top: 1.0,
+    };
+    let area = Area {
+        bounding_rect,
+        centroid: Point::ZERO,
+        elevation: 0.0,
+        zone: None,
+    };
+
+    // A circular orbit within the area is unaffected by the bounds.
+    let mut orbit = Orbit::new(OrbitCentre::Installation, 0.5, PI, 0.0, 0.0, 0.0, &area);
+    assert_eq!(orbit.position().point, Point::new(0.5, 0.0));
+    orbit.update(&time::Duration::from_millis(500), &area);
+    assert!(orbit.position().point.abs_diff_eq(Point::new(0.0, 0.5), 1e-9));
+
+    // Orbits that exceed the area are clamped to its bounds.
+    let centre = OrbitCentre::Fixed(Point::new(1.0, 0.5));
+    let mut orbit = Orbit::new(centre, 4.0, 1.0, 0.9, 0.3, 0.0, &area);
+    for _ in 0..100 {
+        orbit.update(&time::Duration::from_millis(100), &area);
+        let p = orbit.position().point;
+        assert!(p.x >= bounding_rect.left && p.x <= bounding_rect.right);
+        assert!(p.y >= bounding_rect.bottom && p.y <= bounding_rect.top);
+    }
+}
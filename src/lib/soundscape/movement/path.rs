@@ -0,0 +1,217 @@
+use audio;
+use audio::source::movement::{PathMode, SPLINE_SUBDIVISIONS};
+use nannou::glam::DVec2 as Vector2;
+use std::time;
+use utils::duration_to_secs;
+
+// The point type in exhibition space.
+type Point = Vector2;
+
+/// A movement implementation that travels along a path of user-drawn waypoints.
+///
+/// The waypoints are sampled into a polyline upon creation so that the path may be travelled at a
+/// constant speed, regardless of whether the path is traced with straight lines or a spline.
+#[derive(Debug)]
+pub struct Path {
+    /// The rate at which the path is being travelled in metres per second.
+    pub speed: f64,
+    /// What happens when the end of the path is reached.
+    pub mode: PathMode,
+    /// The polyline sampled from the waypoints.
+    vertices: Vec<Point>,
+    /// The distance along the path at which each vertex lies.
+    distances: Vec<f64>,
+    /// The total distance travelled since the sound started.
+    ///
+    /// This is folded into a distance along the path depending on the `mode`.
+    travelled: f64,
+}
+
+impl Path {
+    /// Create a new **Path** movement type.
+    ///
+    /// `start` describes the normalised distance along the path at which the sound starts.
+    pub fn new(points: &[Point], spline: bool, mode: PathMode, speed: f64, start: f64) -> Self {
+        let vertices = polyline(points, spline, mode == PathMode::Loop);
+        let distances = cumulative_distances(&vertices);
+        let travelled = start * total_distance(&distances);
+        Path {
+            speed,
+            mode,
+            vertices,
+            distances,
+            travelled,
+        }
+    }
+
+    /// Replace the waypoints of the path, preserving the sound's progress along it.
+    ///
+    /// If there are no waypoints, the sound remains at its current position.
+    pub fn set_points(&mut self, points: &[Point], spline: bool) {
+        let current = self.position().point;
+        let total = total_distance(&self.distances);
+        let vertices = match points.is_empty() {
+            true => vec![current],
+            false => polyline(points, spline, self.mode == PathMode::Loop),
+        };
+        let distances = cumulative_distances(&vertices);
+        let new_total = total_distance(&distances);
+        if total > 0.0 {
+            self.travelled *= new_total / total;
+        }
+        self.vertices = vertices;
+        self.distances = distances;
+    }
+
+    /// The current position along the path.
+    pub fn position(&self) -> audio::sound::Position {
+        let point = self.point();
+        let radians = 0.0;
+        let elevation = 0.0;
+        audio::sound::Position { point, radians, elevation }
+    }
+
+    /// Update the `Path` state for the given past amount of time.
+    pub fn update(&mut self, delta_time: &time::Duration) {
+        self.travelled += self.speed * duration_to_secs(delta_time);
+    }
+
+    // The distance along the path described by the distance travelled so far.
+    fn distance(&self) -> f64 {
+        let total = total_distance(&self.distances);
+        if total <= 0.0 {
+            return 0.0;
+        }
+        match self.mode {
+            PathMode::Loop => self.travelled % total,
+            PathMode::Once => self.travelled.min(total),
+            PathMode::PingPong => {
+                let phase = self.travelled % (total * 2.0);
+                if phase <= total {
+                    phase
+                } else {
+                    total * 2.0 - phase
+                }
+            }
+        }
+    }
+
+    // The point at the current distance along the path.
+    fn point(&self) -> Point {
+        let distance = self.distance();
+        let end = self
+            .distances
+            .iter()
+            .position(|&d| d > distance)
+            .unwrap_or(self.distances.len());
+        if end == 0 {
+            return self.vertices.first().cloned().unwrap_or(Point::ZERO);
+        }
+        if end == self.vertices.len() {
+            return self.vertices[end - 1];
+        }
+        let start = end - 1;
+        let segment = self.distances[end] - self.distances[start];
+        let lerp = (distance - self.distances[start]) / segment;
+        self.vertices[start].lerp(self.vertices[end], lerp)
+    }
+}
+
+/// Produce the polyline traced through the given waypoints.
+///
+/// If `spline` is `true`, the polyline is sampled from a Catmull-Rom spline passing through every
+/// waypoint. If `closed` is `true`, the polyline returns from the last waypoint to the first.
+pub fn polyline(points: &[Point], spline: bool, closed: bool) -> Vec<Point> {
+    let mut vertices: Vec<Point> = points.to_vec();
+    if points.len() < 2 {
+        return vertices;
+    }
+    if closed {
+        vertices.push(points[0]);
+    }
+    if !spline {
+        return vertices;
+    }
+
+    // The neighbouring control point, wrapping around closed paths and clamping open ones.
+    let n = points.len() as isize;
+    let control_point = |i: isize| -> Point {
+        if closed {
+            points[i.rem_euclid(n) as usize]
+        } else {
+            points[i.max(0).min(n - 1) as usize]
+        }
+    };
+
+    let segments = vertices.len() - 1;
+    let mut spline_vertices = Vec::with_capacity(segments * SPLINE_SUBDIVISIONS + 1);
+    for i in 0..segments as isize {
+        let p0 = control_point(i - 1);
+        let p1 = control_point(i);
+        let p2 = control_point(i + 1);
+        let p3 = control_point(i + 2);
+        for step in 0..SPLINE_SUBDIVISIONS {
+            let t = step as f64 / SPLINE_SUBDIVISIONS as f64;
+            spline_vertices.push(catmull_rom(p0, p1, p2, p3, t));
+        }
+    }
+    spline_vertices.push(vertices[segments]);
+    spline_vertices
+}
+
+// Interpolate between `p1` and `p2` along a uniform Catmull-Rom spline.
+fn catmull_rom(p0: Point, p1: Point, p2: Point, p3: Point, t: f64) -> Point {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    (p1 * 2.0
+        + (p2 - p0) * t
+        + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2
+        + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3)
+        * 0.5
+}
+
+// The distance along the polyline at which each vertex lies.
+fn cumulative_distances(vertices: &[Point]) -> Vec<f64> {
+    let mut total = 0.0;
+    let mut distances = Vec::with_capacity(vertices.len());
+    for (i, &v) in vertices.iter().enumerate() {
+        if i > 0 {
+            total += vertices[i - 1].distance(v);
+        }
+        distances.push(total);
+    }
+    distances
+}
+
+fn total_distance(distances: &[f64]) -> f64 {
+    distances.last().cloned().unwrap_or(0.0)
+}
+
+#[test]
+fn path_modes() {
+    let points = [Point::new(0.0, 0.0), Point::new(4.0, 0.0)];
+    let second = time::Duration::from_secs(1);
+
+    // Ping pong travels back towards the start after reaching the end.
+    let mut path = Path::new(&points, false, PathMode::PingPong, 3.0, 0.0);
+    path.update(&second);
+    assert_eq!(path.position().point, Point::new(3.0, 0.0));
+    path.update(&second);
+    assert_eq!(path.position().point, Point::new(2.0, 0.0));
+
+    // Once stops at the end.
+    let mut path = Path::new(&points, false, PathMode::Once, 3.0, 0.0);
+    path.update(&second);
+    path.update(&second);
+    assert_eq!(path.position().point, Point::new(4.0, 0.0));
+
+    // Loop returns to the first point, closing the path.
+    let mut path = Path::new(&points, false, PathMode::Loop, 5.0, 0.0);
+    path.update(&second);
+    assert_eq!(path.position().point, Point::new(3.0, 0.0));
+
+    // Splines pass through every waypoint.
+    let vertices = polyline(&points, true, false);
+    assert_eq!(vertices.first(), Some(&points[0]));
+    assert_eq!(vertices.last(), Some(&points[1]));
+}
@@ -0,0 +1,150 @@
+use audio;
+use nannou::glam::DVec2 as Vector2;
+use nannou::rand::Rng;
+use std::f64::consts::PI;
+use std::time;
+use super::{Area, BoundingRect};
+use utils::duration_to_secs;
+
+// The duration of each step of the walk in seconds.
+//
+// Each step is travelled at a constant velocity so that the walk appears continuous.
+const STEP_INTERVAL: f64 = 0.5;
+
+// The point and vector types in exhibition space.
+type Point = Vector2;
+type Vector = Vector2;
+
+/// A bounded random walk movement implementation.
+///
+/// With each step the sound travels a fixed distance in a random direction, while drifting in a
+/// constant direction. The walk is reflected off the edges of the installation's bounding rect and
+/// turned back at the edges of its zone, if it has one.
+#[derive(Debug)]
+pub struct RandomWalk {
+    /// The distance in metres travelled in a random direction with each step.
+    pub step: f64,
+    /// The velocity in metres per second at which the walk drifts.
+    pub drift: Vector,
+    /// The current location of the sound.
+    point: Point,
+    /// The velocity at which the current step is being travelled.
+    step_velocity: Vector,
+    /// The time remaining in seconds until the next step begins.
+    step_remaining: f64,
+}
+
+impl RandomWalk {
+    /// Create a new **RandomWalk** movement type starting at the given point.
+    ///
+    /// The first step begins upon the first call to `update`.
+    pub fn new(point: Point, step: f64, drift: Vector) -> Self {
+        RandomWalk {
+            step,
+            drift,
+            point,
+            step_velocity: Vector::ZERO,
+            step_remaining: 0.0,
+        }
+    }
+
+    /// The current location and orientation of the sound.
+    ///
+    /// The sound faces the direction of the current step.
+    pub fn position(&self) -> audio::sound::Position {
+        let point = self.point;
+        let velocity = self.step_velocity + self.drift;
+        let radians = velocity.y.atan2(velocity.x) as f32;
+        let elevation = 0.0;
+        audio::sound::Position { point, radians, elevation }
+    }
+
+    /// Update the walk for the given past amount of time, keeping it within the given area.
+    pub fn update<R>(&mut self, mut rng: R, delta_time: &time::Duration, area: &Area)
+    where
+        R: Rng,
+    {
+        let mut remaining = duration_to_secs(delta_time);
+        while remaining > 0.0 {
+            if self.step_remaining <= 0.0 {
+                let radians = rng.gen::<f64>() * 2.0 * PI;
+                let direction = Vector::new(radians.cos(), radians.sin());
+                self.step_velocity = direction * self.step / STEP_INTERVAL;
+                self.step_remaining += STEP_INTERVAL;
+            }
+            let secs = remaining.min(self.step_remaining);
+            let previous = self.point;
+            self.point += (self.step_velocity + self.drift) * secs;
+            self.step_remaining -= secs;
+            remaining -= secs;
+            self.reflect(&area.bounding_rect);
+            self.turn_back(previous, area);
+        }
+    }
+
+    // Turn the walk back if it has left the area, returning it to its previous point.
+    //
+    // If the previous point was also outside the area, the walk is moved to the closest point
+    // within it instead.
+    fn turn_back(&mut self, previous: Point, area: &Area) {
+        if area.contains(self.point) {
+            return;
+        }
+        self.point = if area.contains(previous) { previous } else { area.clamp(self.point) };
+        self.step_velocity = -self.step_velocity;
+        self.drift = -self.drift;
+    }
+
+    // Reflect the walk off the edges of the given bounds.
+    //
+    // The drift is reflected along with the step so that the walk does not collect at an edge.
+    fn reflect(&mut self, bounds: &BoundingRect) {
+        if self.point.x < bounds.left || self.point.x > bounds.right {
+            let edge = if self.point.x < bounds.left { bounds.left } else { bounds.right };
+            self.point.x = (edge * 2.0 - self.point.x).max(bounds.left).min(bounds.right);
+            self.step_velocity.x = -self.step_velocity.x;
+            self.drift.x = -self.drift.x;
+        }
+        if self.point.y < bounds.bottom || self.point.y > bounds.top {
+            let edge = if self.point.y < bounds.bottom { bounds.bottom } else { bounds.top };
+            self.point.y = (edge * 2.0 - self.point.y).max(bounds.bottom).min(bounds.top);
+            self.step_velocity.y = -self.step_velocity.y;
+            self.drift.y = -self.drift.y;
+        }
+    }
+}
+
+#[test]
+fn random_walk_stays_within_bounds() {
+    use nannou::rand::SeedableRng;
+    let bounds = BoundingRect {
+        left: -1.0,
+        right: 1.0,
+        bottom: -1.0,
+        top: 1.0,
+    };
+    let mut area = Area {
+        bounding_rect: bounds,
+        centroid: Point::ZERO,
+        elevation: 0.0,
+        zone: None,
+    };
+    let mut rng = rand_xorshift::XorShiftRng::from_seed([1; 16]);
+    let mut walk = RandomWalk::new(Point::ZERO, 0.75, Vector::new(1.0, 0.5));
+    let delta = time::Duration::from_millis(16);
+    for _ in 0..1000 {
+        walk.update(&mut rng, &delta, &area);
+        let p = walk.position().point;
+        assert!(p.x >= bounds.left && p.x <= bounds.right);
+        assert!(p.y >= bounds.bottom && p.y <= bounds.top);
+    }
+
+    // A triangular zone within the same bounds.
+    let zone = vec![Point::new(-1.0, -1.0), Point::new(1.0, -1.0), Point::new(-1.0, 1.0)];
+    area.zone = Some(zone);
+    let mut walk = RandomWalk::new(Point::new(-0.5, -0.5), 0.75, Vector::new(1.0, 0.5));
+    for _ in 0..1000 {
+        walk.update(&mut rng, &delta, &area);
+        assert!(area.contains(walk.position().point));
+    }
+}
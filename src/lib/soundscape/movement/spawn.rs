@@ -0,0 +1,232 @@
+//! Strategies for choosing the initial position of a sound within its installation.
+
+use super::{zone, Area, BoundingRect};
+use crate::audio;
+use crate::audio::source::Spawn;
+use nannou::glam;
+use nannou::rand::Rng;
+
+// The number of candidates considered when searching for a Poisson-disc spaced location.
+const POISSON_DISC_CANDIDATES: usize = 30;
+
+// The maximum distance in metres from the least loaded speaker at which a sound may be spawned.
+const SPEAKER_RADIUS: f64 = 0.5;
+
+// The point type in exhibition space.
+type Point = glam::DVec2;
+
+/// Generate the initial position of a sound within the given installation area.
+///
+/// - `speakers` are the locations of the speakers assigned to the installation.
+/// - `sounds` are the locations of all currently active sounds.
+pub fn position<R>(
+    mut rng: R,
+    spawn: &Spawn,
+    area: &Area,
+    speakers: &[Point],
+    sounds: &[Point],
+) -> audio::sound::Position
+where
+    R: Rng,
+{
+    let point = match *spawn {
+        Spawn::Random => random_point(&mut rng, area),
+        Spawn::LeastLoadedSpeaker => least_loaded_speaker_point(&mut rng, area, speakers, sounds),
+        Spawn::PoissonDisc { min_distance } => {
+            poisson_disc_point(&mut rng, area, sounds, min_distance)
+        }
+        Spawn::Perimeter => match area.zone {
+            Some(ref zone) => zone_perimeter_point(&mut rng, zone),
+            None => perimeter_point(&mut rng, &area.bounding_rect),
+        },
+        Spawn::Anchor(anchor) => area.clamp(anchor_point(&area.bounding_rect, anchor)),
+    };
+    let radians = rng.gen::<f32>() * 2.0 * ::std::f32::consts::PI;
+    let elevation = area.elevation;
+    audio::sound::Position { point, radians, elevation }
+}
+
+/// A random point around the centroid of the installation.
+///
+/// The point is equally likely to fall on either side of the centroid on each axis, regardless of
+/// where the centroid lies within the bounding rect.
+///
+/// If the area has a zone, points are drawn until one lies within the zone.
+pub fn random_point<R>(mut rng: R, area: &Area) -> Point
+where
+    R: Rng,
+{
+    let zone = match area.zone {
+        None => return point_around_centroid(rng, area),
+        Some(ref zone) => zone,
+    };
+    let mut point = point_around_centroid(&mut rng, area);
+    for _ in 1..zone::MAX_SAMPLE_ATTEMPTS {
+        if zone::contains(zone, point) {
+            return point;
+        }
+        point = point_around_centroid(&mut rng, area);
+    }
+    area.clamp(point)
+}
+
+// A random point around the centroid within the area's bounding rect.
+fn point_around_centroid<R>(mut rng: R, area: &Area) -> Point
+where
+    R: Rng,
+{
+    let rect = &area.bounding_rect;
+    let left: bool = rng.gen();
+    let x_mag: f64 = rng.gen();
+    let x_edge = if left { rect.left } else { rect.right };
+    let x = area.centroid.x + (x_edge - area.centroid.x) * x_mag;
+    let down: bool = rng.gen();
+    let y_mag: f64 = rng.gen();
+    let y_edge = if down { rect.bottom } else { rect.top };
+    let y = area.centroid.y + (y_edge - area.centroid.y) * y_mag;
+    Point::new(x, y)
+}
+
+// A point near the speaker with the fewest sounds closest to it.
+//
+// Ties are broken randomly so that sounds are spread evenly between equally loaded speakers.
+fn least_loaded_speaker_point<R>(mut rng: R, area: &Area, speakers: &[Point], sounds: &[Point]) -> Point
+where
+    R: Rng,
+{
+    if speakers.is_empty() {
+        return random_point(rng, area);
+    }
+
+    // Count the sounds for which each speaker is the closest.
+    let mut loads = vec![0; speakers.len()];
+    for &sound in sounds {
+        if let Some(i) = closest_index(speakers, sound) {
+            loads[i] += 1;
+        }
+    }
+
+    // Randomly choose one of the least loaded speakers.
+    let min_load = loads.iter().cloned().min().unwrap_or(0);
+    let num_least_loaded = loads.iter().filter(|&&load| load == min_load).count();
+    let nth = rng.gen_range(0, num_least_loaded);
+    let index = loads
+        .iter()
+        .enumerate()
+        .filter(|&(_, &load)| load == min_load)
+        .nth(nth)
+        .map(|(i, _)| i)
+        .expect("no least loaded speaker");
+
+    // Offset the point from the speaker so that sounds spawned at the same speaker don't overlap.
+    let radians = rng.gen::<f64>() * 2.0 * ::std::f64::consts::PI;
+    let radius = rng.gen::<f64>().sqrt() * SPEAKER_RADIUS;
+    let point = speakers[index] + Point::new(radians.cos(), radians.sin()) * radius;
+    match area.zone {
+        Some(ref zone) if !zone::contains(zone, point) => zone::closest_point(zone, point),
+        _ => point,
+    }
+}
+
+// A random point at least `min_distance` from all other sounds.
+//
+// If none of the candidates satisfy the distance, the one furthest from all other sounds is used.
+fn poisson_disc_point<R>(mut rng: R, area: &Area, sounds: &[Point], min_distance: f64) -> Point
+where
+    R: Rng,
+{
+    let mut best = None;
+    for _ in 0..POISSON_DISC_CANDIDATES {
+        let candidate = random_point(&mut rng, area);
+        let distance = sounds
+            .iter()
+            .map(|sound| sound.distance(candidate))
+            .fold(::std::f64::INFINITY, f64::min);
+        if distance >= min_distance {
+            return candidate;
+        }
+        match best {
+            Some((_, best_distance)) if best_distance >= distance => (),
+            _ => best = Some((candidate, distance)),
+        }
+    }
+    best.map(|(point, _)| point).unwrap_or(area.centroid)
+}
+
+// A random point on the perimeter of the given bounding rect.
+//
+// Points are distributed evenly along the length of the perimeter.
+fn perimeter_point<R>(mut rng: R, rect: &BoundingRect) -> Point
+where
+    R: Rng,
+{
+    let w = rect.width();
+    let h = rect.height();
+    let mut d = rng.gen::<f64>() * (w + h) * 2.0;
+    if d < w {
+        return Point::new(rect.left + d, rect.bottom);
+    }
+    d -= w;
+    if d < h {
+        return Point::new(rect.right, rect.bottom + d);
+    }
+    d -= h;
+    if d < w {
+        return Point::new(rect.right - d, rect.top);
+    }
+    d -= w;
+    Point::new(rect.left, rect.top - d.min(h))
+}
+
+// A random point on the perimeter of the given zone.
+//
+// Points are distributed evenly along the length of the zone's edges.
+fn zone_perimeter_point<R>(mut rng: R, zone: &[Point]) -> Point
+where
+    R: Rng,
+{
+    let distance = rng.gen::<f64>() * zone::perimeter(zone);
+    zone::point_along_perimeter(zone, distance).expect("zone has no vertices")
+}
+
+// The anchor point normalised to the given bounding rect.
+fn anchor_point(rect: &BoundingRect, anchor: Point) -> Point {
+    let x = rect.left + rect.width() * anchor.x;
+    let y = rect.bottom + rect.height() * anchor.y;
+    Point::new(x, y)
+}
+
+// The index of the point closest to the given point.
+fn closest_index(points: &[Point], point: Point) -> Option<usize> {
+    points
+        .iter()
+        .map(|p| p.distance_squared(point))
+        .enumerate()
+        .fold(None, |closest, (i, d)| match closest {
+            Some((_, closest_d)) if closest_d <= d => closest,
+            _ => Some((i, d)),
+        })
+        .map(|(i, _)| i)
+}
+
+#[test]
+fn perimeter_and_anchor_points() {
+    use nannou::rand::SeedableRng;
+    let rect = BoundingRect {
+        left: -2.0,
+        right: 2.0,
+        bottom: -1.0,
+        top: 1.0,
+    };
+    let mut rng = rand_xorshift::XorShiftRng::from_seed([1; 16]);
+    for _ in 0..100 {
+        let p = perimeter_point(&mut rng, &rect);
+        let on_x_edge = p.x == rect.left || p.x == rect.right;
+        let on_y_edge = p.y == rect.bottom || p.y == rect.top;
+        assert!(on_x_edge || on_y_edge);
+        assert!(p.x >= rect.left && p.x <= rect.right);
+        assert!(p.y >= rect.bottom && p.y <= rect.top);
+    }
+    assert_eq!(anchor_point(&rect, Point::new(0.5, 0.5)), Point::new(0.0, 0.0));
+    assert_eq!(anchor_point(&rect, Point::new(0.0, 1.0)), Point::new(-2.0, 1.0));
+}
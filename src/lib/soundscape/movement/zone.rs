@@ -0,0 +1,139 @@
+//! Geometry for the user-drawn polygon zones to which installation areas may be restricted.
+//!
+//! Zones are simple polygons described by their vertices in exhibition space. The polygon is
+//! implicitly closed between the last and first vertices.
+
+use nannou::glam;
+
+/// The minimum number of vertices required for a zone to describe an area.
+pub const MIN_VERTICES: usize = 3;
+
+/// The number of attempts made to find a random point within a zone before giving up.
+pub const MAX_SAMPLE_ATTEMPTS: usize = 64;
+
+// The point type in exhibition space.
+type Point = glam::DVec2;
+
+/// Whether or not the given vertices describe a zone.
+pub fn is_valid(zone: &[Point]) -> bool {
+    zone.len() >= MIN_VERTICES
+}
+
+/// The edges of the zone as pairs of vertices, including the closing edge.
+pub fn edges<'a>(zone: &'a [Point]) -> impl Iterator<Item = (Point, Point)> + 'a {
+    let closing = zone.last().cloned();
+    closing
+        .into_iter()
+        .chain(zone.iter().cloned())
+        .zip(zone.iter().cloned())
+}
+
+/// Whether or not the given point lies within the zone.
+///
+/// Uses the even-odd rule, so self-intersecting zones are treated as having holes.
+pub fn contains(zone: &[Point], p: Point) -> bool {
+    let mut inside = false;
+    for (a, b) in edges(zone) {
+        if (a.y > p.y) != (b.y > p.y) {
+            let x = a.x + (p.y - a.y) / (b.y - a.y) * (b.x - a.x);
+            if p.x < x {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+/// The area-weighted centroid of the zone.
+///
+/// Falls back to the average of the vertices for degenerate zones with no area.
+///
+/// Returns `None` if the zone has no vertices.
+pub fn centroid(zone: &[Point]) -> Option<Point> {
+    if zone.is_empty() {
+        return None;
+    }
+    let mut twice_area = 0.0;
+    let mut sum = Point::ZERO;
+    for (a, b) in edges(zone) {
+        let cross = a.x * b.y - b.x * a.y;
+        twice_area += cross;
+        sum += (a + b) * cross;
+    }
+    if twice_area.abs() <= ::std::f64::EPSILON {
+        let sum = zone.iter().fold(Point::ZERO, |acc, &p| acc + p);
+        return Some(sum / zone.len() as f64);
+    }
+    Some(sum / (3.0 * twice_area))
+}
+
+/// The point on the edges of the zone closest to the given point.
+///
+/// Returns `p` if the zone has no vertices.
+pub fn closest_point(zone: &[Point], p: Point) -> Point {
+    edges(zone)
+        .map(|(a, b)| closest_point_on_segment(a, b, p))
+        .fold(None, |closest: Option<Point>, q| match closest {
+            Some(c) if c.distance_squared(p) <= q.distance_squared(p) => Some(c),
+            _ => Some(q),
+        })
+        .unwrap_or(p)
+}
+
+/// The total length of the zone's edges in metres.
+pub fn perimeter(zone: &[Point]) -> f64 {
+    edges(zone).map(|(a, b)| a.distance(b)).sum()
+}
+
+/// The point at the given distance along the zone's edges, starting from the first vertex.
+///
+/// Returns `None` if the zone has no vertices.
+pub fn point_along_perimeter(zone: &[Point], mut distance: f64) -> Option<Point> {
+    let first = *zone.first()?;
+    for (a, b) in zone.iter().cloned().zip(zone.iter().cloned().skip(1).chain(Some(first))) {
+        let len = a.distance(b);
+        if distance <= len && len > 0.0 {
+            return Some(a.lerp(b, distance / len));
+        }
+        distance -= len;
+    }
+    Some(first)
+}
+
+// The point on the segment between `a` and `b` closest to `p`.
+fn closest_point_on_segment(a: Point, b: Point, p: Point) -> Point {
+    let ab = b - a;
+    let len_squared = ab.length_squared();
+    if len_squared == 0.0 {
+        return a;
+    }
+    let t = ((p - a).dot(ab) / len_squared).max(0.0).min(1.0);
+    a + ab * t
+}
+
+#[test]
+fn l_shaped_zone() {
+    // An L-shaped room with the corridor to the top left excluded.
+    let zone = [
+        Point::new(0.0, 0.0),
+        Point::new(4.0, 0.0),
+        Point::new(4.0, 4.0),
+        Point::new(2.0, 4.0),
+        Point::new(2.0, 2.0),
+        Point::new(0.0, 2.0),
+    ];
+    assert!(contains(&zone, Point::new(1.0, 1.0)));
+    assert!(contains(&zone, Point::new(3.0, 3.0)));
+    assert!(!contains(&zone, Point::new(1.0, 3.0)));
+    assert!(!contains(&zone, Point::new(5.0, 1.0)));
+
+    let c = centroid(&zone).unwrap();
+    assert!(c.abs_diff_eq(Point::new(7.0 / 3.0, 5.0 / 3.0), 1e-9));
+
+    let p = closest_point(&zone, Point::new(1.0, 3.0));
+    assert!(p.abs_diff_eq(Point::new(1.0, 2.0), 1e-9) || p.abs_diff_eq(Point::new(2.0, 3.0), 1e-9));
+
+    assert_eq!(perimeter(&zone), 16.0);
+    assert_eq!(point_along_perimeter(&zone, 5.0), Some(Point::new(4.0, 1.0)));
+    assert_eq!(point_along_perimeter(&zone, 16.0), Some(Point::new(0.0, 0.0)));
+}
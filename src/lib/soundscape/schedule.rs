@@ -0,0 +1,292 @@
+//! Items related to the soundscape's weekly schedule.
+//!
+//! A schedule is a list of periods, each of which describes a range of hours on one or more days
+//! of the week. While a period is active it may override the number of simultaneous sounds per
+//! installation, the master volume and which groups may be used to spawn sounds. Overrides are
+//! crossfaded in and out at the boundaries of each period.
+
+use chrono::{self, Datelike, Timelike};
+use fxhash::FxHashSet;
+use soundscape::group;
+use std::time;
+use time_calc::Ms;
+use utils::Range;
+
+/// The number of days within the week.
+pub const DAYS_PER_WEEK: usize = 7;
+
+/// Short names for each day of the week, starting from Monday.
+pub const DAY_NAMES: [&'static str; DAYS_PER_WEEK] =
+    ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+const HOUR_SECS: f64 = 60.0 * 60.0;
+const DAY_SECS: f64 = HOUR_SECS * 24.0;
+const WEEK_SECS: f64 = DAY_SECS * DAYS_PER_WEEK as f64;
+
+/// A weekly calendar of periods that override properties of the soundscape.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Schedule {
+    /// Whether or not the schedule is applied to the soundscape.
+    #[serde(default)]
+    pub enabled: bool,
+    /// The duration over which each period fades in and out at its boundaries.
+    #[serde(default = "default_crossfade")]
+    pub crossfade: Ms,
+    /// The periods that make up the schedule.
+    ///
+    /// Where periods overlap, those later in the list take precedence.
+    #[serde(default)]
+    pub periods: Vec<Period>,
+}
+
+/// A range of hours on one or more days of the week along with the overrides applied during it.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Period {
+    #[serde(default = "default_period_name")]
+    pub name: String,
+    /// The days of the week on which the period starts, beginning with Monday.
+    #[serde(default = "default_days")]
+    pub days: [bool; DAYS_PER_WEEK],
+    /// The hour of the day at which the period starts, e.g. `9.5` for 9:30am.
+    #[serde(default = "default_start_hour")]
+    pub start_hour: f64,
+    /// The hour of the day at which the period ends.
+    ///
+    /// If this is earlier than the `start_hour`, the period continues past midnight.
+    #[serde(default = "default_end_hour")]
+    pub end_hour: f64,
+    /// Overrides the simultaneous sounds of every installation.
+    #[serde(default)]
+    pub simultaneous_sounds: Option<Range<usize>>,
+    /// Overrides the master volume.
+    #[serde(default)]
+    pub volume: Option<f32>,
+    /// Groups that may not be used to spawn new sounds during the period.
+    #[serde(default)]
+    pub disabled_groups: Vec<group::Id>,
+}
+
+/// A moment within the week at which a schedule may be evaluated.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Moment {
+    /// Seconds since midnight at the beginning of Monday.
+    secs: f64,
+}
+
+/// An override of some value, described as `value * scale + offset`.
+///
+/// Describing overrides in this way allows for crossfading between the original value and any
+/// number of overriding values without knowing the original value ahead of time.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Override {
+    pub scale: f64,
+    pub offset: f64,
+}
+
+/// The result of evaluating a schedule at some moment.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct State {
+    pub volume: Override,
+    pub simultaneous_sounds_min: Override,
+    pub simultaneous_sounds_max: Override,
+    pub disabled_groups: FxHashSet<group::Id>,
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Schedule {
+            enabled: false,
+            crossfade: default_crossfade(),
+            periods: Vec::new(),
+        }
+    }
+}
+
+impl Default for Period {
+    fn default() -> Self {
+        Period {
+            name: default_period_name(),
+            days: default_days(),
+            start_hour: default_start_hour(),
+            end_hour: default_end_hour(),
+            simultaneous_sounds: None,
+            volume: None,
+            disabled_groups: Vec::new(),
+        }
+    }
+}
+
+impl Default for Override {
+    fn default() -> Self {
+        Override::NONE
+    }
+}
+
+impl Schedule {
+    /// Evaluate the schedule at the given moment, writing the result to the given `state`.
+    pub fn evaluate(&self, moment: &Moment, state: &mut State) {
+        state.volume = Override::NONE;
+        state.simultaneous_sounds_min = Override::NONE;
+        state.simultaneous_sounds_max = Override::NONE;
+        state.disabled_groups.clear();
+        if !self.enabled {
+            return;
+        }
+        for period in &self.periods {
+            let weight = period.weight(moment, self.crossfade);
+            if weight <= 0.0 {
+                continue;
+            }
+            if let Some(volume) = period.volume {
+                state.volume.blend(volume as f64, weight);
+            }
+            if let Some(range) = period.simultaneous_sounds {
+                state.simultaneous_sounds_min.blend(range.min as f64, weight);
+                state.simultaneous_sounds_max.blend(range.max as f64, weight);
+            }
+            state.disabled_groups.extend(period.disabled_groups.iter().cloned());
+        }
+    }
+}
+
+impl Period {
+    /// The weight of the period at the given moment.
+    ///
+    /// This is `0.0` outside of the period and `1.0` within it, ramping between the two over the
+    /// `crossfade` duration just inside of the period's boundaries.
+    pub fn weight(&self, moment: &Moment, crossfade: Ms) -> f64 {
+        let start = clamp_hour(self.start_hour) * HOUR_SECS;
+        let end = clamp_hour(self.end_hour) * HOUR_SECS;
+        let mut duration = end - start;
+        if duration <= 0.0 {
+            duration += DAY_SECS;
+        }
+        let fade_secs = crossfade.ms().max(0.0) / 1_000.0;
+        self.days
+            .iter()
+            .enumerate()
+            .filter(|&(_, &enabled)| enabled)
+            .map(|(day, _)| {
+                let period_start = day as f64 * DAY_SECS + start;
+                let elapsed = (moment.secs - period_start).rem_euclid(WEEK_SECS);
+                if elapsed >= duration {
+                    0.0
+                } else if fade_secs > 0.0 {
+                    (elapsed / fade_secs).min((duration - elapsed) / fade_secs).min(1.0)
+                } else {
+                    1.0
+                }
+            })
+            .fold(0.0, f64::max)
+    }
+}
+
+impl Moment {
+    /// The given hour of the given day, where day `0` is Monday.
+    pub fn new(day: usize, hour: f64) -> Self {
+        let secs = (day % DAYS_PER_WEEK) as f64 * DAY_SECS + clamp_hour(hour) * HOUR_SECS;
+        Moment { secs }
+    }
+
+    /// The current moment according to the local time of the system.
+    pub fn now() -> Self {
+        let now = chrono::Local::now();
+        let day = now.weekday().num_days_from_monday() as usize;
+        let secs = now.num_seconds_from_midnight() as f64 + now.nanosecond() as f64 * 1e-9;
+        Moment::new(day, secs / HOUR_SECS)
+    }
+
+    /// The moment that occurs the given duration after this one.
+    pub fn after(&self, duration: time::Duration) -> Self {
+        let secs = (self.secs + duration.as_secs_f64()).rem_euclid(WEEK_SECS);
+        Moment { secs }
+    }
+}
+
+impl Override {
+    /// Leaves the value unchanged.
+    pub const NONE: Self = Override {
+        scale: 1.0,
+        offset: 0.0,
+    };
+
+    /// Apply the override to the given value.
+    pub fn apply(&self, value: f64) -> f64 {
+        value * self.scale + self.offset
+    }
+
+    // Crossfade from the current override towards the given value by the given weight.
+    fn blend(&mut self, value: f64, weight: f64) {
+        self.scale *= 1.0 - weight;
+        self.offset = self.offset * (1.0 - weight) + value * weight;
+    }
+}
+
+impl State {
+    /// Apply the simultaneous sounds override to the given range.
+    pub fn simultaneous_sounds(&self, range: &Range<usize>) -> Range<usize> {
+        let apply = |o: &Override, n: usize| o.apply(n as f64).round().max(0.0) as usize;
+        let min = apply(&self.simultaneous_sounds_min, range.min);
+        let max = apply(&self.simultaneous_sounds_max, range.max);
+        Range { min, max: ::std::cmp::max(min, max) }
+    }
+}
+
+/// Format the given hour of the day as `HH:MM`.
+pub fn hour_label(hour: f64) -> String {
+    let minutes = (clamp_hour(hour) * 60.0).round() as usize;
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+// Hours are limited to a single day.
+fn clamp_hour(hour: f64) -> f64 {
+    hour.max(0.0).min(24.0)
+}
+
+fn default_crossfade() -> Ms {
+    Ms(60_000.0)
+}
+
+fn default_period_name() -> String {
+    "Period".into()
+}
+
+fn default_days() -> [bool; DAYS_PER_WEEK] {
+    [true; DAYS_PER_WEEK]
+}
+
+fn default_start_hour() -> f64 {
+    9.0
+}
+
+fn default_end_hour() -> f64 {
+    17.0
+}
+
+#[test]
+fn period_weight() {
+    let period = Period {
+        days: [true, false, false, false, false, false, false],
+        start_hour: 22.0,
+        end_hour: 2.0,
+        ..Default::default()
+    };
+    let crossfade = Ms(60.0 * 60.0 * 1_000.0);
+    // Before, during and after the fades of a period continuing past midnight.
+    assert_eq!(period.weight(&Moment::new(0, 21.0), crossfade), 0.0);
+    assert_eq!(period.weight(&Moment::new(0, 22.5), crossfade), 0.5);
+    assert_eq!(period.weight(&Moment::new(1, 0.0), crossfade), 1.0);
+    assert_eq!(period.weight(&Moment::new(1, 1.5), crossfade), 0.5);
+    assert_eq!(period.weight(&Moment::new(1, 2.0), crossfade), 0.0);
+    // Tuesday is not enabled.
+    assert_eq!(period.weight(&Moment::new(1, 23.0), crossfade), 0.0);
+}
+
+#[test]
+fn override_crossfade() {
+    let mut state = State::default();
+    state.volume.blend(0.5, 0.5);
+    assert_eq!(state.volume.apply(1.0), 0.75);
+    state.volume.blend(0.0, 1.0);
+    assert_eq!(state.volume.apply(1.0), 0.0);
+}
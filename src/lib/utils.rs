@@ -134,6 +134,16 @@ pub fn hz_to_ms_interval(hz: f64) -> Ms {
     ms
 }
 
+/// Convert the given level in decibels to a linear amplitude multiplier.
+pub fn db_to_linear(db: f64) -> f64 {
+    10f64.powf(db / 20.0)
+}
+
+/// Convert the given linear amplitude multiplier to a level in decibels.
+pub fn linear_to_db(amp: f64) -> f64 {
+    20.0 * amp.max(::std::f64::EPSILON).log10()
+}
+
 /// Given a value in hz, produce a more readable "times per second".
 ///
 /// E.g. a returned value of (Hrs, 3.5) can be thought of as "3.5 times per hour".